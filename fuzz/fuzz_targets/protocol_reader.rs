@@ -68,7 +68,11 @@ fn driver(data: &[u8]) -> Result<(), Error> {
                 min_bytes: Int32(0),
                 max_bytes: None,
                 isolation_level: None,
+                session_id: None,
+                session_epoch: None,
                 topics: vec![],
+                forgotten_topics_data: vec![],
+                rack_id: None,
             },
             cursor,
             api_key,
@@ -0,0 +1,440 @@
+//! Access control lists (ACLs).
+//!
+//! ACLs are managed via [`ControllerClient`](super::controller::ControllerClient).
+use crate::{
+    client::error::Result,
+    protocol::{
+        messages::{AclCreation, DeleteAclsFilter, DescribeAclsRequest},
+        primitives::{Int8, NullableString, String_},
+    },
+};
+
+/// Type of the resource an ACL applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum AclResourceType {
+    /// Matches any resource type, only valid in an [`AclBindingFilter`].
+    Any,
+
+    /// A topic.
+    Topic,
+
+    /// A consumer group.
+    Group,
+
+    /// The cluster itself, with the resource name `kafka-cluster`.
+    Cluster,
+
+    /// A transactional ID.
+    TransactionalId,
+
+    /// A delegation token.
+    DelegationToken,
+
+    /// A user, e.g. for creating delegation tokens on behalf of them.
+    User,
+
+    /// The broker sent an unknown resource type.
+    Unknown,
+}
+
+impl AclResourceType {
+    pub(crate) fn code(self) -> i8 {
+        match self {
+            Self::Unknown => 0,
+            Self::Any => 1,
+            Self::Topic => 2,
+            Self::Group => 3,
+            Self::Cluster => 4,
+            Self::TransactionalId => 5,
+            Self::DelegationToken => 6,
+            Self::User => 7,
+        }
+    }
+}
+
+impl From<i8> for AclResourceType {
+    fn from(code: i8) -> Self {
+        match code {
+            1 => Self::Any,
+            2 => Self::Topic,
+            3 => Self::Group,
+            4 => Self::Cluster,
+            5 => Self::TransactionalId,
+            6 => Self::DelegationToken,
+            7 => Self::User,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// How the resource name of an ACL is matched against resources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum AclPatternType {
+    /// Matches any pattern type, only valid in an [`AclBindingFilter`].
+    Any,
+
+    /// Matches all ACLs that apply to the resource name of the filter, i.e. literal ACLs with that name, literal
+    /// wildcard (`*`) ACLs and prefixed ACLs with a matching prefix.
+    ///
+    /// Only valid in an [`AclBindingFilter`] with a resource name.
+    Match,
+
+    /// The resource name is used as is, `*` matches all resources.
+    Literal,
+
+    /// The resource name is a prefix of the names of the resources.
+    ///
+    /// Note: this requires broker version >= 2.0.0 (KIP-290).
+    Prefixed,
+
+    /// The broker sent an unknown pattern type.
+    Unknown,
+}
+
+impl AclPatternType {
+    pub(crate) fn code(self) -> i8 {
+        match self {
+            Self::Unknown => 0,
+            Self::Any => 1,
+            Self::Match => 2,
+            Self::Literal => 3,
+            Self::Prefixed => 4,
+        }
+    }
+}
+
+impl From<i8> for AclPatternType {
+    fn from(code: i8) -> Self {
+        match code {
+            1 => Self::Any,
+            2 => Self::Match,
+            3 => Self::Literal,
+            4 => Self::Prefixed,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// Operation that an ACL allows or denies.
+///
+/// See <https://kafka.apache.org/documentation/#operations_resources_and_protocols> for the operations that each
+/// request requires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum AclOperation {
+    /// Matches any operation, only valid in an [`AclBindingFilter`].
+    Any,
+
+    /// All operations.
+    All,
+
+    /// Read, e.g. fetch records or commit offsets.
+    Read,
+
+    /// Write, e.g. produce records.
+    Write,
+
+    /// Create, e.g. create topics.
+    Create,
+
+    /// Delete, e.g. delete topics or records.
+    Delete,
+
+    /// Alter, e.g. create partitions or ACLs.
+    Alter,
+
+    /// Describe, e.g. fetch metadata or list offsets.
+    Describe,
+
+    /// Requests between brokers.
+    ClusterAction,
+
+    /// Describe configurations.
+    DescribeConfigs,
+
+    /// Alter configurations.
+    AlterConfigs,
+
+    /// Idempotent produce.
+    IdempotentWrite,
+
+    /// Create delegation tokens.
+    CreateTokens,
+
+    /// Describe delegation tokens.
+    DescribeTokens,
+
+    /// The broker sent an unknown operation.
+    Unknown,
+}
+
+impl AclOperation {
+    pub(crate) fn code(self) -> i8 {
+        match self {
+            Self::Unknown => 0,
+            Self::Any => 1,
+            Self::All => 2,
+            Self::Read => 3,
+            Self::Write => 4,
+            Self::Create => 5,
+            Self::Delete => 6,
+            Self::Alter => 7,
+            Self::Describe => 8,
+            Self::ClusterAction => 9,
+            Self::DescribeConfigs => 10,
+            Self::AlterConfigs => 11,
+            Self::IdempotentWrite => 12,
+            Self::CreateTokens => 13,
+            Self::DescribeTokens => 14,
+        }
+    }
+}
+
+impl From<i8> for AclOperation {
+    fn from(code: i8) -> Self {
+        match code {
+            1 => Self::Any,
+            2 => Self::All,
+            3 => Self::Read,
+            4 => Self::Write,
+            5 => Self::Create,
+            6 => Self::Delete,
+            7 => Self::Alter,
+            8 => Self::Describe,
+            9 => Self::ClusterAction,
+            10 => Self::DescribeConfigs,
+            11 => Self::AlterConfigs,
+            12 => Self::IdempotentWrite,
+            13 => Self::CreateTokens,
+            14 => Self::DescribeTokens,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// Whether an ACL allows or denies its operation.
+///
+/// Deny takes precedence over allow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum AclPermissionType {
+    /// Matches any permission type, only valid in an [`AclBindingFilter`].
+    Any,
+
+    /// Deny the operation.
+    Deny,
+
+    /// Allow the operation.
+    Allow,
+
+    /// The broker sent an unknown permission type.
+    Unknown,
+}
+
+impl AclPermissionType {
+    pub(crate) fn code(self) -> i8 {
+        match self {
+            Self::Unknown => 0,
+            Self::Any => 1,
+            Self::Deny => 2,
+            Self::Allow => 3,
+        }
+    }
+}
+
+impl From<i8> for AclPermissionType {
+    fn from(code: i8) -> Self {
+        match code {
+            1 => Self::Any,
+            2 => Self::Deny,
+            3 => Self::Allow,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// An ACL, i.e. a principal that is allowed or denied an operation on some resources.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AclBinding {
+    /// Type of the resources.
+    pub resource_type: AclResourceType,
+
+    /// Name of the resources, interpreted according to [`pattern_type`](Self::pattern_type).
+    pub resource_name: String,
+
+    /// How [`resource_name`](Self::resource_name) is matched against resources.
+    pub pattern_type: AclPatternType,
+
+    /// Principal, e.g. `User:alice`.
+    pub principal: String,
+
+    /// Host the principal connects from, `*` for all hosts.
+    pub host: String,
+
+    /// The operation.
+    pub operation: AclOperation,
+
+    /// Whether the operation is allowed or denied.
+    pub permission_type: AclPermissionType,
+}
+
+impl AclBinding {
+    /// ACL for the resource with the literal name `resource_name`, applying to all hosts.
+    pub fn new(
+        resource_type: AclResourceType,
+        resource_name: impl Into<String>,
+        principal: impl Into<String>,
+        operation: AclOperation,
+        permission_type: AclPermissionType,
+    ) -> Self {
+        Self {
+            resource_type,
+            resource_name: resource_name.into(),
+            pattern_type: AclPatternType::Literal,
+            principal: principal.into(),
+            host: "*".to_owned(),
+            operation,
+            permission_type,
+        }
+    }
+
+    /// Set how the resource name is matched against resources.
+    pub fn with_pattern_type(self, pattern_type: AclPatternType) -> Self {
+        Self {
+            pattern_type,
+            ..self
+        }
+    }
+
+    /// Only apply the ACL to connections from `host`.
+    pub fn with_host(self, host: impl Into<String>) -> Self {
+        Self {
+            host: host.into(),
+            ..self
+        }
+    }
+
+    pub(crate) fn into_creation(self) -> AclCreation {
+        AclCreation {
+            resource_type: Int8(self.resource_type.code()),
+            resource_name: String_(self.resource_name),
+            resource_pattern_type: Some(Int8(self.pattern_type.code())),
+            principal: String_(self.principal),
+            host: String_(self.host),
+            operation: Int8(self.operation.code()),
+            permission_type: Int8(self.permission_type.code()),
+            tagged_fields: None,
+        }
+    }
+}
+
+/// Selects ACLs to describe or delete.
+///
+/// `None` and the `Any` variants match everything.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AclBindingFilter {
+    /// Type of the resources.
+    pub resource_type: AclResourceType,
+
+    /// Name of the resources.
+    pub resource_name: Option<String>,
+
+    /// How [`resource_name`](Self::resource_name) is matched against the ACLs.
+    pub pattern_type: AclPatternType,
+
+    /// Principal, e.g. `User:alice`.
+    pub principal: Option<String>,
+
+    /// Host the principal connects from.
+    pub host: Option<String>,
+
+    /// The operation.
+    pub operation: AclOperation,
+
+    /// Whether the operation is allowed or denied.
+    pub permission_type: AclPermissionType,
+}
+
+impl AclBindingFilter {
+    /// Filter that matches all ACLs.
+    pub fn any() -> Self {
+        Self {
+            resource_type: AclResourceType::Any,
+            resource_name: None,
+            pattern_type: AclPatternType::Any,
+            principal: None,
+            host: None,
+            operation: AclOperation::Any,
+            permission_type: AclPermissionType::Any,
+        }
+    }
+
+    pub(crate) fn to_describe_request(&self) -> DescribeAclsRequest {
+        DescribeAclsRequest {
+            resource_type_filter: Int8(self.resource_type.code()),
+            resource_name_filter: NullableString(self.resource_name.clone()),
+            pattern_type_filter: Some(Int8(self.pattern_type.code())),
+            principal_filter: NullableString(self.principal.clone()),
+            host_filter: NullableString(self.host.clone()),
+            operation: Int8(self.operation.code()),
+            permission_type: Int8(self.permission_type.code()),
+            tagged_fields: None,
+        }
+    }
+
+    pub(crate) fn to_delete_filter(&self) -> DeleteAclsFilter {
+        DeleteAclsFilter {
+            resource_type_filter: Int8(self.resource_type.code()),
+            resource_name_filter: NullableString(self.resource_name.clone()),
+            pattern_type_filter: Some(Int8(self.pattern_type.code())),
+            principal_filter: NullableString(self.principal.clone()),
+            host_filter: NullableString(self.host.clone()),
+            operation: Int8(self.operation.code()),
+            permission_type: Int8(self.permission_type.code()),
+            tagged_fields: None,
+        }
+    }
+}
+
+/// Filter that only matches the given ACL.
+impl From<AclBinding> for AclBindingFilter {
+    fn from(binding: AclBinding) -> Self {
+        Self {
+            resource_type: binding.resource_type,
+            resource_name: Some(binding.resource_name),
+            pattern_type: binding.pattern_type,
+            principal: Some(binding.principal),
+            host: Some(binding.host),
+            operation: binding.operation,
+            permission_type: binding.permission_type,
+        }
+    }
+}
+
+/// Result of creating a single ACL, see [`ControllerClient::create_acls`](super::controller::ControllerClient::create_acls).
+#[derive(Debug)]
+pub struct CreateAclResult {
+    /// The ACL.
+    pub binding: AclBinding,
+
+    /// Whether the ACL was created.
+    pub result: Result<()>,
+}
+
+/// Result of a single filter, see [`ControllerClient::delete_acls`](super::controller::ControllerClient::delete_acls).
+#[derive(Debug)]
+pub struct DeleteAclsResult {
+    /// The filter.
+    pub filter: AclBindingFilter,
+
+    /// The ACLs that matched the filter or why the filter could not be applied.
+    pub result: Result<Vec<DeletedAcl>>,
+}
+
+/// An ACL that matched a filter of [`ControllerClient::delete_acls`](super::controller::ControllerClient::delete_acls).
+#[derive(Debug)]
+pub struct DeletedAcl {
+    /// The ACL.
+    pub binding: AclBinding,
+
+    /// Whether the ACL was deleted.
+    pub result: Result<()>,
+}
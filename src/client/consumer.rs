@@ -48,22 +48,30 @@ use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::time::Duration;
 
+use chrono::{DateTime, Utc};
 use futures::Stream;
 use futures::future::{BoxFuture, Fuse, FusedFuture, FutureExt};
-use tracing::{debug, trace, warn};
+use futures::task::AtomicWaker;
+use parking_lot::Mutex;
+use tracing::{debug, info, trace, warn};
 
 use crate::{
+    backoff::{Backoff, BackoffConfig, BackoffError},
     client::{
-        error::{Error, ProtocolError, Result},
-        partition::PartitionClient,
+        error::{Error, ProtocolError, Result, ServerErrorResponse},
+        partition::{EpochEndOffset, FetchedBatches, IsolationLevel, PartitionClient},
     },
-    record::RecordAndOffset,
+    messenger::RequestError,
+    record::{RecordAndOffset, RecordBatch, RecordBatchMetadata},
 };
 
 use super::partition::OffsetAt;
 
+mod fetch_session;
+pub mod multi;
+
 /// At which position shall the stream start.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum StartOffset {
     /// At the earlist known offset.
     ///
@@ -78,7 +86,94 @@ pub enum StartOffset {
     /// At a specific offset.
     ///
     /// Note that specifying an offset that is unknown to the broker will result in a [`Error::ServerError`] with
-    /// [`ProtocolError::OffsetOutOfRange`] and the stream will terminate right after the error.
+    /// [`ProtocolError::OffsetOutOfRange`] and the stream will terminate right after the error, unless an
+    /// [offset reset policy](StreamConsumerBuilder::with_offset_reset) is set.
+    At(i64),
+
+    /// At the first record with a timestamp that is equal to or later than the given one.
+    ///
+    /// The offset is resolved using [`OffsetAt::Timestamp`] whenever the stream (re)starts without a known position,
+    /// e.g. when it is polled for the first time or when an error occurred before the first successful fetch. Once
+    /// the stream fetched data, it continues from there and never looks up the timestamp again.
+    ///
+    /// If no record has such a timestamp (e.g. because the timestamp is in the future), the stream starts at the end of
+    /// the log, like [`Latest`](Self::Latest), and only returns records that are produced afterwards. Note that the
+    /// timestamps within a partition are not necessarily ordered, so records with an earlier timestamp may follow.
+    Timestamp(DateTime<Utc>),
+
+    /// At the offset that the consumer group `group` committed for this partition.
+    ///
+    /// If the group has not committed an offset for this partition, `fallback` is used instead. A committed offset that
+    /// is unknown to the broker is handled like [`At`](Self::At), i.e. the stream terminates with an error.
+    ///
+    /// See [`GroupOffsetsClient`](crate::client::group_offsets::GroupOffsetsClient) for how to commit offsets.
+    Committed {
+        /// Consumer group ID.
+        group: Arc<str>,
+
+        /// Start offset used if the group has not committed an offset.
+        fallback: CommittedFallback,
+    },
+}
+
+/// Where a stream shall start if the consumer group has not committed an offset, see [`StartOffset::Committed`].
+#[derive(Debug, Clone, Copy)]
+pub enum CommittedFallback {
+    /// See [`StartOffset::Earliest`].
+    Earliest,
+
+    /// See [`StartOffset::Latest`].
+    Latest,
+
+    /// See [`StartOffset::At`].
+    At(i64),
+
+    /// See [`StartOffset::Timestamp`].
+    Timestamp(DateTime<Utc>),
+}
+
+impl From<CommittedFallback> for StartOffset {
+    fn from(fallback: CommittedFallback) -> Self {
+        match fallback {
+            CommittedFallback::Earliest => Self::Earliest,
+            CommittedFallback::Latest => Self::Latest,
+            CommittedFallback::At(offset) => Self::At(offset),
+            CommittedFallback::Timestamp(ts) => Self::Timestamp(ts),
+        }
+    }
+}
+
+/// What a [`StreamConsumer`] does if its fetch offset is out of range, similar to `auto.offset.reset` of the Java
+/// client.
+///
+/// This happens if records were deleted (e.g. due to retention) before they were consumed, or if the fetch offset is
+/// beyond the end of the log (e.g. because the topic was re-created).
+///
+/// The policy also applies to [log truncation](Error::LogTruncation): with [`Earliest`](Self::Earliest) or
+/// [`Latest`](Self::Latest), the stream continues at the truncation point, or applies the policy if the truncation point
+/// is unknown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OffsetReset {
+    /// Continue at the earliest offset.
+    Earliest,
+
+    /// Continue at the latest offset, i.e. skip all records that are currently in the partition.
+    Latest,
+
+    /// Emit the [`ProtocolError::OffsetOutOfRange`] or [`Error::LogTruncation`] error and terminate.
+    Error,
+}
+
+/// Where a [bounded](StreamConsumerBuilder::with_end_offset) stream ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndOffset {
+    /// At the latest offset when the stream starts, i.e. the high watermark or, for
+    /// [`IsolationLevel::ReadCommitted`], the last stable offset.
+    ///
+    /// Records that are produced after the stream started are not returned.
+    Latest,
+
+    /// Before the given offset, i.e. the record at this offset is not returned.
     At(i64),
 }
 
@@ -93,11 +188,22 @@ pub struct StreamConsumerBuilder {
     min_batch_size: i32,
 
     max_batch_size: i32,
+
+    isolation_level: IsolationLevel,
+
+    backoff_config: Option<BackoffConfig>,
+
+    offset_reset: Option<OffsetReset>,
+
+    end_offset: Option<EndOffset>,
+
+    prefetch_bytes: usize,
 }
 
 impl StreamConsumerBuilder {
     pub fn new(client: Arc<PartitionClient>, start_offset: StartOffset) -> Self {
-        Self::new_with_client(client, start_offset)
+        let isolation_level = client.isolation_level();
+        Self::new_with_client(client, start_offset).with_isolation_level(isolation_level)
     }
 
     /// Internal API for creating with any `dyn FetchClient`
@@ -110,6 +216,11 @@ impl StreamConsumerBuilder {
             max_wait_ms: 500,
             min_batch_size: 1,
             max_batch_size: 52428800,
+            isolation_level: IsolationLevel::default(),
+            backoff_config: None,
+            offset_reset: None,
+            end_offset: None,
+            prefetch_bytes: 0,
         }
     }
 
@@ -137,12 +248,94 @@ impl StreamConsumerBuilder {
         }
     }
 
+    /// Set which records of transactional producers are returned, see [`IsolationLevel`].
+    ///
+    /// This also affects which offset [`StartOffset::Latest`] resolves to. Defaults to the
+    /// [isolation level of the client](PartitionClient::with_isolation_level).
+    pub fn with_isolation_level(self, isolation_level: IsolationLevel) -> Self {
+        Self {
+            isolation_level,
+            ..self
+        }
+    }
+
+    /// Retry errors that are likely to go away, like broken connections or leader changes, instead of terminating.
+    ///
+    /// Retries are delayed according to `backoff_config`, which is reset after every successful fetch. If its deadline
+    /// is exceeded, the stream emits [`Error::RetryFailed`] and terminates. All other errors still terminate the
+    /// stream. By default, no errors are retried.
+    ///
+    /// Note that the [`PartitionClient`] already retries some errors on its own, using the backoff configuration of
+    /// the client.
+    pub fn with_retry(self, backoff_config: BackoffConfig) -> Self {
+        Self {
+            backoff_config: Some(backoff_config),
+            ..self
+        }
+    }
+
+    /// Set what happens if the fetch offset is out of range, see [`OffsetReset`].
+    ///
+    /// By default, a stream that started at [`StartOffset::Earliest`] or [`StartOffset::Latest`] resolves that start
+    /// offset again, all other streams terminate.
+    pub fn with_offset_reset(self, offset_reset: OffsetReset) -> Self {
+        Self {
+            offset_reset: Some(offset_reset),
+            ..self
+        }
+    }
+
+    /// End the stream once all records before `end_offset` were returned, instead of waiting for new records forever.
+    ///
+    /// [`EndOffset::Latest`] is resolved once, right before the first fetch. The stream also ends if there are no
+    /// records left before the end offset, e.g. because the records at the tail of the range were removed by
+    /// compaction or are transaction markers. A stream whose start offset is at or after the end offset ends right
+    /// away.
+    ///
+    /// By default, the stream is unbounded.
+    pub fn with_end_offset(self, end_offset: EndOffset) -> Self {
+        Self {
+            end_offset: Some(end_offset),
+            ..self
+        }
+    }
+
+    /// Fetch the next records while buffered records are still being consumed, as long as less than
+    /// `prefetch_bytes` are buffered.
+    ///
+    /// This overlaps processing and network round trips. The budget is compared to the approximate size of the
+    /// buffered records (see [`Record::approximate_size`](crate::record::Record::approximate_size)) before a fetch is
+    /// issued, so the memory used by buffered records is bounded by `prefetch_bytes` plus the result of one fetch (see
+    /// [`with_max_batch_size`](Self::with_max_batch_size)).
+    ///
+    /// Only one fetch is in flight at a time. Errors of a prefetch are handled once all records before it were
+    /// returned. Defaults to `0`, i.e. the next fetch is only issued once the buffer is empty.
+    pub fn with_prefetch_bytes(self, prefetch_bytes: usize) -> Self {
+        Self {
+            prefetch_bytes,
+            ..self
+        }
+    }
+
     pub fn build(self) -> StreamConsumer {
         StreamConsumer {
             client: self.client,
+            control: Default::default(),
             max_wait_ms: self.max_wait_ms,
             min_batch_size: self.min_batch_size,
             max_batch_size: self.max_batch_size,
+            isolation_level: self.isolation_level,
+            backoff_config: self.backoff_config,
+            backoff: None,
+            offset_reset: self.offset_reset,
+            reset_pending: false,
+            reset_to: None,
+            end_offset: self.end_offset,
+            end: None,
+            prefetch_bytes: self.prefetch_bytes,
+            prefetch_error: None,
+            fetched_epoch: None,
+            leader_epoch: None,
             next_offset: None,
             next_backoff: None,
             start_offset: self.start_offset,
@@ -152,65 +345,274 @@ impl StreamConsumerBuilder {
             fetch_fut: Fuse::terminated(),
         }
     }
+
+    /// Build a stream that returns whole record batches instead of single records, see [`BatchStreamConsumer`].
+    pub fn build_batched(self) -> BatchStreamConsumer {
+        BatchStreamConsumer {
+            inner: self.build(),
+        }
+    }
 }
 
 struct FetchResultOk {
-    records_and_offsets: Vec<RecordAndOffset>,
-    watermark: i64,
+    fetched: FetchedBatches,
     used_offset: i64,
+
+    /// Resolved end offset of a bounded stream.
+    end: Option<i64>,
 }
 
 type FetchResult = Result<FetchResultOk>;
 
 /// A trait wrapper to allow mocking
 trait FetchClient: std::fmt::Debug + Send + Sync {
-    /// Fetch records.
+    /// Fetch record batches.
     ///
-    /// Arguments are identical to [`PartitionClient::fetch_records`].
-    fn fetch_records(
+    /// Arguments are identical to [`PartitionClient::fetch_record_batches_with_isolation_level`].
+    fn fetch_batches(
         &self,
         offset: i64,
         bytes: Range<i32>,
         max_wait_ms: i32,
-    ) -> BoxFuture<'_, Result<(Vec<RecordAndOffset>, i64)>>;
+        isolation_level: IsolationLevel,
+    ) -> BoxFuture<'_, Result<FetchedBatches>>;
 
     /// Get offset.
     ///
-    /// Arguments are identical to [`PartitionClient::get_offset`].
-    fn get_offset(&self, at: OffsetAt) -> BoxFuture<'_, Result<i64>>;
+    /// Arguments are identical to [`PartitionClient::get_offset_with_isolation_level`].
+    fn get_offset(
+        &self,
+        at: OffsetAt,
+        isolation_level: IsolationLevel,
+    ) -> BoxFuture<'_, Result<i64>>;
+
+    /// Get offset that the consumer group committed for this partition, if any.
+    fn get_committed_offset(&self, group: String) -> BoxFuture<'_, Result<Option<i64>>>;
+
+    /// Get end offset of a leader epoch.
+    ///
+    /// Arguments are identical to [`PartitionClient::get_epoch_end_offset`].
+    fn get_epoch_end_offset(&self, leader_epoch: i32) -> BoxFuture<'_, Result<EpochEndOffset>>;
 }
 
 impl FetchClient for PartitionClient {
-    fn fetch_records(
+    fn fetch_batches(
         &self,
         offset: i64,
         bytes: Range<i32>,
         max_wait_ms: i32,
-    ) -> BoxFuture<'_, Result<(Vec<RecordAndOffset>, i64)>> {
-        Box::pin(self.fetch_records(offset, bytes, max_wait_ms))
+        isolation_level: IsolationLevel,
+    ) -> BoxFuture<'_, Result<FetchedBatches>> {
+        Box::pin(Self::fetch_batches(
+            self,
+            offset,
+            bytes,
+            max_wait_ms,
+            isolation_level,
+        ))
+    }
+
+    fn get_offset(
+        &self,
+        at: OffsetAt,
+        isolation_level: IsolationLevel,
+    ) -> BoxFuture<'_, Result<i64>> {
+        Box::pin(self.get_offset_with_isolation_level(at, isolation_level))
+    }
+
+    fn get_committed_offset(&self, group: String) -> BoxFuture<'_, Result<Option<i64>>> {
+        Box::pin(async move {
+            self.group_offsets_client(group)
+                .fetch_offset(self.topic(), self.partition())
+                .await
+        })
+    }
+
+    fn get_epoch_end_offset(&self, leader_epoch: i32) -> BoxFuture<'_, Result<EpochEndOffset>> {
+        Box::pin(Self::get_epoch_end_offset(self, leader_epoch))
+    }
+}
+
+/// Resolve the offset at which a stream shall start.
+fn resolve_start_offset<'a>(
+    client: &'a dyn FetchClient,
+    start_offset: &'a StartOffset,
+    isolation_level: IsolationLevel,
+) -> BoxFuture<'a, Result<i64>> {
+    Box::pin(async move {
+        match start_offset {
+            StartOffset::Earliest => {
+                let offset = client
+                    .get_offset(OffsetAt::Earliest, isolation_level)
+                    .await?;
+                debug!(offset, "resolved `earliest` offset");
+                Ok(offset)
+            }
+            StartOffset::Latest => {
+                let offset = client.get_offset(OffsetAt::Latest, isolation_level).await?;
+                debug!(offset, "resolved `latest` offset");
+                Ok(offset)
+            }
+            StartOffset::At(x) => Ok(*x),
+            StartOffset::Timestamp(ts) => {
+                let offset = client
+                    .get_offset(OffsetAt::Timestamp(*ts), isolation_level)
+                    .await?;
+                if offset >= 0 {
+                    debug!(offset, %ts, "resolved `timestamp` offset");
+                    return Ok(offset);
+                }
+
+                // the broker did not find a matching record
+                let offset = client.get_offset(OffsetAt::Latest, isolation_level).await?;
+                debug!(
+                    offset,
+                    %ts,
+                    "timestamp is beyond the end of the log, resolved `latest` offset"
+                );
+                Ok(offset)
+            }
+            StartOffset::Committed { group, fallback } => {
+                match client.get_committed_offset(group.to_string()).await? {
+                    Some(offset) => {
+                        debug!(offset, %group, "resolved `committed` offset");
+                        Ok(offset)
+                    }
+                    None => {
+                        debug!(%group, ?fallback, "no committed offset, resolving fallback");
+                        resolve_start_offset(client, &(*fallback).into(), isolation_level).await
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Check if the log was truncated below `offset`, given that the records right before `offset` were written in leader
+/// epoch `leader_epoch`.
+///
+/// Returns [`Error::LogTruncation`] if the current leader ended this epoch before `offset`.
+async fn check_truncation(client: &dyn FetchClient, leader_epoch: i32, offset: i64) -> Result<()> {
+    let epoch_end = client.get_epoch_end_offset(leader_epoch).await?;
+    match epoch_end.end_offset {
+        Some(end_offset) if end_offset >= offset => {
+            debug!(
+                leader_epoch,
+                offset, end_offset, "validated fetch offset against leader epoch"
+            );
+            Ok(())
+        }
+        truncation_offset => Err(Error::LogTruncation {
+            fetch_offset: offset,
+            truncation_offset,
+            leader_epoch: epoch_end.leader_epoch,
+        }),
     }
+}
 
-    fn get_offset(&self, at: OffsetAt) -> BoxFuture<'_, Result<i64>> {
-        Box::pin(self.get_offset(at))
+/// Whether the [`StreamConsumer`] retries `error` if [retries](StreamConsumerBuilder::with_retry) are enabled.
+fn is_retriable(error: &Error) -> bool {
+    match error {
+        Error::Request(
+            RequestError::Poisoned(_) | RequestError::IO(_) | RequestError::Timeout { .. },
+        )
+        | Error::Connection(_)
+        // the client gave up retrying on its own
+        | Error::RetryFailed(_) => true,
+        Error::ServerError {
+            protocol_error:
+                ProtocolError::CoordinatorLoadInProgress
+                | ProtocolError::CoordinatorNotAvailable
+                | ProtocolError::FencedLeaderEpoch
+                | ProtocolError::InvalidReplicationFactor
+                | ProtocolError::KafkaStorageError
+                | ProtocolError::LeaderNotAvailable
+                | ProtocolError::NetworkException
+                | ProtocolError::NotCoordinator
+                | ProtocolError::NotEnoughReplicas
+                | ProtocolError::NotLeaderOrFollower
+                | ProtocolError::OffsetNotAvailable
+                | ProtocolError::ReplicaNotAvailable
+                | ProtocolError::RequestTimedOut
+                | ProtocolError::UnknownLeaderEpoch,
+            ..
+        } => true,
+        _ => false,
     }
 }
 
 /// Stream consuming data from start offset.
 ///
 /// # Error Handling
-/// If an error is returned by [`fetch_records`](`PartitionClient::fetch_records`) then the stream will emit this error
-/// once and will terminate afterwards.
+/// By default, if an error is returned by [`fetch_records`](`PartitionClient::fetch_records`) then the stream will emit
+/// this error once and will terminate afterwards. The only exception is a stream that started at
+/// [`StartOffset::Earliest`] or [`StartOffset::Latest`] and runs out of range, which resolves its start offset again.
+///
+/// The stream can be made more resilient:
+///
+/// - **[`StreamConsumerBuilder::with_retry`]:** Retry errors that are likely to go away with a backoff.
+/// - **[`StreamConsumerBuilder::with_offset_reset`]:** Jump to the earliest or latest offset if the fetch offset is out
+///   of range.
+///
+/// Errors that are neither retried nor handled by the offset reset policy are still emitted once and terminate the
+/// stream.
+///
+/// # Log Truncation
+/// The stream remembers the leader epoch of the records it fetched. If the partition leader changes or the fetch offset
+/// is out of range, it asks the leader where that epoch ends (see [`PartitionClient::get_epoch_end_offset`]). If the
+/// epoch ended before the fetch offset, the log was truncated, e.g. after an unclean leader election, and some of the
+/// returned records are not part of the log anymore. The stream then emits [`Error::LogTruncation`] and terminates,
+/// unless an [offset reset policy](StreamConsumerBuilder::with_offset_reset) tells it where to continue.
+///
+/// # Seek, Pause and Resume
+/// The position of the stream can be changed and fetching can be paused via a [`StreamConsumerControl`], see
+/// [`control`](Self::control).
 pub struct StreamConsumer {
     client: Arc<dyn FetchClient>,
 
+    /// State shared with [`StreamConsumerControl`].
+    control: Arc<Control>,
+
     min_batch_size: i32,
 
     max_batch_size: i32,
 
     max_wait_ms: i32,
 
+    isolation_level: IsolationLevel,
+
+    /// Set if retriable errors shall be retried.
+    backoff_config: Option<BackoffConfig>,
+
+    /// Backoff since the last successful fetch, if any.
+    backoff: Option<Backoff>,
+
+    offset_reset: Option<OffsetReset>,
+
+    /// The offset was reset due to an offset reset policy and there was no successful fetch since.
+    reset_pending: bool,
+
     start_offset: StartOffset,
 
+    /// Offset to resolve if `next_offset` is not set, overrides `start_offset` until the next successful fetch.
+    reset_to: Option<OffsetAt>,
+
+    end_offset: Option<EndOffset>,
+
+    /// Resolved `end_offset`.
+    end: Option<i64>,
+
+    prefetch_bytes: usize,
+
+    /// Error of a prefetch that is handled once the buffer is drained.
+    prefetch_error: Option<Error>,
+
+    /// Leader epoch of the last fetched record, if any, used to detect log truncation.
+    fetched_epoch: Option<i32>,
+
+    /// Leader epoch of the partition during the last fetch.
+    leader_epoch: Option<i32>,
+
     next_offset: Option<i64>,
 
     next_backoff: Option<Duration>,
@@ -219,91 +621,266 @@ pub struct StreamConsumer {
 
     last_high_watermark: i64,
 
-    buffer: VecDeque<RecordAndOffset>,
+    buffer: Buffer,
 
     fetch_fut: Fuse<BoxFuture<'static, FetchResult>>,
 }
 
+/// Position requested via [`StreamConsumerControl`].
+#[derive(Debug, Clone, Copy)]
+enum Seek {
+    Offset(i64),
+    At(OffsetAt),
+}
+
+#[derive(Debug, Default)]
+struct ControlState {
+    /// Seek that was not applied by the stream yet.
+    seek: Option<Seek>,
+
+    paused: bool,
+
+    /// Position as of the last time the stream was polled.
+    position: Option<i64>,
+}
+
+#[derive(Debug, Default)]
+struct Control {
+    state: Mutex<ControlState>,
+
+    /// Wakes the stream after the state was changed.
+    waker: AtomicWaker,
+}
+
+/// Handle to change the position of a [`StreamConsumer`] and to pause it.
+///
+/// Changes are applied the next time the stream is polled, i.e. they are not racing with a record that is currently
+/// returned. The stream is woken up, so a task that waits for the next record sees the change right away. The handle
+/// can be cloned and sent to other tasks; it has no effect once the stream terminated or was dropped.
+///
+/// # Seek
+/// Seeking discards all records that were fetched but not returned yet and cancels an in-flight fetch, even if it
+/// already holds a response. The next record returned is the first one at or after the new position. If the new
+/// position is out of range, the stream behaves like a stream that was started at that position, i.e. the
+/// [offset reset policy](StreamConsumerBuilder::with_offset_reset) applies. Multiple seeks before the stream is polled
+/// again are coalesced, the last one wins.
+///
+/// # Pause
+/// A paused stream does not return any records and does not fetch new ones, but stays alive (i.e. it is
+/// [pending](Poll::Pending) instead of terminated). An in-flight fetch is cancelled and issued again after
+/// [`resume`](Self::resume), records that were already fetched are kept and returned after resuming. Seeking a paused
+/// stream is possible and takes effect immediately.
+#[derive(Debug, Clone)]
+pub struct StreamConsumerControl {
+    inner: Arc<Control>,
+}
+
+impl StreamConsumerControl {
+    /// Continue at `offset`.
+    pub fn seek(&self, offset: i64) {
+        self.set_seek(Seek::Offset(offset));
+    }
+
+    /// Continue at the offset that `at` resolves to.
+    ///
+    /// The offset is resolved once the stream fetches again, in the same way as the corresponding [`StartOffset`]. In
+    /// particular, a timestamp beyond the end of the log continues at the end, see [`StartOffset::Timestamp`].
+    pub fn seek_to(&self, at: OffsetAt) {
+        self.set_seek(Seek::At(at));
+    }
+
+    fn set_seek(&self, seek: Seek) {
+        self.inner.state.lock().seek = Some(seek);
+        self.inner.waker.wake();
+    }
+
+    /// Stop fetching and returning records until [`resume`](Self::resume) is called.
+    pub fn pause(&self) {
+        self.inner.state.lock().paused = true;
+        self.inner.waker.wake();
+    }
+
+    /// Continue a [paused](Self::pause) stream.
+    pub fn resume(&self) {
+        self.inner.state.lock().paused = false;
+        self.inner.waker.wake();
+    }
+
+    /// The stream is [paused](Self::pause).
+    pub fn is_paused(&self) -> bool {
+        self.inner.state.lock().paused
+    }
+
+    /// Offset of the next record that the stream returns.
+    ///
+    /// This takes a pending [`seek`](Self::seek) into account. Returns `None` if the offset is not known yet, e.g.
+    /// because the start offset or the target of [`seek_to`](Self::seek_to) was not resolved yet.
+    pub fn position(&self) -> Option<i64> {
+        let state = self.inner.state.lock();
+        match state.seek {
+            Some(Seek::Offset(offset)) => Some(offset),
+            Some(Seek::At(_)) => None,
+            None => state.position,
+        }
+    }
+}
+
 impl Stream for StreamConsumer {
     type Item = Result<(RecordAndOffset, i64)>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let poll = self.poll_buffer(cx, Buffer::pop_record);
+        self.publish_position();
+        poll
+    }
+}
+
+/// Fetched batches with the records that were not returned yet.
+#[derive(Debug, Default)]
+struct Buffer {
+    /// Batches are never empty.
+    batches: VecDeque<(RecordBatchMetadata, std::vec::IntoIter<RecordAndOffset>)>,
+
+    /// Approximate size of all records.
+    bytes: usize,
+}
+
+impl Buffer {
+    fn push(&mut self, batch: RecordBatch) {
+        if batch.records.is_empty() {
+            return;
+        }
+        self.bytes += batch
+            .records
+            .iter()
+            .map(|x| x.record.approximate_size())
+            .sum::<usize>();
+        self.batches
+            .push_back((batch.metadata, batch.records.into_iter()));
+    }
+
+    /// Take the next record.
+    fn pop_record(&mut self) -> Option<RecordAndOffset> {
+        let (_metadata, records) = self.batches.front_mut()?;
+        let record = records.next();
+        if records.len() == 0 {
+            self.batches.pop_front();
+        }
+        if let Some(x) = &record {
+            self.bytes -= x.record.approximate_size();
+        }
+        record
+    }
+
+    /// Take the remaining records of the next batch.
+    fn pop_batch(&mut self) -> Option<RecordBatch> {
+        let (metadata, records) = self.batches.pop_front()?;
+        let records: Vec<_> = records.collect();
+        self.bytes -= records
+            .iter()
+            .map(|x| x.record.approximate_size())
+            .sum::<usize>();
+        Some(RecordBatch { metadata, records })
+    }
+
+    /// Offset of the next record.
+    fn next_offset(&self) -> Option<i64> {
+        self.batches
+            .front()
+            .and_then(|(_metadata, records)| records.as_slice().first())
+            .map(|x| x.offset)
+    }
+
+    fn n_records(&self) -> usize {
+        self.batches.iter().map(|(_, records)| records.len()).sum()
+    }
+
+    fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl StreamConsumer {
+    /// Get a handle to control this stream, see [`StreamConsumerControl`].
+    pub fn control(&self) -> StreamConsumerControl {
+        StreamConsumerControl {
+            inner: Arc::clone(&self.control),
+        }
+    }
+
+    /// Fetch data until `pop` returns something from the buffer.
+    fn poll_buffer<T>(
+        &mut self,
+        cx: &mut Context<'_>,
+        pop: fn(&mut Buffer) -> Option<T>,
+    ) -> Poll<Option<Result<(T, i64)>>> {
+        self.control.waker.register(cx.waker());
+
         loop {
             if self.terminated {
+                // seeking a terminated stream has no effect
+                self.control.state.lock().seek = None;
                 return Poll::Ready(None);
             }
-            if let Some(x) = self.buffer.pop_front() {
-                return Poll::Ready(Some(Ok((x, self.last_high_watermark))));
-            }
-
-            if self.fetch_fut.is_terminated() {
-                let next_offset = self.next_offset;
-                let start_offset = self.start_offset;
-                let bytes = (self.min_batch_size)..(self.max_batch_size);
-                let max_wait_ms = self.max_wait_ms;
-                let next_backoff = std::mem::take(&mut self.next_backoff);
-                let client = Arc::clone(&self.client);
-
-                trace!(?start_offset, ?next_offset, "Fetching records at offset");
 
-                self.fetch_fut = FutureExt::fuse(Box::pin(async move {
-                    if let Some(backoff) = next_backoff {
-                        tokio::time::sleep(backoff).await;
-                    }
+            let (seek, paused) = {
+                let mut state = self.control.state.lock();
+                (state.seek.take(), state.paused)
+            };
+            if let Some(seek) = seek {
+                self.seek(seek);
+            }
+            if paused {
+                if !self.fetch_fut.is_terminated() {
+                    debug!("Stream consumer paused, cancelling in-flight fetch");
+                    self.fetch_fut = Fuse::terminated();
+                }
 
-                    let offset = match next_offset {
-                        Some(x) => x,
-                        None => match start_offset {
-                            StartOffset::Earliest => {
-                                let offset = client.get_offset(OffsetAt::Earliest).await?;
-                                debug!(offset, "resolved `earliest` offset");
-                                offset
-                            }
-                            StartOffset::Latest => {
-                                let offset = client.get_offset(OffsetAt::Latest).await?;
-                                debug!(offset, "resolved `latest` offset");
-                                offset
-                            }
-                            StartOffset::At(x) => x,
-                        },
-                    };
+                // woken up by the control handle
+                return Poll::Pending;
+            }
 
-                    let (records_and_offsets, watermark) =
-                        client.fetch_records(offset, bytes, max_wait_ms).await?;
-                    Ok(FetchResultOk {
-                        records_and_offsets,
-                        watermark,
-                        used_offset: offset,
-                    })
-                }));
+            if let Some(x) = pop(&mut self.buffer) {
+                self.prefetch(cx);
+                return Poll::Ready(Some(Ok((x, self.last_high_watermark))));
             }
 
-            let data: FetchResult = futures::ready!(self.fetch_fut.poll_unpin(cx));
+            let data = match self.prefetch_error.take() {
+                Some(e) => Err(e),
+                None => {
+                    if self.fetch_fut.is_terminated() {
+                        if self.reached_end() {
+                            debug!(
+                                end = self.end,
+                                next_offset = self.next_offset,
+                                "Bounded stream consumer reached its end"
+                            );
+                            self.terminated = true;
+                            return Poll::Ready(None);
+                        }
+                        self.start_fetch();
+                    }
+                    futures::ready!(self.fetch_fut.poll_unpin(cx))
+                }
+            };
 
-            match (data, self.start_offset) {
+            match (data, &self.start_offset) {
                 (Ok(inner), _) => {
-                    let FetchResultOk {
-                        mut records_and_offsets,
-                        watermark,
-                        used_offset,
-                    } = inner;
-                    trace!(
-                        high_watermark = watermark,
-                        n_records = records_and_offsets.len(),
-                        "Received records and a high watermark",
-                    );
-
-                    // Remember used offset (might be overwritten if there was any data) so we don't refetch the
-                    // earliest / latest offset for every try. Also fetching the latest offset might be racy otherwise,
-                    // since we'll never be in a position where the latest one can actually be fetched.
-                    self.next_offset = Some(used_offset);
-
-                    // Sort records by offset in case they aren't in order
-                    records_and_offsets.sort_by_key(|x| x.offset);
-                    self.last_high_watermark = watermark;
-                    if let Some(x) = records_and_offsets.last() {
-                        self.next_offset = Some(x.offset + 1);
-                        self.buffer.extend(records_and_offsets)
+                    self.handle_fetched(inner);
+                    continue;
+                }
+                (
+                    Err(
+                        e @ Error::ServerError {
+                            protocol_error: ProtocolError::OffsetOutOfRange,
+                            ..
+                        },
+                    ),
+                    _,
+                ) if self.offset_reset.is_some() => {
+                    if let Err(e) = self.reset_offset(e) {
+                        self.terminated = true;
+                        return Poll::Ready(Some(Err(e)));
                     }
                     continue;
                 }
@@ -332,6 +909,22 @@ impl Stream for StreamConsumer {
 
                     continue;
                 }
+                (Err(e @ Error::LogTruncation { .. }), _)
+                    if matches!(
+                        self.offset_reset,
+                        Some(OffsetReset::Earliest | OffsetReset::Latest)
+                    ) =>
+                {
+                    self.reset_after_truncation(e);
+                    continue;
+                }
+                (Err(e), _) if self.backoff_config.is_some() && is_retriable(&e) => {
+                    if let Err(e) = self.retry(e) {
+                        self.terminated = true;
+                        return Poll::Ready(Some(Err(e)));
+                    }
+                    continue;
+                }
                 // if we have an offset, terminate the stream
                 (Err(e), _) => {
                     self.terminated = true;
@@ -342,51 +935,503 @@ impl Stream for StreamConsumer {
             }
         }
     }
-}
 
-impl std::fmt::Debug for StreamConsumer {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("StreamConsumer")
-            .field("client", &self.client)
-            .field("min_batch_size", &self.min_batch_size)
-            .field("max_batch_size", &self.max_batch_size)
-            .field("max_wait_ms", &self.max_wait_ms)
-            .field("next_offset", &self.next_offset)
-            .field("terminated", &self.terminated)
-            .field("last_high_watermark", &self.last_high_watermark)
-            .field("buffer", &self.buffer)
-            .finish_non_exhaustive()
+    /// A bounded stream returned all records before its end offset.
+    fn reached_end(&self) -> bool {
+        matches!((self.end, self.next_offset), (Some(end), Some(next_offset)) if next_offset >= end)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::time::Duration;
+    /// Start fetching at `next_offset`, or at the start offset if it is not known yet.
+    fn start_fetch(&mut self) {
+        let next_offset = self.next_offset;
+        let reset_to = self.reset_to;
+        let start_offset = self.start_offset.clone();
+        let bytes = (self.min_batch_size)..(self.max_batch_size);
+        let max_wait_ms = self.max_wait_ms;
+        let isolation_level = self.isolation_level;
+        let next_backoff = std::mem::take(&mut self.next_backoff);
+        let end_offset = self.end_offset;
+        let end = self.end;
+        let last_high_watermark = self.last_high_watermark;
+        let fetched_epoch = self.fetched_epoch;
+        let leader_epoch = self.leader_epoch;
+        let client = Arc::clone(&self.client);
+
+        trace!(
+            ?start_offset,
+            ?reset_to,
+            ?next_offset,
+            "Fetching records at offset"
+        );
 
-    use assert_matches::assert_matches;
-    use chrono::{TimeZone, Utc};
-    use futures::{StreamExt, pin_mut};
-    use tokio::sync::{Mutex, mpsc};
+        self.fetch_fut = FutureExt::fuse(Box::pin(async move {
+            if let Some(backoff) = next_backoff {
+                tokio::time::sleep(backoff).await;
+            }
 
-    use crate::{
-        client::error::{Error, ProtocolError, RequestContext},
-        record::Record,
-    };
+            let offset = match (next_offset, reset_to) {
+                (Some(x), _) => x,
+                (None, Some(at)) => {
+                    let offset = client.get_offset(at, isolation_level).await?;
+                    debug!(offset, ?at, "resolved reset offset");
+                    offset
+                }
+                (None, None) => {
+                    resolve_start_offset(client.as_ref(), &start_offset, isolation_level).await?
+                }
+            };
+
+            let end = match (end, end_offset) {
+                (Some(end), _) => Some(end),
+                (None, Some(EndOffset::Latest)) => {
+                    let end = client.get_offset(OffsetAt::Latest, isolation_level).await?;
+                    debug!(end, "resolved `latest` end offset");
+                    Some(end)
+                }
+                (None, Some(EndOffset::At(end))) => Some(end),
+                (None, None) => None,
+            };
+            if end.is_some_and(|end| offset >= end) {
+                // nothing to fetch, don't wait for new records
+                return Ok(FetchResultOk {
+                    fetched: FetchedBatches {
+                        batches: vec![],
+                        high_watermark: last_high_watermark,
+                        last_stable_offset: None,
+                        next_offset: None,
+                        leader_epoch: None,
+                    },
+                    used_offset: offset,
+                    end,
+                });
+            }
 
-    use super::*;
+            let fetched = match client
+                .fetch_batches(offset, bytes, max_wait_ms, isolation_level)
+                .await
+            {
+                Ok(fetched) => fetched,
+                Err(
+                    e @ Error::ServerError {
+                        protocol_error: ProtocolError::OffsetOutOfRange,
+                        ..
+                    },
+                ) => {
+                    // the log might have been truncated below the fetch offset
+                    if let Some(epoch) = fetched_epoch {
+                        check_truncation(client.as_ref(), epoch, offset).await?;
+                    }
+                    return Err(e);
+                }
+                Err(e) => return Err(e),
+            };
+
+            // A new leader might not have all records that we fetched from the previous one, e.g. after an unclean
+            // leader election. The records of this fetch are only used if the logs did not diverge.
+            let leader_changed =
+                fetched.leader_epoch.is_some() && fetched.leader_epoch != leader_epoch;
+            if let Some(epoch) = fetched_epoch.filter(|_| leader_changed) {
+                check_truncation(client.as_ref(), epoch, offset).await?;
+            }
 
-    #[derive(Debug)]
-    struct MockFetch {
-        inner: Arc<Mutex<MockFetchInner>>,
+            Ok(FetchResultOk {
+                fetched,
+                used_offset: offset,
+                end,
+            })
+        }));
     }
 
-    #[derive(Debug)]
+    /// Issue and drive the next fetch while there are still buffered records, if the
+    /// [prefetch budget](StreamConsumerBuilder::with_prefetch_bytes) allows it.
+    ///
+    /// Records of a completed fetch are added to the buffer right away. Errors are kept until the buffer is drained,
+    /// so they are handled in order.
+    fn prefetch(&mut self, cx: &mut Context<'_>) {
+        if self.prefetch_error.is_some() {
+            return;
+        }
+
+        if self.fetch_fut.is_terminated() {
+            if self.buffer.bytes >= self.prefetch_bytes
+                || self.next_offset.is_none()
+                || self.next_backoff.is_some()
+                || self.reached_end()
+            {
+                return;
+            }
+            trace!(
+                buffered_bytes = self.buffer.bytes,
+                prefetch_bytes = self.prefetch_bytes,
+                "Prefetching records",
+            );
+            self.start_fetch();
+        }
+
+        match self.fetch_fut.poll_unpin(cx) {
+            Poll::Ready(Ok(inner)) => self.handle_fetched(inner),
+            Poll::Ready(Err(e)) => self.prefetch_error = Some(e),
+            Poll::Pending => {}
+        }
+    }
+
+    /// Process successful fetch.
+    fn handle_fetched(&mut self, inner: FetchResultOk) {
+        self.backoff = None;
+        self.reset_pending = false;
+        self.reset_to = None;
+
+        let FetchResultOk {
+            fetched:
+                FetchedBatches {
+                    mut batches,
+                    high_watermark: watermark,
+                    last_stable_offset,
+                    next_offset: batches_end,
+                    leader_epoch,
+                },
+            used_offset,
+            end,
+        } = inner;
+        self.end = end;
+        if leader_epoch.is_some() {
+            self.leader_epoch = leader_epoch;
+        }
+        trace!(
+            high_watermark = watermark,
+            n_batches = batches.len(),
+            n_records = batches.iter().map(|b| b.records.len()).sum::<usize>(),
+            "Received records and a high watermark",
+        );
+
+        // Remember used offset (might be overwritten if there was any data) so we don't refetch the
+        // earliest / latest offset for every try. Also fetching the latest offset might be racy otherwise,
+        // since we'll never be in a position where the latest one can actually be fetched.
+        self.next_offset = Some(used_offset);
+
+        // Sort records by offset in case they aren't in order
+        for batch in &mut batches {
+            batch.records.sort_by_key(|x| x.offset);
+            if let Some(end) = end {
+                batch.records.retain(|x| x.offset < end);
+            }
+        }
+        batches.retain(|batch| !batch.records.is_empty());
+        batches.sort_by_key(|batch| batch.records[0].offset);
+        self.last_high_watermark = watermark;
+        if let Some(batch) = batches.last() {
+            self.next_offset = batch.records.last().map(|x| x.offset + 1);
+            self.fetched_epoch =
+                Some(batch.metadata.partition_leader_epoch).filter(|epoch| *epoch >= 0);
+            for batch in batches {
+                self.buffer.push(batch);
+            }
+        }
+
+        // Skip batches that did not result in any records, e.g. transaction markers, so they are not
+        // fetched again.
+        if let Some(batches_end) = batches_end {
+            self.next_offset = self.next_offset.max(Some(batches_end));
+        }
+
+        if let Some(end) = end {
+            // An empty response means that there are no more records up to the end of the readable log.
+            // If the stream end is within that range, the remaining offsets were removed by compaction.
+            let log_end = match self.isolation_level {
+                IsolationLevel::ReadCommitted => last_stable_offset.unwrap_or(watermark),
+                IsolationLevel::ReadUncommitted => watermark,
+            };
+            if batches_end.is_none() && used_offset < end && end <= log_end {
+                debug!(
+                    offset = used_offset,
+                    end, log_end, "No records left before the end offset"
+                );
+                self.next_offset = Some(end);
+            }
+        }
+    }
+
+    /// Move to the position requested via [`StreamConsumerControl`].
+    fn seek(&mut self, seek: Seek) {
+        debug!(
+            ?seek,
+            discarded_records = self.buffer.n_records(),
+            cancelled_fetch = !self.fetch_fut.is_terminated(),
+            "Stream consumer seeking",
+        );
+
+        self.buffer.clear();
+        self.fetch_fut = Fuse::terminated();
+        self.prefetch_error = None;
+        self.next_backoff = None;
+        self.reset_pending = false;
+        self.reset_to = None;
+        self.next_offset = None;
+        self.fetched_epoch = None;
+
+        // Replace the start offset so that an out-of-range seek target is handled like a start offset would be.
+        match seek {
+            Seek::Offset(offset) => {
+                self.start_offset = StartOffset::At(offset);
+                self.next_offset = Some(offset);
+            }
+            Seek::At(OffsetAt::Earliest) => {
+                self.start_offset = StartOffset::Earliest;
+            }
+            Seek::At(OffsetAt::Latest) => {
+                self.start_offset = StartOffset::Latest;
+            }
+            Seek::At(OffsetAt::Timestamp(ts)) => {
+                self.start_offset = StartOffset::Timestamp(ts);
+            }
+        }
+    }
+
+    /// Offset of the next record that the stream returns, if known.
+    fn position(&self) -> Option<i64> {
+        self.buffer.next_offset().or(self.next_offset)
+    }
+
+    fn publish_position(&self) {
+        let position = self.position();
+        self.control.state.lock().position = position;
+    }
+
+    /// Schedule another attempt after the retriable `error`.
+    ///
+    /// Fails if the backoff deadline is exceeded.
+    fn retry(&mut self, error: Error) -> Result<()> {
+        let backoff_config = self
+            .backoff_config
+            .as_ref()
+            .expect("only called if retries are enabled");
+        let backoff = self
+            .backoff
+            .get_or_insert_with(|| Backoff::new(backoff_config));
+
+        match backoff.next() {
+            Some(backoff) => {
+                info!(
+                    e=%error,
+                    backoff_secs = backoff.as_secs(),
+                    "stream consumer encountered non-fatal error - backing off",
+                );
+                self.next_backoff = Some(backoff);
+                Ok(())
+            }
+            None => Err(Error::RetryFailed(BackoffError::DeadlineExceded {
+                deadline: backoff_config.deadline.unwrap_or_default(),
+                source: Box::new(error),
+            })),
+        }
+    }
+
+    /// Apply the [`OffsetReset`] policy after `error`, which must be an [`ProtocolError::OffsetOutOfRange`].
+    ///
+    /// Returns the error if the stream shall terminate.
+    fn reset_offset(&mut self, error: Error) -> Result<()> {
+        let Error::ServerError { response, .. } = &error else {
+            unreachable!("only called for server errors");
+        };
+
+        // where the log ends from our point of view
+        let log_end = match response {
+            Some(ServerErrorResponse::PartitionFetchState {
+                high_watermark,
+                last_stable_offset,
+            }) => match self.isolation_level {
+                IsolationLevel::ReadCommitted => {
+                    Some(last_stable_offset.unwrap_or(*high_watermark))
+                }
+                IsolationLevel::ReadUncommitted => Some(*high_watermark),
+            },
+            _ => None,
+        };
+        let offset = self.next_offset;
+        let offset_reset = self.offset_reset.expect("only called if a policy is set");
+        warn!(
+            ?offset,
+            ?log_end,
+            ?offset_reset,
+            beyond_log_end = matches!((offset, log_end), (Some(o), Some(end)) if o > end),
+            "Fetch offset out of range, resetting offset",
+        );
+
+        self.fetched_epoch = None;
+        match offset_reset {
+            OffsetReset::Earliest => {
+                self.next_offset = None;
+                self.reset_to = Some(OffsetAt::Earliest);
+            }
+            OffsetReset::Latest => match log_end {
+                // no need to ask the broker if it already told us
+                Some(log_end) => {
+                    self.next_offset = Some(log_end);
+                }
+                None => {
+                    self.next_offset = None;
+                    self.reset_to = Some(OffsetAt::Latest);
+                }
+            },
+            OffsetReset::Error => {
+                return Err(error);
+            }
+        }
+
+        // If the reset offset is out of range as well, something is off (e.g. a very aggressive retention). Don't spin.
+        if std::mem::replace(&mut self.reset_pending, true) {
+            self.next_backoff = Some(Duration::from_secs(1));
+        }
+
+        Ok(())
+    }
+
+    /// Continue after `error`, which must be an [`Error::LogTruncation`], according to an [`OffsetReset`] policy other
+    /// than [`OffsetReset::Error`].
+    fn reset_after_truncation(&mut self, error: Error) {
+        let Error::LogTruncation {
+            fetch_offset,
+            truncation_offset,
+            leader_epoch,
+        } = error
+        else {
+            unreachable!("only called for log truncation errors");
+        };
+        let offset_reset = self.offset_reset.expect("only called if a policy is set");
+        warn!(
+            fetch_offset,
+            ?truncation_offset,
+            ?leader_epoch,
+            ?offset_reset,
+            "Log truncation detected, resetting offset",
+        );
+
+        match (truncation_offset, offset_reset) {
+            (Some(offset), _) => {
+                self.next_offset = Some(offset);
+                self.fetched_epoch = leader_epoch;
+            }
+            (None, OffsetReset::Latest) => {
+                self.next_offset = None;
+                self.reset_to = Some(OffsetAt::Latest);
+                self.fetched_epoch = None;
+            }
+            (None, _) => {
+                self.next_offset = None;
+                self.reset_to = Some(OffsetAt::Earliest);
+                self.fetched_epoch = None;
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for StreamConsumer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamConsumer")
+            .field("client", &self.client)
+            .field("min_batch_size", &self.min_batch_size)
+            .field("max_batch_size", &self.max_batch_size)
+            .field("max_wait_ms", &self.max_wait_ms)
+            .field("isolation_level", &self.isolation_level)
+            .field("backoff_config", &self.backoff_config)
+            .field("offset_reset", &self.offset_reset)
+            .field("end_offset", &self.end_offset)
+            .field("end", &self.end)
+            .field("control", &self.control)
+            .field("next_offset", &self.next_offset)
+            .field("terminated", &self.terminated)
+            .field("last_high_watermark", &self.last_high_watermark)
+            .field("buffer", &self.buffer)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Stream consuming record batches from start offset.
+///
+/// Same as [`StreamConsumer`] but returns the records grouped by their [`RecordBatch`], together with the
+/// [batch metadata](RecordBatchMetadata) (e.g. producer ID and sequence number) and the high watermark. Batches
+/// are returned as fetched, so they are not split or merged to meet a certain size. The first batch of a fetch may
+/// contain fewer records than the batch that was written, since records before the fetch offset are removed. Control
+/// batches and batches of aborted transactions are never returned.
+///
+/// Error handling, [seeking and pausing](StreamConsumerControl) work like for [`StreamConsumer`]. Seeking into the
+/// middle of a batch returns the remainder of that batch.
+pub struct BatchStreamConsumer {
+    inner: StreamConsumer,
+}
+
+impl BatchStreamConsumer {
+    /// Get a handle to control this stream, see [`StreamConsumerControl`].
+    pub fn control(&self) -> StreamConsumerControl {
+        self.inner.control()
+    }
+}
+
+impl Stream for BatchStreamConsumer {
+    type Item = Result<(RecordBatch, i64)>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let poll = self.inner.poll_buffer(cx, Buffer::pop_batch);
+        self.inner.publish_position();
+        poll
+    }
+}
+
+impl std::fmt::Debug for BatchStreamConsumer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BatchStreamConsumer")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use assert_matches::assert_matches;
+    use chrono::{TimeZone, Utc};
+    use futures::{StreamExt, pin_mut};
+    use tokio::sync::{Mutex, mpsc};
+
+    use crate::{
+        client::{
+            error::{Error, ProtocolError, RequestContext},
+            partition::Compression,
+        },
+        record::{Record, TimestampType},
+    };
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct MockFetch {
+        inner: Arc<Mutex<MockFetchInner>>,
+    }
+
+    #[derive(Debug)]
     struct MockFetchInner {
         batch_sizes: Vec<usize>,
         stream: mpsc::Receiver<Record>,
         next_err: Option<Error>,
         buffer: Vec<Record>,
         range: (i64, i64),
+        committed: Option<i64>,
+        high_watermark: Option<i64>,
+
+        /// Leader epochs and their start offsets, the last one is the current epoch.
+        epochs: Vec<(i32, i64)>,
+    }
+
+    impl MockFetchInner {
+        fn epoch_at(&self, offset: i64) -> i32 {
+            self.epochs
+                .iter()
+                .rev()
+                .find(|(_, start)| *start <= offset)
+                .map(|(epoch, _)| *epoch)
+                .unwrap_or_default()
+        }
     }
 
     impl MockFetch {
@@ -398,31 +1443,60 @@ mod tests {
                     buffer: Default::default(),
                     next_err,
                     range,
+                    committed: None,
+                    high_watermark: None,
+                    epochs: vec![(0, 0)],
                 })),
             }
         }
 
+        fn with_committed(self, committed: i64) -> Self {
+            self.inner.try_lock().unwrap().committed = Some(committed);
+            self
+        }
+
+        /// Report a high watermark beyond the last record, as if the tail of the log was compacted.
+        fn with_high_watermark(self, high_watermark: i64) -> Self {
+            self.inner.try_lock().unwrap().high_watermark = Some(high_watermark);
+            self
+        }
+
+        /// Elect a new leader that only has the first `log_end` records, as it happens during an unclean leader
+        /// election.
+        async fn elect_leader(&self, log_end: usize) {
+            let mut inner = self.inner.lock().await;
+            while let Ok(x) = inner.stream.try_recv() {
+                inner.buffer.push(x)
+            }
+            inner.buffer.truncate(log_end);
+
+            let epoch = inner.epochs.last().unwrap().0 + 1;
+            inner.epochs.retain(|(_, start)| *start < log_end as i64);
+            inner.epochs.push((epoch, log_end as i64));
+        }
+
         async fn batch_sizes(&self) -> Vec<usize> {
             self.inner.lock().await.batch_sizes.clone()
         }
     }
 
     impl FetchClient for MockFetch {
-        fn fetch_records(
+        fn fetch_batches(
             &self,
             start_offset: i64,
             bytes: Range<i32>,
             max_wait_ms: i32,
-        ) -> BoxFuture<'_, Result<(Vec<RecordAndOffset>, i64)>> {
+            _isolation_level: IsolationLevel,
+        ) -> BoxFuture<'_, Result<FetchedBatches>> {
             let inner = Arc::clone(&self.inner);
             Box::pin(async move {
                 if let Some(err) = inner.lock().await.next_err.take() {
                     return Err(err);
                 }
 
-                println!("MockFetch::fetch_records");
+                println!("MockFetch::fetch_batches");
                 let mut inner = inner.lock().await;
-                println!("MockFetch::fetch_records locked");
+                println!("MockFetch::fetch_batches locked");
 
                 let mut buffer = vec![];
                 let mut buffered = 0;
@@ -491,23 +1565,104 @@ mod tests {
 
                 inner.batch_sizes.push(buffer.len());
 
-                Ok((buffer, inner.buffer.len() as i64 - 1))
+                // return all records as a single batch
+                let batches = match (buffer.first(), buffer.last()) {
+                    (Some(first), Some(last)) => vec![RecordBatch {
+                        metadata: RecordBatchMetadata {
+                            base_offset: first.offset,
+                            last_offset: last.offset,
+                            partition_leader_epoch: inner.epoch_at(last.offset),
+                            first_timestamp: first.record.timestamp,
+                            max_timestamp: last.record.timestamp,
+                            producer_id: -1,
+                            producer_epoch: -1,
+                            base_sequence: -1,
+                            compression: Compression::NoCompression,
+                            timestamp_type: TimestampType::CreateTime,
+                            is_transactional: false,
+                        },
+                        records: buffer,
+                    }],
+                    _ => vec![],
+                };
+
+                Ok(FetchedBatches {
+                    next_offset: batches.last().map(|batch| batch.metadata.last_offset + 1),
+                    batches,
+                    high_watermark: inner
+                        .high_watermark
+                        .unwrap_or(inner.buffer.len() as i64 - 1),
+                    last_stable_offset: None,
+                    leader_epoch: inner.epochs.last().map(|(epoch, _)| *epoch),
+                })
             })
         }
 
-        fn get_offset(&self, at: OffsetAt) -> BoxFuture<'_, Result<i64>> {
+        fn get_offset(
+            &self,
+            at: OffsetAt,
+            _isolation_level: IsolationLevel,
+        ) -> BoxFuture<'_, Result<i64>> {
             let inner = Arc::clone(&self.inner);
 
             Box::pin(async move {
                 match at {
                     OffsetAt::Earliest => Ok(inner.lock().await.range.0),
                     OffsetAt::Latest => Ok(inner.lock().await.range.1),
-                    OffsetAt::Timestamp(_) => {
-                        unreachable!("timestamp based offset is tested in e2e test")
+                    OffsetAt::Timestamp(ts) => {
+                        let mut inner = inner.lock().await;
+                        while let Ok(x) = inner.stream.try_recv() {
+                            inner.buffer.push(x)
+                        }
+
+                        // like Kafka, `-1` if there is no such record
+                        Ok(inner
+                            .buffer
+                            .iter()
+                            .position(|record| record.timestamp >= ts)
+                            .map(|offset| offset as i64)
+                            .unwrap_or(-1))
                     }
                 }
             })
         }
+
+        fn get_committed_offset(&self, group: String) -> BoxFuture<'_, Result<Option<i64>>> {
+            let inner = Arc::clone(&self.inner);
+
+            Box::pin(async move {
+                assert_eq!(group, "my_group");
+                Ok(inner.lock().await.committed)
+            })
+        }
+
+        fn get_epoch_end_offset(&self, leader_epoch: i32) -> BoxFuture<'_, Result<EpochEndOffset>> {
+            let inner = Arc::clone(&self.inner);
+
+            Box::pin(async move {
+                let inner = inner.lock().await;
+
+                // like Kafka, use the largest epoch that is not larger than the requested one
+                let Some(pos) = inner
+                    .epochs
+                    .iter()
+                    .rposition(|(epoch, _)| *epoch <= leader_epoch)
+                else {
+                    return Ok(EpochEndOffset {
+                        leader_epoch: None,
+                        end_offset: None,
+                    });
+                };
+                let end_offset = match inner.epochs.get(pos + 1) {
+                    Some((_, start)) => *start,
+                    None => inner.buffer.len() as i64,
+                };
+                Ok(EpochEndOffset {
+                    leader_epoch: Some(inner.epochs[pos].0),
+                    end_offset: Some(end_offset),
+                })
+            })
+        }
     }
 
     #[tokio::test]
@@ -762,6 +1917,700 @@ mod tests {
         assert_eq!(high_watermark, 2);
     }
 
+    #[tokio::test]
+    async fn test_consumer_committed() {
+        let record = Record {
+            key: Some(vec![0; 4]),
+            value: Some(vec![0; 6]),
+            headers: Default::default(),
+            timestamp: Utc.timestamp_millis_opt(1337).unwrap(),
+        };
+
+        let (sender, receiver) = mpsc::channel(10);
+        let consumer = Arc::new(MockFetch::new(receiver, None, (0, 1_000)).with_committed(1));
+        let mut stream = StreamConsumerBuilder::new_with_client(
+            Arc::<MockFetch>::clone(&consumer),
+            StartOffset::Committed {
+                group: "my_group".into(),
+                fallback: CommittedFallback::Latest,
+            },
+        )
+        .with_max_wait_ms(10)
+        .build();
+
+        sender.send(record.clone()).await.unwrap();
+        sender.send(record.clone()).await.unwrap();
+
+        let unwrap = |e: Result<Option<Result<_, _>>, _>| e.unwrap().unwrap().unwrap();
+        let (record_and_offset, _high_watermark) =
+            unwrap(tokio::time::timeout(Duration::from_secs(1), stream.next()).await);
+        assert_eq!(record_and_offset.offset, 1);
+
+        assert_stream_pending(&mut stream).await;
+    }
+
+    #[tokio::test]
+    async fn test_consumer_committed_fallback() {
+        let record = Record {
+            key: Some(vec![0; 4]),
+            value: Some(vec![0; 6]),
+            headers: Default::default(),
+            timestamp: Utc.timestamp_millis_opt(1337).unwrap(),
+        };
+
+        let (sender, receiver) = mpsc::channel(10);
+        let consumer = Arc::new(MockFetch::new(receiver, None, (2, 1_000)));
+        let mut stream = StreamConsumerBuilder::new_with_client(
+            Arc::<MockFetch>::clone(&consumer),
+            StartOffset::Committed {
+                group: "my_group".into(),
+                fallback: CommittedFallback::Earliest,
+            },
+        )
+        .with_max_wait_ms(10)
+        .build();
+
+        // Write three records, the first two are skipped as start offset is 2 (via "earliest")
+        sender.send(record.clone()).await.unwrap();
+        sender.send(record.clone()).await.unwrap();
+        sender.send(record.clone()).await.unwrap();
+
+        let unwrap = |e: Result<Option<Result<_, _>>, _>| e.unwrap().unwrap().unwrap();
+        let (record_and_offset, _high_watermark) =
+            unwrap(tokio::time::timeout(Duration::from_secs(1), stream.next()).await);
+        assert_eq!(record_and_offset.offset, 2);
+    }
+
+    #[tokio::test]
+    async fn test_consumer_retry() {
+        let record = Record {
+            key: Some(vec![0; 4]),
+            value: Some(vec![0; 6]),
+            headers: Default::default(),
+            timestamp: Utc.timestamp_millis_opt(1337).unwrap(),
+        };
+
+        let e = Error::Request(RequestError::IO(std::io::Error::other("connection reset")));
+        let (sender, receiver) = mpsc::channel(10);
+        let consumer = Arc::new(MockFetch::new(receiver, Some(e), (0, 1_000)));
+        let mut stream = StreamConsumerBuilder::new_with_client(
+            Arc::<MockFetch>::clone(&consumer),
+            StartOffset::At(0),
+        )
+        .with_max_wait_ms(10)
+        .with_retry(BackoffConfig {
+            init_backoff: Duration::from_millis(10),
+            ..Default::default()
+        })
+        .build();
+
+        sender.send(record.clone()).await.unwrap();
+
+        let unwrap = |e: Result<Option<Result<_, _>>, _>| e.unwrap().unwrap().unwrap();
+        let (record_and_offset, _high_watermark) =
+            unwrap(tokio::time::timeout(Duration::from_secs(1), stream.next()).await);
+        assert_eq!(record_and_offset.offset, 0);
+    }
+
+    #[tokio::test]
+    async fn test_consumer_retry_fatal_error() {
+        let e = Error::ServerError {
+            protocol_error: ProtocolError::TopicAuthorizationFailed,
+            error_message: None,
+            request: RequestContext::Partition("foo".into(), 1),
+            response: None,
+            is_virtual: false,
+        };
+        let (_sender, receiver) = mpsc::channel(10);
+        let consumer = Arc::new(MockFetch::new(receiver, Some(e), (0, 1_000)));
+
+        let mut stream = StreamConsumerBuilder::new_with_client(consumer, StartOffset::At(0))
+            .with_retry(BackoffConfig::default())
+            .build();
+
+        let error = stream.next().await.expect("stream not empty").unwrap_err();
+        assert_matches!(
+            error,
+            Error::ServerError {
+                protocol_error: ProtocolError::TopicAuthorizationFailed,
+                ..
+            }
+        );
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_consumer_offset_reset_earliest() {
+        let record = Record {
+            key: Some(vec![0; 4]),
+            value: Some(vec![0; 6]),
+            headers: Default::default(),
+            timestamp: Utc.timestamp_millis_opt(1337).unwrap(),
+        };
+
+        // records before offset 2 were deleted
+        let e = Error::ServerError {
+            protocol_error: ProtocolError::OffsetOutOfRange,
+            error_message: None,
+            request: RequestContext::Partition("foo".into(), 1),
+            response: Some(ServerErrorResponse::PartitionFetchState {
+                high_watermark: 3,
+                last_stable_offset: None,
+            }),
+            is_virtual: false,
+        };
+        let (sender, receiver) = mpsc::channel(10);
+        let consumer = Arc::new(MockFetch::new(receiver, Some(e), (2, 1_000)));
+        let mut stream = StreamConsumerBuilder::new_with_client(
+            Arc::<MockFetch>::clone(&consumer),
+            StartOffset::At(0),
+        )
+        .with_max_wait_ms(10)
+        .with_offset_reset(OffsetReset::Earliest)
+        .build();
+
+        for _ in 0..3 {
+            sender.send(record.clone()).await.unwrap();
+        }
+
+        let unwrap = |e: Result<Option<Result<_, _>>, _>| e.unwrap().unwrap().unwrap();
+        let (record_and_offset, _high_watermark) =
+            unwrap(tokio::time::timeout(Duration::from_secs(1), stream.next()).await);
+        assert_eq!(record_and_offset.offset, 2);
+    }
+
+    #[tokio::test]
+    async fn test_consumer_offset_reset_latest() {
+        let record = Record {
+            key: Some(vec![0; 4]),
+            value: Some(vec![0; 6]),
+            headers: Default::default(),
+            timestamp: Utc.timestamp_millis_opt(1337).unwrap(),
+        };
+
+        // the log end is taken from the error, not from the (bogus) range of the mock
+        let e = Error::ServerError {
+            protocol_error: ProtocolError::OffsetOutOfRange,
+            error_message: None,
+            request: RequestContext::Partition("foo".into(), 1),
+            response: Some(ServerErrorResponse::PartitionFetchState {
+                high_watermark: 4,
+                last_stable_offset: Some(3),
+            }),
+            is_virtual: false,
+        };
+        let (sender, receiver) = mpsc::channel(10);
+        let consumer = Arc::new(MockFetch::new(receiver, Some(e), (0, 1_000)));
+        let mut stream = StreamConsumerBuilder::new_with_client(
+            Arc::<MockFetch>::clone(&consumer),
+            StartOffset::At(10),
+        )
+        .with_max_wait_ms(10)
+        .with_offset_reset(OffsetReset::Latest)
+        .build();
+
+        for _ in 0..5 {
+            sender.send(record.clone()).await.unwrap();
+        }
+
+        // read-committed consumers continue at the last stable offset
+        let unwrap = |e: Result<Option<Result<_, _>>, _>| e.unwrap().unwrap().unwrap();
+        let (record_and_offset, _high_watermark) =
+            unwrap(tokio::time::timeout(Duration::from_secs(1), stream.next()).await);
+        assert_eq!(record_and_offset.offset, 3);
+    }
+
+    #[tokio::test]
+    async fn test_consumer_offset_reset_error() {
+        let e = Error::ServerError {
+            protocol_error: ProtocolError::OffsetOutOfRange,
+            error_message: None,
+            request: RequestContext::Partition("foo".into(), 1),
+            response: None,
+            is_virtual: true,
+        };
+        let (_sender, receiver) = mpsc::channel(10);
+        let consumer = Arc::new(MockFetch::new(receiver, Some(e), (0, 1_000)));
+
+        // policy overrides the default handling of `StartOffset::Earliest`
+        let mut stream = StreamConsumerBuilder::new_with_client(consumer, StartOffset::Earliest)
+            .with_offset_reset(OffsetReset::Error)
+            .build();
+
+        let error = stream.next().await.expect("stream not empty").unwrap_err();
+        assert_matches!(
+            error,
+            Error::ServerError {
+                protocol_error: ProtocolError::OffsetOutOfRange,
+                ..
+            }
+        );
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_consumer_seek() {
+        let record = Record {
+            key: Some(vec![0; 4]),
+            value: Some(vec![0; 6]),
+            headers: Default::default(),
+            timestamp: Utc.timestamp_millis_opt(1337).unwrap(),
+        };
+
+        let (sender, receiver) = mpsc::channel(10);
+        let consumer = Arc::new(MockFetch::new(receiver, None, (0, 1_000)));
+        let mut stream = StreamConsumerBuilder::new_with_client(consumer, StartOffset::At(0))
+            .with_max_wait_ms(10)
+            .build();
+        let control = stream.control();
+        assert_eq!(control.position(), None);
+
+        for _ in 0..3 {
+            sender.send(record.clone()).await.unwrap();
+        }
+
+        let (record_and_offset, _) = stream.next().await.unwrap().unwrap();
+        assert_eq!(record_and_offset.offset, 0);
+        assert_eq!(control.position(), Some(1));
+
+        // buffered records are discarded
+        control.seek(0);
+        assert_eq!(control.position(), Some(0));
+        let (record_and_offset, _) = stream.next().await.unwrap().unwrap();
+        assert_eq!(record_and_offset.offset, 0);
+        let (record_and_offset, _) = stream.next().await.unwrap().unwrap();
+        assert_eq!(record_and_offset.offset, 1);
+
+        // last seek wins
+        control.seek(0);
+        control.seek(2);
+        let (record_and_offset, _) = stream.next().await.unwrap().unwrap();
+        assert_eq!(record_and_offset.offset, 2);
+
+        control.seek_to(OffsetAt::Earliest);
+        assert_eq!(control.position(), None);
+        let (record_and_offset, _) = stream.next().await.unwrap().unwrap();
+        assert_eq!(record_and_offset.offset, 0);
+
+        // seek beyond the available records
+        control.seek(4);
+        assert_stream_pending(&mut stream).await;
+        assert_eq!(control.position(), Some(4));
+
+        sender.send(record.clone()).await.unwrap();
+        sender.send(record.clone()).await.unwrap();
+        let (record_and_offset, _) = stream.next().await.unwrap().unwrap();
+        assert_eq!(record_and_offset.offset, 4);
+    }
+
+    #[tokio::test]
+    async fn test_consumer_pause_resume() {
+        let record = Record {
+            key: Some(vec![0; 4]),
+            value: Some(vec![0; 6]),
+            headers: Default::default(),
+            timestamp: Utc.timestamp_millis_opt(1337).unwrap(),
+        };
+
+        let (sender, receiver) = mpsc::channel(10);
+        let consumer = Arc::new(MockFetch::new(receiver, None, (0, 1_000)));
+        let mut stream = StreamConsumerBuilder::new_with_client(consumer, StartOffset::At(0))
+            .with_max_wait_ms(10)
+            .build();
+        let control = stream.control();
+
+        sender.send(record.clone()).await.unwrap();
+        sender.send(record.clone()).await.unwrap();
+
+        let (record_and_offset, _) = stream.next().await.unwrap().unwrap();
+        assert_eq!(record_and_offset.offset, 0);
+
+        // buffered record is held back
+        control.pause();
+        assert!(control.is_paused());
+        assert_stream_pending(&mut stream).await;
+        assert_eq!(control.position(), Some(1));
+
+        control.resume();
+        assert!(!control.is_paused());
+        let (record_and_offset, _) = stream.next().await.unwrap().unwrap();
+        assert_eq!(record_and_offset.offset, 1);
+
+        // new records are not fetched while paused
+        control.pause();
+        sender.send(record.clone()).await.unwrap();
+        assert_stream_pending(&mut stream).await;
+        assert_eq!(control.position(), Some(2));
+
+        // a waiting task is woken up on resume
+        let control_captured = control.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            control_captured.resume();
+        });
+        let (record_and_offset, _) = tokio::time::timeout(Duration::from_secs(1), stream.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(record_and_offset.offset, 2);
+    }
+
+    #[tokio::test]
+    async fn test_consumer_batched() {
+        let record = Record {
+            key: Some(vec![0; 4]),
+            value: Some(vec![0; 6]),
+            headers: Default::default(),
+            timestamp: Utc.timestamp_millis_opt(1337).unwrap(),
+        };
+
+        let (sender, receiver) = mpsc::channel(10);
+        let consumer = Arc::new(MockFetch::new(receiver, None, (0, 1_000)));
+        let mut stream = StreamConsumerBuilder::new_with_client(consumer, StartOffset::At(1))
+            .with_max_wait_ms(10)
+            .build_batched();
+        let control = stream.control();
+
+        for _ in 0..3 {
+            sender.send(record.clone()).await.unwrap();
+        }
+
+        let (batch, high_watermark) = stream.next().await.unwrap().unwrap();
+        assert_eq!(batch.metadata.base_offset, 1);
+        assert_eq!(batch.metadata.last_offset, 2);
+        assert_eq!(
+            batch.records.iter().map(|r| r.offset).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(high_watermark, 2);
+        assert_eq!(control.position(), Some(3));
+        assert_stream_pending(&mut stream).await;
+
+        control.seek(0);
+        let (batch, _) = stream.next().await.unwrap().unwrap();
+        assert_eq!(
+            batch.records.iter().map(|r| r.offset).collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_consumer_timestamp() {
+        let record = |ts| Record {
+            key: Some(vec![0; 4]),
+            value: Some(vec![0; 6]),
+            headers: Default::default(),
+            timestamp: Utc.timestamp_millis_opt(ts).unwrap(),
+        };
+
+        let (sender, receiver) = mpsc::channel(10);
+        for ts in [10, 20, 30] {
+            sender.send(record(ts)).await.unwrap();
+        }
+        let consumer = Arc::new(MockFetch::new(receiver, None, (0, 3)));
+
+        let mut stream = StreamConsumerBuilder::new_with_client(
+            Arc::<MockFetch>::clone(&consumer),
+            StartOffset::Timestamp(Utc.timestamp_millis_opt(15).unwrap()),
+        )
+        .with_max_wait_ms(10)
+        .build();
+        let (record_and_offset, _) = stream.next().await.unwrap().unwrap();
+        assert_eq!(record_and_offset.offset, 1);
+
+        // beyond the end of the log
+        let mut stream = StreamConsumerBuilder::new_with_client(
+            Arc::<MockFetch>::clone(&consumer),
+            StartOffset::Timestamp(Utc.timestamp_millis_opt(40).unwrap()),
+        )
+        .with_max_wait_ms(10)
+        .build();
+        let control = stream.control();
+        assert_stream_pending(&mut stream).await;
+
+        // new records are returned, even with an earlier timestamp
+        sender.send(record(5)).await.unwrap();
+        let (record_and_offset, _) = stream.next().await.unwrap().unwrap();
+        assert_eq!(record_and_offset.offset, 3);
+        assert_eq!(control.position(), Some(4));
+
+        // seek to timestamp
+        control.seek_to(OffsetAt::Timestamp(Utc.timestamp_millis_opt(30).unwrap()));
+        let (record_and_offset, _) = stream.next().await.unwrap().unwrap();
+        assert_eq!(record_and_offset.offset, 2);
+    }
+
+    #[tokio::test]
+    async fn test_consumer_bounded() {
+        let record = Record {
+            key: Some(vec![0; 4]),
+            value: Some(vec![0; 6]),
+            headers: Default::default(),
+            timestamp: Utc.timestamp_millis_opt(1337).unwrap(),
+        };
+
+        let (sender, receiver) = mpsc::channel(10);
+        for _ in 0..5 {
+            sender.send(record.clone()).await.unwrap();
+        }
+        let consumer = Arc::new(MockFetch::new(receiver, None, (0, 3)));
+
+        // `latest` is resolved to 3, records after it are not returned
+        let stream = StreamConsumerBuilder::new_with_client(
+            Arc::<MockFetch>::clone(&consumer),
+            StartOffset::Earliest,
+        )
+        .with_max_wait_ms(10)
+        .with_end_offset(EndOffset::Latest)
+        .build();
+        let offsets: Vec<_> = stream.map(|r| r.unwrap().0.offset).collect().await;
+        assert_eq!(offsets, vec![0, 1, 2]);
+
+        let stream = StreamConsumerBuilder::new_with_client(
+            Arc::<MockFetch>::clone(&consumer),
+            StartOffset::At(3),
+        )
+        .with_max_wait_ms(10)
+        .with_end_offset(EndOffset::At(5))
+        .build_batched();
+        let offsets: Vec<_> = stream
+            .map(|r| r.unwrap().0.records.into_iter().map(|r| r.offset))
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .flatten()
+            .collect();
+        assert_eq!(offsets, vec![3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_consumer_bounded_empty() {
+        let (_sender, receiver) = mpsc::channel(10);
+        let consumer = Arc::new(MockFetch::new(receiver, None, (0, 0)));
+
+        // start offset at the end, no fetch is issued
+        let mut stream = StreamConsumerBuilder::new_with_client(
+            Arc::<MockFetch>::clone(&consumer),
+            StartOffset::Latest,
+        )
+        .with_max_wait_ms(10_000)
+        .with_end_offset(EndOffset::Latest)
+        .build();
+        assert!(
+            tokio::time::timeout(Duration::from_secs(1), stream.next())
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(consumer.batch_sizes().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_consumer_bounded_compacted_tail() {
+        let record = Record {
+            key: Some(vec![0; 4]),
+            value: Some(vec![0; 6]),
+            headers: Default::default(),
+            timestamp: Utc.timestamp_millis_opt(1337).unwrap(),
+        };
+
+        let (sender, receiver) = mpsc::channel(10);
+        for _ in 0..2 {
+            sender.send(record.clone()).await.unwrap();
+        }
+
+        // offsets 2 to 4 are gone
+        let consumer = Arc::new(MockFetch::new(receiver, None, (0, 5)).with_high_watermark(5));
+        let stream = StreamConsumerBuilder::new_with_client(
+            Arc::<MockFetch>::clone(&consumer),
+            StartOffset::Earliest,
+        )
+        .with_max_wait_ms(10)
+        .with_end_offset(EndOffset::Latest)
+        .build();
+        let offsets: Vec<_> = tokio::time::timeout(
+            Duration::from_secs(1),
+            stream.map(|r| r.unwrap().0.offset).collect::<Vec<_>>(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(offsets, vec![0, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_consumer_prefetch() {
+        // 10 bytes each
+        let record = Record {
+            key: Some(vec![0; 4]),
+            value: Some(vec![0; 6]),
+            headers: Default::default(),
+            timestamp: Utc.timestamp_millis_opt(1337).unwrap(),
+        };
+
+        for (prefetch_bytes, expected) in [(0, vec![2]), (10, vec![2]), (15, vec![2, 2])] {
+            let (sender, receiver) = mpsc::channel(10);
+            for _ in 0..4 {
+                sender.send(record.clone()).await.unwrap();
+            }
+            let consumer = Arc::new(MockFetch::new(receiver, None, (0, 1_000)));
+            let mut stream = StreamConsumerBuilder::new_with_client(
+                Arc::<MockFetch>::clone(&consumer),
+                StartOffset::At(0),
+            )
+            .with_max_wait_ms(10)
+            .with_max_batch_size(20)
+            .with_prefetch_bytes(prefetch_bytes)
+            .build();
+
+            let (record_and_offset, _) = stream.next().await.unwrap().unwrap();
+            assert_eq!(record_and_offset.offset, 0);
+            assert_eq!(consumer.batch_sizes().await, expected, "{prefetch_bytes}");
+
+            // records are returned in order either way
+            for offset in 1..4 {
+                let (record_and_offset, _) = stream.next().await.unwrap().unwrap();
+                assert_eq!(record_and_offset.offset, offset);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_consumer_prefetch_error() {
+        let record = Record {
+            key: Some(vec![0; 4]),
+            value: Some(vec![0; 6]),
+            headers: Default::default(),
+            timestamp: Utc.timestamp_millis_opt(1337).unwrap(),
+        };
+
+        let (sender, receiver) = mpsc::channel(10);
+        for _ in 0..4 {
+            sender.send(record.clone()).await.unwrap();
+        }
+        let consumer = Arc::new(MockFetch::new(receiver, None, (0, 1_000)));
+        let mut stream = StreamConsumerBuilder::new_with_client(
+            Arc::<MockFetch>::clone(&consumer),
+            StartOffset::At(0),
+        )
+        .with_max_wait_ms(10)
+        .with_max_batch_size(20)
+        .with_prefetch_bytes(100)
+        .build();
+
+        let (record_and_offset, _) = stream.next().await.unwrap().unwrap();
+        assert_eq!(record_and_offset.offset, 0);
+
+        // next prefetch fails
+        consumer.inner.lock().await.next_err = Some(Error::ServerError {
+            protocol_error: ProtocolError::CorruptMessage,
+            error_message: None,
+            request: RequestContext::Partition("foo".into(), 1),
+            response: None,
+            is_virtual: true,
+        });
+
+        // buffered records are returned first
+        for offset in 1..4 {
+            let (record_and_offset, _) = stream.next().await.unwrap().unwrap();
+            assert_eq!(record_and_offset.offset, offset);
+        }
+        assert_matches!(
+            stream.next().await.unwrap(),
+            Err(Error::ServerError {
+                protocol_error: ProtocolError::CorruptMessage,
+                ..
+            })
+        );
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_consumer_log_truncation() {
+        let record = Record {
+            key: Some(vec![0; 4]),
+            value: Some(vec![0; 6]),
+            headers: Default::default(),
+            timestamp: Utc.timestamp_millis_opt(1337).unwrap(),
+        };
+
+        let (sender, receiver) = mpsc::channel(10);
+        for _ in 0..5 {
+            sender.send(record.clone()).await.unwrap();
+        }
+        let consumer = Arc::new(MockFetch::new(receiver, None, (0, 1_000)));
+        let mut stream = StreamConsumerBuilder::new_with_client(
+            Arc::<MockFetch>::clone(&consumer),
+            StartOffset::At(0),
+        )
+        .with_max_wait_ms(10)
+        .build();
+
+        for offset in 0..5 {
+            let (record_and_offset, _) = stream.next().await.unwrap().unwrap();
+            assert_eq!(record_and_offset.offset, offset);
+        }
+
+        // the new leader has all records
+        consumer.elect_leader(5).await;
+        sender.send(record.clone()).await.unwrap();
+        let (record_and_offset, _) = stream.next().await.unwrap().unwrap();
+        assert_eq!(record_and_offset.offset, 5);
+
+        // the new leader lost the last three records
+        consumer.elect_leader(3).await;
+        sender.send(record.clone()).await.unwrap();
+        assert_matches!(
+            stream.next().await.unwrap(),
+            Err(Error::LogTruncation {
+                fetch_offset: 6,
+                truncation_offset: Some(3),
+                leader_epoch: Some(0),
+            })
+        );
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_consumer_log_truncation_reset() {
+        let record = Record {
+            key: Some(vec![0; 4]),
+            value: Some(vec![0; 6]),
+            headers: Default::default(),
+            timestamp: Utc.timestamp_millis_opt(1337).unwrap(),
+        };
+        let diverged = Record {
+            value: Some(vec![1; 6]),
+            ..record.clone()
+        };
+
+        let (sender, receiver) = mpsc::channel(10);
+        for _ in 0..5 {
+            sender.send(record.clone()).await.unwrap();
+        }
+        let consumer = Arc::new(MockFetch::new(receiver, None, (0, 1_000)));
+        let mut stream = StreamConsumerBuilder::new_with_client(
+            Arc::<MockFetch>::clone(&consumer),
+            StartOffset::At(0),
+        )
+        .with_max_wait_ms(10)
+        .with_offset_reset(OffsetReset::Earliest)
+        .build();
+
+        for offset in 0..5 {
+            let (record_and_offset, _) = stream.next().await.unwrap().unwrap();
+            assert_eq!(record_and_offset.offset, offset);
+        }
+
+        consumer.elect_leader(3).await;
+        sender.send(diverged.clone()).await.unwrap();
+
+        // continues at the truncation point instead of the earliest offset
+        let (record_and_offset, _) = stream.next().await.unwrap().unwrap();
+        assert_eq!(record_and_offset.offset, 3);
+        assert_eq!(record_and_offset.record, diverged);
+    }
+
     /// Assert that given stream is pending.
     ///
     /// This will will try to poll the stream for a bit to ensure that async IO has a chance to catch up.
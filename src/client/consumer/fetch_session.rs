@@ -0,0 +1,261 @@
+//! Client side of incremental fetch sessions, see [KIP-227].
+//!
+//! Within a session, the broker remembers the partitions (and their fetch parameters) of the previous request. A
+//! request only lists partitions that were added or whose fetch offset changed, and the response only contains
+//! partitions with new data or errors.
+//!
+//! [KIP-227]: https://cwiki.apache.org/confluence/display/KAFKA/KIP-227%3A+Introduce+Incremental+FetchRequests+to+Increase+Partition+Scalability
+use std::collections::BTreeMap;
+
+use tracing::debug;
+
+/// Topic and partition.
+type Key = (String, i32);
+
+/// Fetch parameters of a single partition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct PartitionFetch {
+    pub(super) fetch_offset: i64,
+    pub(super) max_bytes: i32,
+}
+
+/// Session-related parts of the next fetch request.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct SessionRequest {
+    pub(super) session_id: i32,
+    pub(super) session_epoch: i32,
+
+    /// Partitions that must be listed in the request.
+    pub(super) partitions: Vec<(Key, PartitionFetch)>,
+
+    /// Partitions that shall be removed from the session.
+    pub(super) forgotten: Vec<Key>,
+
+    /// The response may omit partitions without changes.
+    pub(super) incremental: bool,
+}
+
+/// Fetch session with a single broker.
+///
+/// A session is created by a full fetch request and is used for every following request until it fails. Only one
+/// request may be in flight at a time.
+#[derive(Debug, Default)]
+pub(super) struct FetchSession {
+    /// Session ID assigned by the broker, `0` if there is no session.
+    id: i32,
+
+    /// Epoch of the next request.
+    epoch: i32,
+
+    /// Partitions of the session, as known by the broker.
+    partitions: BTreeMap<Key, PartitionFetch>,
+
+    /// Partitions of the session once the in-flight request succeeded.
+    pending: Option<BTreeMap<Key, PartitionFetch>>,
+}
+
+impl FetchSession {
+    /// Prepare a request that fetches exactly the `wanted` partitions.
+    pub(super) fn next_request(&mut self, wanted: Vec<(Key, PartitionFetch)>) -> SessionRequest {
+        let next: BTreeMap<_, _> = wanted.iter().cloned().collect();
+
+        let request = if self.id == 0 {
+            // full request, asking the broker to create a new session
+            SessionRequest {
+                session_id: 0,
+                session_epoch: 0,
+                partitions: wanted,
+                forgotten: vec![],
+                incremental: false,
+            }
+        } else {
+            let forgotten = self
+                .partitions
+                .keys()
+                .filter(|key| !next.contains_key(*key))
+                .cloned()
+                .collect();
+            let partitions = wanted
+                .into_iter()
+                .filter(|(key, fetch)| self.partitions.get(key) != Some(fetch))
+                .collect();
+
+            SessionRequest {
+                session_id: self.id,
+                session_epoch: self.epoch,
+                partitions,
+                forgotten,
+                incremental: true,
+            }
+        };
+
+        self.pending = Some(next);
+        request
+    }
+
+    /// The request returned by [`next_request`](Self::next_request) succeeded.
+    ///
+    /// `session_id` is the ID from the response, `None` if the broker does not support sessions.
+    pub(super) fn complete(&mut self, session_id: Option<i32>) {
+        let partitions = self.pending.take().unwrap_or_default();
+
+        match session_id.unwrap_or_default() {
+            0 => {
+                if self.id != 0 {
+                    debug!(session_id = self.id, "Broker closed fetch session");
+                }
+                self.reset();
+            }
+            id if self.id == 0 || self.id == id => {
+                if self.id == 0 {
+                    debug!(session_id = id, "Created fetch session");
+                }
+                self.id = id;
+                self.epoch = next_epoch(self.epoch);
+                self.partitions = partitions;
+            }
+            id => {
+                debug!(
+                    session_id = self.id,
+                    response_session_id = id,
+                    "Broker responded with a different fetch session",
+                );
+                self.reset();
+            }
+        }
+    }
+
+    /// Drop the session, e.g. because the broker does not know it anymore or the state of the last request is unknown.
+    ///
+    /// The next request will be a full request that creates a new session.
+    pub(super) fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Epoch following `epoch`, skipping `0` (new session) and negative values (close session) on overflow.
+fn next_epoch(epoch: i32) -> i32 {
+    epoch.checked_add(1).unwrap_or(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(partition: i32) -> Key {
+        ("t".to_owned(), partition)
+    }
+
+    fn fetch(fetch_offset: i64) -> PartitionFetch {
+        PartitionFetch {
+            fetch_offset,
+            max_bytes: 100,
+        }
+    }
+
+    #[test]
+    fn test_session_lifecycle() {
+        let mut session = FetchSession::default();
+
+        // first request is a full one
+        let request = session.next_request(vec![(key(0), fetch(0)), (key(1), fetch(10))]);
+        assert_eq!(
+            request,
+            SessionRequest {
+                session_id: 0,
+                session_epoch: 0,
+                partitions: vec![(key(0), fetch(0)), (key(1), fetch(10))],
+                forgotten: vec![],
+                incremental: false,
+            }
+        );
+        session.complete(Some(42));
+
+        // only changed partitions are sent
+        let request = session.next_request(vec![(key(0), fetch(5)), (key(1), fetch(10))]);
+        assert_eq!(
+            request,
+            SessionRequest {
+                session_id: 42,
+                session_epoch: 1,
+                partitions: vec![(key(0), fetch(5))],
+                forgotten: vec![],
+                incremental: true,
+            }
+        );
+        session.complete(Some(42));
+
+        // partition added and removed
+        let request = session.next_request(vec![(key(0), fetch(5)), (key(2), fetch(0))]);
+        assert_eq!(
+            request,
+            SessionRequest {
+                session_id: 42,
+                session_epoch: 2,
+                partitions: vec![(key(2), fetch(0))],
+                forgotten: vec![key(1)],
+                incremental: true,
+            }
+        );
+        session.complete(Some(42));
+
+        // nothing changed
+        let request = session.next_request(vec![(key(0), fetch(5)), (key(2), fetch(0))]);
+        assert_eq!(request.session_epoch, 3);
+        assert!(request.partitions.is_empty());
+        assert!(request.forgotten.is_empty());
+
+        // failed request falls back to a full request
+        session.reset();
+        let request = session.next_request(vec![(key(0), fetch(5)), (key(2), fetch(0))]);
+        assert_eq!(
+            request,
+            SessionRequest {
+                session_id: 0,
+                session_epoch: 0,
+                partitions: vec![(key(0), fetch(5)), (key(2), fetch(0))],
+                forgotten: vec![],
+                incremental: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_no_session() {
+        let mut session = FetchSession::default();
+
+        // broker does not support sessions or did not create one
+        for session_id in [None, Some(0)] {
+            let request = session.next_request(vec![(key(0), fetch(0))]);
+            assert!(!request.incremental);
+            session.complete(session_id);
+
+            let request = session.next_request(vec![(key(0), fetch(0))]);
+            assert!(!request.incremental);
+            assert_eq!(request.partitions, vec![(key(0), fetch(0))]);
+            session.complete(session_id);
+        }
+    }
+
+    #[test]
+    fn test_session_closed_by_broker() {
+        let mut session = FetchSession::default();
+        session.next_request(vec![(key(0), fetch(0))]);
+        session.complete(Some(1));
+
+        let request = session.next_request(vec![(key(0), fetch(0))]);
+        assert!(request.incremental);
+        session.complete(Some(0));
+
+        let request = session.next_request(vec![(key(0), fetch(0))]);
+        assert!(!request.incremental);
+        assert_eq!(request.session_id, 0);
+    }
+
+    #[test]
+    fn test_next_epoch() {
+        assert_eq!(next_epoch(0), 1);
+        assert_eq!(next_epoch(1), 2);
+        assert_eq!(next_epoch(i32::MAX), 1);
+    }
+}
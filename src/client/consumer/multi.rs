@@ -0,0 +1,1051 @@
+//! Consumer that reads many partitions using one fetch request per broker.
+//!
+//! The assigned partitions are grouped by their leader and every broker is asked for the data of all partitions it
+//! leads in a single request. Brokers are fetched from independently, so a broker without new data does not delay the
+//! records from the others.
+//!
+//! If the broker supports it, an incremental fetch session ([KIP-227]) is used for every broker. After the first
+//! request, only partitions whose fetch offset changed are sent and the broker only returns partitions with new data.
+//! If the broker loses the session, the consumer falls back to a full request and creates a new one.
+//!
+//! [KIP-227]: https://cwiki.apache.org/confluence/display/KAFKA/KIP-227%3A+Introduce+Incremental+FetchRequests+to+Increase+Partition+Scalability
+//!
+//! # Usage
+//! ```no_run
+//! # async fn test() {
+//! use futures::StreamExt;
+//! use rskafka::client::{ClientBuilder, consumer::StartOffset};
+//!
+//! let connection = "localhost:9093".to_owned();
+//! let client = ClientBuilder::new(vec![connection]).build().await.unwrap();
+//!
+//! let mut stream = client
+//!     .multi_partition_consumer()
+//!     .with_partition("my_topic", 0, StartOffset::Earliest)
+//!     .with_partition("my_topic", 1, StartOffset::Earliest)
+//!     .with_partition("other_topic", 0, StartOffset::Latest)
+//!     .with_max_wait_ms(100)
+//!     .build();
+//!
+//! let record = stream
+//!     .next()
+//!     .await
+//!     .expect("some records")
+//!     .expect("no error");
+//! println!(
+//!     "topic={} partition={} offset={}",
+//!     record.topic, record.partition, record.record.offset,
+//! );
+//! # }
+//! ```
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures::{
+    Stream, StreamExt,
+    future::{BoxFuture, join_all},
+    stream::FuturesUnordered,
+};
+use tracing::{debug, info, trace, warn};
+
+use super::{
+    StartOffset,
+    fetch_session::{FetchSession, PartitionFetch, SessionRequest},
+    resolve_start_offset,
+};
+use crate::{
+    backoff::{Backoff, BackoffConfig, BackoffError},
+    client::{
+        error::{Error, ProtocolError, RequestContext, Result},
+        metadata_cache::MetadataCacheGeneration,
+        partition::{
+            IsolationLevel, PartitionClient, UnknownTopicHandling, broker_wait, extract_records,
+            process_fetch_partition_response,
+        },
+    },
+    connection::{BrokerConnector, MetadataLookupMode},
+    messenger::{RequestError, TimeoutOverride},
+    protocol::{
+        messages::{
+            FetchRequest, FetchRequestForgottenTopic, FetchRequestPartition, FetchRequestTopic,
+            FetchResponse, MetadataResponse, NORMAL_CONSUMER,
+        },
+        primitives::{Int32, Int64, String_},
+    },
+    record::RecordAndOffset,
+};
+
+/// Topic and partition.
+type Key = (String, i32);
+
+/// Builder for [`MultiPartitionConsumer`].
+#[derive(Debug)]
+pub struct MultiPartitionConsumerBuilder {
+    brokers: Arc<BrokerConnector>,
+
+    backoff_config: Arc<BackoffConfig>,
+
+    unknown_topic_handling: UnknownTopicHandling,
+
+    isolation_level: IsolationLevel,
+
+    partitions: BTreeMap<Key, StartOffset>,
+
+    max_wait_ms: i32,
+
+    min_batch_size: i32,
+
+    max_batch_size: i32,
+
+    request_timeout: TimeoutOverride,
+}
+
+impl MultiPartitionConsumerBuilder {
+    pub(crate) fn new(brokers: Arc<BrokerConnector>, backoff_config: Arc<BackoffConfig>) -> Self {
+        Self {
+            brokers,
+            backoff_config,
+            unknown_topic_handling: UnknownTopicHandling::Retry,
+            isolation_level: IsolationLevel::default(),
+            partitions: BTreeMap::new(),
+            // same defaults as the StreamConsumer
+            max_wait_ms: 500,
+            min_batch_size: 1,
+            max_batch_size: 52428800,
+            request_timeout: TimeoutOverride::Default,
+        }
+    }
+
+    /// Consume `partition` of `topic`, starting at `start_offset`.
+    ///
+    /// Adding the same partition twice replaces the start offset.
+    pub fn with_partition(
+        mut self,
+        topic: impl Into<String>,
+        partition: i32,
+        start_offset: StartOffset,
+    ) -> Self {
+        self.partitions
+            .insert((topic.into(), partition), start_offset);
+        self
+    }
+
+    /// Set how [`ProtocolError::UnknownTopicOrPartition`] is handled, see [`UnknownTopicHandling`].
+    ///
+    /// Defaults to [`UnknownTopicHandling::Retry`].
+    pub fn with_unknown_topic_handling(self, unknown_topic_handling: UnknownTopicHandling) -> Self {
+        Self {
+            unknown_topic_handling,
+            ..self
+        }
+    }
+
+    /// Set which records of transactional producers are returned, see [`IsolationLevel`].
+    ///
+    /// Defaults to [`IsolationLevel::ReadCommitted`].
+    pub fn with_isolation_level(self, isolation_level: IsolationLevel) -> Self {
+        Self {
+            isolation_level,
+            ..self
+        }
+    }
+
+    /// Will wait for at least `min_batch_size` bytes of data per broker
+    pub fn with_min_batch_size(self, min_batch_size: i32) -> Self {
+        Self {
+            min_batch_size,
+            ..self
+        }
+    }
+
+    /// The maximum amount of data to fetch from a single broker in a single request.
+    ///
+    /// This is split evenly between the partitions of the request.
+    pub fn with_max_batch_size(self, max_batch_size: i32) -> Self {
+        Self {
+            max_batch_size,
+            ..self
+        }
+    }
+
+    /// The maximum amount of time a broker waits for data before returning
+    pub fn with_max_wait_ms(self, max_wait_ms: i32) -> Self {
+        Self {
+            max_wait_ms,
+            ..self
+        }
+    }
+
+    /// Override the [request timeout](crate::client::ClientBuilder::request_timeout) for requests sent by this
+    /// consumer.
+    ///
+    /// Fetch requests extend the timeout by `max_wait_ms`, just like they do for the default. Metadata requests still
+    /// use the default. Defaults to [`TimeoutOverride::Default`].
+    pub fn with_request_timeout(self, request_timeout: TimeoutOverride) -> Self {
+        Self {
+            request_timeout,
+            ..self
+        }
+    }
+
+    pub fn build(self) -> MultiPartitionConsumer {
+        let unrouted = self.partitions.keys().cloned().collect();
+        let partitions = self
+            .partitions
+            .into_iter()
+            .map(|(key, start_offset)| {
+                (
+                    key,
+                    PartitionState {
+                        start_offset,
+                        next_offset: None,
+                        client: None,
+                    },
+                )
+            })
+            .collect();
+
+        MultiPartitionConsumer {
+            shared: Arc::new(Shared {
+                brokers: self.brokers,
+                backoff_config: Arc::clone(&self.backoff_config),
+                unknown_topic_handling: self.unknown_topic_handling,
+                isolation_level: self.isolation_level,
+                request_timeout: self.request_timeout,
+            }),
+            backoff: Backoff::new(&self.backoff_config),
+            max_wait_ms: self.max_wait_ms,
+            min_batch_size: self.min_batch_size,
+            max_batch_size: self.max_batch_size,
+            partitions,
+            unrouted,
+            routing: false,
+            next_route_delay: None,
+            metadata_gen: None,
+            bound: BTreeMap::new(),
+            busy: HashSet::new(),
+            throttle: HashMap::new(),
+            sessions: HashMap::new(),
+            rotation: 0,
+            buffer: VecDeque::new(),
+            in_flight: FuturesUnordered::new(),
+            terminated: false,
+        }
+    }
+}
+
+/// A record together with the partition it was read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartitionRecord {
+    pub topic: String,
+    pub partition: i32,
+    pub record: RecordAndOffset,
+
+    /// High watermark of the partition at the time the record was fetched.
+    pub high_watermark: i64,
+}
+
+#[derive(Debug)]
+struct PartitionState {
+    start_offset: StartOffset,
+
+    /// Offset of the next fetch, `None` if the start offset needs to be resolved first.
+    next_offset: Option<i64>,
+
+    /// Client used to resolve the start offset, created on first use.
+    client: Option<Arc<PartitionClient>>,
+}
+
+/// State that is shared with the request futures.
+#[derive(Debug)]
+struct Shared {
+    brokers: Arc<BrokerConnector>,
+    backoff_config: Arc<BackoffConfig>,
+    unknown_topic_handling: UnknownTopicHandling,
+    isolation_level: IsolationLevel,
+    request_timeout: TimeoutOverride,
+}
+
+impl Shared {
+    /// Whether a partition that failed with `error` should be retried, possibly at a different broker.
+    fn is_retriable(&self, error: &Error) -> bool {
+        match error {
+            Error::Request(
+                RequestError::Poisoned(_) | RequestError::IO(_) | RequestError::Timeout { .. },
+            )
+            | Error::Connection(_) => true,
+            Error::ServerError {
+                protocol_error:
+                    ProtocolError::InvalidReplicationFactor
+                    | ProtocolError::LeaderNotAvailable
+                    | ProtocolError::NotLeaderOrFollower
+                    | ProtocolError::OffsetNotAvailable,
+                ..
+            } => true,
+            Error::ServerError {
+                protocol_error: ProtocolError::UnknownTopicOrPartition,
+                ..
+            } => self.unknown_topic_handling == UnknownTopicHandling::Retry,
+            _ => false,
+        }
+    }
+
+    /// Determine leader and fetch offset for every partition.
+    async fn route(
+        self: Arc<Self>,
+        partitions: Vec<RouteRequest>,
+        delay: Option<Duration>,
+    ) -> Event {
+        if let Some(delay) = delay {
+            tokio::time::sleep(delay).await;
+        }
+
+        let topics: BTreeSet<_> = partitions.iter().map(|p| p.key.0.clone()).collect();
+        let (metadata, r#gen) = match self
+            .brokers
+            .request_metadata(
+                &MetadataLookupMode::CachedArbitrary,
+                Some(topics.into_iter().collect()),
+            )
+            .await
+        {
+            Ok(x) => x,
+            Err(e) => {
+                return Event::RouteFailed {
+                    keys: partitions.into_iter().map(|p| p.key).collect(),
+                    error: e.into(),
+                };
+            }
+        };
+
+        let results = join_all(partitions.into_iter().map(|p| {
+            let leader = leader(&metadata, &p.key);
+            let this = &self;
+            async move {
+                let result = match leader {
+                    Ok(leader) => this.resolve(&p).await.map(|(offset, client)| Route {
+                        leader,
+                        offset,
+                        client,
+                    }),
+                    Err(e) => Err(e),
+                };
+                (p.key, result)
+            }
+        }))
+        .await;
+
+        Event::Routed { results, r#gen }
+    }
+
+    /// Determine the offset at which to continue fetching.
+    async fn resolve(&self, request: &RouteRequest) -> Result<(i64, Option<Arc<PartitionClient>>)> {
+        if let Some(offset) = request.next_offset {
+            return Ok((offset, request.client.clone()));
+        }
+
+        let client = match &request.client {
+            Some(client) => Arc::clone(client),
+            None => Arc::new(
+                PartitionClient::new(
+                    request.key.0.clone(),
+                    request.key.1,
+                    Arc::clone(&self.brokers),
+                    self.unknown_topic_handling,
+                    Arc::clone(&self.backoff_config),
+                )
+                .await?
+                .with_isolation_level(self.isolation_level)
+                .with_request_timeout(self.request_timeout),
+            ),
+        };
+        let offset =
+            resolve_start_offset(client.as_ref(), &request.start_offset, self.isolation_level)
+                .await?;
+        Ok((offset, Some(client)))
+    }
+
+    /// Fetch records from `broker_id` in a single request.
+    ///
+    /// `offsets` lists all partitions of the fetch, including those that are not part of an `incremental` request.
+    async fn fetch(
+        self: Arc<Self>,
+        broker_id: i32,
+        request: FetchRequest,
+        offsets: Vec<(Key, i64)>,
+        incremental: bool,
+        throttle: Option<Duration>,
+    ) -> Event {
+        if let Some(throttle) = throttle {
+            info!(
+                ?throttle,
+                broker_id,
+                request_name = "fetch",
+                "broker asked us to throttle",
+            );
+            tokio::time::sleep(throttle).await;
+        }
+
+        let response = match self.brokers.connect(broker_id).await {
+            Ok(Some(connection)) => {
+                let timeout = connection.extended_request_timeout(
+                    self.request_timeout,
+                    broker_wait(request.max_wait_ms.0),
+                );
+                connection
+                    .request_with_timeout(&request, timeout)
+                    .await
+                    .map_err(Error::from)
+            }
+            Ok(None) => Err(Error::InvalidResponse(format!(
+                "Broker {broker_id} not found in metadata"
+            ))),
+            Err(e) => Err(e.into()),
+        };
+        match response {
+            Ok(response) => {
+                let throttle = response
+                    .throttle_time_ms
+                    .and_then(|t| u64::try_from(t.0).ok())
+                    .filter(|t| *t > 0)
+                    .map(Duration::from_millis);
+                let session_id = response.session_id.map(|id| id.0);
+                let (results, unchanged) = process_response(response, offsets, incremental);
+                Event::Fetched {
+                    broker_id,
+                    results,
+                    unchanged,
+                    session_id,
+                    throttle,
+                }
+            }
+            Err(error) => Event::FetchFailed {
+                broker_id,
+                keys: offsets.into_iter().map(|(key, _offset)| key).collect(),
+                error,
+            },
+        }
+    }
+}
+
+/// Leader of the partition `key` according to `metadata`.
+fn leader(metadata: &MetadataResponse, key: &Key) -> Result<i32> {
+    let (topic, partition) = key;
+    let server_error = |protocol_error, is_virtual| Error::ServerError {
+        protocol_error,
+        error_message: None,
+        request: RequestContext::Partition(topic.clone(), *partition),
+        response: None,
+        is_virtual,
+    };
+
+    let topic_metadata = metadata
+        .topics
+        .iter()
+        .find(|t| &t.name.0 == topic)
+        .ok_or_else(|| server_error(ProtocolError::UnknownTopicOrPartition, true))?;
+    if let Some(e) = topic_metadata.error {
+        return Err(server_error(e, false));
+    }
+
+    let partition_metadata = topic_metadata
+        .partitions
+        .iter()
+        .find(|p| p.partition_index.0 == *partition)
+        .ok_or_else(|| server_error(ProtocolError::UnknownTopicOrPartition, true))?;
+    if let Some(e) = partition_metadata.error {
+        return Err(server_error(e, false));
+    }
+    if partition_metadata.leader_id.0 == -1 {
+        return Err(server_error(ProtocolError::LeaderNotAvailable, true));
+    }
+
+    Ok(partition_metadata.leader_id.0)
+}
+
+/// Split a fetch response into per-partition results.
+///
+/// For `incremental` requests, partitions without changes are not part of the response. These are returned separately.
+fn process_response(
+    mut response: FetchResponse,
+    offsets: Vec<(Key, i64)>,
+    incremental: bool,
+) -> (Vec<FetchedPartition>, Vec<Key>) {
+    let mut unchanged = vec![];
+    let results = offsets
+        .into_iter()
+        .filter_map(|(key, offset)| {
+            if let Some(e) = response.error_code {
+                // top-level errors (e.g. for the fetch session) apply to all partitions
+                let result = Err(Error::ServerError {
+                    protocol_error: e,
+                    error_message: None,
+                    request: RequestContext::Fetch {
+                        topic_name: key.0.clone(),
+                        partition_id: key.1,
+                        offset,
+                    },
+                    response: None,
+                    is_virtual: false,
+                });
+                return Some(FetchedPartition {
+                    key,
+                    offset,
+                    result,
+                });
+            }
+
+            let response_partition = response
+                .responses
+                .iter_mut()
+                .filter(|t| t.topic.0 == key.0)
+                .find_map(|t| {
+                    let idx = t
+                        .partitions
+                        .iter()
+                        .position(|p| p.partition_index.0 == key.1)?;
+                    Some(t.partitions.swap_remove(idx))
+                });
+
+            let result = match response_partition {
+                Some(response_partition) => {
+                    process_fetch_partition_response(key.1, &key.0, response_partition, offset)
+                        .and_then(|p| {
+                            let high_watermark = p.high_watermark.0;
+                            let mut records =
+                                extract_records(p.records.0, offset, p.aborted_transactions)?;
+                            // Sort records by offset in case they aren't in order
+                            records.sort_by_key(|x| x.offset);
+                            Ok((records, high_watermark))
+                        })
+                }
+                None if incremental => {
+                    unchanged.push(key);
+                    return None;
+                }
+                None => Err(Error::InvalidResponse(format!(
+                    "No data for partition {} of topic '{}' in fetch response",
+                    key.1, key.0
+                ))),
+            };
+            Some(FetchedPartition {
+                key,
+                offset,
+                result,
+            })
+        })
+        .collect();
+
+    (results, unchanged)
+}
+
+/// The broker does not know the fetch session (anymore).
+fn is_session_error<T>(result: &Result<T>) -> bool {
+    matches!(
+        result,
+        Err(Error::ServerError {
+            protocol_error: ProtocolError::FetchSessionIdNotFound
+                | ProtocolError::InvalidFetchSessionEpoch,
+            ..
+        })
+    )
+}
+
+#[derive(Debug)]
+struct RouteRequest {
+    key: Key,
+    start_offset: StartOffset,
+    next_offset: Option<i64>,
+    client: Option<Arc<PartitionClient>>,
+}
+
+#[derive(Debug)]
+struct Route {
+    leader: i32,
+    offset: i64,
+    client: Option<Arc<PartitionClient>>,
+}
+
+#[derive(Debug)]
+struct FetchedPartition {
+    key: Key,
+
+    /// Offset used for the fetch.
+    offset: i64,
+
+    /// Records and high watermark.
+    result: Result<(Vec<RecordAndOffset>, i64)>,
+}
+
+/// Result of a request future.
+#[derive(Debug)]
+enum Event {
+    Routed {
+        results: Vec<(Key, Result<Route>)>,
+        r#gen: Option<MetadataCacheGeneration>,
+    },
+    RouteFailed {
+        keys: Vec<Key>,
+        error: Error,
+    },
+    Fetched {
+        broker_id: i32,
+        results: Vec<FetchedPartition>,
+
+        /// Partitions of an incremental fetch without new data.
+        unchanged: Vec<Key>,
+
+        /// Fetch session ID from the response.
+        session_id: Option<i32>,
+
+        throttle: Option<Duration>,
+    },
+    FetchFailed {
+        broker_id: i32,
+        keys: Vec<Key>,
+        error: Error,
+    },
+}
+
+/// Stream of records from many partitions, see the [module docs](self).
+///
+/// Records of one partition are emitted in offset order. There is no ordering between partitions.
+///
+/// New data is only requested from a broker once all records previously fetched (from any broker) were consumed from
+/// the stream, so at most one response per broker is buffered.
+///
+/// Records are always read from the partition leader, the [client rack](crate::client::ClientBuilder::client_rack) is
+/// not used for follower fetching.
+///
+/// # Error Handling
+/// Partitions whose leader moved or is temporarily unavailable are routed again, independently of the other
+/// partitions, with a backoff according to the [`BackoffConfig`] of the client. The same applies to partitions that
+/// start at [`StartOffset::Earliest`] or [`StartOffset::Latest`] and ran out of range, similar to
+/// [`StreamConsumer`](super::StreamConsumer).
+///
+/// All other errors are emitted once and terminate the stream.
+pub struct MultiPartitionConsumer {
+    shared: Arc<Shared>,
+
+    /// Backoff for re-routing partitions, reset after an error-free fetch.
+    backoff: Backoff,
+
+    max_wait_ms: i32,
+
+    min_batch_size: i32,
+
+    max_batch_size: i32,
+
+    partitions: BTreeMap<Key, PartitionState>,
+
+    /// Partitions that need a leader (and maybe a start offset).
+    unrouted: Vec<Key>,
+
+    /// A routing future is in flight.
+    routing: bool,
+
+    next_route_delay: Option<Duration>,
+
+    /// Generation of the metadata used for routing.
+    metadata_gen: Option<MetadataCacheGeneration>,
+
+    /// Routed partitions that are not part of an in-flight request, by leader.
+    bound: BTreeMap<i32, Vec<Key>>,
+
+    /// Brokers with an in-flight fetch request.
+    busy: HashSet<i32>,
+
+    /// Throttle requested by a broker, applied before the next request.
+    throttle: HashMap<i32, Duration>,
+
+    /// Fetch session per broker.
+    sessions: HashMap<i32, FetchSession>,
+
+    /// Rotates the order of partitions within fetch requests.
+    rotation: usize,
+
+    buffer: VecDeque<PartitionRecord>,
+
+    in_flight: FuturesUnordered<BoxFuture<'static, Event>>,
+
+    terminated: bool,
+}
+
+impl MultiPartitionConsumer {
+    /// Start routing and fetch requests for all partitions that are not part of an in-flight request.
+    fn dispatch(&mut self) {
+        if !self.routing && !self.unrouted.is_empty() {
+            let partitions = std::mem::take(&mut self.unrouted)
+                .into_iter()
+                .map(|key| {
+                    let state = &self.partitions[&key];
+                    RouteRequest {
+                        start_offset: state.start_offset.clone(),
+                        next_offset: state.next_offset,
+                        client: state.client.clone(),
+                        key,
+                    }
+                })
+                .collect::<Vec<_>>();
+            trace!(n = partitions.len(), "Routing partitions");
+
+            let delay = self.next_route_delay.take();
+            self.in_flight
+                .push(Box::pin(Arc::clone(&self.shared).route(partitions, delay)));
+            self.routing = true;
+        }
+
+        let idle: Vec<i32> = self
+            .bound
+            .iter()
+            .filter(|(broker_id, keys)| !keys.is_empty() && !self.busy.contains(broker_id))
+            .map(|(broker_id, _)| *broker_id)
+            .collect();
+        for broker_id in idle {
+            let mut keys = self.bound.remove(&broker_id).unwrap_or_default();
+
+            // Brokers fill the response in request order, so rotate the partitions to not always favor the same ones.
+            let n = keys.len();
+            keys.rotate_left(self.rotation % n);
+            self.rotation = self.rotation.wrapping_add(1);
+
+            let partition_max_bytes = (self.max_batch_size / n as i32).max(1);
+            let wanted: Vec<_> = keys
+                .into_iter()
+                .map(|key| {
+                    let fetch_offset = self.partitions[&key]
+                        .next_offset
+                        .expect("routed partitions have an offset");
+                    let fetch = PartitionFetch {
+                        fetch_offset,
+                        max_bytes: partition_max_bytes,
+                    };
+                    (key, fetch)
+                })
+                .collect();
+            let offsets = wanted
+                .iter()
+                .map(|(key, fetch)| (key.clone(), fetch.fetch_offset))
+                .collect();
+
+            let session_request = self
+                .sessions
+                .entry(broker_id)
+                .or_default()
+                .next_request(wanted);
+            let incremental = session_request.incremental;
+            debug!(
+                broker_id,
+                n,
+                session_id = session_request.session_id,
+                session_epoch = session_request.session_epoch,
+                n_sent = session_request.partitions.len(),
+                n_forgotten = session_request.forgotten.len(),
+                "Fetching partitions",
+            );
+            let request = self.build_fetch_request(session_request);
+
+            let throttle = self.throttle.remove(&broker_id);
+            self.in_flight.push(Box::pin(Arc::clone(&self.shared).fetch(
+                broker_id,
+                request,
+                offsets,
+                incremental,
+                throttle,
+            )));
+            self.busy.insert(broker_id);
+        }
+    }
+
+    fn build_fetch_request(&self, session_request: SessionRequest) -> FetchRequest {
+        let mut topics: Vec<FetchRequestTopic> = vec![];
+        for (key, fetch) in session_request.partitions {
+            let partition = FetchRequestPartition {
+                partition: Int32(key.1),
+                current_leader_epoch: None,
+                fetch_offset: Int64(fetch.fetch_offset),
+                log_start_offset: None,
+                partition_max_bytes: Int32(fetch.max_bytes),
+            };
+
+            // partitions of the same topic must be grouped, but keep the order otherwise
+            match topics.last_mut() {
+                Some(t) if t.topic.0 == key.0 => t.partitions.push(partition),
+                _ => topics.push(FetchRequestTopic {
+                    topic: String_(key.0),
+                    partitions: vec![partition],
+                }),
+            }
+        }
+
+        let mut forgotten_topics_data: Vec<FetchRequestForgottenTopic> = vec![];
+        for (topic, partition) in session_request.forgotten {
+            match forgotten_topics_data.last_mut() {
+                Some(t) if t.topic.0 == topic => t.partitions.push(Int32(partition)),
+                _ => forgotten_topics_data.push(FetchRequestForgottenTopic {
+                    topic: String_(topic),
+                    partitions: vec![Int32(partition)],
+                }),
+            }
+        }
+
+        FetchRequest {
+            replica_id: NORMAL_CONSUMER,
+            max_wait_ms: Int32(self.max_wait_ms),
+            min_bytes: Int32(self.min_batch_size),
+            max_bytes: Some(Int32(self.max_batch_size)),
+            isolation_level: Some(self.shared.isolation_level.into()),
+            session_id: Some(Int32(session_request.session_id)),
+            session_epoch: Some(Int32(session_request.session_epoch)),
+            topics,
+            forgotten_topics_data,
+            // records are always read from the leader, see `MultiPartitionConsumer`
+            rack_id: None,
+        }
+    }
+
+    /// Delay the next routing after a retriable `error`.
+    ///
+    /// Fails if the backoff deadline is exceeded.
+    fn backoff(&mut self, error: Error) -> Result<()> {
+        if self.next_route_delay.is_some() {
+            // already backing off
+            return Ok(());
+        }
+
+        match self.backoff.next() {
+            Some(backoff) => {
+                info!(
+                    e=%error,
+                    backoff_secs = backoff.as_secs(),
+                    "fetch encountered non-fatal error - backing off",
+                );
+                self.next_route_delay = Some(backoff);
+                Ok(())
+            }
+            None => Err(Error::RetryFailed(BackoffError::DeadlineExceded {
+                deadline: self.shared.backoff_config.deadline.unwrap_or_default(),
+                source: Box::new(error),
+            })),
+        }
+    }
+
+    fn invalidate_metadata(&mut self, reason: &'static str) {
+        if let Some(r#gen) = self.metadata_gen.take() {
+            self.shared.brokers.invalidate_metadata_cache(reason, r#gen);
+        }
+    }
+
+    /// Process the result of a request future.
+    ///
+    /// Returns an error if the stream shall terminate.
+    fn handle(&mut self, event: Event) -> Result<()> {
+        match event {
+            Event::Routed { results, r#gen } => {
+                self.routing = false;
+                if r#gen.is_some() {
+                    self.metadata_gen = r#gen;
+                }
+
+                for (key, result) in results {
+                    match result {
+                        Ok(route) => {
+                            let state = self
+                                .partitions
+                                .get_mut(&key)
+                                .expect("routed partitions are known");
+                            state.next_offset = Some(route.offset);
+                            state.client = route.client;
+                            self.bound.entry(route.leader).or_default().push(key);
+                        }
+                        Err(e) if self.shared.is_retriable(&e) => {
+                            self.invalidate_metadata("multi-partition consumer: routing failed");
+                            self.backoff(e)?;
+                            self.unrouted.push(key);
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+            }
+            Event::RouteFailed { keys, error } => {
+                self.routing = false;
+                if !self.shared.is_retriable(&error) {
+                    return Err(error);
+                }
+                self.backoff(error)?;
+                self.unrouted.extend(keys);
+            }
+            Event::Fetched {
+                broker_id,
+                results,
+                unchanged,
+                session_id,
+                throttle,
+            } => {
+                self.busy.remove(&broker_id);
+                if let Some(throttle) = throttle {
+                    self.throttle.insert(broker_id, throttle);
+                }
+
+                let session = self.sessions.entry(broker_id).or_default();
+                if results.iter().any(|p| is_session_error(&p.result)) {
+                    info!(broker_id, "Fetch session lost, falling back to full fetch");
+                    session.reset();
+                } else {
+                    session.complete(session_id);
+                }
+                self.bound.entry(broker_id).or_default().extend(unchanged);
+
+                let mut had_error = false;
+                for FetchedPartition {
+                    key,
+                    offset,
+                    result,
+                } in results
+                {
+                    let state = self
+                        .partitions
+                        .get_mut(&key)
+                        .expect("fetched partitions are known");
+                    match result {
+                        Ok((records, high_watermark)) => {
+                            trace!(
+                                topic = key.0.as_str(),
+                                partition = key.1,
+                                high_watermark,
+                                n_records = records.len(),
+                                "Received records and a high watermark",
+                            );
+                            if let Some(x) = records.last() {
+                                state.next_offset = Some(x.offset + 1);
+                            }
+                            self.buffer
+                                .extend(records.into_iter().map(|record| PartitionRecord {
+                                    topic: key.0.clone(),
+                                    partition: key.1,
+                                    record,
+                                    high_watermark,
+                                }));
+                            self.bound.entry(broker_id).or_default().push(key);
+                        }
+                        Err(_) if is_session_error(&result) => {
+                            // retry right away, the next request is a full one
+                            self.bound.entry(broker_id).or_default().push(key);
+                        }
+                        Err(Error::ServerError {
+                            protocol_error: ProtocolError::OffsetOutOfRange,
+                            ..
+                        }) if matches!(
+                            state.start_offset,
+                            StartOffset::Earliest | StartOffset::Latest
+                        ) =>
+                        {
+                            // Records are gone between ListOffsets and Fetch, see `StreamConsumer`. Resolve the
+                            // start offset again after a short break.
+                            let backoff_secs = 1;
+                            warn!(
+                                topic = key.0.as_str(),
+                                partition = key.1,
+                                offset,
+                                start_offset=?state.start_offset,
+                                backoff_secs,
+                                "Records are gone between ListOffsets and Fetch, backoff a bit",
+                            );
+                            state.next_offset = None;
+                            self.next_route_delay = Some(
+                                self.next_route_delay
+                                    .unwrap_or_default()
+                                    .max(Duration::from_secs(backoff_secs)),
+                            );
+                            self.unrouted.push(key);
+                            had_error = true;
+                        }
+                        Err(e) if self.shared.is_retriable(&e) => {
+                            self.invalidate_metadata("multi-partition consumer: leader moved");
+                            self.backoff(e)?;
+                            self.unrouted.push(key);
+                            had_error = true;
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+
+                if !had_error {
+                    self.backoff = Backoff::new(&self.shared.backoff_config);
+                }
+            }
+            Event::FetchFailed {
+                broker_id,
+                keys,
+                error,
+            } => {
+                self.busy.remove(&broker_id);
+
+                // the broker might or might not have processed the request
+                if let Some(session) = self.sessions.get_mut(&broker_id) {
+                    session.reset();
+                }
+                if !self.shared.is_retriable(&error) {
+                    return Err(error);
+                }
+                self.invalidate_metadata("multi-partition consumer: broker request failed");
+                self.backoff(error)?;
+                self.unrouted.extend(keys);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Stream for MultiPartitionConsumer {
+    type Item = Result<PartitionRecord>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if self.terminated {
+                return Poll::Ready(None);
+            }
+            if let Some(x) = self.buffer.pop_front() {
+                return Poll::Ready(Some(Ok(x)));
+            }
+            if self.partitions.is_empty() {
+                // nothing assigned
+                self.terminated = true;
+                continue;
+            }
+
+            self.dispatch();
+
+            let event = futures::ready!(self.in_flight.poll_next_unpin(cx))
+                .expect("every partition is either in flight or was just dispatched");
+            if let Err(e) = self.handle(event) {
+                self.terminated = true;
+
+                // report error once
+                return Poll::Ready(Some(Err(e)));
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for MultiPartitionConsumer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MultiPartitionConsumer")
+            .field("max_wait_ms", &self.max_wait_ms)
+            .field("min_batch_size", &self.min_batch_size)
+            .field("max_batch_size", &self.max_batch_size)
+            .field("partitions", &self.partitions)
+            .field("unrouted", &self.unrouted)
+            .field("bound", &self.bound)
+            .field("busy", &self.busy)
+            .field("terminated", &self.terminated)
+            .field("buffer", &self.buffer)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,1206 @@
+//! Consumer group membership.
+//!
+//! A [`ConsumerGroupClient`] joins a consumer group, takes part in rebalances and keeps the membership alive by sending
+//! heartbeats in the background. Every time the group settles on a new partition assignment, a new [`Generation`] is
+//! handed out. A generation is revoked as soon as the group starts to rebalance again.
+//!
+//! # Usage
+//! ```no_run
+//! # async fn test() {
+//! use futures::StreamExt;
+//! use rskafka::client::{
+//!     ClientBuilder,
+//!     consumer::CommittedFallback,
+//! };
+//!
+//! let connection = "localhost:9093".to_owned();
+//! let client = ClientBuilder::new(vec![connection]).build().await.unwrap();
+//!
+//! // join group
+//! let mut group = client
+//!     .consumer_group("my_group", vec!["my_topic".to_owned()])
+//!     .build();
+//!
+//! while let Some(generation) = group.next_generation().await {
+//!     let generation = generation.unwrap();
+//!
+//!     // the streams resume at the committed offsets and end as soon as the assignment is revoked
+//!     let streams = generation
+//!         .stream_consumers(CommittedFallback::Earliest)
+//!         .await
+//!         .unwrap();
+//!     let mut stream = futures::stream::select_all(streams);
+//!     while let Some(res) = stream.next().await {
+//!         let (record, high_water_mark) = res.unwrap();
+//!     }
+//! }
+//! # }
+//! ```
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Cursor;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures::Stream;
+use futures::future::{BoxFuture, Fuse, FusedFuture, FutureExt};
+use parking_lot::Mutex;
+use tokio::sync::{mpsc, oneshot, watch};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use crate::{
+    backoff::{BackoffConfig, ErrorOrThrottle},
+    client::{
+        consumer::{CommittedFallback, StartOffset, StreamConsumer, StreamConsumerBuilder},
+        coordinator::{Coordinator, maybe_retry},
+        error::{Error, ProtocolError, Result},
+        group_offsets::{CommittedOffset, GroupMember, GroupOffsetsClient},
+        partition::{PartitionClient, UnknownTopicHandling},
+    },
+    connection::{BrokerCache, BrokerConnector, MetadataLookupMode},
+    messenger::{RequestError, TimeoutOverride},
+    protocol::{
+        messages::{
+            CONSUMER_PROTOCOL_TYPE, ConsumerProtocolAssignment, ConsumerProtocolAssignmentTopic,
+            ConsumerProtocolSubscription, CoordinatorType, HeartbeatRequest, HeartbeatResponse,
+            JoinGroupRequest, JoinGroupRequestProtocol, JoinGroupResponse, LeaveGroupRequest,
+            LeaveGroupResponse, ReadVersionedType, RequestBody, SyncGroupRequest,
+            SyncGroupRequestAssignment, SyncGroupResponse, WriteVersionedError, WriteVersionedType,
+        },
+        primitives::{Array, Bytes, Int32, NullableBytes, NullableString, String_},
+        traits::{ReadType, WriteType},
+    },
+    record::RecordAndOffset,
+    throttle::maybe_throttle,
+};
+
+/// Strategy that the group leader uses to distribute partitions across the group members.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum AssignmentStrategy {
+    /// Assigns each member a consecutive range of partitions per topic.
+    ///
+    /// This is compatible with the `range` assignor of the Java client.
+    #[default]
+    Range,
+
+    /// Assigns all partitions one by one to the members in turn.
+    ///
+    /// This is compatible with the `roundrobin` assignor of the Java client.
+    RoundRobin,
+}
+
+impl AssignmentStrategy {
+    fn protocol_name(&self) -> &'static str {
+        match self {
+            Self::Range => "range",
+            Self::RoundRobin => "roundrobin",
+        }
+    }
+
+    fn from_protocol_name(name: &str) -> Option<Self> {
+        match name {
+            "range" => Some(Self::Range),
+            "roundrobin" => Some(Self::RoundRobin),
+            _ => None,
+        }
+    }
+}
+
+/// Builder for [`ConsumerGroupClient`].
+#[derive(Debug)]
+pub struct ConsumerGroupBuilder {
+    brokers: Arc<BrokerConnector>,
+
+    backoff_config: Arc<BackoffConfig>,
+
+    group_id: String,
+
+    topics: Vec<String>,
+
+    group_instance_id: Option<String>,
+
+    session_timeout: Duration,
+
+    rebalance_timeout: Duration,
+
+    heartbeat_interval: Duration,
+
+    assignment_strategy: AssignmentStrategy,
+
+    request_timeout: TimeoutOverride,
+}
+
+impl ConsumerGroupBuilder {
+    pub(super) fn new(
+        brokers: Arc<BrokerConnector>,
+        backoff_config: Arc<BackoffConfig>,
+        group_id: String,
+        topics: Vec<String>,
+    ) -> Self {
+        Self {
+            brokers,
+            backoff_config,
+            group_id,
+            topics,
+            group_instance_id: None,
+            // Use same defaults as the Java client:
+            // - <https://kafka.apache.org/documentation/#consumerconfigs>
+            session_timeout: Duration::from_secs(45),
+            rebalance_timeout: Duration::from_secs(300),
+            heartbeat_interval: Duration::from_secs(3),
+            assignment_strategy: AssignmentStrategy::default(),
+            request_timeout: TimeoutOverride::Default,
+        }
+    }
+
+    /// Use static group membership with the given instance ID.
+    ///
+    /// Static members are not removed from the group when they leave, so a restart within the session timeout does
+    /// not trigger a rebalance. This requires a broker that supports `JoinGroup` version 5.
+    pub fn with_group_instance_id(self, group_instance_id: impl Into<String>) -> Self {
+        Self {
+            group_instance_id: Some(group_instance_id.into()),
+            ..self
+        }
+    }
+
+    /// The coordinator considers the member dead if it receives no heartbeat within this timeout.
+    pub fn with_session_timeout(self, session_timeout: Duration) -> Self {
+        Self {
+            session_timeout,
+            ..self
+        }
+    }
+
+    /// The maximum time the coordinator waits for all members to rejoin during a rebalance.
+    pub fn with_rebalance_timeout(self, rebalance_timeout: Duration) -> Self {
+        Self {
+            rebalance_timeout,
+            ..self
+        }
+    }
+
+    /// How often heartbeats are sent to the coordinator.
+    ///
+    /// This should be considerably lower than the [session timeout](Self::with_session_timeout).
+    pub fn with_heartbeat_interval(self, heartbeat_interval: Duration) -> Self {
+        Self {
+            heartbeat_interval,
+            ..self
+        }
+    }
+
+    /// Strategy that is used to assign partitions if this member becomes the group leader.
+    pub fn with_assignment_strategy(self, assignment_strategy: AssignmentStrategy) -> Self {
+        Self {
+            assignment_strategy,
+            ..self
+        }
+    }
+
+    /// Override the [request timeout](super::ClientBuilder::request_timeout) for requests sent by this member.
+    ///
+    /// This applies to requests sent to the coordinator and to the [partition clients](Generation::partition_clients)
+    /// of each generation. Joining and syncing the group extend the timeout by the
+    /// [rebalance timeout](Self::with_rebalance_timeout), because the coordinator holds back the response until all
+    /// members rejoined. Looking up the coordinator uses the override as well. Defaults to
+    /// [`TimeoutOverride::Default`].
+    pub fn with_request_timeout(self, request_timeout: TimeoutOverride) -> Self {
+        Self {
+            request_timeout,
+            ..self
+        }
+    }
+
+    /// Join the group.
+    ///
+    /// The membership is maintained by a background task. Use [`ConsumerGroupClient::next_generation`] to wait for a
+    /// partition assignment.
+    pub fn build(self) -> ConsumerGroupClient {
+        let (state_tx, _state_rx) = watch::channel(MembershipState::Joining);
+        let offsets = GroupOffsetsClient::new(
+            self.group_id.clone(),
+            Arc::clone(&self.brokers),
+            Arc::clone(&self.backoff_config),
+        )
+        .with_request_timeout(self.request_timeout);
+        let membership = Arc::new(Membership {
+            coordinator: Coordinator::new(
+                self.group_id.clone(),
+                CoordinatorType::Group,
+                Arc::clone(&self.brokers),
+            )
+            .with_request_timeout(self.request_timeout),
+            offsets: Arc::new(offsets),
+            brokers: self.brokers,
+            backoff_config: self.backoff_config,
+            group_id: self.group_id,
+            topics: self.topics,
+            group_instance_id: self.group_instance_id,
+            session_timeout: self.session_timeout,
+            rebalance_timeout: self.rebalance_timeout,
+            heartbeat_interval: self.heartbeat_interval,
+            assignment_strategy: self.assignment_strategy,
+            request_timeout: self.request_timeout,
+            member_id: Mutex::new(String::new()),
+            state: state_tx,
+        });
+
+        let (generations_tx, generations_rx) = mpsc::unbounded_channel();
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+        let join_handle = tokio::spawn({
+            let membership = Arc::clone(&membership);
+            async move { membership.run(shutdown_rx, generations_tx).await }
+        });
+
+        ConsumerGroupClient {
+            group_id: membership.group_id.clone(),
+            generations: generations_rx,
+            shutdown: Some(shutdown_tx),
+            join_handle: Some(join_handle),
+        }
+    }
+}
+
+/// Member of a consumer group.
+///
+/// Must be constructed using [`ConsumerGroupBuilder`].
+///
+/// Dropping the client leaves the group in the background. Use [`leave`](Self::leave) to wait for this to happen.
+#[derive(Debug)]
+pub struct ConsumerGroupClient {
+    group_id: String,
+
+    generations: mpsc::UnboundedReceiver<Result<Generation>>,
+
+    shutdown: Option<oneshot::Sender<()>>,
+
+    join_handle: Option<JoinHandle<Result<()>>>,
+}
+
+impl ConsumerGroupClient {
+    /// Group ID.
+    pub fn group_id(&self) -> &str {
+        &self.group_id
+    }
+
+    /// Wait for the next generation of the group that assigns partitions to this member.
+    ///
+    /// Generations that were already revoked before this method was called are skipped.
+    ///
+    /// Returns an error if the group membership failed irrecoverably. Returns `None` after such an error was reported.
+    pub async fn next_generation(&mut self) -> Option<Result<Generation>> {
+        loop {
+            match self.generations.recv().await? {
+                Ok(generation) if generation.is_revoked() => {
+                    debug!(
+                        group_id = self.group_id.as_str(),
+                        generation_id = generation.generation_id,
+                        "Skipping revoked generation",
+                    );
+                }
+                res => return Some(res),
+            }
+        }
+    }
+
+    /// Leave the group.
+    ///
+    /// This revokes the current generation.
+    pub async fn leave(mut self) -> Result<()> {
+        if let Some(shutdown) = self.shutdown.take() {
+            // the task might be gone already due to an error
+            shutdown.send(()).ok();
+        }
+
+        match self.join_handle.take() {
+            Some(join_handle) => join_handle.await.expect("membership task panicked"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Membership state that is shared with the [`Generation`]s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MembershipState {
+    /// Joining or rejoining the group.
+    Joining,
+
+    /// Partitions were assigned within the given generation.
+    Assigned { generation_id: i32 },
+
+    /// Left the group.
+    Terminated,
+}
+
+/// Partitions assigned to this member per topic.
+type Assignment = BTreeMap<String, BTreeSet<i32>>;
+
+/// A generation of the group and the partitions that were assigned to this member.
+pub struct Generation {
+    generation_id: i32,
+
+    member_id: String,
+
+    assignment: Assignment,
+
+    group_instance_id: Option<String>,
+
+    state: watch::Receiver<MembershipState>,
+
+    offsets: Arc<GroupOffsetsClient>,
+
+    brokers: Arc<BrokerConnector>,
+
+    backoff_config: Arc<BackoffConfig>,
+
+    request_timeout: TimeoutOverride,
+}
+
+impl std::fmt::Debug for Generation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Generation")
+            .field("generation_id", &self.generation_id)
+            .field("member_id", &self.member_id)
+            .field("assignment", &self.assignment)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Generation {
+    /// Generation ID.
+    pub fn generation_id(&self) -> i32 {
+        self.generation_id
+    }
+
+    /// Member ID that the coordinator assigned to us.
+    pub fn member_id(&self) -> &str {
+        &self.member_id
+    }
+
+    /// Partitions assigned to this member, per topic.
+    pub fn assignment(&self) -> &BTreeMap<String, BTreeSet<i32>> {
+        &self.assignment
+    }
+
+    /// Returns `true` if the group started a new rebalance or if we left the group.
+    pub fn is_revoked(&self) -> bool {
+        is_revoked(*self.state.borrow(), self.generation_id)
+    }
+
+    /// Wait until this generation is [revoked](Self::is_revoked).
+    pub async fn revoked(&self) {
+        wait_revoked(self.state.clone(), self.generation_id).await
+    }
+
+    /// Commit offsets as a member of this generation.
+    ///
+    /// The coordinator rejects the commit with [`ProtocolError::IllegalGeneration`] or
+    /// [`ProtocolError::RebalanceInProgress`] once the generation is [revoked](Self::is_revoked).
+    pub async fn commit_offsets(&self, offsets: &[CommittedOffset]) -> Result<()> {
+        let member = GroupMember {
+            generation_id: self.generation_id,
+            member_id: self.member_id.clone(),
+            group_instance_id: self.group_instance_id.clone(),
+        };
+        self.offsets.commit_offsets_as(offsets, Some(&member)).await
+    }
+
+    /// Fetch the committed offsets of all assigned partitions.
+    ///
+    /// Partitions without a committed offset are omitted from the result.
+    pub async fn committed_offsets(&self) -> Result<Vec<CommittedOffset>> {
+        self.offsets.fetch_offsets(&self.assignment).await
+    }
+
+    /// Create a [`PartitionClient`] for each assigned partition.
+    pub async fn partition_clients(
+        &self,
+        unknown_topic_handling: UnknownTopicHandling,
+    ) -> Result<Vec<Arc<PartitionClient>>> {
+        let mut clients = vec![];
+        for (topic, partitions) in &self.assignment {
+            for partition in partitions {
+                let client = PartitionClient::new(
+                    topic.clone(),
+                    *partition,
+                    Arc::clone(&self.brokers),
+                    unknown_topic_handling,
+                    Arc::clone(&self.backoff_config),
+                )
+                .await?
+                .with_request_timeout(self.request_timeout);
+                clients.push(Arc::new(client));
+            }
+        }
+        Ok(clients)
+    }
+
+    /// Create a stream for each assigned partition.
+    ///
+    /// The streams start at the offsets committed by this group, partitions without a committed offset start at
+    /// `fallback`, see [`StartOffset::Committed`].
+    ///
+    /// The streams end once the generation is [revoked](Self::is_revoked).
+    pub async fn stream_consumers(
+        &self,
+        fallback: CommittedFallback,
+    ) -> Result<Vec<GroupStreamConsumer>> {
+        let start_offset = StartOffset::Committed {
+            group: self.offsets.group_id().into(),
+            fallback,
+        };
+        Ok(self
+            .partition_clients(UnknownTopicHandling::Retry)
+            .await?
+            .into_iter()
+            .map(|client| GroupStreamConsumer {
+                topic: client.topic().to_owned(),
+                partition: client.partition(),
+                inner: StreamConsumerBuilder::new(client, start_offset.clone()).build(),
+                revoked: FutureExt::fuse(Box::pin(wait_revoked(
+                    self.state.clone(),
+                    self.generation_id,
+                ))),
+            })
+            .collect())
+    }
+}
+
+fn is_revoked(state: MembershipState, generation_id: i32) -> bool {
+    state != MembershipState::Assigned { generation_id }
+}
+
+async fn wait_revoked(mut state: watch::Receiver<MembershipState>, generation_id: i32) {
+    loop {
+        if is_revoked(*state.borrow_and_update(), generation_id) {
+            return;
+        }
+        if state.changed().await.is_err() {
+            // membership task is gone
+            return;
+        }
+    }
+}
+
+/// [`StreamConsumer`] for a partition that was assigned within a [`Generation`].
+///
+/// The stream ends once the generation is revoked.
+pub struct GroupStreamConsumer {
+    topic: String,
+
+    partition: i32,
+
+    inner: StreamConsumer,
+
+    revoked: Fuse<BoxFuture<'static, ()>>,
+}
+
+impl GroupStreamConsumer {
+    /// Topic
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    /// Partition
+    pub fn partition(&self) -> i32 {
+        self.partition
+    }
+}
+
+impl Stream for GroupStreamConsumer {
+    type Item = Result<(RecordAndOffset, i64)>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.revoked.is_terminated() || self.revoked.poll_unpin(cx).is_ready() {
+            return Poll::Ready(None);
+        }
+
+        Pin::new(&mut self.inner).poll_next(cx)
+    }
+}
+
+impl std::fmt::Debug for GroupStreamConsumer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GroupStreamConsumer")
+            .field("topic", &self.topic)
+            .field("partition", &self.partition)
+            .field("inner", &self.inner)
+            .field("revoked", &self.revoked.is_terminated())
+            .finish()
+    }
+}
+
+/// Common fields of the responses sent by the group coordinator.
+trait GroupResponse {
+    fn throttle_time_ms(&self) -> Option<Int32>;
+
+    fn error(&self) -> Option<ProtocolError>;
+}
+
+impl GroupResponse for JoinGroupResponse {
+    fn throttle_time_ms(&self) -> Option<Int32> {
+        self.throttle_time_ms
+    }
+
+    fn error(&self) -> Option<ProtocolError> {
+        self.error
+    }
+}
+
+impl GroupResponse for SyncGroupResponse {
+    fn throttle_time_ms(&self) -> Option<Int32> {
+        self.throttle_time_ms
+    }
+
+    fn error(&self) -> Option<ProtocolError> {
+        self.error
+    }
+}
+
+impl GroupResponse for HeartbeatResponse {
+    fn throttle_time_ms(&self) -> Option<Int32> {
+        self.throttle_time_ms
+    }
+
+    fn error(&self) -> Option<ProtocolError> {
+        self.error
+    }
+}
+
+impl GroupResponse for LeaveGroupResponse {
+    fn throttle_time_ms(&self) -> Option<Int32> {
+        self.throttle_time_ms
+    }
+
+    fn error(&self) -> Option<ProtocolError> {
+        self.error
+    }
+}
+
+/// State of the background task that maintains the group membership.
+#[derive(Debug)]
+struct Membership {
+    coordinator: Coordinator,
+
+    offsets: Arc<GroupOffsetsClient>,
+
+    brokers: Arc<BrokerConnector>,
+
+    backoff_config: Arc<BackoffConfig>,
+
+    group_id: String,
+
+    topics: Vec<String>,
+
+    group_instance_id: Option<String>,
+
+    session_timeout: Duration,
+
+    rebalance_timeout: Duration,
+
+    heartbeat_interval: Duration,
+
+    assignment_strategy: AssignmentStrategy,
+
+    /// See [`ConsumerGroupBuilder::with_request_timeout`].
+    request_timeout: TimeoutOverride,
+
+    /// Member ID assigned by the coordinator, empty if we are not a member (yet).
+    member_id: Mutex<String>,
+
+    state: watch::Sender<MembershipState>,
+}
+
+impl Membership {
+    async fn run(
+        &self,
+        shutdown: oneshot::Receiver<()>,
+        generations: mpsc::UnboundedSender<Result<Generation>>,
+    ) -> Result<()> {
+        // Dropping the client also closes the shutdown channel, in which case we leave the group as well.
+        let res = tokio::select! {
+            res = self.maintain(&generations) => res,
+            _ = shutdown => Ok(()),
+        };
+        self.state.send_replace(MembershipState::Terminated);
+
+        match res {
+            Ok(()) => self.leave().await,
+            Err(e) => {
+                // report error to the client, it might be gone already
+                generations.send(Err(e)).ok();
+                Ok(())
+            }
+        }
+    }
+
+    /// Join the group and keep the membership alive.
+    ///
+    /// This only returns if an unrecoverable error occurs.
+    async fn maintain(
+        &self,
+        generations: &mpsc::UnboundedSender<Result<Generation>>,
+    ) -> Result<()> {
+        loop {
+            self.state.send_replace(MembershipState::Joining);
+
+            let joined = self.join().await?;
+            let generation_id = joined.generation_id.0;
+            let Some(assignment) = self.sync(&joined).await? else {
+                continue;
+            };
+
+            info!(
+                group_id = self.group_id.as_str(),
+                generation_id,
+                ?assignment,
+                "Joined consumer group",
+            );
+            self.state
+                .send_replace(MembershipState::Assigned { generation_id });
+            generations
+                .send(Ok(Generation {
+                    generation_id,
+                    member_id: joined.member_id.0,
+                    assignment,
+                    group_instance_id: self.group_instance_id.clone(),
+                    state: self.state.subscribe(),
+                    offsets: Arc::clone(&self.offsets),
+                    brokers: Arc::clone(&self.brokers),
+                    backoff_config: Arc::clone(&self.backoff_config),
+                    request_timeout: self.request_timeout,
+                }))
+                .ok();
+
+            self.heartbeat(generation_id).await?;
+        }
+    }
+
+    /// Send a request to the coordinator.
+    ///
+    /// `broker_wait` is the time the coordinator may hold back the response on purpose, it extends the request timeout.
+    ///
+    /// Only errors that concern the coordinator itself are handled here, all other errors are returned within the
+    /// response.
+    async fn request<Req, Resp>(
+        &self,
+        request_name: &str,
+        request: &Req,
+        broker_wait: Duration,
+    ) -> Result<Resp>
+    where
+        Req: RequestBody<ResponseBody = Resp> + WriteVersionedType<Vec<u8>> + Send + Sync,
+        Resp: GroupResponse + ReadVersionedType<Cursor<Vec<u8>>> + Send,
+    {
+        maybe_retry(
+            &self.backoff_config,
+            &self.coordinator,
+            request_name,
+            || async move {
+                let (broker, r#gen) = (&self.coordinator)
+                    .get()
+                    .await
+                    .map_err(|e| ErrorOrThrottle::Error((e, None)))?;
+                let timeout = broker.extended_request_timeout(self.request_timeout, broker_wait);
+                let response = broker
+                    .request_with_timeout(request, timeout)
+                    .await
+                    .map_err(|e| ErrorOrThrottle::Error((e.into(), Some(r#gen))))?;
+                maybe_throttle(response.throttle_time_ms())?;
+
+                match response.error() {
+                    Some(
+                        e @ (ProtocolError::NotCoordinator
+                        | ProtocolError::CoordinatorNotAvailable
+                        | ProtocolError::CoordinatorLoadInProgress),
+                    ) => Err(ErrorOrThrottle::Error((self.server_error(e), Some(r#gen)))),
+                    _ => Ok(response),
+                }
+            },
+        )
+        .await
+    }
+
+    fn server_error(&self, protocol_error: ProtocolError) -> Error {
+        Error::ServerError {
+            protocol_error,
+            error_message: None,
+            request: self.coordinator.request_context(),
+            response: None,
+            is_virtual: false,
+        }
+    }
+
+    fn member_id(&self) -> String {
+        self.member_id.lock().clone()
+    }
+
+    fn reset_member_id(&self) {
+        self.member_id.lock().clear();
+    }
+
+    async fn join(&self) -> Result<JoinGroupResponse> {
+        let metadata = encode(&ConsumerProtocolSubscription {
+            topics: Array(Some(
+                self.topics.iter().cloned().map(String_).collect::<Vec<_>>(),
+            )),
+            user_data: NullableBytes(None),
+        })?;
+
+        loop {
+            let request = JoinGroupRequest {
+                group_id: String_(self.group_id.clone()),
+                session_timeout_ms: Int32(duration_ms(self.session_timeout)),
+                rebalance_timeout_ms: Some(Int32(duration_ms(self.rebalance_timeout))),
+                member_id: String_(self.member_id()),
+                group_instance_id: Some(NullableString(self.group_instance_id.clone())),
+                protocol_type: String_(CONSUMER_PROTOCOL_TYPE.to_owned()),
+                protocols: vec![JoinGroupRequestProtocol {
+                    name: String_(self.assignment_strategy.protocol_name().to_owned()),
+                    metadata: Bytes(metadata.clone()),
+                }],
+            };
+
+            let response: JoinGroupResponse = self
+                .request("join_group", &request, self.rebalance_timeout)
+                .await?;
+            match response.error {
+                None => {
+                    *self.member_id.lock() = response.member_id.0.clone();
+                    return Ok(response);
+                }
+                Some(ProtocolError::MemberIdRequired) => {
+                    // KIP-394: the coordinator hands out a member ID that we have to use for the actual join
+                    debug!(
+                        group_id = self.group_id.as_str(),
+                        member_id = response.member_id.0.as_str(),
+                        "Got member ID, rejoining",
+                    );
+                    *self.member_id.lock() = response.member_id.0;
+                }
+                Some(ProtocolError::UnknownMemberId) => {
+                    warn!(
+                        group_id = self.group_id.as_str(),
+                        "Member ID unknown to coordinator, rejoining",
+                    );
+                    self.reset_member_id();
+                }
+                Some(ProtocolError::RebalanceInProgress) => {
+                    debug!(
+                        group_id = self.group_id.as_str(),
+                        "Rebalance in progress, rejoining"
+                    );
+                }
+                Some(e) => return Err(self.server_error(e)),
+            }
+        }
+    }
+
+    /// Sync group state.
+    ///
+    /// Returns `None` if we need to rejoin the group.
+    async fn sync(&self, joined: &JoinGroupResponse) -> Result<Option<Assignment>> {
+        let assignments = if joined.leader.0 == joined.member_id.0 {
+            self.assign(joined).await?
+        } else {
+            vec![]
+        };
+
+        let request = SyncGroupRequest {
+            group_id: String_(self.group_id.clone()),
+            generation_id: joined.generation_id,
+            member_id: joined.member_id.clone(),
+            group_instance_id: Some(NullableString(self.group_instance_id.clone())),
+            assignments,
+        };
+
+        let response: SyncGroupResponse = self
+            .request("sync_group", &request, self.rebalance_timeout)
+            .await?;
+        match response.error {
+            None => {}
+            Some(ProtocolError::RebalanceInProgress | ProtocolError::IllegalGeneration) => {
+                debug!(
+                    group_id = self.group_id.as_str(),
+                    "Rebalance in progress, rejoining"
+                );
+                return Ok(None);
+            }
+            Some(ProtocolError::UnknownMemberId) => {
+                warn!(
+                    group_id = self.group_id.as_str(),
+                    "Member ID unknown to coordinator, rejoining",
+                );
+                self.reset_member_id();
+                return Ok(None);
+            }
+            Some(e) => return Err(self.server_error(e)),
+        }
+
+        // an empty assignment means that we did not get any partitions
+        if response.assignment.0.is_empty() {
+            return Ok(Some(Assignment::default()));
+        }
+
+        let assignment = ConsumerProtocolAssignment::read(&mut Cursor::new(response.assignment.0))
+            .map_err(|e| Error::InvalidResponse(format!("Cannot decode group assignment: {e}")))?;
+
+        Ok(Some(
+            assignment
+                .assigned_partitions
+                .0
+                .unwrap_or_default()
+                .into_iter()
+                .map(|t| {
+                    (
+                        t.topic.0,
+                        t.partitions
+                            .0
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|p| p.0)
+                            .collect(),
+                    )
+                })
+                .collect(),
+        ))
+    }
+
+    /// Compute assignments as the group leader.
+    async fn assign(&self, joined: &JoinGroupResponse) -> Result<Vec<SyncGroupRequestAssignment>> {
+        let strategy =
+            AssignmentStrategy::from_protocol_name(&joined.protocol_name.0).ok_or_else(|| {
+                Error::InvalidResponse(format!(
+                    "Unknown assignment strategy: {}",
+                    joined.protocol_name.0
+                ))
+            })?;
+
+        let mut subscriptions = BTreeMap::new();
+        for member in &joined.members {
+            let subscription =
+                ConsumerProtocolSubscription::read(&mut Cursor::new(member.metadata.0.as_slice()))
+                    .map_err(|e| {
+                        Error::InvalidResponse(format!(
+                            "Cannot decode subscription of member {}: {e}",
+                            member.member_id.0
+                        ))
+                    })?;
+            let topics: BTreeSet<String> = subscription
+                .topics
+                .0
+                .unwrap_or_default()
+                .into_iter()
+                .map(|t| t.0)
+                .collect();
+            subscriptions.insert(member.member_id.0.clone(), topics);
+        }
+
+        let topics: BTreeSet<String> = subscriptions.values().flatten().cloned().collect();
+        let (metadata, _gen) = self
+            .brokers
+            .request_metadata(
+                &MetadataLookupMode::ArbitraryBroker,
+                Some(topics.into_iter().collect()),
+            )
+            .await?;
+        let partitions: BTreeMap<String, Vec<i32>> = metadata
+            .topics
+            .into_iter()
+            .filter(|t| t.error.is_none())
+            .map(|t| {
+                let mut partitions: Vec<i32> = t
+                    .partitions
+                    .into_iter()
+                    .map(|p| p.partition_index.0)
+                    .collect();
+                partitions.sort_unstable();
+                (t.name.0, partitions)
+            })
+            .collect();
+
+        let assignments = assign(strategy, &subscriptions, &partitions);
+        debug!(
+            group_id = self.group_id.as_str(),
+            ?assignments,
+            "Computed assignments as group leader",
+        );
+
+        assignments
+            .into_iter()
+            .map(|(member_id, topics)| {
+                let assignment = encode(&ConsumerProtocolAssignment {
+                    assigned_partitions: Array(Some(
+                        topics
+                            .into_iter()
+                            .map(|(topic, partitions)| ConsumerProtocolAssignmentTopic {
+                                topic: String_(topic),
+                                partitions: Array(Some(
+                                    partitions.into_iter().map(Int32).collect(),
+                                )),
+                            })
+                            .collect(),
+                    )),
+                    user_data: NullableBytes(None),
+                })?;
+                Ok(SyncGroupRequestAssignment {
+                    member_id: String_(member_id),
+                    assignment: Bytes(assignment),
+                })
+            })
+            .collect()
+    }
+
+    /// Send heartbeats until the group rebalances.
+    async fn heartbeat(&self, generation_id: i32) -> Result<()> {
+        loop {
+            tokio::time::sleep(self.heartbeat_interval).await;
+
+            let request = HeartbeatRequest {
+                group_id: String_(self.group_id.clone()),
+                generation_id: Int32(generation_id),
+                member_id: String_(self.member_id()),
+                group_instance_id: Some(NullableString(self.group_instance_id.clone())),
+            };
+
+            let response: HeartbeatResponse =
+                self.request("heartbeat", &request, Duration::ZERO).await?;
+            match response.error {
+                None => {}
+                Some(ProtocolError::RebalanceInProgress | ProtocolError::IllegalGeneration) => {
+                    info!(
+                        group_id = self.group_id.as_str(),
+                        generation_id, "Group is rebalancing",
+                    );
+                    return Ok(());
+                }
+                Some(ProtocolError::UnknownMemberId) => {
+                    warn!(
+                        group_id = self.group_id.as_str(),
+                        generation_id, "Member ID unknown to coordinator, rejoining",
+                    );
+                    self.reset_member_id();
+                    return Ok(());
+                }
+                Some(e) => return Err(self.server_error(e)),
+            }
+        }
+    }
+
+    async fn leave(&self) -> Result<()> {
+        let member_id = std::mem::take(&mut *self.member_id.lock());
+
+        // static members stay part of the group until their session times out
+        if member_id.is_empty() || self.group_instance_id.is_some() {
+            return Ok(());
+        }
+
+        let request = LeaveGroupRequest {
+            group_id: String_(self.group_id.clone()),
+            member_id: String_(member_id),
+        };
+
+        let response: LeaveGroupResponse = self
+            .request("leave_group", &request, Duration::ZERO)
+            .await?;
+        match response.error {
+            None | Some(ProtocolError::UnknownMemberId) => {
+                info!(group_id = self.group_id.as_str(), "Left consumer group");
+                Ok(())
+            }
+            Some(e) => Err(self.server_error(e)),
+        }
+    }
+}
+
+fn encode<T>(data: &T) -> Result<Vec<u8>>
+where
+    T: WriteType<Vec<u8>>,
+{
+    let mut buf = vec![];
+    data.write(&mut buf)
+        .map_err(|e| RequestError::from(WriteVersionedError::from(e)))?;
+    Ok(buf)
+}
+
+fn duration_ms(d: Duration) -> i32 {
+    d.as_millis().try_into().unwrap_or(i32::MAX)
+}
+
+/// Assign partitions to group members.
+///
+/// Returns the assigned partitions per topic for every member, including members that did not get any partitions.
+fn assign(
+    strategy: AssignmentStrategy,
+    subscriptions: &BTreeMap<String, BTreeSet<String>>,
+    partitions: &BTreeMap<String, Vec<i32>>,
+) -> BTreeMap<String, BTreeMap<String, Vec<i32>>> {
+    let mut assignments: BTreeMap<String, BTreeMap<String, Vec<i32>>> = subscriptions
+        .keys()
+        .map(|member_id| (member_id.clone(), BTreeMap::new()))
+        .collect();
+
+    match strategy {
+        AssignmentStrategy::Range => {
+            for (topic, partitions) in partitions {
+                let members: Vec<&String> = subscriptions
+                    .iter()
+                    .filter(|(_, topics)| topics.contains(topic))
+                    .map(|(member_id, _)| member_id)
+                    .collect();
+                if members.is_empty() {
+                    continue;
+                }
+
+                let per_member = partitions.len() / members.len();
+                let extra = partitions.len() % members.len();
+                let mut partitions = partitions.iter().copied();
+                for (i, member_id) in members.into_iter().enumerate() {
+                    let n = per_member + usize::from(i < extra);
+                    let assigned: Vec<i32> = partitions.by_ref().take(n).collect();
+                    if !assigned.is_empty() {
+                        assignments
+                            .get_mut(member_id)
+                            .expect("all members are known")
+                            .insert(topic.clone(), assigned);
+                    }
+                }
+            }
+        }
+        AssignmentStrategy::RoundRobin => {
+            let members: Vec<&String> = subscriptions.keys().collect();
+            let mut next = 0;
+            for (topic, partitions) in partitions {
+                for partition in partitions {
+                    // pick the next member that is subscribed to this topic
+                    for _ in 0..members.len() {
+                        let member_id = members[next % members.len()];
+                        next += 1;
+                        if subscriptions[member_id].contains(topic) {
+                            assignments
+                                .get_mut(member_id)
+                                .expect("all members are known")
+                                .entry(topic.clone())
+                                .or_default()
+                                .push(*partition);
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    assignments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subscriptions(members: &[(&str, &[&str])]) -> BTreeMap<String, BTreeSet<String>> {
+        members
+            .iter()
+            .map(|(member_id, topics)| {
+                (
+                    member_id.to_string(),
+                    topics.iter().map(|t| t.to_string()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    fn partitions(topics: &[(&str, i32)]) -> BTreeMap<String, Vec<i32>> {
+        topics
+            .iter()
+            .map(|(topic, n)| (topic.to_string(), (0..*n).collect()))
+            .collect()
+    }
+
+    type MemberAssignment<'a> = (&'a str, &'a [(&'a str, &'a [i32])]);
+
+    fn assignment(
+        members: &[MemberAssignment<'_>],
+    ) -> BTreeMap<String, BTreeMap<String, Vec<i32>>> {
+        members
+            .iter()
+            .map(|(member_id, topics)| {
+                (
+                    member_id.to_string(),
+                    topics
+                        .iter()
+                        .map(|(topic, partitions)| (topic.to_string(), partitions.to_vec()))
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_assign_range() {
+        let actual = assign(
+            AssignmentStrategy::Range,
+            &subscriptions(&[("a", &["t1", "t2"]), ("b", &["t1", "t2"])]),
+            &partitions(&[("t1", 3), ("t2", 3)]),
+        );
+        let expected = assignment(&[
+            ("a", &[("t1", &[0, 1]), ("t2", &[0, 1])]),
+            ("b", &[("t1", &[2]), ("t2", &[2])]),
+        ]);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_assign_round_robin() {
+        let actual = assign(
+            AssignmentStrategy::RoundRobin,
+            &subscriptions(&[("a", &["t1", "t2"]), ("b", &["t1", "t2"])]),
+            &partitions(&[("t1", 3), ("t2", 3)]),
+        );
+        let expected = assignment(&[
+            ("a", &[("t1", &[0, 2]), ("t2", &[1])]),
+            ("b", &[("t1", &[1]), ("t2", &[0, 2])]),
+        ]);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_assign_partial_subscriptions() {
+        let subscriptions = subscriptions(&[("a", &["t1"]), ("b", &["t1", "t2"]), ("c", &[])]);
+        let partitions = partitions(&[("t1", 2), ("t2", 2)]);
+
+        for strategy in [AssignmentStrategy::Range, AssignmentStrategy::RoundRobin] {
+            let actual = assign(strategy, &subscriptions, &partitions);
+            let expected = assignment(&[
+                ("a", &[("t1", &[0])]),
+                ("b", &[("t1", &[1]), ("t2", &[0, 1])]),
+                ("c", &[]),
+            ]);
+            assert_eq!(actual, expected, "strategy: {strategy:?}");
+        }
+    }
+
+    #[test]
+    fn test_assign_more_members_than_partitions() {
+        let actual = assign(
+            AssignmentStrategy::Range,
+            &subscriptions(&[("a", &["t1"]), ("b", &["t1"]), ("c", &["t1"])]),
+            &partitions(&[("t1", 2)]),
+        );
+        let expected = assignment(&[("a", &[("t1", &[0])]), ("b", &[("t1", &[1])]), ("c", &[])]);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_strategy_protocol_name_roundtrip() {
+        for strategy in [AssignmentStrategy::Range, AssignmentStrategy::RoundRobin] {
+            assert_eq!(
+                AssignmentStrategy::from_protocol_name(strategy.protocol_name()),
+                Some(strategy)
+            );
+        }
+    }
+}
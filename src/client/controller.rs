@@ -1,7 +1,8 @@
+use std::collections::BTreeMap;
 use std::ops::ControlFlow;
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 use crate::{
     backoff::{Backoff, BackoffConfig, ErrorOrThrottle},
@@ -10,17 +11,467 @@ use crate::{
         BrokerCache, BrokerCacheGeneration, BrokerConnection, BrokerConnector, MessengerTransport,
         MetadataLookupMode,
     },
-    messenger::RequestError,
+    messenger::{RequestError, TimeoutOverride},
     protocol::{
         error::Error as ProtocolError,
-        messages::{CreateTopicRequest, CreateTopicsRequest, DeleteTopicsRequest},
-        primitives::{Array, Int16, Int32, String_},
+        messages::{
+            AlterConfigsRequest, AlterConfigsResource, AlterableConfig, CreateAclsRequest,
+            CreatePartitionsAssignment, CreatePartitionsRequest, CreatePartitionsTopic,
+            CreateTopicAssignment, CreateTopicConfig, CreateTopicRequest, CreateTopicResponse,
+            CreateTopicsRequest, DeleteAclsRequest, DeleteTopicsRequest, DescribeConfigsRequest,
+            DescribeConfigsRequestResource, DescribeConfigsResponseResource,
+            IncrementalAlterConfigsRequest, IncrementalAlterConfigsResource,
+            IncrementalAlterableConfig,
+        },
+        primitives::{Array, Boolean, Int8, Int16, Int32, NullableString, String_},
     },
     throttle::maybe_throttle,
     validation::ExactlyOne,
 };
 
-use super::error::RequestContext;
+use super::{
+    acl::{
+        AclBinding, AclBindingFilter, AclOperation, AclPatternType, AclPermissionType,
+        AclResourceType, CreateAclResult, DeleteAclsResult, DeletedAcl,
+    },
+    error::RequestContext,
+    partition::broker_wait,
+};
+
+/// Topic that shall be created, see [`ControllerClient::create_topics`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewTopic {
+    name: String,
+    num_partitions: i32,
+    replication_factor: i16,
+    assignments: BTreeMap<i32, Vec<i32>>,
+    configs: BTreeMap<String, String>,
+}
+
+impl NewTopic {
+    /// Topic with the default partition count and replication factor of the broker.
+    ///
+    /// Note: the broker defaults require broker version >= 2.4.0 (KIP-464), use
+    /// [`with_num_partitions`](Self::with_num_partitions) and [`with_replication_factor`](Self::with_replication_factor)
+    /// for older brokers.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            num_partitions: -1,
+            replication_factor: -1,
+            assignments: BTreeMap::new(),
+            configs: BTreeMap::new(),
+        }
+    }
+
+    /// Set the number of partitions.
+    pub fn with_num_partitions(self, num_partitions: i32) -> Self {
+        Self {
+            num_partitions,
+            ..self
+        }
+    }
+
+    /// Set the number of replicas of each partition.
+    pub fn with_replication_factor(self, replication_factor: i16) -> Self {
+        Self {
+            replication_factor,
+            ..self
+        }
+    }
+
+    /// Place the replicas of `partition` on the brokers `broker_ids`, the first one is the preferred leader.
+    ///
+    /// If replica assignments are set, they must cover all partitions, starting at `0`, and the partition count and
+    /// replication factor are derived from them instead.
+    pub fn with_replica_assignment(mut self, partition: i32, broker_ids: Vec<i32>) -> Self {
+        self.assignments.insert(partition, broker_ids);
+        self
+    }
+
+    /// Set the topic configuration `name` (e.g. `retention.ms`, `cleanup.policy` or `min.insync.replicas`) to `value`.
+    ///
+    /// See <https://kafka.apache.org/documentation/#topicconfigs> for the available configurations.
+    pub fn with_config(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.configs.insert(name.into(), value.into());
+        self
+    }
+
+    /// Topic name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn into_request(self) -> CreateTopicRequest {
+        // the broker rejects explicit values next to manual assignments
+        let (num_partitions, replication_factor) = if self.assignments.is_empty() {
+            (self.num_partitions, self.replication_factor)
+        } else {
+            (-1, -1)
+        };
+
+        CreateTopicRequest {
+            name: String_(self.name),
+            num_partitions: Int32(num_partitions),
+            replication_factor: Int16(replication_factor),
+            assignments: self
+                .assignments
+                .into_iter()
+                .map(|(partition, broker_ids)| CreateTopicAssignment {
+                    partition_index: Int32(partition),
+                    broker_ids: Array(Some(broker_ids.into_iter().map(Int32).collect())),
+                    tagged_fields: None,
+                })
+                .collect(),
+            configs: self
+                .configs
+                .into_iter()
+                .map(|(name, value)| CreateTopicConfig {
+                    name: String_(name),
+                    value: NullableString(Some(value)),
+                    tagged_fields: None,
+                })
+                .collect(),
+            tagged_fields: None,
+        }
+    }
+}
+
+/// Partitions that shall be added to an existing topic, see [`ControllerClient::create_partitions`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewPartitions {
+    topic: String,
+    count: i32,
+    assignments: Vec<Vec<i32>>,
+}
+
+impl NewPartitions {
+    /// Increase the number of partitions of `topic` to `count`.
+    pub fn new(topic: impl Into<String>, count: i32) -> Self {
+        Self {
+            topic: topic.into(),
+            count,
+            assignments: vec![],
+        }
+    }
+
+    /// Place the replicas of the next new partition on the brokers `broker_ids`, the first one is the preferred leader.
+    ///
+    /// If assignments are set, there must be one for every new partition, in the order of the partitions. By default,
+    /// the broker assigns the replicas.
+    pub fn with_assignment(mut self, broker_ids: Vec<i32>) -> Self {
+        self.assignments.push(broker_ids);
+        self
+    }
+
+    /// Topic name.
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    fn into_request(self) -> CreatePartitionsTopic {
+        CreatePartitionsTopic {
+            name: String_(self.topic),
+            count: Int32(self.count),
+            assignments: (!self.assignments.is_empty()).then(|| {
+                self.assignments
+                    .into_iter()
+                    .map(|broker_ids| CreatePartitionsAssignment {
+                        broker_ids: Array(Some(broker_ids.into_iter().map(Int32).collect())),
+                        tagged_fields: None,
+                    })
+                    .collect()
+            }),
+            tagged_fields: None,
+        }
+    }
+}
+
+/// A topic or broker whose configuration can be described or altered, see [`ControllerClient::describe_configs`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ConfigResource {
+    /// Configuration of a topic.
+    Topic(String),
+
+    /// Configuration of a specific broker.
+    ///
+    /// Requests for this resource are sent to the broker itself.
+    Broker(i32),
+
+    /// Dynamic default configuration shared by all brokers.
+    BrokerDefault,
+
+    /// Log levels of the loggers of a specific broker.
+    ///
+    /// Requests for this resource are sent to the broker itself. Not supported by
+    /// [`alter_configs`](ControllerClient::alter_configs).
+    BrokerLogger(i32),
+}
+
+impl ConfigResource {
+    fn resource_type(&self) -> i8 {
+        match self {
+            Self::Topic(_) => 2,
+            Self::Broker(_) | Self::BrokerDefault => 4,
+            Self::BrokerLogger(_) => 8,
+        }
+    }
+
+    fn resource_name(&self) -> String {
+        match self {
+            Self::Topic(name) => name.clone(),
+            Self::Broker(broker_id) | Self::BrokerLogger(broker_id) => broker_id.to_string(),
+            Self::BrokerDefault => String::new(),
+        }
+    }
+
+    /// Broker that has to handle requests for this resource, `None` if any broker can.
+    fn broker_id(&self) -> Option<i32> {
+        match self {
+            Self::Broker(broker_id) | Self::BrokerLogger(broker_id) => Some(*broker_id),
+            Self::Topic(_) | Self::BrokerDefault => None,
+        }
+    }
+}
+
+/// Where the value of a [`ConfigEntry`] comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// Set for the topic.
+    DynamicTopic,
+
+    /// Set for a specific broker while it is running.
+    DynamicBroker,
+
+    /// Set as the default for all brokers while they are running.
+    DynamicDefaultBroker,
+
+    /// Set in the configuration file of the broker.
+    StaticBroker,
+
+    /// Built-in default.
+    Default,
+
+    /// Set for a logger of a specific broker.
+    DynamicBrokerLogger,
+
+    /// The broker did not report a source or sent an unknown one.
+    Unknown,
+}
+
+impl From<i8> for ConfigSource {
+    fn from(source: i8) -> Self {
+        match source {
+            1 => Self::DynamicTopic,
+            2 => Self::DynamicBroker,
+            3 => Self::DynamicDefaultBroker,
+            4 => Self::StaticBroker,
+            5 => Self::Default,
+            6 => Self::DynamicBrokerLogger,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// A configuration value as reported by the broker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigEntry {
+    /// Configuration name.
+    pub name: String,
+
+    /// Configuration value, `None` if it is not set or [sensitive](Self::is_sensitive).
+    pub value: Option<String>,
+
+    /// The value cannot be changed.
+    pub read_only: bool,
+
+    /// Where the value comes from.
+    pub source: ConfigSource,
+
+    /// The value is sensitive (e.g. a password) and therefore not reported.
+    pub is_sensitive: bool,
+
+    /// Other values for this configuration, in order of precedence, starting with the one in effect.
+    ///
+    /// Only reported by [`ControllerClient::describe_configs`].
+    pub synonyms: Vec<ConfigSynonym>,
+}
+
+impl ConfigEntry {
+    /// The configuration is not set explicitly, i.e. the built-in default is in effect.
+    pub fn is_default(&self) -> bool {
+        self.source == ConfigSource::Default
+    }
+}
+
+/// A value of a configuration that may be overridden by another value, see [`ConfigEntry::synonyms`].
+///
+/// For example, the topic configuration `retention.ms` is backed by the broker configuration `log.retention.ms`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigSynonym {
+    /// Configuration name, which may differ from the name of the [`ConfigEntry`].
+    pub name: String,
+
+    /// Configuration value, `None` if it is sensitive.
+    pub value: Option<String>,
+
+    /// Where the value comes from.
+    pub source: ConfigSource,
+}
+
+/// Configuration of a single resource, see [`ControllerClient::describe_configs`].
+#[derive(Debug)]
+pub struct DescribeConfigsResult {
+    /// The described resource.
+    pub resource: ConfigResource,
+
+    /// The configuration entries or why they could not be described.
+    pub result: Result<Vec<ConfigEntry>>,
+}
+
+/// Operation of an incremental configuration change, see [`ConfigChanges`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlterConfigOp {
+    /// Set the value.
+    Set(String),
+
+    /// Remove the value, so that the default takes effect.
+    Delete,
+
+    /// Add a value to a list configuration (e.g. `cleanup.policy`).
+    Append(String),
+
+    /// Remove a value from a list configuration.
+    Subtract(String),
+}
+
+impl AlterConfigOp {
+    fn operation(&self) -> i8 {
+        match self {
+            Self::Set(_) => 0,
+            Self::Delete => 1,
+            Self::Append(_) => 2,
+            Self::Subtract(_) => 3,
+        }
+    }
+
+    fn into_value(self) -> Option<String> {
+        match self {
+            Self::Set(value) | Self::Append(value) | Self::Subtract(value) => Some(value),
+            Self::Delete => None,
+        }
+    }
+}
+
+/// Changes to the configuration of a single resource, see [`ControllerClient::incremental_alter_configs`].
+///
+/// Configurations that are not mentioned keep their current value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigChanges {
+    resource: ConfigResource,
+    ops: Vec<(String, AlterConfigOp)>,
+}
+
+impl ConfigChanges {
+    /// No changes to `resource` yet.
+    pub fn new(resource: ConfigResource) -> Self {
+        Self {
+            resource,
+            ops: vec![],
+        }
+    }
+
+    /// Set the configuration `name` to `value`.
+    pub fn with_set(self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.with_op(name, AlterConfigOp::Set(value.into()))
+    }
+
+    /// Reset the configuration `name` to its default.
+    pub fn with_delete(self, name: impl Into<String>) -> Self {
+        self.with_op(name, AlterConfigOp::Delete)
+    }
+
+    /// Add `value` to the list configuration `name`.
+    pub fn with_append(self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.with_op(name, AlterConfigOp::Append(value.into()))
+    }
+
+    /// Remove `value` from the list configuration `name`.
+    pub fn with_subtract(self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.with_op(name, AlterConfigOp::Subtract(value.into()))
+    }
+
+    /// Apply `op` to the configuration `name`.
+    pub fn with_op(mut self, name: impl Into<String>, op: AlterConfigOp) -> Self {
+        self.ops.push((name.into(), op));
+        self
+    }
+
+    /// The resource to change.
+    pub fn resource(&self) -> &ConfigResource {
+        &self.resource
+    }
+
+    fn into_request(self) -> IncrementalAlterConfigsResource {
+        IncrementalAlterConfigsResource {
+            resource_type: Int8(self.resource.resource_type()),
+            resource_name: String_(self.resource.resource_name()),
+            configs: self
+                .ops
+                .into_iter()
+                .map(|(name, op)| IncrementalAlterableConfig {
+                    name: String_(name),
+                    config_operation: Int8(op.operation()),
+                    value: NullableString(op.into_value()),
+                    tagged_fields: None,
+                })
+                .collect(),
+            tagged_fields: None,
+        }
+    }
+}
+
+/// Result of altering the configuration of a single resource, see [`ControllerClient::incremental_alter_configs`].
+#[derive(Debug)]
+pub struct AlterConfigsResult {
+    /// The altered resource.
+    pub resource: ConfigResource,
+
+    /// Whether the configuration was altered (or would have been altered).
+    pub result: Result<()>,
+}
+
+/// A topic that was created (or would have been created), see [`ControllerClient::create_topics`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreatedTopic {
+    /// Number of partitions, if reported by the broker.
+    ///
+    /// Note: this requires broker version >= 2.4.0 (KIP-525).
+    pub num_partitions: Option<i32>,
+
+    /// Replication factor, if reported by the broker.
+    ///
+    /// Note: this requires broker version >= 2.4.0 (KIP-525).
+    pub replication_factor: Option<i16>,
+
+    /// Effective configuration of the topic, including the defaults of the broker.
+    ///
+    /// Empty if not reported by the broker, e.g. because the client is not allowed to describe the topic
+    /// configuration.
+    ///
+    /// Note: this requires broker version >= 2.4.0 (KIP-525).
+    pub configs: Vec<ConfigEntry>,
+}
+
+/// Result of creating a single topic, see [`ControllerClient::create_topics`].
+#[derive(Debug)]
+pub struct CreateTopicResult {
+    /// Topic name.
+    pub name: String,
+
+    /// The created topic or why it could not be created.
+    pub result: Result<CreatedTopic>,
+}
 
 #[derive(Debug)]
 pub struct ControllerClient {
@@ -30,6 +481,9 @@ pub struct ControllerClient {
 
     /// Current broker connection if any
     current_broker: Mutex<(Option<BrokerConnection>, BrokerCacheGeneration)>,
+
+    /// Request timeout that overrides [`ClientBuilder::request_timeout`](super::ClientBuilder::request_timeout).
+    request_timeout: TimeoutOverride,
 }
 
 impl ControllerClient {
@@ -38,6 +492,20 @@ impl ControllerClient {
             brokers,
             backoff_config,
             current_broker: Mutex::new((None, BrokerCacheGeneration::START)),
+            request_timeout: TimeoutOverride::Default,
+        }
+    }
+
+    /// Override the [request timeout](super::ClientBuilder::request_timeout) for requests sent by this client.
+    ///
+    /// Requests that make the broker wait, e.g. [`create_topics`](Self::create_topics), extend the timeout by their
+    /// `timeout_ms`, just like they do for the default. Metadata requests still use the default, see
+    /// [`TimeoutOverride::scope`] to override the timeout of a single call including its metadata requests. Defaults to
+    /// [`TimeoutOverride::Default`].
+    pub fn with_request_timeout(self, request_timeout: TimeoutOverride) -> Self {
+        Self {
+            request_timeout,
+            ..self
         }
     }
 
@@ -49,59 +517,232 @@ impl ControllerClient {
         replication_factor: i16,
         timeout_ms: i32,
     ) -> Result<()> {
+        let topic = NewTopic::new(name)
+            .with_num_partitions(num_partitions)
+            .with_replication_factor(replication_factor);
+        let result = self
+            .create_topics(vec![topic], timeout_ms, false)
+            .await?
+            .exactly_one()
+            .map_err(Error::exactly_one_topic)?;
+        result.result.map(|_| ())
+    }
+
+    /// Create multiple topics at once.
+    ///
+    /// Returns one result per topic, in the order of `topics`, with the effective configuration of each created topic.
+    /// If `validate_only` is set, the broker only checks if the topics can be created, without creating them.
+    ///
+    /// Errors that apply to the whole request (e.g. the connection failed) are returned as the outer error.
+    pub async fn create_topics(
+        &self,
+        topics: Vec<NewTopic>,
+        timeout_ms: i32,
+        validate_only: bool,
+    ) -> Result<Vec<CreateTopicResult>> {
+        let names = topics
+            .iter()
+            .map(|topic| topic.name.clone())
+            .collect::<Vec<_>>();
         let request = &CreateTopicsRequest {
-            topics: vec![CreateTopicRequest {
-                name: String_(name.into()),
-                num_partitions: Int32(num_partitions),
-                replication_factor: Int16(replication_factor),
-                assignments: vec![],
-                configs: vec![],
-                tagged_fields: None,
-            }],
+            topics: topics.into_iter().map(NewTopic::into_request).collect(),
             timeout_ms: Int32(timeout_ms),
-            validate_only: None,
+            // not supported by the oldest version, so only send it if needed
+            validate_only: validate_only.then_some(Boolean(true)),
             tagged_fields: None,
         };
 
-        maybe_retry(&self.backoff_config, self, "create_topic", || async move {
-            let (broker, r#gen) = self
-                .get()
-                .await
-                .map_err(|e| ErrorOrThrottle::Error((e, None)))?;
-            let response = broker
-                .request(request)
-                .await
-                .map_err(|e| ErrorOrThrottle::Error((e.into(), Some(r#gen))))?;
+        let mut response_topics =
+            maybe_retry(&self.backoff_config, self, "create_topics", || async move {
+                let (broker, r#gen) = self
+                    .get()
+                    .await
+                    .map_err(|e| ErrorOrThrottle::Error((e, None)))?;
+                let response = broker
+                    .request_with_timeout(
+                        request,
+                        broker.extended_request_timeout(
+                            self.request_timeout,
+                            broker_wait(request.timeout_ms.0),
+                        ),
+                    )
+                    .await
+                    .map_err(|e| ErrorOrThrottle::Error((e.into(), Some(r#gen))))?;
 
-            maybe_throttle(response.throttle_time_ms)?;
+                maybe_throttle(response.throttle_time_ms)?;
 
-            let topic = response
-                .topics
-                .exactly_one()
-                .map_err(|e| ErrorOrThrottle::Error((Error::exactly_one_topic(e), Some(r#gen))))?;
+                // the controller moved, so none of the topics was created
+                if let Some(topic) = response
+                    .topics
+                    .iter()
+                    .find(|topic| topic.error == Some(ProtocolError::NotController))
+                {
+                    return Err(ErrorOrThrottle::Error((
+                        Error::ServerError {
+                            protocol_error: ProtocolError::NotController,
+                            error_message: None,
+                            request: RequestContext::Topic(topic.name.0.clone()),
+                            response: None,
+                            is_virtual: false,
+                        },
+                        Some(r#gen),
+                    )));
+                }
 
-            match topic.error {
-                None => Ok(()),
-                Some(protocol_error) => Err(ErrorOrThrottle::Error((
-                    Error::ServerError {
-                        protocol_error,
-                        error_message: topic.error_message.and_then(|s| s.0),
-                        request: RequestContext::Topic(topic.name.0),
-                        response: None,
-                        is_virtual: false,
-                    },
-                    Some(r#gen),
-                ))),
-            }
-        })
+                Ok(response
+                    .topics
+                    .into_iter()
+                    .map(|topic| (topic.name.0.clone(), topic))
+                    .collect::<BTreeMap<_, _>>())
+            })
+            .await?;
+
+        let results = names
+            .into_iter()
+            .map(|name| {
+                let topic = response_topics.remove(&name).ok_or_else(|| {
+                    Error::InvalidResponse(format!("No result for topic \"{name}\" in response"))
+                })?;
+                Ok(CreateTopicResult {
+                    name,
+                    result: created_topic(topic),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if !validate_only && results.iter().any(|topic| topic.result.is_ok()) {
+            // Refresh the cache now there are new topics to observe.
+            let _ = self.brokers.refresh_metadata().await;
+        }
+
+        Ok(results)
+    }
+
+    /// Increase the number of partitions of a topic.
+    ///
+    /// Partitions cannot be removed. Note that adding partitions changes which partition a record key is mapped to by
+    /// most partitioners. If `validate_only` is set, the broker only checks if the partitions can be created, without
+    /// creating them.
+    ///
+    /// Once the partitions are created, this waits up to `timeout_ms` until the cached metadata of the client contains
+    /// them, so that [`Client::list_topics`](super::Client::list_topics) and producers see the new partitions right
+    /// away. If they do not show up in time, [`Error::Timeout`] is returned even though the partitions were created.
+    pub async fn create_partitions(
+        &self,
+        partitions: NewPartitions,
+        timeout_ms: i32,
+        validate_only: bool,
+    ) -> Result<()> {
+        let topic = partitions.topic.clone();
+        let count = partitions.count;
+        let request = &CreatePartitionsRequest {
+            topics: vec![partitions.into_request()],
+            timeout_ms: Int32(timeout_ms),
+            validate_only: Boolean(validate_only),
+            tagged_fields: None,
+        };
+
+        maybe_retry(
+            &self.backoff_config,
+            self,
+            "create_partitions",
+            || async move {
+                let (broker, r#gen) = self
+                    .get()
+                    .await
+                    .map_err(|e| ErrorOrThrottle::Error((e, None)))?;
+                let response = broker
+                    .request_with_timeout(
+                        request,
+                        broker.extended_request_timeout(
+                            self.request_timeout,
+                            broker_wait(request.timeout_ms.0),
+                        ),
+                    )
+                    .await
+                    .map_err(|e| ErrorOrThrottle::Error((e.into(), Some(r#gen))))?;
+
+                maybe_throttle(Some(response.throttle_time_ms))?;
+
+                let topic = response.results.exactly_one().map_err(|e| {
+                    ErrorOrThrottle::Error((Error::exactly_one_topic(e), Some(r#gen)))
+                })?;
+
+                match topic.error {
+                    None => Ok(()),
+                    Some(protocol_error) => Err(ErrorOrThrottle::Error((
+                        Error::ServerError {
+                            protocol_error,
+                            error_message: topic.error_message.0,
+                            request: RequestContext::Topic(topic.name.0),
+                            response: None,
+                            is_virtual: false,
+                        },
+                        Some(r#gen),
+                    ))),
+                }
+            },
+        )
         .await?;
 
-        // Refresh the cache now there is definitely a new topic to observe.
-        let _ = self.brokers.refresh_metadata().await;
+        if !validate_only {
+            self.await_partition_count(&topic, count, timeout_ms)
+                .await?;
+        }
 
         Ok(())
     }
 
+    /// Refresh the cached metadata until `topic` has at least `count` partitions, for up to `timeout_ms`.
+    ///
+    /// The controller answers once the partitions exist, but other brokers may learn about them a bit later. Returns
+    /// [`Error::Timeout`] if the partitions are not visible in time.
+    async fn await_partition_count(&self, topic: &str, count: i32, timeout_ms: i32) -> Result<()> {
+        let mut backoff = Backoff::new(&self.backoff_config);
+        let refresh = async {
+            loop {
+                // an unfiltered request updates the metadata cache
+                match self
+                    .brokers
+                    .request_metadata(&MetadataLookupMode::ArbitraryBroker, None)
+                    .await
+                {
+                    Ok((metadata, _gen)) => {
+                        let n_partitions = metadata
+                            .topics
+                            .iter()
+                            .find(|t| t.name.0 == topic)
+                            .map(|t| t.partitions.len())
+                            .unwrap_or_default();
+                        if n_partitions >= usize::try_from(count).unwrap_or_default() {
+                            return true;
+                        }
+                        debug!(topic, n_partitions, count, "new partitions not visible yet");
+                    }
+                    Err(e) => {
+                        debug!(%e, topic, "cannot refresh metadata");
+                    }
+                }
+
+                match backoff.next() {
+                    Some(backoff) => tokio::time::sleep(backoff).await,
+                    None => return false,
+                }
+            }
+        };
+
+        match tokio::time::timeout(broker_wait(timeout_ms), refresh).await {
+            Ok(true) => Ok(()),
+            Ok(false) | Err(_) => {
+                warn!(
+                    topic,
+                    count, "cached metadata does not contain the new partitions yet"
+                );
+                Err(Error::Timeout)
+            }
+        }
+    }
+
     /// Delete a topic
     pub async fn delete_topic(
         &self,
@@ -120,7 +761,13 @@ impl ControllerClient {
                 .await
                 .map_err(|e| ErrorOrThrottle::Error((e, None)))?;
             let response = broker
-                .request(request)
+                .request_with_timeout(
+                    request,
+                    broker.extended_request_timeout(
+                        self.request_timeout,
+                        broker_wait(request.timeout_ms.0),
+                    ),
+                )
                 .await
                 .map_err(|e| ErrorOrThrottle::Error((e.into(), Some(r#gen))))?;
 
@@ -153,6 +800,360 @@ impl ControllerClient {
         Ok(())
     }
 
+    /// Describe the configuration of topics and brokers.
+    ///
+    /// Returns one result per resource, in the order of `resources`. Every entry reports where its value comes from,
+    /// whether it is sensitive and which values it overrides (see [`ConfigEntry::synonyms`]).
+    ///
+    /// Broker-specific resources ([`ConfigResource::Broker`] and [`ConfigResource::BrokerLogger`]) are described by
+    /// the respective broker, all others by the controller. Errors that apply to a whole request (e.g. the connection
+    /// failed) are returned as the outer error.
+    pub async fn describe_configs(
+        &self,
+        resources: Vec<ConfigResource>,
+    ) -> Result<Vec<DescribeConfigsResult>> {
+        let mut responses = BTreeMap::new();
+        for (broker_id, group) in group_by_broker(resources.iter(), |r| r.broker_id()) {
+            let request = &DescribeConfigsRequest {
+                resources: group
+                    .into_iter()
+                    .map(|resource| DescribeConfigsRequestResource {
+                        resource_type: Int8(resource.resource_type()),
+                        resource_name: String_(resource.resource_name()),
+                        configuration_keys: None,
+                        tagged_fields: None,
+                    })
+                    .collect(),
+                include_synonyms: Some(Boolean(true)),
+                include_documentation: None,
+                tagged_fields: None,
+            };
+
+            let results = maybe_retry(
+                &self.backoff_config,
+                self,
+                "describe_configs",
+                || async move {
+                    let (broker, r#gen) = self
+                        .get_for(broker_id)
+                        .await
+                        .map_err(|e| ErrorOrThrottle::Error((e, None)))?;
+                    let response = broker
+                        .request_with_timeout(request, broker.request_timeout(self.request_timeout))
+                        .await
+                        .map_err(|e| ErrorOrThrottle::Error((e.into(), r#gen)))?;
+
+                    maybe_throttle(Some(response.throttle_time_ms))?;
+
+                    Ok(response.results)
+                },
+            )
+            .await?;
+
+            responses.extend(results.into_iter().map(|result| {
+                (
+                    (result.resource_type.0, result.resource_name.0.clone()),
+                    result,
+                )
+            }));
+        }
+
+        resources
+            .into_iter()
+            .map(|resource| {
+                let response = take_response(&mut responses, &resource)?;
+                Ok(DescribeConfigsResult {
+                    result: described_configs(response, &resource),
+                    resource,
+                })
+            })
+            .collect()
+    }
+
+    /// Change individual configurations of topics and brokers.
+    ///
+    /// Returns one result per resource, in the order of `changes`. If `validate_only` is set, the brokers only check
+    /// if the changes can be applied, without applying them.
+    ///
+    /// Broker-specific resources ([`ConfigResource::Broker`] and [`ConfigResource::BrokerLogger`]) are altered by the
+    /// respective broker, all others by the controller. Errors that apply to a whole request (e.g. the connection
+    /// failed) are returned as the outer error.
+    ///
+    /// Note: this requires broker version >= 2.3.0 (KIP-339).
+    pub async fn incremental_alter_configs(
+        &self,
+        changes: Vec<ConfigChanges>,
+        validate_only: bool,
+    ) -> Result<Vec<AlterConfigsResult>> {
+        let resources = changes
+            .iter()
+            .map(|changes| changes.resource.clone())
+            .collect::<Vec<_>>();
+
+        let mut responses = BTreeMap::new();
+        for (broker_id, group) in group_by_broker(changes, |c| c.resource.broker_id()) {
+            let request = &IncrementalAlterConfigsRequest {
+                resources: group.into_iter().map(ConfigChanges::into_request).collect(),
+                validate_only: Boolean(validate_only),
+                tagged_fields: None,
+            };
+
+            let results = maybe_retry(
+                &self.backoff_config,
+                self,
+                "incremental_alter_configs",
+                || async move {
+                    let (broker, r#gen) = self
+                        .get_for(broker_id)
+                        .await
+                        .map_err(|e| ErrorOrThrottle::Error((e, None)))?;
+                    let response = broker
+                        .request_with_timeout(request, broker.request_timeout(self.request_timeout))
+                        .await
+                        .map_err(|e| ErrorOrThrottle::Error((e.into(), r#gen)))?;
+
+                    maybe_throttle(Some(response.throttle_time_ms))?;
+
+                    Ok(response.responses)
+                },
+            )
+            .await?;
+
+            responses.extend(results.into_iter().map(|result| {
+                (
+                    (result.resource_type.0, result.resource_name.0.clone()),
+                    result,
+                )
+            }));
+        }
+
+        resources
+            .into_iter()
+            .map(|resource| {
+                let response = take_response(&mut responses, &resource)?;
+                Ok(AlterConfigsResult {
+                    result: config_result(response.error, response.error_message.0, &resource),
+                    resource,
+                })
+            })
+            .collect()
+    }
+
+    /// Replace the whole configuration of a topic or broker with `configs`.
+    ///
+    /// Configurations that are not part of `configs` are reset to their defaults, so this should only be used for
+    /// brokers that do not support [`incremental_alter_configs`](Self::incremental_alter_configs). If `validate_only`
+    /// is set, the broker only checks if the configuration can be applied, without applying it.
+    pub async fn alter_configs(
+        &self,
+        resource: ConfigResource,
+        configs: BTreeMap<String, String>,
+        validate_only: bool,
+    ) -> Result<()> {
+        let broker_id = resource.broker_id();
+        let request = &AlterConfigsRequest {
+            resources: vec![AlterConfigsResource {
+                resource_type: Int8(resource.resource_type()),
+                resource_name: String_(resource.resource_name()),
+                configs: configs
+                    .into_iter()
+                    .map(|(name, value)| AlterableConfig {
+                        name: String_(name),
+                        value: NullableString(Some(value)),
+                        tagged_fields: None,
+                    })
+                    .collect(),
+                tagged_fields: None,
+            }],
+            validate_only: Boolean(validate_only),
+            tagged_fields: None,
+        };
+
+        let response = maybe_retry(&self.backoff_config, self, "alter_configs", || async move {
+            let (broker, r#gen) = self
+                .get_for(broker_id)
+                .await
+                .map_err(|e| ErrorOrThrottle::Error((e, None)))?;
+            let response = broker
+                .request_with_timeout(request, broker.request_timeout(self.request_timeout))
+                .await
+                .map_err(|e| ErrorOrThrottle::Error((e.into(), r#gen)))?;
+
+            maybe_throttle(Some(response.throttle_time_ms))?;
+
+            response.responses.exactly_one().map_err(|n| {
+                ErrorOrThrottle::Error((
+                    Error::InvalidResponse(format!(
+                        "Expected a single resource in response, got {n}"
+                    )),
+                    r#gen,
+                ))
+            })
+        })
+        .await?;
+
+        config_result(response.error, response.error_message.0, &resource)
+    }
+
+    /// Create ACLs.
+    ///
+    /// Returns one result per ACL, in the order of `bindings`. Creating an ACL that already exists succeeds. Errors
+    /// that apply to the whole request (e.g. the connection failed) are returned as the outer error.
+    ///
+    /// Note: this requires the broker to have an authorizer configured, otherwise every ACL fails with
+    /// [`ProtocolError::SecurityDisabled`].
+    pub async fn create_acls(&self, bindings: Vec<AclBinding>) -> Result<Vec<CreateAclResult>> {
+        let request = &CreateAclsRequest {
+            creations: bindings
+                .iter()
+                .cloned()
+                .map(AclBinding::into_creation)
+                .collect(),
+            tagged_fields: None,
+        };
+
+        let results = maybe_retry(&self.backoff_config, self, "create_acls", || async move {
+            let (broker, r#gen) = self
+                .get()
+                .await
+                .map_err(|e| ErrorOrThrottle::Error((e, None)))?;
+            let response = broker
+                .request_with_timeout(request, broker.request_timeout(self.request_timeout))
+                .await
+                .map_err(|e| ErrorOrThrottle::Error((e.into(), Some(r#gen))))?;
+
+            maybe_throttle(Some(response.throttle_time_ms))?;
+
+            Ok(response.results)
+        })
+        .await?;
+
+        check_result_count(bindings.len(), results.len())?;
+
+        Ok(bindings
+            .into_iter()
+            .zip(results)
+            .map(|(binding, result)| CreateAclResult {
+                result: acl_result(
+                    result.error,
+                    result.error_message.0,
+                    AclBindingFilter::from(binding.clone()),
+                ),
+                binding,
+            })
+            .collect())
+    }
+
+    /// List the ACLs that match `filter`.
+    pub async fn describe_acls(&self, filter: AclBindingFilter) -> Result<Vec<AclBinding>> {
+        let request = &filter.to_describe_request();
+
+        let response = maybe_retry(&self.backoff_config, self, "describe_acls", || async move {
+            let (broker, r#gen) = self
+                .get()
+                .await
+                .map_err(|e| ErrorOrThrottle::Error((e, None)))?;
+            let response = broker
+                .request_with_timeout(request, broker.request_timeout(self.request_timeout))
+                .await
+                .map_err(|e| ErrorOrThrottle::Error((e.into(), Some(r#gen))))?;
+
+            maybe_throttle(Some(response.throttle_time_ms))?;
+
+            Ok(response)
+        })
+        .await?;
+
+        acl_result(response.error, response.error_message.0, filter)?;
+
+        Ok(response
+            .resources
+            .into_iter()
+            .flat_map(|resource| {
+                let resource_type = AclResourceType::from(resource.resource_type.0);
+                let pattern_type = acl_pattern_type(resource.pattern_type);
+                resource.acls.into_iter().map(move |acl| AclBinding {
+                    resource_type,
+                    resource_name: resource.resource_name.0.clone(),
+                    pattern_type,
+                    principal: acl.principal.0,
+                    host: acl.host.0,
+                    operation: AclOperation::from(acl.operation.0),
+                    permission_type: AclPermissionType::from(acl.permission_type.0),
+                })
+            })
+            .collect())
+    }
+
+    /// Delete all ACLs that match any of the `filters`.
+    ///
+    /// Returns one result per filter, in the order of `filters`, with the ACLs that matched the filter. Errors that
+    /// apply to the whole request (e.g. the connection failed) are returned as the outer error.
+    pub async fn delete_acls(
+        &self,
+        filters: Vec<AclBindingFilter>,
+    ) -> Result<Vec<DeleteAclsResult>> {
+        let request = &DeleteAclsRequest {
+            filters: filters
+                .iter()
+                .map(AclBindingFilter::to_delete_filter)
+                .collect(),
+            tagged_fields: None,
+        };
+
+        let results = maybe_retry(&self.backoff_config, self, "delete_acls", || async move {
+            let (broker, r#gen) = self
+                .get()
+                .await
+                .map_err(|e| ErrorOrThrottle::Error((e, None)))?;
+            let response = broker
+                .request_with_timeout(request, broker.request_timeout(self.request_timeout))
+                .await
+                .map_err(|e| ErrorOrThrottle::Error((e.into(), Some(r#gen))))?;
+
+            maybe_throttle(Some(response.throttle_time_ms))?;
+
+            Ok(response.filter_results)
+        })
+        .await?;
+
+        check_result_count(filters.len(), results.len())?;
+
+        Ok(filters
+            .into_iter()
+            .zip(results)
+            .map(|(filter, result)| {
+                let matching_acls = result.matching_acls;
+                let result =
+                    acl_result(result.error, result.error_message.0, filter.clone()).map(|()| {
+                        matching_acls
+                            .into_iter()
+                            .map(|acl| {
+                                let binding = AclBinding {
+                                    resource_type: AclResourceType::from(acl.resource_type.0),
+                                    resource_name: acl.resource_name.0,
+                                    pattern_type: acl_pattern_type(acl.pattern_type),
+                                    principal: acl.principal.0,
+                                    host: acl.host.0,
+                                    operation: AclOperation::from(acl.operation.0),
+                                    permission_type: AclPermissionType::from(acl.permission_type.0),
+                                };
+                                DeletedAcl {
+                                    result: acl_result(
+                                        acl.error,
+                                        acl.error_message.0,
+                                        AclBindingFilter::from(binding.clone()),
+                                    ),
+                                    binding,
+                                }
+                            })
+                            .collect()
+                    });
+                DeleteAclsResult { filter, result }
+            })
+            .collect())
+    }
+
     /// Retrieve the broker ID of the controller
     async fn get_controller_id(&self) -> Result<i32> {
         // Request an uncached, fresh copy of the metadata.
@@ -168,6 +1169,178 @@ impl ControllerClient {
 
         Ok(controller_id)
     }
+    /// Connection to the broker `broker_id`, or to the controller if `None`.
+    ///
+    /// Only the controller connection is cached and has a generation.
+    async fn get_for(
+        &self,
+        broker_id: Option<i32>,
+    ) -> Result<(BrokerConnection, Option<BrokerCacheGeneration>)> {
+        match broker_id {
+            Some(broker_id) => {
+                let broker = self.brokers.connect(broker_id).await?.ok_or_else(|| {
+                    Error::InvalidResponse(format!(
+                        "Broker {broker_id} not found in metadata response"
+                    ))
+                })?;
+                Ok((broker, None))
+            }
+            None => {
+                let (broker, r#gen) = self.get().await?;
+                Ok((broker, Some(r#gen)))
+            }
+        }
+    }
+}
+
+/// Convert the response for a single topic of a CreateTopics request.
+fn created_topic(topic: CreateTopicResponse) -> Result<CreatedTopic> {
+    if let Some(protocol_error) = topic.error {
+        return Err(Error::ServerError {
+            protocol_error,
+            error_message: topic.error_message.and_then(|s| s.0),
+            request: RequestContext::Topic(topic.name.0),
+            response: None,
+            is_virtual: false,
+        });
+    }
+
+    Ok(CreatedTopic {
+        // `-1` if the client is not allowed to describe the topic configuration
+        num_partitions: topic.num_partitions.map(|n| n.0).filter(|n| *n >= 0),
+        replication_factor: topic.replication_factor.map(|n| n.0).filter(|n| *n >= 0),
+        configs: topic
+            .configs
+            .into_iter()
+            .map(|config| ConfigEntry {
+                name: config.name.0,
+                value: config.value.0,
+                read_only: config.read_only.0,
+                source: ConfigSource::from(config.config_source.0),
+                is_sensitive: config.is_sensitive.0,
+                synonyms: vec![],
+            })
+            .collect(),
+    })
+}
+
+/// Split `items` by the broker that has to handle them, `None` being the controller.
+fn group_by_broker<T>(
+    items: impl IntoIterator<Item = T>,
+    broker_id: impl Fn(&T) -> Option<i32>,
+) -> BTreeMap<Option<i32>, Vec<T>> {
+    let mut groups: BTreeMap<_, Vec<_>> = BTreeMap::new();
+    for item in items {
+        groups.entry(broker_id(&item)).or_default().push(item);
+    }
+    groups
+}
+
+/// Take the response for `resource` out of the responses of a config request, keyed by resource type and name.
+fn take_response<T>(
+    responses: &mut BTreeMap<(i8, String), T>,
+    resource: &ConfigResource,
+) -> Result<T> {
+    responses
+        .remove(&(resource.resource_type(), resource.resource_name()))
+        .ok_or_else(|| {
+            Error::InvalidResponse(format!("No result for resource {resource:?} in response"))
+        })
+}
+
+/// Convert the error of a single resource of a config request.
+fn config_result(
+    error: Option<ProtocolError>,
+    error_message: Option<String>,
+    resource: &ConfigResource,
+) -> Result<()> {
+    match error {
+        None => Ok(()),
+        Some(protocol_error) => Err(Error::ServerError {
+            protocol_error,
+            error_message,
+            request: RequestContext::Config(resource.clone()),
+            response: None,
+            is_virtual: false,
+        }),
+    }
+}
+
+/// Convert the response for a single resource of a DescribeConfigs request.
+fn described_configs(
+    response: DescribeConfigsResponseResource,
+    resource: &ConfigResource,
+) -> Result<Vec<ConfigEntry>> {
+    config_result(response.error, response.error_message.0, resource)?;
+
+    Ok(response
+        .configs
+        .into_iter()
+        .map(|config| {
+            let source = match (config.config_source, config.is_default) {
+                (Some(source), _) => ConfigSource::from(source.0),
+                // version 0 only tells if the value is the default
+                (None, Some(Boolean(true))) => ConfigSource::Default,
+                (None, _) => match resource {
+                    ConfigResource::Topic(_) => ConfigSource::DynamicTopic,
+                    _ => ConfigSource::Unknown,
+                },
+            };
+
+            ConfigEntry {
+                name: config.name.0,
+                value: config.value.0,
+                read_only: config.read_only.0,
+                source,
+                is_sensitive: config.is_sensitive.0,
+                synonyms: config
+                    .synonyms
+                    .into_iter()
+                    .map(|synonym| ConfigSynonym {
+                        name: synonym.name.0,
+                        value: synonym.value.0,
+                        source: ConfigSource::from(synonym.source.0),
+                    })
+                    .collect(),
+            }
+        })
+        .collect())
+}
+
+/// Check that a response contains one result per entry of the request.
+fn check_result_count(expected: usize, actual: usize) -> Result<()> {
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(Error::InvalidResponse(format!(
+            "Expected {expected} results in response, got {actual}"
+        )))
+    }
+}
+
+/// Convert the error of a single ACL or filter.
+fn acl_result(
+    error: Option<ProtocolError>,
+    error_message: Option<String>,
+    filter: AclBindingFilter,
+) -> Result<()> {
+    match error {
+        None => Ok(()),
+        Some(protocol_error) => Err(Error::ServerError {
+            protocol_error,
+            error_message,
+            request: RequestContext::Acl(Box::new(filter)),
+            response: None,
+            is_virtual: false,
+        }),
+    }
+}
+
+/// Pattern type of an ACL in a response, which is only reported by version 1 and later.
+fn acl_pattern_type(pattern_type: Option<Int8>) -> AclPatternType {
+    pattern_type
+        .map(|pattern_type| AclPatternType::from(pattern_type.0))
+        .unwrap_or(AclPatternType::Literal)
 }
 
 /// Caches the cluster controller broker.
@@ -246,7 +1419,9 @@ where
 
             match error {
                 // broken connection
-                Error::Request(RequestError::Poisoned(_) | RequestError::IO(_))
+                Error::Request(
+                    RequestError::Poisoned(_) | RequestError::IO(_) | RequestError::Timeout { .. },
+                )
                 | Error::Connection(_) => {
                     if let Some(cache_gen) = cache_gen {
                         broker_cache
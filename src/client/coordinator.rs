@@ -0,0 +1,243 @@
+use std::ops::ControlFlow;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{debug, error, info};
+
+use crate::{
+    backoff::{Backoff, BackoffConfig, ErrorOrThrottle},
+    client::error::{Error, RequestContext, Result},
+    connection::{
+        BrokerCache, BrokerCacheGeneration, BrokerConnection, BrokerConnector, MessengerTransport,
+    },
+    messenger::{RequestError, TimeoutOverride},
+    protocol::{
+        error::Error as ProtocolError,
+        messages::{CoordinatorType, FindCoordinatorRequest},
+        primitives::String_,
+    },
+};
+
+/// Caches the connection to the coordinator that is responsible for a given key.
+///
+/// For consumer groups the key is the group ID, for transactions it is the transactional ID.
+#[derive(Debug)]
+pub(crate) struct Coordinator {
+    key: String,
+
+    key_type: CoordinatorType,
+
+    brokers: Arc<BrokerConnector>,
+
+    /// Timeout override for looking up the coordinator.
+    request_timeout: TimeoutOverride,
+
+    /// Current broker connection if any
+    current_broker: Mutex<(Option<BrokerConnection>, BrokerCacheGeneration)>,
+}
+
+impl Coordinator {
+    pub(crate) fn new(
+        key: String,
+        key_type: CoordinatorType,
+        brokers: Arc<BrokerConnector>,
+    ) -> Self {
+        Self {
+            key,
+            key_type,
+            brokers,
+            request_timeout: TimeoutOverride::Default,
+            current_broker: Mutex::new((None, BrokerCacheGeneration::START)),
+        }
+    }
+
+    /// Override the request timeout for looking up the coordinator.
+    pub(crate) fn with_request_timeout(self, request_timeout: TimeoutOverride) -> Self {
+        Self {
+            request_timeout,
+            ..self
+        }
+    }
+
+    /// Request context used for errors that concern the coordinator key.
+    pub(crate) fn request_context(&self) -> RequestContext {
+        match self.key_type {
+            CoordinatorType::Group => RequestContext::Group(self.key.clone()),
+            CoordinatorType::Transaction => RequestContext::Transaction(self.key.clone()),
+        }
+    }
+
+    /// Retrieve the broker ID of the coordinator.
+    async fn get_coordinator_id(&self) -> Result<i32> {
+        let request = &FindCoordinatorRequest {
+            key: String_(self.key.clone()),
+            key_type: Some(self.key_type),
+        };
+
+        let (broker, r#gen) = (&*self.brokers).get().await?;
+        let response = match broker
+            .request_with_timeout(request, broker.request_timeout(self.request_timeout))
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                if matches!(
+                    e,
+                    RequestError::Poisoned(_) | RequestError::IO(_) | RequestError::Timeout { .. }
+                ) {
+                    (&*self.brokers)
+                        .invalidate("coordinator: connection broken", r#gen)
+                        .await;
+                }
+                return Err(e.into());
+            }
+        };
+
+        if let Some(protocol_error) = response.error {
+            return Err(Error::ServerError {
+                protocol_error,
+                error_message: response.error_message.and_then(|s| s.0),
+                request: self.request_context(),
+                response: None,
+                is_virtual: false,
+            });
+        }
+
+        Ok(response.node_id.0)
+    }
+}
+
+/// Caches the coordinator broker.
+impl BrokerCache for &Coordinator {
+    type R = MessengerTransport;
+    type E = Error;
+
+    async fn get(&self) -> Result<(Arc<Self::R>, BrokerCacheGeneration)> {
+        let mut current_broker = self.current_broker.lock().await;
+        if let Some(broker) = &current_broker.0 {
+            return Ok((Arc::clone(broker), current_broker.1));
+        }
+
+        info!(
+            key = self.key.as_str(),
+            "Creating new coordinator broker connection"
+        );
+
+        let coordinator_id = self.get_coordinator_id().await?;
+        let broker = match self.brokers.connect(coordinator_id).await? {
+            Some(broker) => broker,
+            None => {
+                // The coordinator might be a broker that we have not seen yet, so refresh our view of the cluster.
+                self.brokers.refresh_metadata().await?;
+                self.brokers.connect(coordinator_id).await?.ok_or_else(|| {
+                    Error::InvalidResponse(format!(
+                        "Coordinator {coordinator_id} not found in metadata response"
+                    ))
+                })?
+            }
+        };
+
+        current_broker.0 = Some(Arc::clone(&broker));
+        current_broker.1.bump();
+
+        Ok((broker, current_broker.1))
+    }
+
+    async fn invalidate(&self, reason: &'static str, r#gen: BrokerCacheGeneration) {
+        let mut guard = self.current_broker.lock().await;
+
+        if guard.1 != r#gen {
+            // stale request
+            debug!(
+                reason,
+                current_gen = guard.1.get(),
+                request_gen = r#gen.get(),
+                "stale invalidation request for coordinator broker cache",
+            );
+            return;
+        }
+
+        info!(
+            key = self.key.as_str(),
+            reason, "Invalidating cached coordinator broker",
+        );
+        guard.0.take();
+    }
+}
+
+/// Takes a `request_name` and a function yielding a fallible future
+/// and handles certain classes of error
+pub(crate) async fn maybe_retry<B, R, F, T>(
+    backoff_config: &BackoffConfig,
+    broker_cache: B,
+    request_name: &str,
+    f: R,
+) -> Result<T>
+where
+    B: BrokerCache,
+    R: (Fn() -> F) + Send + Sync,
+    F: std::future::Future<
+            Output = Result<T, ErrorOrThrottle<(Error, Option<BrokerCacheGeneration>)>>,
+        > + Send,
+{
+    let mut backoff = Backoff::new(backoff_config);
+
+    backoff
+        .retry_with_backoff(request_name, || async {
+            let (error, cache_gen) = match f().await {
+                Ok(v) => {
+                    return ControlFlow::Break(Ok(v));
+                }
+                Err(ErrorOrThrottle::Throttle(t)) => {
+                    return ControlFlow::Continue(ErrorOrThrottle::Throttle(t));
+                }
+                Err(ErrorOrThrottle::Error(e)) => e,
+            };
+
+            match error {
+                // broken connection
+                Error::Request(
+                    RequestError::Poisoned(_) | RequestError::IO(_) | RequestError::Timeout { .. },
+                )
+                | Error::Connection(_) => {
+                    if let Some(cache_gen) = cache_gen {
+                        broker_cache
+                            .invalidate("coordinator: connection broken", cache_gen)
+                            .await
+                    }
+                }
+
+                // coordinator moved or is not ready yet
+                Error::ServerError {
+                    protocol_error:
+                        ProtocolError::NotCoordinator | ProtocolError::CoordinatorNotAvailable,
+                    ..
+                } => {
+                    if let Some(cache_gen) = cache_gen {
+                        broker_cache
+                            .invalidate("coordinator: server error: not coordinator", cache_gen)
+                            .await;
+                    }
+                }
+
+                // coordinator is still loading its state or busy finishing a previous transaction
+                Error::ServerError {
+                    protocol_error:
+                        ProtocolError::CoordinatorLoadInProgress | ProtocolError::ConcurrentTransactions,
+                    ..
+                } => {}
+
+                // fatal
+                _ => {
+                    error!(
+                        e=%error,
+                        request_name,
+                        "request encountered fatal error",
+                    );
+                    return ControlFlow::Break(Err(error));
+                }
+            }
+            ControlFlow::Continue(ErrorOrThrottle::Error(error))
+        })
+        .await
+        .map_err(Error::RetryFailed)?
+}
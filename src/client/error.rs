@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use super::{acl::AclBindingFilter, controller::ConfigResource};
+
 pub use crate::messenger::RequestError;
 pub use crate::protocol::error::Error as ProtocolError;
 
@@ -25,6 +27,18 @@ pub enum RequestContext {
         /// Offset used during the request.
         offset: i64,
     },
+
+    /// Error is specific to a consumer group.
+    Group(String),
+
+    /// Error is specific to a transactional ID.
+    Transaction(String),
+
+    /// Error is specific to the configuration of a topic or broker.
+    Config(ConfigResource),
+
+    /// Error is specific to an ACL or an ACL filter.
+    Acl(Box<AclBindingFilter>),
 }
 
 /// Usable broker data for [`Error::ServerError`].
@@ -96,6 +110,45 @@ pub enum Error {
 
     #[error("Timeout")]
     Timeout,
+
+    #[error("Invalid transaction state: {0}")]
+    InvalidTransactionState(String),
+
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+
+    #[error(
+        "Log truncation detected at offset {}, fetch offset: {}",
+        offset_or_na(truncation_offset),
+        fetch_offset
+    )]
+    LogTruncation {
+        /// Offset at which the consumer was about to continue.
+        fetch_offset: i64,
+
+        /// First offset at which the log of the current leader diverges from the consumed log, i.e. records at and
+        /// after this offset that were consumed before are not part of the log anymore.
+        ///
+        /// `None` if the leader does not know the leader epoch of the consumed records.
+        truncation_offset: Option<i64>,
+
+        /// Leader epoch of the last record before the truncation point, if known.
+        leader_epoch: Option<i32>,
+    },
+
+    #[error(
+        "Partitioner returned partition {partition} for topic \"{topic}\" with {num_partitions} partitions"
+    )]
+    InvalidPartition {
+        /// Topic name.
+        topic: String,
+
+        /// Partition returned by the [`Partitioner`](crate::client::partitioner::Partitioner).
+        partition: i32,
+
+        /// Partition count that was passed to the partitioner.
+        num_partitions: i32,
+    },
 }
 
 impl Error {
@@ -119,3 +172,11 @@ fn string_or_na(s: &Option<String>) -> &str {
         None => "n/a",
     }
 }
+
+/// Same as [`string_or_na`] but for offsets.
+fn offset_or_na(offset: &Option<i64>) -> String {
+    match offset {
+        Some(offset) => offset.to_string(),
+        None => "n/a".to_owned(),
+    }
+}
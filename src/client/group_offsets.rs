@@ -0,0 +1,320 @@
+//! Offsets that are committed for a consumer group.
+//!
+//! The group coordinator stores the position of each consumer group per partition. This allows consumers to resume
+//! where they (or a former member of the group) left off, see [`StartOffset::Committed`].
+//!
+//! # Usage
+//! ```no_run
+//! # async fn test() {
+//! use rskafka::client::{ClientBuilder, group_offsets::CommittedOffset};
+//!
+//! let connection = "localhost:9093".to_owned();
+//! let client = ClientBuilder::new(vec![connection]).build().await.unwrap();
+//! let offsets_client = client.group_offsets_client("my_group");
+//!
+//! // commit offset
+//! offsets_client
+//!     .commit_offsets(&[CommittedOffset {
+//!         topic: "my_topic".to_owned(),
+//!         partition: 0,
+//!         offset: 42,
+//!         metadata: None,
+//!     }])
+//!     .await
+//!     .unwrap();
+//!
+//! // fetch it again
+//! let offset = offsets_client.fetch_offset("my_topic", 0).await.unwrap();
+//! assert_eq!(offset, Some(42));
+//! # }
+//! ```
+//!
+//! [`StartOffset::Committed`]: crate::client::consumer::StartOffset::Committed
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+use tracing::debug;
+
+use crate::{
+    backoff::{BackoffConfig, ErrorOrThrottle},
+    client::{
+        coordinator::{Coordinator, maybe_retry},
+        error::{Error, RequestContext, Result},
+    },
+    connection::{BrokerCache, BrokerConnector},
+    messenger::TimeoutOverride,
+    protocol::{
+        messages::{
+            CoordinatorType, OffsetCommitRequest, OffsetCommitRequestPartition,
+            OffsetCommitRequestTopic, OffsetFetchRequest, OffsetFetchRequestTopic,
+        },
+        primitives::{Array, Int32, Int64, NullableString, String_},
+    },
+    throttle::maybe_throttle,
+};
+
+/// Offset of a partition that is committed for a consumer group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommittedOffset {
+    /// Topic name.
+    pub topic: String,
+
+    /// Partition ID.
+    pub partition: i32,
+
+    /// Offset of the next record that should be consumed.
+    pub offset: i64,
+
+    /// Arbitrary metadata that is stored alongside the offset.
+    pub metadata: Option<String>,
+}
+
+/// Identifies a member of the group within a specific generation.
+///
+/// The coordinator only accepts commits of group members if they belong to the current generation.
+#[derive(Debug, Clone)]
+pub(crate) struct GroupMember {
+    pub(crate) generation_id: i32,
+    pub(crate) member_id: String,
+    pub(crate) group_instance_id: Option<String>,
+}
+
+/// Client to commit and fetch offsets of a consumer group.
+///
+/// Requests are sent to the group coordinator, which is looked up (and re-discovered if it moves) automatically.
+#[derive(Debug)]
+pub struct GroupOffsetsClient {
+    group_id: String,
+
+    backoff_config: Arc<BackoffConfig>,
+
+    coordinator: Coordinator,
+
+    /// Request timeout that overrides [`ClientBuilder::request_timeout`](crate::client::ClientBuilder::request_timeout).
+    request_timeout: TimeoutOverride,
+}
+
+impl GroupOffsetsClient {
+    pub(crate) fn new(
+        group_id: String,
+        brokers: Arc<BrokerConnector>,
+        backoff_config: Arc<BackoffConfig>,
+    ) -> Self {
+        Self {
+            coordinator: Coordinator::new(group_id.clone(), CoordinatorType::Group, brokers),
+            group_id,
+            backoff_config,
+            request_timeout: TimeoutOverride::Default,
+        }
+    }
+
+    /// Override the [request timeout](crate::client::ClientBuilder::request_timeout) for requests sent to the
+    /// coordinator.
+    ///
+    /// This includes looking up the coordinator. Defaults to [`TimeoutOverride::Default`].
+    pub fn with_request_timeout(self, request_timeout: TimeoutOverride) -> Self {
+        Self {
+            coordinator: self.coordinator.with_request_timeout(request_timeout),
+            request_timeout,
+            ..self
+        }
+    }
+
+    /// Group ID.
+    pub fn group_id(&self) -> &str {
+        &self.group_id
+    }
+
+    /// Commit offsets without being a member of the group.
+    ///
+    /// The coordinator rejects this while the group has active members. Use
+    /// [`Generation::commit_offsets`](crate::client::consumer_group::Generation::commit_offsets) in that case.
+    pub async fn commit_offsets(&self, offsets: &[CommittedOffset]) -> Result<()> {
+        self.commit_offsets_as(offsets, None).await
+    }
+
+    /// Commit offsets, optionally as a member of the group.
+    pub(crate) async fn commit_offsets_as(
+        &self,
+        offsets: &[CommittedOffset],
+        member: Option<&GroupMember>,
+    ) -> Result<()> {
+        let mut topics: BTreeMap<&str, Vec<OffsetCommitRequestPartition>> = BTreeMap::new();
+        for offset in offsets {
+            topics
+                .entry(offset.topic.as_str())
+                .or_default()
+                .push(OffsetCommitRequestPartition {
+                    partition_index: Int32(offset.partition),
+                    committed_offset: Int64(offset.offset),
+                    committed_leader_epoch: None,
+                    commit_timestamp: None,
+                    committed_metadata: NullableString(offset.metadata.clone()),
+                });
+        }
+
+        let request = &OffsetCommitRequest {
+            group_id: String_(self.group_id.clone()),
+            generation_id: member.map(|m| Int32(m.generation_id)),
+            member_id: member.map(|m| String_(m.member_id.clone())),
+            group_instance_id: member.map(|m| NullableString(m.group_instance_id.clone())),
+            retention_time_ms: None,
+            topics: topics
+                .into_iter()
+                .map(|(topic, partitions)| OffsetCommitRequestTopic {
+                    name: String_(topic.to_owned()),
+                    partitions,
+                })
+                .collect(),
+        };
+
+        maybe_retry(
+            &self.backoff_config,
+            &self.coordinator,
+            "commit_offsets",
+            || async move {
+                let (broker, r#gen) = (&self.coordinator)
+                    .get()
+                    .await
+                    .map_err(|e| ErrorOrThrottle::Error((e, None)))?;
+                let response = broker
+                    .request_with_timeout(request, broker.request_timeout(self.request_timeout))
+                    .await
+                    .map_err(|e| ErrorOrThrottle::Error((e.into(), Some(r#gen))))?;
+                maybe_throttle(response.throttle_time_ms)?;
+
+                for topic in response.topics {
+                    for partition in topic.partitions {
+                        if let Some(protocol_error) = partition.error {
+                            return Err(ErrorOrThrottle::Error((
+                                Error::ServerError {
+                                    protocol_error,
+                                    error_message: None,
+                                    request: RequestContext::Partition(
+                                        topic.name.0,
+                                        partition.partition_index.0,
+                                    ),
+                                    response: None,
+                                    is_virtual: false,
+                                },
+                                Some(r#gen),
+                            )));
+                        }
+                    }
+                }
+
+                Ok(())
+            },
+        )
+        .await?;
+
+        debug!(
+            group_id = self.group_id.as_str(),
+            n_offsets = offsets.len(),
+            "Committed offsets",
+        );
+        Ok(())
+    }
+
+    /// Fetch the committed offsets of the given partitions, per topic.
+    ///
+    /// Partitions without a committed offset are omitted from the result.
+    pub async fn fetch_offsets(
+        &self,
+        partitions: &BTreeMap<String, BTreeSet<i32>>,
+    ) -> Result<Vec<CommittedOffset>> {
+        let request = &OffsetFetchRequest {
+            group_id: String_(self.group_id.clone()),
+            topics: Some(
+                partitions
+                    .iter()
+                    .map(|(topic, partitions)| OffsetFetchRequestTopic {
+                        name: String_(topic.clone()),
+                        partition_indexes: Array(Some(
+                            partitions.iter().copied().map(Int32).collect(),
+                        )),
+                    })
+                    .collect(),
+            ),
+        };
+
+        maybe_retry(
+            &self.backoff_config,
+            &self.coordinator,
+            "fetch_offsets",
+            || async move {
+                let (broker, r#gen) = (&self.coordinator)
+                    .get()
+                    .await
+                    .map_err(|e| ErrorOrThrottle::Error((e, None)))?;
+                let response = broker
+                    .request_with_timeout(request, broker.request_timeout(self.request_timeout))
+                    .await
+                    .map_err(|e| ErrorOrThrottle::Error((e.into(), Some(r#gen))))?;
+                maybe_throttle(response.throttle_time_ms)?;
+
+                if let Some(protocol_error) = response.error {
+                    return Err(ErrorOrThrottle::Error((
+                        Error::ServerError {
+                            protocol_error,
+                            error_message: None,
+                            request: self.coordinator.request_context(),
+                            response: None,
+                            is_virtual: false,
+                        },
+                        Some(r#gen),
+                    )));
+                }
+
+                let mut offsets = vec![];
+                for topic in response.topics {
+                    for partition in topic.partitions {
+                        if let Some(protocol_error) = partition.error {
+                            return Err(ErrorOrThrottle::Error((
+                                Error::ServerError {
+                                    protocol_error,
+                                    error_message: None,
+                                    request: RequestContext::Partition(
+                                        topic.name.0,
+                                        partition.partition_index.0,
+                                    ),
+                                    response: None,
+                                    is_virtual: false,
+                                },
+                                Some(r#gen),
+                            )));
+                        }
+
+                        // the group has not committed an offset for this partition
+                        if partition.committed_offset.0 < 0 {
+                            continue;
+                        }
+
+                        offsets.push(CommittedOffset {
+                            topic: topic.name.0.clone(),
+                            partition: partition.partition_index.0,
+                            offset: partition.committed_offset.0,
+                            metadata: partition.metadata.0,
+                        });
+                    }
+                }
+
+                Ok(offsets)
+            },
+        )
+        .await
+    }
+
+    /// Fetch the committed offset of a single partition.
+    ///
+    /// Returns `None` if the group has not committed an offset for this partition.
+    pub async fn fetch_offset(&self, topic: &str, partition: i32) -> Result<Option<i64>> {
+        let partitions = BTreeMap::from([(topic.to_owned(), BTreeSet::from([partition]))]);
+        let offsets = self.fetch_offsets(&partitions).await?;
+
+        Ok(offsets
+            .into_iter()
+            .find(|o| o.topic == topic && o.partition == partition)
+            .map(|o| o.offset))
+    }
+}
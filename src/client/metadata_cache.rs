@@ -3,7 +3,7 @@ use std::ops::Deref;
 use parking_lot::Mutex;
 use tracing::{debug, info};
 
-use crate::protocol::messages::MetadataResponse;
+use crate::protocol::messages::{MetadataResponse, MetadataResponseTopic};
 
 /// Cache generation for [`MetadataCache`].
 ///
@@ -69,6 +69,27 @@ impl MetadataCache {
         Some((m, r#gen))
     }
 
+    /// Current generation, which changes whenever the cache is updated.
+    pub(crate) fn generation(&self) -> MetadataCacheGeneration {
+        self.cache.lock().1
+    }
+
+    /// Grab a copy of the cached metadata of `topic`.
+    ///
+    /// Unlike [`get`](Self::get), this does not invalidate the cache if the topic is unknown.
+    pub(crate) fn topic(
+        &self,
+        topic: &str,
+    ) -> (Option<MetadataResponseTopic>, MetadataCacheGeneration) {
+        let guard = self.cache.lock();
+        let topic = guard
+            .0
+            .as_ref()
+            .and_then(|m| m.topics.iter().find(|t| t.name.0 == topic))
+            .cloned();
+        (topic, guard.1)
+    }
+
     pub(crate) fn invalidate(&self, reason: &'static str, r#gen: MetadataCacheGeneration) {
         let mut guard = self.cache.lock();
         if guard.1 != r#gen {
@@ -170,6 +191,25 @@ mod tests {
         assert!(cache.get(&Some(vec!["bananas".to_string()])).is_none());
     }
 
+    #[test]
+    fn test_topic() {
+        let cache = MetadataCache::default();
+        let (topic, gen1) = cache.topic("bananas");
+        assert!(topic.is_none());
+
+        cache.update(response_with_topics(Some(&["bananas", "platanos"])));
+        let (topic, gen2) = cache.topic("bananas");
+        assert_eq!(topic.unwrap().name.0, "bananas");
+        assert_ne!(gen1, gen2);
+        assert_eq!(cache.generation(), gen2);
+
+        // unknown topics do not invalidate the cache
+        let (topic, gen3) = cache.topic("goats");
+        assert!(topic.is_none());
+        assert_eq!(gen2, gen3);
+        assert!(cache.get(&None).is_some());
+    }
+
     #[test]
     fn test_explicit_invalidate() {
         let cache = MetadataCache::default();
@@ -1,4 +1,4 @@
-use std::{sync::Arc, time::Duration};
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
 
 use thiserror::Error;
 
@@ -7,22 +7,37 @@ use crate::{
     build_info::DEFAULT_CLIENT_ID,
     client::partition::PartitionClient,
     connection::{BrokerConnector, MetadataLookupMode, TlsConfig},
+    messenger::RequestTimeoutConfig,
     protocol::primitives::Boolean,
-    topic::Topic,
+    topic::{PartitionDescription, Topic, TopicDescription},
 };
 
+pub mod acl;
 pub mod consumer;
+pub mod consumer_group;
 pub mod controller;
+mod coordinator;
 pub mod error;
+pub mod group_offsets;
 pub(crate) mod metadata_cache;
 pub mod partition;
+pub mod partitioner;
 pub mod producer;
+mod producer_id;
+pub mod topic_producer;
+pub mod transaction;
 
 use error::{Error, Result};
 
-use self::{controller::ControllerClient, partition::UnknownTopicHandling};
+use self::{
+    consumer::multi::MultiPartitionConsumerBuilder, consumer_group::ConsumerGroupBuilder,
+    controller::ControllerClient, group_offsets::GroupOffsetsClient,
+    partition::UnknownTopicHandling, producer::broker::BrokerProducerBuilder,
+    topic_producer::TopicProducerBuilder, transaction::TransactionalProducerBuilder,
+};
 
 pub use crate::connection::{Credentials, OauthBearerCredentials, OauthCallback, SaslConfig};
+pub use crate::messenger::TimeoutOverride;
 
 #[derive(Debug, Error)]
 pub enum ProduceError {
@@ -49,6 +64,9 @@ pub struct ClientBuilder {
     sasl_config: Option<SaslConfig>,
     backoff_config: Arc<BackoffConfig>,
     connect_timeout: Option<Duration>,
+    timeout_config: RequestTimeoutConfig,
+    connections_per_broker: usize,
+    client_rack: Option<Arc<str>>,
 }
 
 impl ClientBuilder {
@@ -63,6 +81,9 @@ impl ClientBuilder {
             sasl_config: None,
             backoff_config: Default::default(),
             connect_timeout: Some(Duration::from_secs(30)),
+            timeout_config: RequestTimeoutConfig::default(),
+            connections_per_broker: 1,
+            client_rack: None,
         }
     }
 
@@ -117,6 +138,60 @@ impl ClientBuilder {
         self
     }
 
+    /// Set the time after which a request to a broker fails with a timeout error.
+    ///
+    /// Fetch and produce requests allow the broker to hold back the response on purpose (see `max_wait_ms` of
+    /// [`PartitionClient::fetch_records`] and [`PartitionClient::with_produce_timeout`]). For these the timeout is
+    /// extended by that time.
+    ///
+    /// By setting this to `None` (the default), requests wait for a response forever. Sub-clients and single calls can
+    /// override this, see [`TimeoutOverride`].
+    pub fn request_timeout(mut self, request_timeout: Option<Duration>) -> Self {
+        self.timeout_config.timeout = request_timeout;
+        self
+    }
+
+    /// Treat a connection as broken after this many consecutive requests timed out.
+    ///
+    /// A broken connection is re-established by the next request. This only has an effect if a
+    /// [request timeout](Self::request_timeout) is set. By setting this to `None` (the default), connections are never
+    /// considered broken because of timeouts.
+    pub fn max_consecutive_request_timeouts(
+        mut self,
+        max_consecutive_request_timeouts: Option<usize>,
+    ) -> Self {
+        self.timeout_config.max_consecutive_timeouts = max_consecutive_request_timeouts;
+        self
+    }
+
+    /// Set the number of connections to every broker that are shared by all sub-clients.
+    ///
+    /// Requests are pipelined, so a single connection per broker (the default) is usually sufficient. More connections
+    /// may help if many large requests to the same broker block each other.
+    ///
+    /// # Panics
+    /// Panics if `connections_per_broker` is zero.
+    pub fn connections_per_broker(mut self, connections_per_broker: usize) -> Self {
+        assert!(
+            connections_per_broker > 0,
+            "need at least one connection per broker"
+        );
+        self.connections_per_broker = connections_per_broker;
+        self
+    }
+
+    /// Set the rack (e.g. the availability zone) the client runs in.
+    ///
+    /// This is sent with every fetch request of a [`PartitionClient`]. If the brokers are configured with a rack-aware
+    /// replica selector (see [KIP-392]), the leader may point the client to a follower replica in the same rack, which
+    /// is then used for fetching. Defaults to no rack, i.e. records are always fetched from the partition leader.
+    ///
+    /// [KIP-392]: https://cwiki.apache.org/confluence/display/KAFKA/KIP-392%3A+Allow+consumers+to+fetch+from+closest+replica
+    pub fn client_rack(mut self, client_rack: impl Into<Arc<str>>) -> Self {
+        self.client_rack = Some(client_rack.into());
+        self
+    }
+
     /// Build [`Client`].
     pub async fn build(self) -> Result<Client> {
         let brokers = Arc::new(BrokerConnector::new(
@@ -129,6 +204,9 @@ impl ClientBuilder {
             self.max_message_size,
             Arc::clone(&self.backoff_config),
             self.connect_timeout,
+            self.timeout_config,
+            self.connections_per_broker,
+            self.client_rack,
         ));
         brokers.refresh_metadata().await?;
 
@@ -148,7 +226,9 @@ impl std::fmt::Debug for ClientBuilder {
 /// Top-level cluster-wide client.
 ///
 /// This client can be used to query some cluster-wide metadata and construct task-specific sub-clients like
-/// [`ControllerClient`] and [`PartitionClient`].
+/// [`ControllerClient`], [`PartitionClient`], [`TopicProducer`](topic_producer::TopicProducer),
+/// [`ConsumerGroupClient`](consumer_group::ConsumerGroupClient), [`GroupOffsetsClient`] and
+/// [`TransactionalProducer`](transaction::TransactionalProducer).
 ///
 /// Must be constructed using [`ClientBuilder`].
 #[derive(Debug)]
@@ -183,6 +263,68 @@ impl Client {
         .await
     }
 
+    /// Returns a builder for a producer that sends one produce request per broker for all partitions it leads.
+    pub fn broker_producer(&self) -> BrokerProducerBuilder {
+        BrokerProducerBuilder::new(Arc::clone(&self.brokers), Arc::clone(&self.backoff_config))
+    }
+
+    /// Returns a builder for a producer that writes to all partitions of `topic`.
+    pub fn topic_producer(
+        &self,
+        topic: impl Into<String>,
+        unknown_topic_handling: UnknownTopicHandling,
+    ) -> TopicProducerBuilder {
+        TopicProducerBuilder::new(
+            topic.into(),
+            Arc::clone(&self.brokers),
+            Arc::clone(&self.backoff_config),
+            unknown_topic_handling,
+        )
+    }
+
+    /// Returns a builder for a consumer that reads many partitions using one fetch request per broker.
+    pub fn multi_partition_consumer(&self) -> MultiPartitionConsumerBuilder {
+        MultiPartitionConsumerBuilder::new(
+            Arc::clone(&self.brokers),
+            Arc::clone(&self.backoff_config),
+        )
+    }
+
+    /// Returns a builder to join the consumer group `group_id`, subscribing to the given topics.
+    pub fn consumer_group(
+        &self,
+        group_id: impl Into<String>,
+        topics: Vec<String>,
+    ) -> ConsumerGroupBuilder {
+        ConsumerGroupBuilder::new(
+            Arc::clone(&self.brokers),
+            Arc::clone(&self.backoff_config),
+            group_id.into(),
+            topics,
+        )
+    }
+
+    /// Returns a client to commit and fetch the offsets of the consumer group `group_id`.
+    pub fn group_offsets_client(&self, group_id: impl Into<String>) -> GroupOffsetsClient {
+        GroupOffsetsClient::new(
+            group_id.into(),
+            Arc::clone(&self.brokers),
+            Arc::clone(&self.backoff_config),
+        )
+    }
+
+    /// Returns a builder for a producer that writes within transactions identified by `transactional_id`.
+    pub fn transactional_producer(
+        &self,
+        transactional_id: impl Into<String>,
+    ) -> TransactionalProducerBuilder {
+        TransactionalProducerBuilder::new(
+            Arc::clone(&self.brokers),
+            Arc::clone(&self.backoff_config),
+            transactional_id.into(),
+        )
+    }
+
     /// Returns a list of topics in the cluster
     pub async fn list_topics(&self) -> Result<Vec<Topic>> {
         // Do not used a cached metadata response to satisfy this request, in
@@ -212,4 +354,95 @@ impl Client {
             })
             .collect())
     }
+
+    /// Returns the detailed state of topics in the cluster.
+    ///
+    /// If `names` is `None`, all topics are described, ordered by name, and internal topics (e.g.
+    /// `__consumer_offsets`) are only included if `include_internal` is set. Otherwise exactly the given topics are
+    /// described, in the order of `names`, and topics that do not exist are reported via [`TopicDescription::error`].
+    /// Note that brokers with `auto.create.topics.enable` create topics that do not exist.
+    ///
+    /// Unlike [`list_topics`](Self::list_topics), this reports the leader, replicas and in-sync replicas of every
+    /// partition, e.g. to detect under-replicated partitions.
+    pub async fn describe_topics(
+        &self,
+        names: Option<Vec<String>>,
+        include_internal: bool,
+    ) -> Result<Vec<TopicDescription>> {
+        // Always ask the brokers, cached metadata may be arbitrarily stale.
+        let (response, _gen) = self
+            .brokers
+            .request_metadata(&MetadataLookupMode::ArbitraryBroker, names.clone())
+            .await?;
+
+        let mut topics = response
+            .topics
+            .into_iter()
+            .map(|t| {
+                let mut partitions = t
+                    .partitions
+                    .into_iter()
+                    .map(|p| PartitionDescription {
+                        partition: p.partition_index.0,
+                        leader: (p.leader_id.0 >= 0).then_some(p.leader_id.0),
+                        leader_epoch: p.leader_epoch.map(|e| e.0).filter(|e| *e >= 0),
+                        replicas: p
+                            .replica_nodes
+                            .0
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|n| n.0)
+                            .collect(),
+                        isr: p
+                            .isr_nodes
+                            .0
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|n| n.0)
+                            .collect(),
+                        offline_replicas: p
+                            .offline_replicas
+                            .and_then(|nodes| nodes.0)
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|n| n.0)
+                            .collect(),
+                        error: p.error,
+                    })
+                    .collect::<Vec<_>>();
+                partitions.sort_by_key(|p| p.partition);
+
+                TopicDescription {
+                    name: t.name.0,
+                    is_internal: matches!(t.is_internal, Some(Boolean(true))),
+                    error: t.error,
+                    partitions,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        match names {
+            Some(names) => {
+                let mut topics = topics
+                    .into_iter()
+                    .map(|t| (t.name.clone(), t))
+                    .collect::<BTreeMap<_, _>>();
+                names
+                    .into_iter()
+                    .map(|name| {
+                        topics.remove(&name).ok_or_else(|| {
+                            Error::InvalidResponse(format!(
+                                "No result for topic \"{name}\" in response"
+                            ))
+                        })
+                    })
+                    .collect()
+            }
+            None => {
+                topics.retain(|t| include_internal || !t.is_internal);
+                topics.sort_by(|a, b| a.name.cmp(&b.name));
+                Ok(topics)
+            }
+        }
+    }
 }
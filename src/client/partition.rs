@@ -5,33 +5,52 @@ use crate::{
         BrokerCache, BrokerCacheGeneration, BrokerConnection, BrokerConnector, MessengerTransport,
         MetadataLookupMode,
     },
-    messenger::RequestError,
+    messenger::{RequestError, TimeoutOverride},
     protocol::{
         error::Error as ProtocolError,
         messages::{
             DeleteRecordsRequest, DeleteRecordsResponse, DeleteRequestPartition,
             DeleteRequestTopic, DeleteResponsePartition, FetchRequest, FetchRequestPartition,
-            FetchRequestTopic, FetchResponse, FetchResponsePartition, IsolationLevel,
-            ListOffsetsRequest, ListOffsetsRequestPartition, ListOffsetsRequestTopic,
-            ListOffsetsResponse, ListOffsetsResponsePartition, NORMAL_CONSUMER, ProduceRequest,
+            FetchRequestTopic, FetchResponse, FetchResponseAbortedTransaction,
+            FetchResponsePartition, IsolationLevel as ProtocolIsolationLevel, ListOffsetsRequest,
+            ListOffsetsRequestPartition, ListOffsetsRequestTopic, ListOffsetsResponse,
+            ListOffsetsResponsePartition, NORMAL_CONSUMER, OffsetForLeaderEpochRequest,
+            OffsetForLeaderEpochRequestPartition, OffsetForLeaderEpochRequestTopic,
+            OffsetForLeaderEpochResponse, OffsetForLeaderEpochResponsePartition, ProduceRequest,
             ProduceRequestPartitionData, ProduceRequestTopicData, ProduceResponse,
+            ProduceResponsePartitionResponse,
         },
         primitives::*,
         record::{Record as ProtocolRecord, *},
     },
-    record::{Record, RecordAndOffset},
+    record::{self, Record, RecordAndOffset, RecordBatchMetadata, TimestampType},
     throttle::maybe_throttle,
     validation::ExactlyOne,
 };
 use chrono::{DateTime, LocalResult, TimeZone, Utc};
 use std::{
+    collections::HashSet,
     ops::{ControlFlow, Deref, Range},
     sync::Arc,
+    time::{Duration, Instant},
 };
 use tokio::sync::Mutex;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
-use super::{error::ServerErrorResponse, metadata_cache::MetadataCacheGeneration};
+use super::{
+    error::ServerErrorResponse,
+    group_offsets::GroupOffsetsClient,
+    metadata_cache::MetadataCacheGeneration,
+    producer_id::{PartitionSequence, ProducerIdAndEpoch, init_producer_id},
+};
+
+/// Default time the broker may wait for the acknowledgements of a produce request.
+const DEFAULT_PRODUCE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Time after which fetches go to the leader again, so it can pick a different read replica.
+///
+/// Same as the default metadata max age of the Java client, which it uses for this purpose.
+const READ_REPLICA_MAX_AGE: Duration = Duration::from_secs(5 * 60);
 
 /// How strongly a [`PartitionClient`] is bound to a partition.
 ///
@@ -81,6 +100,61 @@ pub enum Compression {
     Zstd,
 }
 
+/// How many replicas must acknowledge a write before [`PartitionClient::produce`] returns.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Acks {
+    /// Do not wait for the broker at all ("fire and forget").
+    ///
+    /// The broker does not send a response, so write errors go unnoticed and no offsets are known. The offsets
+    /// returned by [`PartitionClient::produce`] are all `-1` in this case.
+    None,
+
+    /// Wait until the partition leader has written the records to its local log.
+    ///
+    /// Records might be lost if the leader fails before the followers replicated them.
+    Leader,
+
+    /// Wait until all in-sync replicas have acknowledged the records.
+    #[default]
+    All,
+}
+
+impl Acks {
+    pub(crate) fn value(self) -> i16 {
+        match self {
+            Self::None => 0,
+            Self::Leader => 1,
+            Self::All => -1,
+        }
+    }
+}
+
+/// Which records of transactional producers are returned by [`PartitionClient::fetch_records`].
+///
+/// Records of non-transactional producers are always visible.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum IsolationLevel {
+    /// All records, including those of open and aborted transactions.
+    ReadUncommitted,
+
+    /// Only records of committed transactions.
+    ///
+    /// The broker only returns records up to the last stable offset (LSO), i.e. the first offset of the earliest open
+    /// transaction. Records of aborted transactions are dropped by the client. [`OffsetAt::Latest`] also refers to the
+    /// LSO instead of the high watermark.
+    #[default]
+    ReadCommitted,
+}
+
+impl From<IsolationLevel> for ProtocolIsolationLevel {
+    fn from(isolation_level: IsolationLevel) -> Self {
+        match isolation_level {
+            IsolationLevel::ReadUncommitted => Self::ReadUncommitted,
+            IsolationLevel::ReadCommitted => Self::ReadCommitted,
+        }
+    }
+}
+
 /// Which type of offset should be requested by [`PartitionClient::get_offset`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OffsetAt {
@@ -107,6 +181,51 @@ struct CurrentBroker {
     gen_broker: BrokerCacheGeneration,
     gen_leader_from_arbitrary: Option<MetadataCacheGeneration>,
     gen_leader_from_self: Option<MetadataCacheGeneration>,
+
+    /// Leader epoch of the partition as reported by the leader, kept when the broker connection is invalidated.
+    leader_epoch: Option<i32>,
+}
+
+/// Result of [`PartitionClient::fetch_batches`].
+#[derive(Debug)]
+pub(crate) struct FetchedBatches {
+    pub(crate) batches: Vec<record::RecordBatch>,
+
+    pub(crate) high_watermark: i64,
+
+    /// Last stable offset, if reported by the broker.
+    pub(crate) last_stable_offset: Option<i64>,
+
+    /// Offset after the last batch of the response, if any.
+    ///
+    /// This also accounts for batches that are not returned (control batches and aborted transactions) and for
+    /// records that were removed by compaction, so it may be larger than the offset after the last returned record.
+    pub(crate) next_offset: Option<i64>,
+
+    /// Leader epoch that was sent with the fetch request, if known.
+    pub(crate) leader_epoch: Option<i32>,
+}
+
+/// Result of [`PartitionClient::get_epoch_end_offset`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EpochEndOffset {
+    /// Largest leader epoch that is smaller than or equal to the requested one.
+    ///
+    /// `None` if the leader does not know such an epoch or does not report it.
+    pub leader_epoch: Option<i32>,
+
+    /// Offset after the last record of [`leader_epoch`](Self::leader_epoch), i.e. the first offset of the next epoch or
+    /// the end of the log if it is the current epoch.
+    ///
+    /// `None` if the leader does not know the epoch.
+    pub end_offset: Option<i64>,
+}
+
+/// Follower replica that the leader asked us to fetch from.
+#[derive(Debug, Clone, Copy)]
+struct ReadReplica {
+    broker_id: i32,
+    expires_at: Instant,
 }
 
 /// Many operations must be performed on the leader for a partition
@@ -129,7 +248,27 @@ pub struct PartitionClient {
     /// Current broker connection if any
     current_broker: Mutex<CurrentBroker>,
 
+    /// Replica used for fetching instead of the leader, see [`ClientBuilder::client_rack`](super::ClientBuilder::client_rack).
+    read_replica: parking_lot::Mutex<Option<ReadReplica>>,
+
     unknown_topic_handling: UnknownTopicHandling,
+
+    /// Acknowledgements required for produce requests.
+    acks: Acks,
+
+    /// Time the broker may wait for the required acknowledgements.
+    produce_timeout: Duration,
+
+    /// Isolation level for fetch and list offsets requests.
+    isolation_level: IsolationLevel,
+
+    /// Sequence number state if the client is idempotent.
+    ///
+    /// The lock is held for the entire produce call, so there is at most one batch in flight.
+    idempotence: Option<Mutex<Option<PartitionSequence>>>,
+
+    /// Request timeout that overrides [`ClientBuilder::request_timeout`](super::ClientBuilder::request_timeout).
+    request_timeout: TimeoutOverride,
 }
 
 impl std::fmt::Debug for PartitionClient {
@@ -156,8 +295,15 @@ impl PartitionClient {
                 gen_broker: BrokerCacheGeneration::START,
                 gen_leader_from_arbitrary: None,
                 gen_leader_from_self: None,
+                leader_epoch: None,
             }),
+            read_replica: parking_lot::Mutex::new(None),
             unknown_topic_handling,
+            acks: Acks::default(),
+            produce_timeout: DEFAULT_PRODUCE_TIMEOUT,
+            isolation_level: IsolationLevel::default(),
+            idempotence: None,
+            request_timeout: TimeoutOverride::Default,
         };
 
         // Force discover and establish a cached connection to the leader
@@ -190,11 +336,116 @@ impl PartitionClient {
         self.partition
     }
 
+    /// Acknowledgements that [`produce`](Self::produce) waits for.
+    pub fn acks(&self) -> Acks {
+        self.acks
+    }
+
+    /// Time the broker may wait for the acknowledgements of a produce request.
+    pub fn produce_timeout(&self) -> Duration {
+        self.produce_timeout
+    }
+
+    /// Isolation level used by [`fetch_records`](Self::fetch_records) and [`get_offset`](Self::get_offset).
+    pub fn isolation_level(&self) -> IsolationLevel {
+        self.isolation_level
+    }
+
+    /// Set the acknowledgements that [`produce`](Self::produce) waits for.
+    ///
+    /// Defaults to [`Acks::All`]. [Idempotent](Self::with_idempotence) clients require [`Acks::All`], `produce` fails
+    /// with [`Error::InvalidConfig`] otherwise.
+    pub fn with_acks(self, acks: Acks) -> Self {
+        Self { acks, ..self }
+    }
+
+    /// Set how long the broker may wait for the acknowledgements before it fails a produce request.
+    ///
+    /// Defaults to 30 seconds. This has no effect for [`Acks::None`].
+    pub fn with_produce_timeout(self, produce_timeout: Duration) -> Self {
+        Self {
+            produce_timeout,
+            ..self
+        }
+    }
+
+    /// Set the isolation level used by [`fetch_records`](Self::fetch_records) and [`get_offset`](Self::get_offset).
+    ///
+    /// Defaults to [`IsolationLevel::ReadCommitted`].
+    pub fn with_isolation_level(self, isolation_level: IsolationLevel) -> Self {
+        Self {
+            isolation_level,
+            ..self
+        }
+    }
+
+    /// Enable or disable idempotent writes.
+    ///
+    /// An idempotent client obtains a producer ID from the cluster and stamps every batch with a sequence number, so
+    /// that the broker can discard batches that [`produce`](Self::produce) retries internally but that were already
+    /// written. This makes these retries exactly-once.
+    ///
+    /// Concurrent [`produce`](Self::produce) calls are serialized while idempotence is enabled. If `produce` returns an
+    /// error, the client obtains a fresh producer ID for the next call, i.e. records that the caller re-sends are NOT
+    /// deduplicated because it is unknown whether the failed batch was written.
+    ///
+    /// Idempotent writes require [`Acks::All`], which is the default.
+    pub fn with_idempotence(self, idempotence: bool) -> Self {
+        Self {
+            idempotence: idempotence.then(|| Mutex::new(None)),
+            ..self
+        }
+    }
+
+    /// Override the [request timeout](super::ClientBuilder::request_timeout) for requests sent by this client.
+    ///
+    /// Fetch and produce requests extend the timeout by the time the broker may wait on purpose, just like they do for
+    /// the default. Metadata requests still use the default, see [`TimeoutOverride::scope`] to override the timeout of
+    /// a single call including its metadata requests. Defaults to [`TimeoutOverride::Default`].
+    pub fn with_request_timeout(self, request_timeout: TimeoutOverride) -> Self {
+        Self {
+            request_timeout,
+            ..self
+        }
+    }
+
+    /// Client for the offsets that `group_id` committed, sharing our connections.
+    pub(super) fn group_offsets_client(&self, group_id: String) -> GroupOffsetsClient {
+        GroupOffsetsClient::new(
+            group_id,
+            Arc::clone(&self.brokers),
+            Arc::clone(&self.backoff_config),
+        )
+        .with_request_timeout(self.request_timeout)
+    }
+
     /// Produce a batch of records to the partition
+    ///
+    /// Uses the [acknowledgements](Self::with_acks) and [timeout](Self::with_produce_timeout) configured for this
+    /// client.
+    ///
+    /// Returns the offsets of the records. The offsets are `-1` if they are unknown, i.e. for [`Acks::None`] and if the
+    /// broker reports that an [idempotent](Self::with_idempotence) batch was already written but (like older brokers)
+    /// does not tell the offsets of the original batch.
     pub async fn produce(
         &self,
         records: Vec<Record>,
         compression: Compression,
+    ) -> Result<Vec<i64>> {
+        self.produce_with_acks(records, compression, self.acks, self.produce_timeout)
+            .await
+    }
+
+    /// Produce a batch of records to the partition, overriding the acknowledgement settings of this client.
+    ///
+    /// [Idempotent](Self::with_idempotence) clients require [`Acks::All`] and fail with [`Error::InvalidConfig`]
+    /// otherwise, like the Java client.
+    pub async fn produce_with_acks(
+        &self,
+        records: Vec<Record>,
+        compression: Compression,
+        acks: Acks,
+        timeout: Duration,
     ) -> Result<Vec<i64>> {
         // skip request entirely if `records` is empty
         if records.is_empty() {
@@ -202,8 +453,142 @@ impl PartitionClient {
         }
 
         let n = records.len() as i64;
-        let request = &build_produce_request(self.partition, &self.topic, records, compression);
 
+        match &self.idempotence {
+            Some(_) if acks != Acks::All => Err(Error::InvalidConfig(format!(
+                "idempotent produce requires acks = all, got {acks:?}"
+            ))),
+            Some(sequence) => {
+                let request = build_produce_request(
+                    self.partition,
+                    &self.topic,
+                    records,
+                    compression,
+                    Acks::All,
+                    timeout,
+                );
+                let mut sequence = sequence.lock().await;
+                self.produce_idempotent(&mut sequence, request, n).await
+            }
+            None => {
+                let request = build_produce_request(
+                    self.partition,
+                    &self.topic,
+                    records,
+                    compression,
+                    acks,
+                    timeout,
+                );
+                if acks == Acks::None {
+                    self.send_produce_request_without_response(&request, n)
+                        .await
+                } else {
+                    self.send_produce_request(&request, n).await
+                }
+            }
+        }
+    }
+
+    async fn produce_idempotent(
+        &self,
+        sequence: &mut Option<PartitionSequence>,
+        mut request: ProduceRequest,
+        n: i64,
+    ) -> Result<Vec<i64>> {
+        let mut renewed_producer_id = false;
+
+        loop {
+            let current = match sequence {
+                Some(current) => current,
+                None => sequence.insert(PartitionSequence::new(self.init_producer_id().await?)),
+            };
+            set_producer(&mut request, current);
+
+            match self.send_produce_request(&request, n).await {
+                Ok(offsets) => {
+                    // this includes duplicates of an already written batch, see `process_produce_partition_response`
+                    current.advance(n as i32);
+                    return Ok(offsets);
+                }
+                Err(Error::ServerError {
+                    protocol_error:
+                        e @ (ProtocolError::OutOfOrderSequenceNumber
+                        | ProtocolError::UnknownProducerId
+                        | ProtocolError::InvalidProducerEpoch),
+                    ..
+                }) if !renewed_producer_id => {
+                    // The broker rejected the batch, so it is safe to send it again using a fresh producer ID.
+                    warn!(
+                        e=%e,
+                        topic = self.topic.as_str(),
+                        partition = self.partition,
+                        "Producer state out of sync with broker, renewing producer ID",
+                    );
+                    *sequence = None;
+                    renewed_producer_id = true;
+                }
+                Err(e) => {
+                    // We do not know if the batch was written. Continuing with the same sequence number would let the
+                    // broker mistake the next batch for a duplicate of this one, so start over.
+                    *sequence = None;
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    /// Produce a batch of records as part of a transaction.
+    ///
+    /// The caller is responsible for adding the partition to the transaction and for tracking the sequence numbers.
+    pub(crate) async fn produce_transactional(
+        &self,
+        records: Vec<Record>,
+        compression: Compression,
+        transactional_id: &str,
+        sequence: &PartitionSequence,
+    ) -> Result<Vec<i64>> {
+        if records.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let n = records.len() as i64;
+        let mut request = build_produce_request(
+            self.partition,
+            &self.topic,
+            records,
+            compression,
+            Acks::All,
+            self.produce_timeout,
+        );
+        request.transactional_id = NullableString(Some(transactional_id.to_owned()));
+        set_producer(&mut request, sequence);
+        for batch in request
+            .topic_data
+            .iter_mut()
+            .flat_map(|t| t.partition_data.iter_mut())
+            .flat_map(|p| p.records.0.iter_mut())
+        {
+            batch.is_transactional = true;
+        }
+
+        self.send_produce_request(&request, n).await
+    }
+
+    async fn init_producer_id(&self) -> Result<ProducerIdAndEpoch> {
+        init_producer_id(
+            &self.backoff_config,
+            &*self.brokers,
+            None,
+            // transaction timeout is irrelevant for non-transactional producers
+            0,
+            None,
+            self.request_timeout,
+            || RequestContext::Partition(self.topic.clone(), self.partition),
+        )
+        .await
+    }
+
+    async fn send_produce_request(&self, request: &ProduceRequest, n: i64) -> Result<Vec<i64>> {
         maybe_retry(
             &self.backoff_config,
             self.unknown_topic_handling,
@@ -214,8 +599,12 @@ impl PartitionClient {
                     .get()
                     .await
                     .map_err(|e| ErrorOrThrottle::Error((e, None)))?;
+                let timeout = broker.extended_request_timeout(
+                    self.request_timeout,
+                    broker_wait(request.timeout_ms.0),
+                );
                 let response = broker
-                    .request(&request)
+                    .request_with_timeout(request, timeout)
                     .await
                     .map_err(|e| ErrorOrThrottle::Error((e.into(), Some(r#gen))))?;
                 maybe_throttle(response.throttle_time_ms)?;
@@ -226,10 +615,39 @@ impl PartitionClient {
         .await
     }
 
+    /// Send a produce request with `acks = 0`, for which the broker does not respond.
+    async fn send_produce_request_without_response(
+        &self,
+        request: &ProduceRequest,
+        n: i64,
+    ) -> Result<Vec<i64>> {
+        maybe_retry(
+            &self.backoff_config,
+            self.unknown_topic_handling,
+            self,
+            "produce",
+            || async move {
+                let (broker, r#gen) = self
+                    .get()
+                    .await
+                    .map_err(|e| ErrorOrThrottle::Error((e, None)))?;
+                broker
+                    .request_without_response(request)
+                    .await
+                    .map_err(|e| ErrorOrThrottle::Error((e.into(), Some(r#gen))))?;
+                Ok(vec![-1; n as usize])
+            },
+        )
+        .await
+    }
+
     /// Fetch `bytes` bytes of record data starting at sequence number `offset`
     ///
     /// Returns the records, and the current high watermark.
     ///
+    /// If a [client rack](super::ClientBuilder::client_rack) is configured, the leader may ask the client to fetch from
+    /// a follower replica instead. The follower is used until it fails or for a few minutes, after which the leader is
+    /// asked again.
     ///
     /// # Error Handling
     /// Fetching records outside the range known the to broker (marked by low and high watermark) will lead to a
@@ -241,66 +659,278 @@ impl PartitionClient {
         bytes: Range<i32>,
         max_wait_ms: i32,
     ) -> Result<(Vec<RecordAndOffset>, i64)> {
-        let request = &build_fetch_request(offset, bytes, max_wait_ms, self.partition, &self.topic);
+        self.fetch_records_with_isolation_level(offset, bytes, max_wait_ms, self.isolation_level)
+            .await
+    }
+
+    /// Same as [`fetch_records`](Self::fetch_records) but with an explicit isolation level instead of the one
+    /// [configured for this client](Self::with_isolation_level).
+    pub async fn fetch_records_with_isolation_level(
+        &self,
+        offset: i64,
+        bytes: Range<i32>,
+        max_wait_ms: i32,
+        isolation_level: IsolationLevel,
+    ) -> Result<(Vec<RecordAndOffset>, i64)> {
+        let (partition, _leader_epoch) = self
+            .fetch_partition(offset, bytes, max_wait_ms, isolation_level)
+            .await?;
+        let records = extract_records(partition.records.0, offset, partition.aborted_transactions)?;
+
+        Ok((records, partition.high_watermark.0))
+    }
+
+    /// Same as [`fetch_records`](Self::fetch_records) but keeps the records grouped by their record batch and returns
+    /// the [batch metadata](RecordBatchMetadata) as well.
+    ///
+    /// Records before `offset` are removed from the first batch, so its [base offset](RecordBatchMetadata::base_offset)
+    /// may be smaller than the offset of its first record. Control batches and batches of aborted transactions are
+    /// dropped.
+    pub async fn fetch_record_batches(
+        &self,
+        offset: i64,
+        bytes: Range<i32>,
+        max_wait_ms: i32,
+    ) -> Result<(Vec<record::RecordBatch>, i64)> {
+        self.fetch_record_batches_with_isolation_level(
+            offset,
+            bytes,
+            max_wait_ms,
+            self.isolation_level,
+        )
+        .await
+    }
+
+    /// Same as [`fetch_record_batches`](Self::fetch_record_batches) but with an explicit isolation level instead of
+    /// the one [configured for this client](Self::with_isolation_level).
+    pub async fn fetch_record_batches_with_isolation_level(
+        &self,
+        offset: i64,
+        bytes: Range<i32>,
+        max_wait_ms: i32,
+        isolation_level: IsolationLevel,
+    ) -> Result<(Vec<record::RecordBatch>, i64)> {
+        let fetched = self
+            .fetch_batches(offset, bytes, max_wait_ms, isolation_level)
+            .await?;
+
+        Ok((fetched.batches, fetched.high_watermark))
+    }
+
+    /// Same as [`fetch_record_batches_with_isolation_level`](Self::fetch_record_batches_with_isolation_level) but
+    /// with additional information about the response.
+    pub(crate) async fn fetch_batches(
+        &self,
+        offset: i64,
+        bytes: Range<i32>,
+        max_wait_ms: i32,
+        isolation_level: IsolationLevel,
+    ) -> Result<FetchedBatches> {
+        let (partition, leader_epoch) = self
+            .fetch_partition(offset, bytes, max_wait_ms, isolation_level)
+            .await?;
+        let next_offset = partition
+            .records
+            .0
+            .iter()
+            .map(|batch| batch.base_offset + i64::from(batch.last_offset_delta) + 1)
+            .max();
+        let batches = extract_batches(partition.records.0, offset, partition.aborted_transactions)?;
+
+        Ok(FetchedBatches {
+            batches,
+            high_watermark: partition.high_watermark.0,
+            last_stable_offset: partition
+                .last_stable_offset
+                .map(|offset| offset.0)
+                .filter(|offset| *offset >= 0),
+            next_offset,
+            leader_epoch,
+        })
+    }
+
+    /// Fetch data of this partition, including retries and read replica handling.
+    ///
+    /// Returns the partition data together with the leader epoch that was sent with the request.
+    async fn fetch_partition(
+        &self,
+        offset: i64,
+        bytes: Range<i32>,
+        max_wait_ms: i32,
+        isolation_level: IsolationLevel,
+    ) -> Result<(FetchResponsePartition, Option<i32>)> {
+        let build_request = |leader_epoch| {
+            build_fetch_request(
+                offset,
+                bytes.clone(),
+                max_wait_ms,
+                isolation_level,
+                leader_epoch,
+                self.partition,
+                &self.topic,
+                self.brokers.client_rack(),
+            )
+        };
+        let build_request = &build_request;
+
+        // When the leader redirects us to a read replica, it does not return any records. Fetch from the replica right
+        // away instead of returning an empty result.
+        let mut redirected = false;
+        let (partition, leader_epoch) = loop {
+            let (partition, source, leader_epoch) = maybe_retry(
+                &self.backoff_config,
+                self.unknown_topic_handling,
+                self,
+                "fetch_records",
+                || async move {
+                    if let Some(broker_id) = self.read_replica() {
+                        // the replica checks the epoch against its own view of the leader
+                        let leader_epoch = self.current_broker.lock().await.leader_epoch;
+                        match self
+                            .fetch_from_replica(
+                                broker_id,
+                                &build_request(leader_epoch),
+                                offset,
+                                max_wait_ms,
+                            )
+                            .await
+                        {
+                            Ok(partition) => return Ok((partition, Some(broker_id), leader_epoch)),
+                            Err(ErrorOrThrottle::Throttle(throttle)) => {
+                                return Err(ErrorOrThrottle::Throttle(throttle));
+                            }
+                            Err(ErrorOrThrottle::Error(e)) => {
+                                warn!(
+                                    e=%e,
+                                    topic=%self.topic,
+                                    partition=%self.partition,
+                                    broker_id,
+                                    "Fetch from read replica failed, falling back to leader",
+                                );
+                                *self.read_replica.lock() = None;
+                            }
+                        }
+                    }
+
+                    let (broker, r#gen, leader_epoch) = self
+                        .get_with_leader_epoch()
+                        .await
+                        .map_err(|e| ErrorOrThrottle::Error((e, None)))?;
+                    let timeout = broker
+                        .extended_request_timeout(self.request_timeout, broker_wait(max_wait_ms));
+                    let response = broker
+                        .request_with_timeout(&build_request(leader_epoch), timeout)
+                        .await
+                        .map_err(|e| ErrorOrThrottle::Error((e.into(), Some(r#gen))))?;
+                    maybe_throttle(response.throttle_time_ms)?;
+                    let partition =
+                        process_fetch_response(self.partition, &self.topic, response, offset)
+                            .map_err(|e| ErrorOrThrottle::Error((e, Some(r#gen))))?;
+                    Ok((partition, None, leader_epoch))
+                },
+            )
+            .await?;
+
+            let switched = self.update_read_replica(partition.preferred_read_replica, source);
+            if switched && !redirected && partition.records.0.is_empty() {
+                redirected = true;
+                continue;
+            }
+            break (partition, leader_epoch);
+        };
+
+        Ok((partition, leader_epoch))
+    }
+
+    /// Get offset for this partition.
+    ///
+    /// Note that the value returned by this method should be considered stale data, since:
+    ///
+    /// - **[`OffsetAt::Earliest`]:** Might be change at any time due to the Kafka retention policy or by
+    ///   [deleting records](Self::delete_records).
+    /// - **[`OffsetAt::Latest`]:** Might be change at any time by [producing records](Self::produce).
+    pub async fn get_offset(&self, at: OffsetAt) -> Result<i64> {
+        self.get_offset_with_isolation_level(at, self.isolation_level)
+            .await
+    }
+
+    /// Same as [`get_offset`](Self::get_offset) but with an explicit isolation level instead of the one
+    /// [configured for this client](Self::with_isolation_level).
+    pub async fn get_offset_with_isolation_level(
+        &self,
+        at: OffsetAt,
+        isolation_level: IsolationLevel,
+    ) -> Result<i64> {
+        let request = &build_list_offsets_request(self.partition, &self.topic, at, isolation_level);
 
         let partition = maybe_retry(
             &self.backoff_config,
             self.unknown_topic_handling,
             self,
-            "fetch_records",
+            "get_offset",
             || async move {
                 let (broker, r#gen) = self
                     .get()
                     .await
                     .map_err(|e| ErrorOrThrottle::Error((e, None)))?;
                 let response = broker
-                    .request(&request)
+                    .request_with_timeout(&request, broker.request_timeout(self.request_timeout))
                     .await
                     .map_err(|e| ErrorOrThrottle::Error((e.into(), Some(r#gen))))?;
                 maybe_throttle(response.throttle_time_ms)?;
-                process_fetch_response(self.partition, &self.topic, response, offset)
+                process_list_offsets_response(self.partition, &self.topic, response)
                     .map_err(|e| ErrorOrThrottle::Error((e, Some(r#gen))))
             },
         )
         .await?;
 
-        let records = extract_records(partition.records.0, offset)?;
-
-        Ok((records, partition.high_watermark.0))
+        extract_offset(partition)
     }
 
-    /// Get offset for this partition.
+    /// Get the end offset of the leader epoch `leader_epoch`, as seen by the current leader.
     ///
-    /// Note that the value returned by this method should be considered stale data, since:
+    /// This is used to detect log truncation (see [KIP-320]): if records of `leader_epoch` were consumed up to some
+    /// offset and the end offset of that epoch is smaller, the log diverged, e.g. after an unclean leader election, and
+    /// the records at and after the end offset are not part of the log anymore.
     ///
-    /// - **[`OffsetAt::Earliest`]:** Might be change at any time due to the Kafka retention policy or by
-    ///   [deleting records](Self::delete_records).
-    /// - **[`OffsetAt::Latest`]:** Might be change at any time by [producing records](Self::produce).
-    pub async fn get_offset(&self, at: OffsetAt) -> Result<i64> {
-        let request = &build_list_offsets_request(self.partition, &self.topic, at);
-
+    /// The leader epoch of consumed records is available via [`RecordBatchMetadata::partition_leader_epoch`].
+    ///
+    /// [KIP-320]: https://cwiki.apache.org/confluence/display/KAFKA/KIP-320%3A+Allow+fetchers+to+detect+and+handle+log+truncation
+    pub async fn get_epoch_end_offset(&self, leader_epoch: i32) -> Result<EpochEndOffset> {
         let partition = maybe_retry(
             &self.backoff_config,
             self.unknown_topic_handling,
             self,
-            "get_offset",
+            "get_epoch_end_offset",
             || async move {
-                let (broker, r#gen) = self
-                    .get()
+                let (broker, r#gen, current_leader_epoch) = self
+                    .get_with_leader_epoch()
                     .await
                     .map_err(|e| ErrorOrThrottle::Error((e, None)))?;
+                let request = build_offset_for_leader_epoch_request(
+                    leader_epoch,
+                    current_leader_epoch,
+                    self.partition,
+                    &self.topic,
+                );
                 let response = broker
-                    .request(&request)
+                    .request_with_timeout(&request, broker.request_timeout(self.request_timeout))
                     .await
                     .map_err(|e| ErrorOrThrottle::Error((e.into(), Some(r#gen))))?;
                 maybe_throttle(response.throttle_time_ms)?;
-                process_list_offsets_response(self.partition, &self.topic, response)
+                process_offset_for_leader_epoch_response(self.partition, &self.topic, response)
                     .map_err(|e| ErrorOrThrottle::Error((e, Some(r#gen))))
             },
         )
         .await?;
 
-        extract_offset(partition)
+        Ok(EpochEndOffset {
+            leader_epoch: partition
+                .leader_epoch
+                .map(|epoch| epoch.0)
+                .filter(|epoch| *epoch >= 0),
+            end_offset: Some(partition.end_offset.0).filter(|offset| *offset >= 0),
+        })
     }
 
     /// Delete records whose offset is smaller than the given offset.
@@ -322,8 +952,10 @@ impl PartitionClient {
                     .get()
                     .await
                     .map_err(|e| ErrorOrThrottle::Error((e, None)))?;
+                let timeout =
+                    broker.extended_request_timeout(self.request_timeout, broker_wait(timeout_ms));
                 let response = broker
-                    .request(&request)
+                    .request_with_timeout(&request, timeout)
                     .await
                     .map_err(|e| ErrorOrThrottle::Error((e.into(), Some(r#gen))))?;
                 maybe_throttle(Some(response.throttle_time_ms))?;
@@ -336,11 +968,87 @@ impl PartitionClient {
         Ok(())
     }
 
-    /// Retrieve the broker ID of the partition leader
+    /// Read replica to fetch from, if any.
+    fn read_replica(&self) -> Option<i32> {
+        let mut read_replica = self.read_replica.lock();
+        match *read_replica {
+            Some(replica) if replica.expires_at <= Instant::now() => {
+                info!(
+                    topic=%self.topic,
+                    partition=%self.partition,
+                    broker_id=replica.broker_id,
+                    "Read replica expired, fetching from leader",
+                );
+                *read_replica = None;
+                None
+            }
+            Some(replica) => Some(replica.broker_id),
+            None => None,
+        }
+    }
+
+    /// Switch to the `preferred_read_replica` of a fetch response that was sent by `source` (`None` for the leader).
+    ///
+    /// Returns `true` if the client switched to a different replica.
+    fn update_read_replica(
+        &self,
+        preferred_read_replica: Option<Int32>,
+        source: Option<i32>,
+    ) -> bool {
+        let Some(broker_id) = preferred_read_replica.map(|id| id.0).filter(|id| *id >= 0) else {
+            return false;
+        };
+        if source == Some(broker_id) {
+            return false;
+        }
+
+        info!(
+            topic=%self.topic,
+            partition=%self.partition,
+            broker_id,
+            "Fetching from read replica",
+        );
+        *self.read_replica.lock() = Some(ReadReplica {
+            broker_id,
+            expires_at: Instant::now() + READ_REPLICA_MAX_AGE,
+        });
+        true
+    }
+
+    /// Send fetch `request` to the read replica `broker_id`.
+    async fn fetch_from_replica(
+        &self,
+        broker_id: i32,
+        request: &FetchRequest,
+        offset: i64,
+        max_wait_ms: i32,
+    ) -> Result<FetchResponsePartition, ErrorOrThrottle<Error>> {
+        let broker = self
+            .brokers
+            .connect(broker_id)
+            .await
+            .map_err(|e| ErrorOrThrottle::Error(e.into()))?
+            .ok_or_else(|| {
+                ErrorOrThrottle::Error(Error::InvalidResponse(format!(
+                    "Read replica {broker_id} not found in metadata"
+                )))
+            })?;
+        let timeout =
+            broker.extended_request_timeout(self.request_timeout, broker_wait(max_wait_ms));
+        let response = broker
+            .request_with_timeout(request, timeout)
+            .await
+            .map_err(|e| ErrorOrThrottle::Error(e.into()))?;
+        maybe_throttle(response.throttle_time_ms)?;
+        process_fetch_response(self.partition, &self.topic, response, offset)
+            .map_err(ErrorOrThrottle::Error)
+    }
+
+    /// Retrieve the broker ID and the leader epoch (if reported) of the partition leader
     async fn get_leader(
         &self,
         metadata_mode: MetadataLookupMode,
-    ) -> Result<(i32, Option<MetadataCacheGeneration>)> {
+    ) -> Result<(i32, Option<i32>, Option<MetadataCacheGeneration>)> {
         let (metadata, r#gen) = self
             .brokers
             .request_metadata(&metadata_mode, Some(vec![self.topic.clone()]))
@@ -401,26 +1109,34 @@ impl PartitionClient {
             });
         }
 
+        let leader_epoch = partition
+            .leader_epoch
+            .map(|epoch| epoch.0)
+            .filter(|epoch| *epoch >= 0);
+
         info!(
             topic=%self.topic,
             partition=%self.partition,
             leader=partition.leader_id.0,
+            leader_epoch,
             %metadata_mode,
             "Detected leader",
         );
-        Ok((partition.leader_id.0, r#gen))
+        Ok((partition.leader_id.0, leader_epoch, r#gen))
     }
-}
 
-/// Caches the partition leader broker.
-impl BrokerCache for &PartitionClient {
-    type R = MessengerTransport;
-    type E = Error;
-
-    async fn get(&self) -> Result<(Arc<Self::R>, BrokerCacheGeneration)> {
+    /// Same as [`BrokerCache::get`] but also returns the leader epoch that the leader reported when the connection was
+    /// established.
+    async fn get_with_leader_epoch(
+        &self,
+    ) -> Result<(BrokerConnection, BrokerCacheGeneration, Option<i32>)> {
         let mut current_broker = self.current_broker.lock().await;
         if let Some(broker) = &current_broker.broker {
-            return Ok((Arc::clone(broker), current_broker.gen_broker));
+            return Ok((
+                Arc::clone(broker),
+                current_broker.gen_broker,
+                current_broker.leader_epoch,
+            ));
         }
 
         info!(
@@ -438,7 +1154,7 @@ impl BrokerCache for &PartitionClient {
         //   * A subsequent query is performed against the leader that does not use the cached entry - this validates
         //     the correctness of the cached entry.
         //
-        let (leader, gen_leader_from_arbitrary) =
+        let (leader, _, gen_leader_from_arbitrary) =
             self.get_leader(MetadataLookupMode::CachedArbitrary).await?;
         let broker = match self.brokers.connect(leader).await {
             Ok(Some(c)) => Ok(c),
@@ -475,7 +1191,7 @@ impl BrokerCache for &PartitionClient {
         //
         // Because this check requires the most up-to-date metadata from a specific broker, do not accept a cached
         // metadata response.
-        let (leader_self, gen_leader_from_self) = self
+        let (leader_self, leader_epoch, gen_leader_from_self) = self
             .get_leader(MetadataLookupMode::SpecificBroker(Arc::clone(&broker)))
             .await?;
         if leader != leader_self {
@@ -506,15 +1222,28 @@ impl BrokerCache for &PartitionClient {
             gen_broker: current_broker.gen_broker.bump(),
             gen_leader_from_arbitrary,
             gen_leader_from_self,
+            leader_epoch,
         };
 
         info!(
             topic=%self.topic,
             partition=%self.partition,
             leader,
+            leader_epoch,
             "Created new partition-specific broker connection",
         );
-        Ok((broker, current_broker.gen_broker))
+        Ok((broker, current_broker.gen_broker, leader_epoch))
+    }
+}
+
+/// Caches the partition leader broker.
+impl BrokerCache for &PartitionClient {
+    type R = MessengerTransport;
+    type E = Error;
+
+    async fn get(&self) -> Result<(Arc<Self::R>, BrokerCacheGeneration)> {
+        let (broker, r#gen, _leader_epoch) = self.get_with_leader_epoch().await?;
+        Ok((broker, r#gen))
     }
 
     async fn invalidate(&self, reason: &'static str, r#gen: BrokerCacheGeneration) {
@@ -580,7 +1309,9 @@ where
             };
 
             let retry = match error {
-                Error::Request(RequestError::Poisoned(_) | RequestError::IO(_))
+                Error::Request(
+                    RequestError::Poisoned(_) | RequestError::IO(_) | RequestError::Timeout { .. },
+                )
                 | Error::Connection(_) => {
                     if let Some(cache_gen) = cache_gen {
                         broker_cache
@@ -593,9 +1324,25 @@ where
                     protocol_error:
                         ProtocolError::InvalidReplicationFactor
                         | ProtocolError::LeaderNotAvailable
-                        | ProtocolError::OffsetNotAvailable,
+                        | ProtocolError::OffsetNotAvailable
+                        // the broker has not caught up with the leader epoch from the metadata yet
+                        | ProtocolError::UnknownLeaderEpoch,
                     ..
                 } => true,
+                Error::ServerError {
+                    protocol_error: ProtocolError::FencedLeaderEpoch,
+                    ..
+                } => {
+                    if let Some(cache_gen) = cache_gen {
+                        broker_cache
+                            .invalidate(
+                                "partition client: server error: fenced leader epoch",
+                                cache_gen,
+                            )
+                            .await;
+                    }
+                    true
+                }
                 Error::ServerError {
                     protocol_error: ProtocolError::NotLeaderOrFollower,
                     ..
@@ -647,7 +1394,32 @@ fn build_produce_request(
     topic: &str,
     records: Vec<Record>,
     compression: Compression,
+    acks: Acks,
+    timeout: Duration,
 ) -> ProduceRequest {
+    ProduceRequest {
+        transactional_id: NullableString(None),
+        acks: Int16(acks.value()),
+        timeout_ms: Int32(timeout.as_millis().try_into().unwrap_or(i32::MAX)),
+        topic_data: vec![ProduceRequestTopicData {
+            name: String_(topic.to_string()),
+            partition_data: vec![build_produce_partition_data(
+                partition,
+                records,
+                compression,
+            )],
+        }],
+    }
+}
+
+/// Build a single record batch for `partition`.
+///
+/// `records` must not be empty.
+pub(super) fn build_produce_partition_data(
+    partition: i32,
+    records: Vec<Record>,
+    compression: Compression,
+) -> ProduceRequestPartitionData {
     let n = records.len() as i32;
 
     // TODO: Retry on failure
@@ -675,7 +1447,7 @@ fn build_produce_request(
         })
         .collect();
 
-    let record_batch = ProduceRequestPartitionData {
+    ProduceRequestPartitionData {
         index: Int32(partition),
         records: Records(vec![RecordBatch {
             base_offset: 0,
@@ -701,16 +1473,20 @@ fn build_produce_request(
             max_timestamp: max_timestamp.timestamp_millis(),
             records: ControlBatchOrRecords::Records(records),
         }]),
-    };
+    }
+}
 
-    ProduceRequest {
-        transactional_id: crate::protocol::primitives::NullableString(None),
-        acks: Int16(-1),
-        timeout_ms: Int32(30_000),
-        topic_data: vec![ProduceRequestTopicData {
-            name: String_(topic.to_string()),
-            partition_data: vec![record_batch],
-        }],
+/// Stamp the producer ID, epoch and sequence number into all batches of `request`.
+fn set_producer(request: &mut ProduceRequest, sequence: &PartitionSequence) {
+    let batches = request
+        .topic_data
+        .iter_mut()
+        .flat_map(|t| t.partition_data.iter_mut())
+        .flat_map(|p| p.records.0.iter_mut());
+    for batch in batches {
+        batch.producer_id = sequence.producer.producer_id;
+        batch.producer_epoch = sequence.producer.producer_epoch;
+        batch.base_sequence = sequence.next_sequence;
     }
 }
 
@@ -744,41 +1520,74 @@ fn process_produce_response(
         )));
     }
 
+    process_produce_partition_response(partition, topic, num_records, response)
+}
+
+/// Offsets of the records written to `partition`, or the error reported by the broker.
+///
+/// A [`DuplicateSequenceNumber`](ProtocolError::DuplicateSequenceNumber) means that an earlier attempt already wrote
+/// the batch, so it counts as success. Brokers that do not report the offsets of the original batch in that case get
+/// offsets of `-1`, like produce requests without acknowledgements.
+pub(super) fn process_produce_partition_response(
+    partition: i32,
+    topic: &str,
+    num_records: i64,
+    response: ProduceResponsePartitionResponse,
+) -> Result<Vec<i64>> {
     match response.error {
-        Some(e) => Err(Error::ServerError {
+        Some(ProtocolError::DuplicateSequenceNumber) if response.base_offset.0 < 0 => {
+            Ok(vec![-1; num_records as usize])
+        }
+        Some(e) if e != ProtocolError::DuplicateSequenceNumber => Err(Error::ServerError {
             protocol_error: e,
             error_message: None,
             request: RequestContext::Partition(topic.to_owned(), partition),
             response: None,
             is_virtual: false,
         }),
-        None => Ok((0..num_records)
+        _ => Ok((0..num_records)
             .map(|x| x + response.base_offset.0)
             .collect()),
     }
 }
 
+/// Time in milliseconds the broker may wait before it answers a request, e.g. the `max_wait_ms` of a fetch request.
+pub(super) fn broker_wait(ms: i32) -> Duration {
+    Duration::from_millis(u64::try_from(ms).unwrap_or_default())
+}
+
+#[allow(clippy::too_many_arguments, reason = "Function is internal")]
 fn build_fetch_request(
     offset: i64,
     bytes: Range<i32>,
     max_wait_ms: i32,
+    isolation_level: IsolationLevel,
+    leader_epoch: Option<i32>,
     partition: i32,
     topic: &str,
+    rack_id: Option<&str>,
 ) -> FetchRequest {
     FetchRequest {
         replica_id: NORMAL_CONSUMER,
         max_wait_ms: Int32(max_wait_ms),
         min_bytes: Int32(bytes.start),
         max_bytes: Some(Int32(bytes.end.saturating_sub(1))),
-        isolation_level: Some(IsolationLevel::ReadCommitted),
+        isolation_level: Some(isolation_level.into()),
+        // full fetch without a session
+        session_id: None,
+        session_epoch: None,
         topics: vec![FetchRequestTopic {
             topic: String_(topic.to_string()),
             partitions: vec![FetchRequestPartition {
                 partition: Int32(partition),
+                current_leader_epoch: leader_epoch.map(Int32),
                 fetch_offset: Int64(offset),
+                log_start_offset: None,
                 partition_max_bytes: Int32(bytes.end.saturating_sub(1)),
             }],
         }],
+        forgotten_topics_data: vec![],
+        rack_id: rack_id.map(|rack_id| String_(rack_id.to_owned())),
     }
 }
 
@@ -788,6 +1597,20 @@ fn process_fetch_response(
     response: FetchResponse,
     request_offset: i64,
 ) -> Result<FetchResponsePartition> {
+    if let Some(err) = response.error_code {
+        return Err(Error::ServerError {
+            protocol_error: err,
+            error_message: None,
+            request: RequestContext::Fetch {
+                topic_name: topic.to_owned(),
+                partition_id: partition,
+                offset: request_offset,
+            },
+            response: None,
+            is_virtual: false,
+        });
+    }
+
     let response_topic = response
         .responses
         .exactly_one()
@@ -812,6 +1635,16 @@ fn process_fetch_response(
         )));
     }
 
+    process_fetch_partition_response(partition, topic, response_partition, request_offset)
+}
+
+/// Check the response for a single partition of a fetch request.
+pub(super) fn process_fetch_partition_response(
+    partition: i32,
+    topic: &str,
+    response_partition: FetchResponsePartition,
+    request_offset: i64,
+) -> Result<FetchResponsePartition> {
     if let Some(err) = response_partition.error_code {
         return Err(Error::ServerError {
             protocol_error: err,
@@ -832,19 +1665,68 @@ fn process_fetch_response(
     Ok(response_partition)
 }
 
-fn extract_records(
+/// Convert the record batches of a fetch response into records, starting at `request_offset`.
+///
+/// Control batches and the batches of `aborted_transactions` are dropped. The broker only reports aborted transactions
+/// for [`IsolationLevel::ReadCommitted`], so this list is empty otherwise.
+pub(super) fn extract_records(
     partition_records: Vec<RecordBatch>,
     request_offset: i64,
+    aborted_transactions: Vec<FetchResponseAbortedTransaction>,
 ) -> Result<Vec<RecordAndOffset>> {
-    let mut records = vec![];
+    let batches = extract_batches(partition_records, request_offset, aborted_transactions)?;
+
+    let mut records = Vec::with_capacity(batches.iter().map(|batch| batch.records.len()).sum());
+    for batch in batches {
+        records.extend(batch.records);
+    }
+    Ok(records)
+}
+
+/// Same as [`extract_records`] but keeps the records grouped by their batch.
+///
+/// Batches without any records at or after `request_offset` are dropped.
+pub(super) fn extract_batches(
+    partition_records: Vec<RecordBatch>,
+    request_offset: i64,
+    mut aborted_transactions: Vec<FetchResponseAbortedTransaction>,
+) -> Result<Vec<record::RecordBatch>> {
+    let mut batches = vec![];
+
+    // Same approach as the Java client: an aborted transaction becomes "active" once we reach its first offset and
+    // stays active until the abort marker of its producer.
+    aborted_transactions.sort_by_key(|txn| txn.first_offset.0);
+    let mut aborted_transactions = aborted_transactions.into_iter().peekable();
+    let mut aborted_producers = HashSet::new();
 
     for batch in partition_records {
+        let last_offset = batch.base_offset + i64::from(batch.last_offset_delta);
+        if batch.is_transactional {
+            while let Some(txn) =
+                aborted_transactions.next_if(|txn| txn.first_offset.0 <= last_offset)
+            {
+                aborted_producers.insert(txn.producer_id.0);
+            }
+        }
+
         match batch.records {
-            ControlBatchOrRecords::ControlBatch(_) => {
+            ControlBatchOrRecords::ControlBatch(ControlBatchRecord::Abort) => {
+                aborted_producers.remove(&batch.producer_id);
+            }
+            ControlBatchOrRecords::ControlBatch(ControlBatchRecord::Commit) => {
                 // ignore
             }
+            ControlBatchOrRecords::Records(_)
+                if batch.is_transactional && aborted_producers.contains(&batch.producer_id) =>
+            {
+                debug!(
+                    producer_id = batch.producer_id,
+                    base_offset = batch.base_offset,
+                    "Dropping batch of aborted transaction",
+                );
+            }
             ControlBatchOrRecords::Records(protocol_records) => {
-                records.reserve(protocol_records.len());
+                let mut records = Vec::with_capacity(protocol_records.len());
 
                 for record in protocol_records {
                     let offset = batch.base_offset + record.offset_delta as i64;
@@ -864,19 +1746,6 @@ fn extract_records(
                                 )));
                             }
                         };
-                    let timestamp = match Utc.timestamp_millis_opt(timestamp_millis) {
-                        LocalResult::None => {
-                            return Err(Error::InvalidResponse(format!(
-                                "Not a valid timestamp ({timestamp_millis})"
-                            )));
-                        }
-                        LocalResult::Single(ts) => ts,
-                        LocalResult::Ambiguous(a, b) => {
-                            return Err(Error::InvalidResponse(format!(
-                                "Ambiguous timestamp ({timestamp_millis}): {a} or {b}"
-                            )));
-                        }
-                    };
 
                     records.push(RecordAndOffset {
                         record: Record {
@@ -887,19 +1756,83 @@ fn extract_records(
                                 .into_iter()
                                 .map(|header| (header.key, header.value))
                                 .collect(),
-                            timestamp,
+                            timestamp: timestamp(timestamp_millis)?,
                         },
                         offset,
                     })
                 }
+
+                if records.is_empty() {
+                    continue;
+                }
+
+                batches.push(record::RecordBatch {
+                    metadata: RecordBatchMetadata {
+                        base_offset: batch.base_offset,
+                        last_offset,
+                        partition_leader_epoch: batch.partition_leader_epoch,
+                        first_timestamp: timestamp(batch.first_timestamp)?,
+                        max_timestamp: timestamp(batch.max_timestamp)?,
+                        producer_id: batch.producer_id,
+                        producer_epoch: batch.producer_epoch,
+                        base_sequence: batch.base_sequence,
+                        compression: batch_compression(batch.compression)?,
+                        timestamp_type: match batch.timestamp_type {
+                            RecordBatchTimestampType::CreateTime => TimestampType::CreateTime,
+                            RecordBatchTimestampType::LogAppendTime => TimestampType::LogAppendTime,
+                        },
+                        is_transactional: batch.is_transactional,
+                    },
+                    records,
+                });
             }
         }
     }
 
-    Ok(records)
+    Ok(batches)
+}
+
+/// Convert timestamp of a record batch.
+fn timestamp(timestamp_millis: i64) -> Result<DateTime<Utc>> {
+    match Utc.timestamp_millis_opt(timestamp_millis) {
+        LocalResult::None => Err(Error::InvalidResponse(format!(
+            "Not a valid timestamp ({timestamp_millis})"
+        ))),
+        LocalResult::Single(ts) => Ok(ts),
+        LocalResult::Ambiguous(a, b) => Err(Error::InvalidResponse(format!(
+            "Ambiguous timestamp ({timestamp_millis}): {a} or {b}"
+        ))),
+    }
+}
+
+/// Convert compression of a record batch.
+///
+/// Batches that use a compression that is not enabled cannot be decoded in the first place, so this only fails for
+/// invalid responses.
+fn batch_compression(compression: RecordBatchCompression) -> Result<Compression> {
+    match compression {
+        RecordBatchCompression::NoCompression => Ok(Compression::NoCompression),
+        #[cfg(feature = "compression-gzip")]
+        RecordBatchCompression::Gzip => Ok(Compression::Gzip),
+        #[cfg(feature = "compression-lz4")]
+        RecordBatchCompression::Lz4 => Ok(Compression::Lz4),
+        #[cfg(feature = "compression-snappy")]
+        RecordBatchCompression::Snappy => Ok(Compression::Snappy),
+        #[cfg(feature = "compression-zstd")]
+        RecordBatchCompression::Zstd => Ok(Compression::Zstd),
+        #[allow(unreachable_patterns)]
+        other => Err(Error::InvalidResponse(format!(
+            "Record batch uses disabled compression: {other:?}"
+        ))),
+    }
 }
 
-fn build_list_offsets_request(partition: i32, topic: &str, at: OffsetAt) -> ListOffsetsRequest {
+fn build_list_offsets_request(
+    partition: i32,
+    topic: &str,
+    at: OffsetAt,
+    isolation_level: IsolationLevel,
+) -> ListOffsetsRequest {
     let timestamp = match at {
         OffsetAt::Earliest => -2,
         OffsetAt::Latest => -1,
@@ -908,7 +1841,7 @@ fn build_list_offsets_request(partition: i32, topic: &str, at: OffsetAt) -> List
 
     ListOffsetsRequest {
         replica_id: NORMAL_CONSUMER,
-        isolation_level: Some(IsolationLevel::ReadCommitted),
+        isolation_level: Some(isolation_level.into()),
         topics: vec![ListOffsetsRequestTopic {
             name: String_(topic.to_owned()),
             partitions: vec![ListOffsetsRequestPartition {
@@ -1045,3 +1978,293 @@ fn process_delete_records_response(
         None => Ok(response_partition),
     }
 }
+
+fn build_offset_for_leader_epoch_request(
+    leader_epoch: i32,
+    current_leader_epoch: Option<i32>,
+    partition: i32,
+    topic: &str,
+) -> OffsetForLeaderEpochRequest {
+    OffsetForLeaderEpochRequest {
+        replica_id: Some(NORMAL_CONSUMER),
+        topics: vec![OffsetForLeaderEpochRequestTopic {
+            topic: String_(topic.to_string()),
+            partitions: vec![OffsetForLeaderEpochRequestPartition {
+                partition: Int32(partition),
+                current_leader_epoch: current_leader_epoch.map(Int32),
+                leader_epoch: Int32(leader_epoch),
+                tagged_fields: None,
+            }],
+            tagged_fields: None,
+        }],
+        tagged_fields: None,
+    }
+}
+
+fn process_offset_for_leader_epoch_response(
+    partition: i32,
+    topic: &str,
+    response: OffsetForLeaderEpochResponse,
+) -> Result<OffsetForLeaderEpochResponsePartition> {
+    let response_topic = response
+        .topics
+        .exactly_one()
+        .map_err(Error::exactly_one_topic)?;
+
+    if response_topic.topic.0 != topic {
+        return Err(Error::InvalidResponse(format!(
+            "Expected data for topic '{}' but got data for topic '{}'",
+            topic, response_topic.topic.0
+        )));
+    }
+
+    let response_partition = response_topic
+        .partitions
+        .exactly_one()
+        .map_err(Error::exactly_one_partition)?;
+
+    if response_partition.partition != Int32(partition) {
+        return Err(Error::InvalidResponse(format!(
+            "Expected data for partition {} but got data for partition {}",
+            partition, response_partition.partition.0
+        )));
+    }
+
+    match response_partition.error {
+        Some(err) => Err(Error::ServerError {
+            protocol_error: err,
+            error_message: None,
+            request: RequestContext::Partition(topic.to_owned(), partition),
+            response: None,
+            is_virtual: false,
+        }),
+        None => Ok(response_partition),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    fn batch(
+        base_offset: i64,
+        producer_id: i64,
+        is_transactional: bool,
+        records: ControlBatchOrRecords,
+    ) -> RecordBatch {
+        let last_offset_delta = match &records {
+            ControlBatchOrRecords::ControlBatch(_) => 0,
+            ControlBatchOrRecords::Records(records) => records.len() as i32 - 1,
+        };
+        RecordBatch {
+            base_offset,
+            partition_leader_epoch: 0,
+            last_offset_delta,
+            first_timestamp: 0,
+            max_timestamp: 0,
+            producer_id,
+            producer_epoch: 0,
+            base_sequence: 0,
+            records,
+            compression: RecordBatchCompression::NoCompression,
+            is_transactional,
+            timestamp_type: RecordBatchTimestampType::CreateTime,
+        }
+    }
+
+    fn data(base_offset: i64, producer_id: i64, is_transactional: bool, n: i32) -> RecordBatch {
+        let records = (0..n)
+            .map(|offset_delta| ProtocolRecord {
+                timestamp_delta: 0,
+                offset_delta,
+                key: None,
+                value: Some(vec![]),
+                headers: vec![],
+            })
+            .collect();
+        batch(
+            base_offset,
+            producer_id,
+            is_transactional,
+            ControlBatchOrRecords::Records(records),
+        )
+    }
+
+    fn marker(base_offset: i64, producer_id: i64, record: ControlBatchRecord) -> RecordBatch {
+        batch(
+            base_offset,
+            producer_id,
+            true,
+            ControlBatchOrRecords::ControlBatch(record),
+        )
+    }
+
+    fn aborted(producer_id: i64, first_offset: i64) -> FetchResponseAbortedTransaction {
+        FetchResponseAbortedTransaction {
+            producer_id: Int64(producer_id),
+            first_offset: Int64(first_offset),
+        }
+    }
+
+    fn offsets(records: Vec<RecordAndOffset>) -> Vec<i64> {
+        records.into_iter().map(|r| r.offset).collect()
+    }
+
+    #[test]
+    fn test_extract_records_without_aborted_transactions() {
+        let batches = vec![
+            data(0, -1, false, 2),
+            data(2, 1, true, 2),
+            marker(4, 1, ControlBatchRecord::Commit),
+            data(5, 1, true, 1),
+            marker(6, 1, ControlBatchRecord::Abort),
+        ];
+
+        // control batches are never returned, read-uncommitted consumers see aborted data
+        let records = extract_records(batches, 1, vec![]).unwrap();
+        assert_eq!(offsets(records), vec![1, 2, 3, 5]);
+    }
+
+    #[test]
+    fn test_extract_records_aborted_transactions() {
+        let batches = vec![
+            // aborted transaction of producer 1, interleaved with committed transaction of producer 2
+            data(0, 1, true, 2),
+            data(2, 2, true, 1),
+            data(3, -1, false, 1),
+            marker(4, 1, ControlBatchRecord::Abort),
+            data(5, 2, true, 1),
+            marker(6, 2, ControlBatchRecord::Commit),
+            // next transaction of producer 1 is committed
+            data(7, 1, true, 1),
+            marker(8, 1, ControlBatchRecord::Commit),
+            // second aborted transaction of producer 2
+            data(9, 2, true, 2),
+            marker(11, 2, ControlBatchRecord::Abort),
+            data(12, 2, true, 1),
+        ];
+
+        // the broker does not guarantee any order
+        let aborted_transactions = vec![aborted(2, 9), aborted(1, 0)];
+
+        let records = extract_records(batches, 0, aborted_transactions).unwrap();
+        assert_eq!(offsets(records), vec![2, 3, 5, 7, 12]);
+    }
+
+    #[test]
+    fn test_extract_records_aborted_transaction_starts_before_request() {
+        // the fetch starts in the middle of an aborted transaction
+        let batches = vec![
+            data(10, 1, true, 2),
+            marker(12, 1, ControlBatchRecord::Abort),
+            data(13, 1, true, 1),
+        ];
+
+        let records = extract_records(batches, 11, vec![aborted(1, 5)]).unwrap();
+        assert_eq!(offsets(records), vec![13]);
+    }
+
+    #[test]
+    fn test_extract_batches() {
+        let mut first = data(1, 1, true, 3);
+        first.partition_leader_epoch = 5;
+        first.first_timestamp = 1_000;
+        first.max_timestamp = 1_002;
+        first.producer_epoch = 2;
+        first.base_sequence = 7;
+        first.timestamp_type = RecordBatchTimestampType::LogAppendTime;
+        let batches = vec![
+            // entirely before the requested offset
+            data(0, -1, false, 1),
+            first,
+            marker(4, 1, ControlBatchRecord::Commit),
+        ];
+
+        let batches = extract_batches(batches, 2, vec![]).unwrap();
+        assert_eq!(batches.len(), 1);
+        let batch = batches.into_iter().next().unwrap();
+        assert_eq!(
+            batch.metadata,
+            RecordBatchMetadata {
+                base_offset: 1,
+                last_offset: 3,
+                partition_leader_epoch: 5,
+                first_timestamp: Utc.timestamp_millis_opt(1_000).unwrap(),
+                max_timestamp: Utc.timestamp_millis_opt(1_002).unwrap(),
+                producer_id: 1,
+                producer_epoch: 2,
+                base_sequence: 7,
+                compression: Compression::NoCompression,
+                timestamp_type: TimestampType::LogAppendTime,
+                is_transactional: true,
+            }
+        );
+        // records before the requested offset are removed
+        assert_eq!(offsets(batch.records), vec![2, 3]);
+    }
+
+    fn produce_response(
+        error: Option<ProtocolError>,
+        base_offset: i64,
+    ) -> ProduceResponsePartitionResponse {
+        ProduceResponsePartitionResponse {
+            index: Int32(0),
+            error,
+            base_offset: Int64(base_offset),
+            log_append_time_ms: None,
+            log_start_offset: None,
+        }
+    }
+
+    #[test]
+    fn test_process_produce_partition_response() {
+        assert_eq!(
+            process_produce_partition_response(0, "t", 3, produce_response(None, 10)).unwrap(),
+            vec![10, 11, 12]
+        );
+
+        let err = process_produce_partition_response(
+            0,
+            "t",
+            3,
+            produce_response(Some(ProtocolError::NotLeaderOrFollower), -1),
+        )
+        .unwrap_err();
+        assert_matches!(
+            err,
+            Error::ServerError {
+                protocol_error: ProtocolError::NotLeaderOrFollower,
+                ..
+            }
+        );
+    }
+
+    #[test]
+    fn test_process_produce_partition_response_duplicate() {
+        // the batch was already written, with known offsets
+        assert_eq!(
+            process_produce_partition_response(
+                0,
+                "t",
+                3,
+                produce_response(Some(ProtocolError::DuplicateSequenceNumber), 10),
+            )
+            .unwrap(),
+            vec![10, 11, 12]
+        );
+
+        // the batch was already written, but the broker does not tell the offsets
+        assert_eq!(
+            process_produce_partition_response(
+                0,
+                "t",
+                3,
+                produce_response(Some(ProtocolError::DuplicateSequenceNumber), -1),
+            )
+            .unwrap(),
+            vec![-1, -1, -1]
+        );
+    }
+}
@@ -0,0 +1,199 @@
+//! Mapping of records to partitions.
+//!
+//! Used by [`TopicProducer`](super::topic_producer::TopicProducer) to route records.
+use std::collections::BTreeMap;
+
+use parking_lot::Mutex;
+use rand::Rng;
+
+use crate::record::Record;
+
+/// Chooses the partition for records written to a topic.
+pub trait Partitioner: std::fmt::Debug + Send + Sync {
+    /// Partition for `record`.
+    ///
+    /// Must return a value in `0..num_partitions`, otherwise
+    /// [`TopicProducer::produce`](super::topic_producer::TopicProducer::produce) fails with
+    /// [`Error::InvalidPartition`](super::error::Error::InvalidPartition). `num_partitions` is never zero.
+    fn partition(&self, topic: &str, record: &Record, num_partitions: i32) -> i32;
+
+    /// Called after all records of a batch for `topic` were partitioned.
+    ///
+    /// Partitioners that keep state between records (like a sticky partitioner) may use this to switch to a different
+    /// partition. The default implementation does nothing.
+    fn on_new_batch(&self, topic: &str, num_partitions: i32) {
+        let _ = (topic, num_partitions);
+    }
+}
+
+/// Partitioner compatible with the default partitioner of the Java client.
+///
+/// - **records with a key:** Partition is derived from the [`murmur2`] hash of the key, so records with the
+///   same key end up in the same partition as if they were written by the Java client.
+/// - **records without a key:** All records of a batch go to the same ("sticky") partition. A new random partition
+///   is chosen for the next batch. This results in fewer but larger produce requests than a round-robin approach.
+#[derive(Debug, Default)]
+pub struct DefaultPartitioner {
+    /// Sticky partition per topic.
+    sticky: Mutex<BTreeMap<String, i32>>,
+}
+
+impl DefaultPartitioner {
+    /// Create new partitioner.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Partitioner for DefaultPartitioner {
+    fn partition(&self, topic: &str, record: &Record, num_partitions: i32) -> i32 {
+        match &record.key {
+            Some(key) => partition_for_key(key, num_partitions),
+            None => {
+                let mut sticky = self.sticky.lock();
+                match sticky.get(topic) {
+                    Some(partition) if *partition < num_partitions => *partition,
+                    _ => {
+                        let partition = rand::rng().random_range(0..num_partitions);
+                        sticky.insert(topic.to_owned(), partition);
+                        partition
+                    }
+                }
+            }
+        }
+    }
+
+    fn on_new_batch(&self, topic: &str, num_partitions: i32) {
+        let mut sticky = self.sticky.lock();
+        let Some(previous) = sticky.get(topic).copied() else {
+            return;
+        };
+
+        let partition = if num_partitions > 1 {
+            // pick a different partition by skipping over the previous one
+            let partition = rand::rng().random_range(0..(num_partitions - 1));
+            if partition >= previous {
+                partition + 1
+            } else {
+                partition
+            }
+        } else {
+            0
+        };
+        sticky.insert(topic.to_owned(), partition);
+    }
+}
+
+/// Partition for a record `key`, same as the Java client.
+fn partition_for_key(key: &[u8], num_partitions: i32) -> i32 {
+    (murmur2(key) & 0x7fffffff) % num_partitions
+}
+
+/// 32-bit murmur2 hash, as implemented by the Java client (`org.apache.kafka.common.utils.Utils.murmur2`).
+pub fn murmur2(data: &[u8]) -> i32 {
+    const SEED: u32 = 0x9747b28c;
+    const M: u32 = 0x5bd1e995;
+    const R: u32 = 24;
+
+    let mut h = SEED ^ (data.len() as u32);
+
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k = u32::from_le_bytes(chunk.try_into().expect("chunk size"));
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+        h = h.wrapping_mul(M);
+        h ^= k;
+    }
+
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        if tail.len() >= 3 {
+            h ^= u32::from(tail[2]) << 16;
+        }
+        if tail.len() >= 2 {
+            h ^= u32::from(tail[1]) << 8;
+        }
+        h ^= u32::from(tail[0]);
+        h = h.wrapping_mul(M);
+    }
+
+    h ^= h >> 13;
+    h = h.wrapping_mul(M);
+    h ^= h >> 15;
+
+    h as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    #[test]
+    fn test_murmur2() {
+        // test vectors from the Java client
+        let cases: [(&[u8], i32); 6] = [
+            (b"21", -973932308),
+            (b"foobar", -790332482),
+            (b"a-little-bit-long-string", -985981536),
+            (b"a-little-bit-longer-string", -1486304829),
+            (
+                b"lkjh234lh9fiuh90y23oiuhsafujhadof229phr9h19h89h8",
+                -58897971,
+            ),
+            (b"abc", 479470107),
+        ];
+        for (data, expected) in cases {
+            assert_eq!(murmur2(data), expected);
+        }
+    }
+
+    #[test]
+    fn test_default_partitioner_key() {
+        let partitioner = DefaultPartitioner::new();
+
+        for key in [b"".as_slice(), b"foo", b"a-little-bit-long-string"] {
+            let record = record(Some(key));
+            let partition = partitioner.partition("t", &record, 7);
+            assert_eq!(partition, partition_for_key(key, 7));
+            assert!((0..7).contains(&partition));
+
+            partitioner.on_new_batch("t", 7);
+            assert_eq!(partitioner.partition("t", &record, 7), partition);
+        }
+    }
+
+    #[test]
+    fn test_default_partitioner_sticky() {
+        let partitioner = DefaultPartitioner::new();
+        let record = record(None);
+
+        let p1 = partitioner.partition("t", &record, 3);
+        assert!((0..3).contains(&p1));
+        assert_eq!(partitioner.partition("t", &record, 3), p1);
+
+        partitioner.on_new_batch("t", 3);
+        let p2 = partitioner.partition("t", &record, 3);
+        assert!((0..3).contains(&p2));
+        assert_ne!(p1, p2);
+        assert_eq!(partitioner.partition("t", &record, 3), p2);
+
+        // partition count shrank (e.g. topic re-created)
+        let p3 = partitioner.partition("t", &record, 1);
+        assert_eq!(p3, 0);
+        partitioner.on_new_batch("t", 1);
+        assert_eq!(partitioner.partition("t", &record, 1), 0);
+    }
+
+    fn record(key: Option<&[u8]>) -> Record {
+        Record {
+            key: key.map(|k| k.to_vec()),
+            value: None,
+            headers: Default::default(),
+            timestamp: Utc.timestamp_millis_opt(0).unwrap(),
+        }
+    }
+}
@@ -219,7 +219,7 @@ use self::{
 use crate::{
     client::{
         error::Error as ClientError,
-        partition::{Compression, PartitionClient},
+        partition::{Acks, Compression, PartitionClient},
         producer::aggregator::TryPush,
     },
     record::Record,
@@ -228,6 +228,7 @@ use crate::{
 pub mod aggregator;
 mod batch;
 pub(crate) mod broadcast;
+pub mod broker;
 
 #[derive(Debug, Error, Clone)]
 pub enum Error {
@@ -254,6 +255,10 @@ pub struct BatchProducerBuilder {
     linger: Duration,
 
     compression: Compression,
+
+    acks: Option<Acks>,
+
+    produce_timeout: Option<Duration>,
 }
 
 impl BatchProducerBuilder {
@@ -269,6 +274,8 @@ impl BatchProducerBuilder {
             client,
             linger: Duration::from_millis(5),
             compression: Compression::default(),
+            acks: None,
+            produce_timeout: None,
         }
     }
 
@@ -285,15 +292,47 @@ impl BatchProducerBuilder {
         }
     }
 
+    /// Sets the acknowledgements that every flush waits for, overriding the setting of the client.
+    ///
+    /// Flushes fail with [`ClientError::InvalidConfig`] if the client does not support this, see
+    /// [`ProducerClient::produce_with_acks`].
+    pub fn with_acks(self, acks: Acks) -> Self {
+        Self {
+            acks: Some(acks),
+            ..self
+        }
+    }
+
+    /// Sets how long the broker may wait for the acknowledgements, overriding the setting of the client.
+    ///
+    /// Flushes fail with [`ClientError::InvalidConfig`] if the client does not support this, see
+    /// [`ProducerClient::produce_with_acks`].
+    pub fn with_produce_timeout(self, produce_timeout: Duration) -> Self {
+        Self {
+            produce_timeout: Some(produce_timeout),
+            ..self
+        }
+    }
+
     pub fn build<A>(self, aggregator: A) -> BatchProducer<A>
     where
         A: aggregator::Aggregator,
     {
+        let client = if self.acks.is_some() || self.produce_timeout.is_some() {
+            Arc::new(AcksOverride {
+                inner: self.client,
+                acks: self.acks,
+                produce_timeout: self.produce_timeout,
+            })
+        } else {
+            self.client
+        };
+
         BatchProducer {
             linger: self.linger,
             inner: Arc::new(parking_lot::Mutex::new(ProducerInner::new(
                 aggregator,
-                self.client,
+                client,
                 self.compression,
             ))),
         }
@@ -317,6 +356,27 @@ pub trait ProducerClient: std::fmt::Debug + Send + Sync {
         records: Vec<Record>,
         compression: Compression,
     ) -> BoxFuture<'_, Result<Vec<i64>, ClientError>>;
+
+    /// Like [`produce`](Self::produce), but with the given acknowledgement settings. `None` falls back to the settings
+    /// of the client.
+    ///
+    /// The default implementation cannot apply any settings: it calls [`produce`](Self::produce) if both are `None`
+    /// and fails with [`ClientError::InvalidConfig`] otherwise.
+    fn produce_with_acks(
+        &self,
+        records: Vec<Record>,
+        compression: Compression,
+        acks: Option<Acks>,
+        produce_timeout: Option<Duration>,
+    ) -> BoxFuture<'_, Result<Vec<i64>, ClientError>> {
+        if acks.is_some() || produce_timeout.is_some() {
+            return Box::pin(futures::future::ready(Err(ClientError::InvalidConfig(
+                "producer client does not support overriding the acknowledgement settings"
+                    .to_owned(),
+            ))));
+        }
+        self.produce(records, compression)
+    }
 }
 
 impl ProducerClient for PartitionClient {
@@ -327,6 +387,40 @@ impl ProducerClient for PartitionClient {
     ) -> BoxFuture<'_, Result<Vec<i64>, ClientError>> {
         Box::pin(self.produce(records, compression))
     }
+
+    fn produce_with_acks(
+        &self,
+        records: Vec<Record>,
+        compression: Compression,
+        acks: Option<Acks>,
+        produce_timeout: Option<Duration>,
+    ) -> BoxFuture<'_, Result<Vec<i64>, ClientError>> {
+        Box::pin(self.produce_with_acks(
+            records,
+            compression,
+            acks.unwrap_or(self.acks()),
+            produce_timeout.unwrap_or(self.produce_timeout()),
+        ))
+    }
+}
+
+/// [`ProducerClient`] that applies the acknowledgement settings of a [`BatchProducerBuilder`].
+#[derive(Debug)]
+struct AcksOverride {
+    inner: Arc<dyn ProducerClient>,
+    acks: Option<Acks>,
+    produce_timeout: Option<Duration>,
+}
+
+impl ProducerClient for AcksOverride {
+    fn produce(
+        &self,
+        records: Vec<Record>,
+        compression: Compression,
+    ) -> BoxFuture<'_, Result<Vec<i64>, ClientError>> {
+        self.inner
+            .produce_with_acks(records, compression, self.acks, self.produce_timeout)
+    }
 }
 
 #[derive(Debug)]
@@ -686,6 +780,7 @@ mod tests {
     use crate::{
         client::producer::aggregator::RecordAggregator, protocol::error::Error as ProtocolError,
     };
+    use assert_matches::assert_matches;
     use chrono::{TimeZone, Utc};
     use futures::stream::{FuturesOrdered, FuturesUnordered};
     use futures::{FutureExt, StreamExt, TryStreamExt, pin_mut};
@@ -732,6 +827,33 @@ mod tests {
         }
     }
 
+    /// Client that records the acknowledgement settings it was asked to use.
+    #[derive(Debug, Default)]
+    struct AcksClient {
+        settings: parking_lot::Mutex<Vec<(Option<Acks>, Option<Duration>)>>,
+    }
+
+    impl ProducerClient for AcksClient {
+        fn produce(
+            &self,
+            records: Vec<Record>,
+            compression: Compression,
+        ) -> BoxFuture<'_, Result<Vec<i64>, ClientError>> {
+            self.produce_with_acks(records, compression, None, None)
+        }
+
+        fn produce_with_acks(
+            &self,
+            records: Vec<Record>,
+            _compression: Compression,
+            acks: Option<Acks>,
+            produce_timeout: Option<Duration>,
+        ) -> BoxFuture<'_, Result<Vec<i64>, ClientError>> {
+            self.settings.lock().push((acks, produce_timeout));
+            Box::pin(async move { Ok((0..records.len() as i64).collect()) })
+        }
+    }
+
     fn record() -> Record {
         Record {
             key: Some(vec![0; 4]),
@@ -741,6 +863,59 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_producer_acks() {
+        let record = record();
+        let linger = Duration::from_millis(1);
+
+        let client = Arc::new(AcksClient::default());
+        let producer = BatchProducerBuilder::new_with_client(Arc::<AcksClient>::clone(&client))
+            .with_linger(linger)
+            .build(RecordAggregator::new(record.approximate_size()));
+        producer.produce(record.clone()).await.unwrap();
+
+        let producer = BatchProducerBuilder::new_with_client(Arc::<AcksClient>::clone(&client))
+            .with_linger(linger)
+            .with_acks(Acks::Leader)
+            .build(RecordAggregator::new(record.approximate_size()));
+        producer.produce(record.clone()).await.unwrap();
+
+        let producer = BatchProducerBuilder::new_with_client(Arc::<AcksClient>::clone(&client))
+            .with_linger(linger)
+            .with_produce_timeout(Duration::from_secs(1))
+            .build(RecordAggregator::new(record.approximate_size()));
+        producer.produce(record).await.unwrap();
+
+        assert_eq!(
+            *client.settings.lock(),
+            vec![
+                (None, None),
+                (Some(Acks::Leader), None),
+                (None, Some(Duration::from_secs(1))),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_producer_acks_unsupported() {
+        let record = record();
+
+        let client = Arc::new(MockClient {
+            error: None,
+            panic: None,
+            delay: Duration::from_millis(0),
+            batch_sizes: Default::default(),
+        });
+        let producer = BatchProducerBuilder::new_with_client(Arc::<MockClient>::clone(&client))
+            .with_linger(Duration::from_millis(1))
+            .with_acks(Acks::None)
+            .build(RecordAggregator::new(record.approximate_size()));
+
+        let err = producer.produce(record).await.unwrap_err();
+        assert_matches!(err, Error::Client(e) if matches!(e.as_ref(), ClientError::InvalidConfig(_)));
+        assert!(client.batch_sizes.lock().is_empty());
+    }
+
     #[tokio::test]
     async fn test_producer() {
         let record = record();
@@ -0,0 +1,569 @@
+//! Producer that coalesces writes to many partitions into one produce request per broker.
+//!
+//! Every [`BrokerProducer::produce`] call is queued for the leader of its partition. While a request to a broker is in
+//! flight, further batches for that broker accumulate and are sent together in the next request. The per-partition
+//! results are then handed back to the individual callers.
+//!
+//! # Usage
+//! ```no_run
+//! # async fn test() {
+//! use rskafka::{
+//!     client::{ClientBuilder, partition::Compression},
+//!     record::Record,
+//! };
+//! use chrono::{TimeZone, Utc};
+//! use std::collections::BTreeMap;
+//!
+//! let connection = "localhost:9093".to_owned();
+//! let client = ClientBuilder::new(vec![connection]).build().await.unwrap();
+//! let producer = client.broker_producer().build();
+//!
+//! let record = Record {
+//!     key: None,
+//!     value: Some(b"hello kafka".to_vec()),
+//!     headers: BTreeMap::new(),
+//!     timestamp: Utc.timestamp_millis_opt(42).unwrap(),
+//! };
+//!
+//! // partitions led by the same broker are written using a single request
+//! let (offsets_a, offsets_b) = futures::join!(
+//!     producer.produce("my_topic", 0, vec![record.clone()], Compression::default()),
+//!     producer.produce("my_topic", 1, vec![record], Compression::default()),
+//! );
+//! # }
+//! ```
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
+
+use parking_lot::Mutex;
+use tokio::sync::oneshot;
+use tracing::{debug, info, warn};
+
+use super::{Error, Result};
+use crate::{
+    backoff::{Backoff, BackoffConfig, BackoffError},
+    client::{
+        error::{Error as ClientError, ProtocolError, RequestContext},
+        metadata_cache::MetadataCacheGeneration,
+        partition::{
+            Acks, Compression, UnknownTopicHandling, build_produce_partition_data,
+            process_produce_partition_response,
+        },
+    },
+    connection::{BrokerConnector, MetadataLookupMode},
+    messenger::{RequestError, TimeoutOverride},
+    protocol::{
+        messages::{ProduceRequest, ProduceRequestPartitionData, ProduceRequestTopicData},
+        primitives::{Int16, Int32, NullableString, String_},
+    },
+    record::Record,
+};
+
+/// Builder for [`BrokerProducer`].
+#[derive(Debug)]
+pub struct BrokerProducerBuilder {
+    brokers: Arc<BrokerConnector>,
+
+    backoff_config: Arc<BackoffConfig>,
+
+    unknown_topic_handling: UnknownTopicHandling,
+
+    acks: Acks,
+
+    produce_timeout: Duration,
+
+    request_timeout: TimeoutOverride,
+}
+
+impl BrokerProducerBuilder {
+    pub(crate) fn new(brokers: Arc<BrokerConnector>, backoff_config: Arc<BackoffConfig>) -> Self {
+        Self {
+            brokers,
+            backoff_config,
+            unknown_topic_handling: UnknownTopicHandling::Retry,
+            acks: Acks::default(),
+            produce_timeout: Duration::from_secs(30),
+            request_timeout: TimeoutOverride::Default,
+        }
+    }
+
+    /// Set how [`ProtocolError::UnknownTopicOrPartition`] is handled, see [`UnknownTopicHandling`].
+    ///
+    /// Defaults to [`UnknownTopicHandling::Retry`].
+    pub fn with_unknown_topic_handling(self, unknown_topic_handling: UnknownTopicHandling) -> Self {
+        Self {
+            unknown_topic_handling,
+            ..self
+        }
+    }
+
+    /// Set the acknowledgements that every produce request waits for.
+    ///
+    /// Defaults to [`Acks::All`].
+    pub fn with_acks(self, acks: Acks) -> Self {
+        Self { acks, ..self }
+    }
+
+    /// Set how long the broker may wait for the acknowledgements before it fails a produce request.
+    ///
+    /// Defaults to 30 seconds.
+    pub fn with_produce_timeout(self, produce_timeout: Duration) -> Self {
+        Self {
+            produce_timeout,
+            ..self
+        }
+    }
+
+    /// Override the [request timeout](crate::client::ClientBuilder::request_timeout) for produce requests.
+    ///
+    /// The timeout is extended by the [produce timeout](Self::with_produce_timeout), just like it is for the default.
+    /// Produce requests are sent by background tasks, so [`TimeoutOverride::scope`] does not apply to them. Defaults to
+    /// [`TimeoutOverride::Default`].
+    pub fn with_request_timeout(self, request_timeout: TimeoutOverride) -> Self {
+        Self {
+            request_timeout,
+            ..self
+        }
+    }
+
+    pub fn build(self) -> BrokerProducer {
+        BrokerProducer {
+            inner: Arc::new(Inner {
+                brokers: self.brokers,
+                backoff_config: self.backoff_config,
+                unknown_topic_handling: self.unknown_topic_handling,
+                acks: self.acks,
+                produce_timeout: self.produce_timeout,
+                request_timeout: self.request_timeout,
+                queues: Mutex::new(HashMap::new()),
+            }),
+        }
+    }
+}
+
+/// Producer that writes to arbitrary partitions, using one produce request per broker for all batches that are
+/// pending at the same time.
+///
+/// Must be constructed using [`BrokerProducerBuilder`].
+#[derive(Debug)]
+pub struct BrokerProducer {
+    inner: Arc<Inner>,
+}
+
+impl BrokerProducer {
+    /// Write `records` as a single batch to the given partition.
+    ///
+    /// Returns the offsets of the records. Batches that fail with a retriable error (e.g. because the partition
+    /// leader moved) are sent again according to the backoff configuration of the client. Concurrent batches for the
+    /// same partition are sent in order, but a retried batch may end up behind batches that were queued after it.
+    pub async fn produce(
+        &self,
+        topic: &str,
+        partition: i32,
+        records: Vec<Record>,
+        compression: Compression,
+    ) -> Result<Vec<i64>> {
+        if records.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let num_records = records.len() as i64;
+        let mut data = build_produce_partition_data(partition, records, compression);
+        let mut backoff = Backoff::new(&self.inner.backoff_config);
+
+        loop {
+            let error = match self.inner.leader(topic, partition).await {
+                Ok((leader, r#gen)) => {
+                    let (tx, rx) = oneshot::channel();
+                    self.inner.enqueue(
+                        leader,
+                        PendingBatch {
+                            topic: topic.to_owned(),
+                            partition,
+                            num_records,
+                            data,
+                            tx,
+                        },
+                    );
+
+                    match rx.await {
+                        Ok(Outcome::Done(res)) => return res.map_err(Error::Client),
+                        Ok(Outcome::Retry { data: d, error }) => {
+                            data = d;
+                            if let Some(r#gen) = r#gen {
+                                self.inner.brokers.invalidate_metadata_cache(
+                                    "broker producer: retriable error",
+                                    r#gen,
+                                );
+                            }
+                            error
+                        }
+                        Err(_) => {
+                            return Err(Error::FlushError(
+                                "flush task ended without a result".to_owned(),
+                            ));
+                        }
+                    }
+                }
+                Err(e) if self.inner.is_retriable(&e) => Arc::new(e),
+                Err(e) => return Err(Error::Client(Arc::new(e))),
+            };
+
+            match backoff.next() {
+                Some(backoff) => {
+                    info!(
+                        e=%error,
+                        topic,
+                        partition,
+                        backoff_secs = backoff.as_secs(),
+                        "produce encountered non-fatal error - backing off",
+                    );
+                    tokio::time::sleep(backoff).await;
+                }
+                None => {
+                    return Err(Error::Client(Arc::new(ClientError::RetryFailed(
+                        BackoffError::DeadlineExceded {
+                            deadline: self.inner.backoff_config.deadline.unwrap_or_default(),
+                            source: Box::new(error),
+                        },
+                    ))));
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Inner {
+    brokers: Arc<BrokerConnector>,
+    backoff_config: Arc<BackoffConfig>,
+    unknown_topic_handling: UnknownTopicHandling,
+    acks: Acks,
+    produce_timeout: Duration,
+    request_timeout: TimeoutOverride,
+
+    /// Pending batches by broker ID.
+    queues: Mutex<HashMap<i32, BrokerQueue>>,
+}
+
+#[derive(Debug, Default)]
+struct BrokerQueue {
+    pending: VecDeque<PendingBatch>,
+
+    /// A flush task is running for this broker.
+    flushing: bool,
+}
+
+#[derive(Debug)]
+struct PendingBatch {
+    topic: String,
+    partition: i32,
+    num_records: i64,
+    data: ProduceRequestPartitionData,
+    tx: oneshot::Sender<Outcome>,
+}
+
+/// Result of a batch, sent from the flush task back to the caller.
+#[derive(Debug)]
+enum Outcome {
+    /// The batch was written or failed permanently.
+    Done(Result<Vec<i64>, Arc<ClientError>>),
+
+    /// The batch was not written but may succeed if sent again, possibly to another broker.
+    Retry {
+        data: ProduceRequestPartitionData,
+        error: Arc<ClientError>,
+    },
+}
+
+type Waiting = HashMap<(String, i32), (i64, oneshot::Sender<Outcome>)>;
+
+impl Inner {
+    /// Retrieve the broker ID of the partition leader.
+    async fn leader(
+        &self,
+        topic: &str,
+        partition: i32,
+    ) -> Result<(i32, Option<MetadataCacheGeneration>), ClientError> {
+        let (metadata, r#gen) = self
+            .brokers
+            .request_metadata(
+                &MetadataLookupMode::CachedArbitrary,
+                Some(vec![topic.to_owned()]),
+            )
+            .await?;
+
+        let server_error = |protocol_error, is_virtual| {
+            let request = RequestContext::Partition(topic.to_owned(), partition);
+            ClientError::ServerError {
+                protocol_error,
+                error_message: None,
+                request,
+                response: None,
+                is_virtual,
+            }
+        };
+
+        let topic_metadata = metadata
+            .topics
+            .into_iter()
+            .find(|t| t.name.0 == topic)
+            .ok_or_else(|| server_error(ProtocolError::UnknownTopicOrPartition, true))?;
+        if let Some(e) = topic_metadata.error {
+            return Err(server_error(e, false));
+        }
+
+        let partition_metadata = topic_metadata
+            .partitions
+            .into_iter()
+            .find(|p| p.partition_index.0 == partition)
+            .ok_or_else(|| server_error(ProtocolError::UnknownTopicOrPartition, true))?;
+        if let Some(e) = partition_metadata.error {
+            return Err(server_error(e, false));
+        }
+        if partition_metadata.leader_id.0 == -1 {
+            return Err(server_error(ProtocolError::LeaderNotAvailable, true));
+        }
+
+        Ok((partition_metadata.leader_id.0, r#gen))
+    }
+
+    /// Whether an operation that failed with `error` should be retried.
+    fn is_retriable(&self, error: &ClientError) -> bool {
+        match error {
+            ClientError::Request(
+                RequestError::Poisoned(_) | RequestError::IO(_) | RequestError::Timeout { .. },
+            )
+            | ClientError::Connection(_) => true,
+            ClientError::ServerError {
+                protocol_error:
+                    ProtocolError::InvalidReplicationFactor
+                    | ProtocolError::LeaderNotAvailable
+                    | ProtocolError::NotLeaderOrFollower
+                    | ProtocolError::OffsetNotAvailable,
+                ..
+            } => true,
+            ClientError::ServerError {
+                protocol_error: ProtocolError::UnknownTopicOrPartition,
+                ..
+            } => self.unknown_topic_handling == UnknownTopicHandling::Retry,
+            _ => false,
+        }
+    }
+
+    /// Queue `batch` for `broker_id` and make sure that a flush task is running.
+    fn enqueue(self: &Arc<Self>, broker_id: i32, batch: PendingBatch) {
+        let start_flush = {
+            let mut queues = self.queues.lock();
+            let queue = queues.entry(broker_id).or_default();
+            queue.pending.push_back(batch);
+            !std::mem::replace(&mut queue.flushing, true)
+        };
+
+        if start_flush {
+            tokio::spawn(Arc::clone(self).flush(broker_id));
+        }
+    }
+
+    /// Send requests to `broker_id` until its queue is empty.
+    async fn flush(self: Arc<Self>, broker_id: i32) {
+        loop {
+            let batches = {
+                let mut queues = self.queues.lock();
+                let queue = queues
+                    .get_mut(&broker_id)
+                    .expect("queue exists while flushing");
+
+                // A produce request may only contain one batch per partition, so later batches for the same
+                // partition wait for the next request. This also keeps them in order.
+                let mut partitions = HashSet::new();
+                let mut batches = vec![];
+                let mut rest = VecDeque::new();
+                for batch in queue.pending.drain(..) {
+                    if partitions.insert((batch.topic.clone(), batch.partition)) {
+                        batches.push(batch);
+                    } else {
+                        rest.push_back(batch);
+                    }
+                }
+                queue.pending = rest;
+
+                if batches.is_empty() {
+                    queue.flushing = false;
+                    return;
+                }
+                batches
+            };
+
+            if let Some(throttle) = self.send(broker_id, batches).await {
+                info!(
+                    ?throttle,
+                    broker_id,
+                    request_name = "produce",
+                    "broker asked us to throttle",
+                );
+                tokio::time::sleep(throttle).await;
+            }
+        }
+    }
+
+    /// Send `batches` as a single request and hand the results to the callers.
+    ///
+    /// Returns the time the broker asked us to throttle, if any.
+    async fn send(&self, broker_id: i32, batches: Vec<PendingBatch>) -> Option<Duration> {
+        debug!(
+            broker_id,
+            n = batches.len(),
+            "Sending coalesced produce request"
+        );
+
+        let mut waiting = Waiting::with_capacity(batches.len());
+        let mut topic_data: Vec<ProduceRequestTopicData> = vec![];
+        for batch in batches {
+            match topic_data.iter_mut().find(|t| t.name.0 == batch.topic) {
+                Some(t) => t.partition_data.push(batch.data),
+                None => topic_data.push(ProduceRequestTopicData {
+                    name: String_(batch.topic.clone()),
+                    partition_data: vec![batch.data],
+                }),
+            }
+            waiting.insert(
+                (batch.topic, batch.partition),
+                (batch.num_records, batch.tx),
+            );
+        }
+        let request = ProduceRequest {
+            transactional_id: NullableString(None),
+            acks: Int16(self.acks.value()),
+            timeout_ms: Int32(
+                self.produce_timeout
+                    .as_millis()
+                    .try_into()
+                    .unwrap_or(i32::MAX),
+            ),
+            topic_data,
+        };
+
+        let connection = match self.brokers.connect(broker_id).await {
+            Ok(Some(connection)) => connection,
+            Ok(None) => {
+                self.fail(
+                    request,
+                    waiting,
+                    ClientError::InvalidResponse(format!(
+                        "Broker {broker_id} not found in metadata"
+                    )),
+                );
+                return None;
+            }
+            Err(e) => {
+                self.fail(request, waiting, e.into());
+                return None;
+            }
+        };
+
+        if self.acks == Acks::None {
+            match connection.request_without_response(&request).await {
+                Ok(()) => {
+                    for (n, tx) in waiting.into_values() {
+                        tx.send(Outcome::Done(Ok(vec![-1; n as usize]))).ok();
+                    }
+                }
+                Err(e) => {
+                    self.fail(request, waiting, e.into());
+                }
+            }
+            return None;
+        }
+
+        let timeout =
+            connection.extended_request_timeout(self.request_timeout, self.produce_timeout);
+        let response = match connection.request_with_timeout(&request, timeout).await {
+            Ok(response) => response,
+            Err(e) => {
+                self.fail(request, waiting, e.into());
+                return None;
+            }
+        };
+
+        let mut data = reclaim(request);
+        for topic_response in response.responses {
+            for partition_response in topic_response.partition_responses {
+                let key = (topic_response.name.0.clone(), partition_response.index.0);
+                let Some((n, tx)) = waiting.remove(&key) else {
+                    warn!(
+                        broker_id,
+                        topic = key.0.as_str(),
+                        partition = key.1,
+                        "Got unexpected or duplicate produce result",
+                    );
+                    continue;
+                };
+                let data = data.remove(&key).expect("data for every waiting batch");
+
+                let outcome = match process_produce_partition_response(
+                    key.1,
+                    &key.0,
+                    n,
+                    partition_response,
+                ) {
+                    Ok(offsets) => Outcome::Done(Ok(offsets)),
+                    Err(e) if self.is_retriable(&e) => Outcome::Retry {
+                        data,
+                        error: Arc::new(e),
+                    },
+                    Err(e) => Outcome::Done(Err(Arc::new(e))),
+                };
+                tx.send(outcome).ok();
+            }
+        }
+
+        for ((topic, partition), (_n, tx)) in waiting {
+            let e = ClientError::InvalidResponse(format!(
+                "No produce result for partition {partition} of topic \"{topic}\""
+            ));
+            tx.send(Outcome::Done(Err(Arc::new(e)))).ok();
+        }
+
+        response
+            .throttle_time_ms
+            .filter(|t| t.0 > 0)
+            .map(|t| Duration::from_millis(t.0 as u64))
+    }
+
+    /// Fail all batches of `request` with the same `error`.
+    fn fail(&self, request: ProduceRequest, waiting: Waiting, error: ClientError) {
+        let retry = self.is_retriable(&error);
+        let error = Arc::new(error);
+        let mut data = reclaim(request);
+
+        for (key, (_n, tx)) in waiting {
+            let outcome = if retry {
+                Outcome::Retry {
+                    data: data.remove(&key).expect("data for every waiting batch"),
+                    error: Arc::clone(&error),
+                }
+            } else {
+                Outcome::Done(Err(Arc::clone(&error)))
+            };
+            tx.send(outcome).ok();
+        }
+    }
+}
+
+/// Take the batches out of a request that was sent.
+fn reclaim(request: ProduceRequest) -> HashMap<(String, i32), ProduceRequestPartitionData> {
+    request
+        .topic_data
+        .into_iter()
+        .flat_map(|t| {
+            let name = t.name.0;
+            t.partition_data
+                .into_iter()
+                .map(move |p| ((name.clone(), p.index.0), p))
+        })
+        .collect()
+}
@@ -0,0 +1,143 @@
+use tracing::info;
+
+use crate::{
+    backoff::{BackoffConfig, ErrorOrThrottle},
+    client::{
+        coordinator::maybe_retry,
+        error::{Error, RequestContext, Result},
+    },
+    connection::{BrokerCache, MessengerTransport},
+    messenger::TimeoutOverride,
+    protocol::{
+        messages::InitProducerIdRequest,
+        primitives::{Int16, Int32, Int64, NullableString},
+    },
+    throttle::maybe_throttle,
+};
+
+/// Producer ID and epoch handed out by the cluster.
+///
+/// Brokers use them to deduplicate retried writes and to fence zombie producers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ProducerIdAndEpoch {
+    pub(crate) producer_id: i64,
+    pub(crate) producer_epoch: i16,
+}
+
+/// Sequence number state of an idempotent producer for a single partition.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PartitionSequence {
+    pub(crate) producer: ProducerIdAndEpoch,
+
+    /// Sequence number of the first record of the next batch.
+    pub(crate) next_sequence: i32,
+}
+
+impl PartitionSequence {
+    pub(crate) fn new(producer: ProducerIdAndEpoch) -> Self {
+        Self {
+            producer,
+            next_sequence: 0,
+        }
+    }
+
+    /// Account for a batch of `n` records that was written.
+    pub(crate) fn advance(&mut self, n: i32) {
+        self.next_sequence = increment_sequence(self.next_sequence, n);
+    }
+}
+
+/// Sequence numbers wrap around to 0 after `i32::MAX`, see `DefaultRecordBatch.incrementSequence` in the Java client.
+fn increment_sequence(sequence: i32, increment: i32) -> i32 {
+    if sequence > i32::MAX - increment {
+        increment - (i32::MAX - sequence) - 1
+    } else {
+        sequence + increment
+    }
+}
+
+/// Obtain a producer ID (or bump the epoch of `current`) via `InitProducerId`.
+///
+/// Idempotent producers can send this to any broker, transactional producers have to use their transaction
+/// coordinator.
+pub(crate) async fn init_producer_id<B>(
+    backoff_config: &BackoffConfig,
+    broker_cache: B,
+    transactional_id: Option<&str>,
+    transaction_timeout_ms: i32,
+    current: Option<ProducerIdAndEpoch>,
+    request_timeout: TimeoutOverride,
+    request_context: impl Fn() -> RequestContext + Send + Sync,
+) -> Result<ProducerIdAndEpoch>
+where
+    B: BrokerCache<R = MessengerTransport> + Copy,
+    Error: From<B::E>,
+{
+    let request = &InitProducerIdRequest {
+        transactional_id: NullableString(transactional_id.map(ToOwned::to_owned)),
+        transaction_timeout_ms: Int32(transaction_timeout_ms),
+        producer_id: current.map(|p| Int64(p.producer_id)),
+        producer_epoch: current.map(|p| Int16(p.producer_epoch)),
+        tagged_fields: None,
+    };
+    let request_context = &request_context;
+
+    let producer = maybe_retry(
+        backoff_config,
+        broker_cache,
+        "init_producer_id",
+        || async move {
+            let (broker, r#gen) = broker_cache
+                .get()
+                .await
+                .map_err(|e| ErrorOrThrottle::Error((e.into(), None)))?;
+            let response = broker
+                .request_with_timeout(request, broker.request_timeout(request_timeout))
+                .await
+                .map_err(|e| ErrorOrThrottle::Error((e.into(), Some(r#gen))))?;
+            maybe_throttle(Some(response.throttle_time_ms))?;
+
+            if let Some(protocol_error) = response.error {
+                return Err(ErrorOrThrottle::Error((
+                    Error::ServerError {
+                        protocol_error,
+                        error_message: None,
+                        request: request_context(),
+                        response: None,
+                        is_virtual: false,
+                    },
+                    Some(r#gen),
+                )));
+            }
+
+            Ok(ProducerIdAndEpoch {
+                producer_id: response.producer_id.0,
+                producer_epoch: response.producer_epoch.0,
+            })
+        },
+    )
+    .await?;
+
+    info!(
+        transactional_id,
+        producer_id = producer.producer_id,
+        producer_epoch = producer.producer_epoch,
+        "Initialized producer ID",
+    );
+
+    Ok(producer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_increment_sequence() {
+        assert_eq!(increment_sequence(0, 1), 1);
+        assert_eq!(increment_sequence(5, 10), 15);
+        assert_eq!(increment_sequence(i32::MAX - 1, 1), i32::MAX);
+        assert_eq!(increment_sequence(i32::MAX, 1), 0);
+        assert_eq!(increment_sequence(i32::MAX - 1, 3), 1);
+    }
+}
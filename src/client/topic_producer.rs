@@ -0,0 +1,418 @@
+//! Producer that writes to all partitions of a topic.
+//!
+//! # Usage
+//! ```no_run
+//! # async fn test() {
+//! use rskafka::{
+//!     client::{
+//!         ClientBuilder,
+//!         partition::{Compression, UnknownTopicHandling},
+//!     },
+//!     record::Record,
+//! };
+//! use chrono::{TimeZone, Utc};
+//! use std::collections::BTreeMap;
+//!
+//! let connection = "localhost:9093".to_owned();
+//! let client = ClientBuilder::new(vec![connection]).build().await.unwrap();
+//!
+//! let producer = client
+//!     .topic_producer("my_topic", UnknownTopicHandling::Retry)
+//!     .build()
+//!     .await
+//!     .unwrap();
+//!
+//! // records with the same key end up in the same partition
+//! let record = Record {
+//!     key: Some(b"my_key".to_vec()),
+//!     value: Some(b"hello kafka".to_vec()),
+//!     headers: BTreeMap::new(),
+//!     timestamp: Utc.timestamp_millis_opt(42).unwrap(),
+//! };
+//! let produced = producer
+//!     .produce(vec![record], Compression::default())
+//!     .await
+//!     .unwrap();
+//! println!("partition={} offset={}", produced[0].partition, produced[0].offset);
+//! # }
+//! ```
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use futures::future::try_join_all;
+use tracing::{info, warn};
+
+use crate::{
+    backoff::BackoffConfig,
+    client::{
+        error::{Error, RequestContext, Result},
+        metadata_cache::MetadataCacheGeneration,
+        partition::{Acks, Compression, PartitionClient, UnknownTopicHandling},
+        partitioner::{DefaultPartitioner, Partitioner},
+    },
+    connection::{BrokerConnector, MetadataLookupMode},
+    messenger::TimeoutOverride,
+    record::Record,
+    validation::ExactlyOne,
+};
+
+/// Builder for [`TopicProducer`].
+#[derive(Debug)]
+pub struct TopicProducerBuilder {
+    topic: String,
+
+    brokers: Arc<BrokerConnector>,
+
+    backoff_config: Arc<BackoffConfig>,
+
+    unknown_topic_handling: UnknownTopicHandling,
+
+    partitioner: Arc<dyn Partitioner>,
+
+    metadata_max_age: Duration,
+
+    acks: Acks,
+
+    produce_timeout: Duration,
+
+    request_timeout: TimeoutOverride,
+}
+
+impl TopicProducerBuilder {
+    pub(super) fn new(
+        topic: String,
+        brokers: Arc<BrokerConnector>,
+        backoff_config: Arc<BackoffConfig>,
+        unknown_topic_handling: UnknownTopicHandling,
+    ) -> Self {
+        Self {
+            topic,
+            brokers,
+            backoff_config,
+            unknown_topic_handling,
+            partitioner: Arc::new(DefaultPartitioner::new()),
+            // same default as the Java client
+            metadata_max_age: Duration::from_secs(5 * 60),
+            acks: Acks::default(),
+            produce_timeout: Duration::from_secs(30),
+            request_timeout: TimeoutOverride::Default,
+        }
+    }
+
+    /// Set the partitioner that routes records to partitions.
+    ///
+    /// Defaults to [`DefaultPartitioner`].
+    pub fn with_partitioner(self, partitioner: Arc<dyn Partitioner>) -> Self {
+        Self {
+            partitioner,
+            ..self
+        }
+    }
+
+    /// Set after which time the partition count of the topic is refreshed from the cluster metadata.
+    ///
+    /// Partitions that are added to the topic are used after this refresh, or as soon as the cached metadata of the
+    /// client contains them, e.g. after [`ControllerClient::create_partitions`](super::controller::ControllerClient::create_partitions).
+    /// Defaults to 5 minutes.
+    pub fn with_metadata_max_age(self, metadata_max_age: Duration) -> Self {
+        Self {
+            metadata_max_age,
+            ..self
+        }
+    }
+
+    /// Set the acknowledgements that every produce request waits for, see [`PartitionClient::with_acks`].
+    pub fn with_acks(self, acks: Acks) -> Self {
+        Self { acks, ..self }
+    }
+
+    /// Set how long the broker may wait for the acknowledgements, see [`PartitionClient::with_produce_timeout`].
+    pub fn with_produce_timeout(self, produce_timeout: Duration) -> Self {
+        Self {
+            produce_timeout,
+            ..self
+        }
+    }
+
+    /// Override the request timeout of the partition clients, see [`PartitionClient::with_request_timeout`].
+    pub fn with_request_timeout(self, request_timeout: TimeoutOverride) -> Self {
+        Self {
+            request_timeout,
+            ..self
+        }
+    }
+
+    /// Build producer.
+    ///
+    /// This determines the current partition count of the topic.
+    pub async fn build(self) -> Result<TopicProducer> {
+        let producer = TopicProducer {
+            topic: self.topic,
+            brokers: self.brokers,
+            backoff_config: self.backoff_config,
+            unknown_topic_handling: self.unknown_topic_handling,
+            partitioner: self.partitioner,
+            metadata_max_age: self.metadata_max_age,
+            acks: self.acks,
+            produce_timeout: self.produce_timeout,
+            request_timeout: self.request_timeout,
+            partitions: parking_lot::Mutex::new(Partitions {
+                num_partitions: 0,
+                refreshed_at: None,
+                cache_gen: None,
+                clients: BTreeMap::new(),
+            }),
+        };
+        producer.refresh_metadata().await?;
+        Ok(producer)
+    }
+}
+
+/// Partition and offset of a record written by [`TopicProducer::produce`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartitionAndOffset {
+    /// Partition the record was written to.
+    pub partition: i32,
+
+    /// Offset of the record, see [`PartitionClient::produce`] for when this is unknown.
+    pub offset: i64,
+}
+
+#[derive(Debug)]
+struct Partitions {
+    num_partitions: i32,
+
+    /// Time of the last metadata refresh, `None` before the first one.
+    refreshed_at: Option<Instant>,
+
+    /// Generation of the metadata cache of the client that was last checked for a new partition count.
+    cache_gen: Option<MetadataCacheGeneration>,
+
+    /// Partition clients, created on first use.
+    clients: BTreeMap<i32, Arc<PartitionClient>>,
+}
+
+/// Producer that routes records to the partitions of a topic using a [`Partitioner`].
+///
+/// Must be constructed using [`TopicProducerBuilder`].
+#[derive(Debug)]
+pub struct TopicProducer {
+    topic: String,
+    brokers: Arc<BrokerConnector>,
+    backoff_config: Arc<BackoffConfig>,
+    unknown_topic_handling: UnknownTopicHandling,
+    partitioner: Arc<dyn Partitioner>,
+    metadata_max_age: Duration,
+    acks: Acks,
+    produce_timeout: Duration,
+    request_timeout: TimeoutOverride,
+    partitions: parking_lot::Mutex<Partitions>,
+}
+
+impl TopicProducer {
+    /// Topic
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    /// Partition count of the topic, as of the last metadata refresh or update of the cached metadata of the client.
+    pub fn num_partitions(&self) -> i32 {
+        self.check_metadata_cache();
+        self.partitions.lock().num_partitions
+    }
+
+    /// Determine the current partition count of the topic.
+    ///
+    /// This happens automatically once the [metadata max age](TopicProducerBuilder::with_metadata_max_age) has
+    /// passed, but can be used to pick up new partitions sooner.
+    pub async fn refresh_metadata(&self) -> Result<()> {
+        // taken before the request, so that cache updates during the request are checked afterwards
+        let cache_gen = self.brokers.cached_metadata_generation();
+        let (metadata, _gen) = self
+            .brokers
+            .request_metadata(
+                &MetadataLookupMode::ArbitraryBroker,
+                Some(vec![self.topic.clone()]),
+            )
+            .await?;
+
+        let topic = metadata
+            .topics
+            .exactly_one()
+            .map_err(Error::exactly_one_topic)?;
+
+        if topic.name.0 != self.topic {
+            return Err(Error::InvalidResponse(format!(
+                "Expected metadata for topic \"{}\" got \"{}\"",
+                self.topic, topic.name.0
+            )));
+        }
+
+        if let Some(e) = topic.error {
+            return Err(Error::ServerError {
+                protocol_error: e,
+                error_message: None,
+                request: RequestContext::Topic(self.topic.clone()),
+                response: None,
+                is_virtual: false,
+            });
+        }
+
+        self.update_partitions(topic.partitions.len() as i32, cache_gen);
+
+        Ok(())
+    }
+
+    /// Use the partition count from the cached metadata of the client if the cache was updated since the last check.
+    ///
+    /// The count is kept if the cache does not contain the topic or lists an error for it.
+    fn check_metadata_cache(&self) {
+        let cache_gen = self.brokers.cached_metadata_generation();
+        if self.partitions.lock().cache_gen == Some(cache_gen) {
+            return;
+        }
+
+        match self.brokers.cached_topic_metadata(&self.topic) {
+            (Some(topic), cache_gen) if topic.error.is_none() && !topic.partitions.is_empty() => {
+                self.update_partitions(topic.partitions.len() as i32, cache_gen);
+            }
+            (_, cache_gen) => {
+                self.partitions.lock().cache_gen = Some(cache_gen);
+            }
+        }
+    }
+
+    fn update_partitions(&self, num_partitions: i32, cache_gen: MetadataCacheGeneration) {
+        let mut partitions = self.partitions.lock();
+        if partitions.refreshed_at.is_some() && partitions.num_partitions != num_partitions {
+            if num_partitions < partitions.num_partitions {
+                warn!(
+                    topic = self.topic.as_str(),
+                    old = partitions.num_partitions,
+                    new = num_partitions,
+                    "Partition count of topic decreased, was it re-created?",
+                );
+            } else {
+                info!(
+                    topic = self.topic.as_str(),
+                    old = partitions.num_partitions,
+                    new = num_partitions,
+                    "Partition count of topic changed",
+                );
+            }
+        }
+        partitions.num_partitions = num_partitions;
+        partitions.refreshed_at = Some(Instant::now());
+        partitions.cache_gen = Some(cache_gen);
+        partitions.clients.retain(|p, _| *p < num_partitions);
+    }
+
+    /// Write records to the topic.
+    ///
+    /// Records are routed using the [`Partitioner`] and all records of one partition are written as a single batch,
+    /// concurrently to the other partitions. Returns the partition and offset of every record, in input order.
+    ///
+    /// If an error is returned, the records of some partitions might still have been written. A [`Partitioner`] that
+    /// returns a partition outside of the partition count leads to [`Error::InvalidPartition`] before any record is
+    /// written.
+    pub async fn produce(
+        &self,
+        records: Vec<Record>,
+        compression: Compression,
+    ) -> Result<Vec<PartitionAndOffset>> {
+        if records.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let refresh_due = self
+            .partitions
+            .lock()
+            .refreshed_at
+            .is_none_or(|t| t.elapsed() >= self.metadata_max_age);
+        if refresh_due {
+            self.refresh_metadata().await?;
+        }
+        let num_partitions = self.num_partitions();
+        if num_partitions == 0 {
+            return Err(Error::InvalidResponse(format!(
+                "Metadata lists no partitions for topic \"{}\"",
+                self.topic
+            )));
+        }
+
+        // group records by partition but remember their original position
+        let mut batches: BTreeMap<i32, (Vec<usize>, Vec<Record>)> = BTreeMap::new();
+        for (idx, record) in records.into_iter().enumerate() {
+            let partition = self
+                .partitioner
+                .partition(&self.topic, &record, num_partitions);
+            if !(0..num_partitions).contains(&partition) {
+                return Err(Error::InvalidPartition {
+                    topic: self.topic.clone(),
+                    partition,
+                    num_partitions,
+                });
+            }
+
+            let (indices, batch) = batches.entry(partition).or_default();
+            indices.push(idx);
+            batch.push(record);
+        }
+        self.partitioner.on_new_batch(&self.topic, num_partitions);
+
+        let n = batches.values().map(|(indices, _)| indices.len()).sum();
+        let results = try_join_all(batches.into_iter().map(
+            |(partition, (indices, batch))| async move {
+                let client = self.partition_client(partition).await?;
+                let offsets = client
+                    .produce_with_acks(batch, compression, self.acks, self.produce_timeout)
+                    .await?;
+                Ok::<_, Error>((partition, indices, offsets))
+            },
+        ))
+        .await?;
+
+        let mut produced = vec![
+            PartitionAndOffset {
+                partition: -1,
+                offset: -1
+            };
+            n
+        ];
+        for (partition, indices, offsets) in results {
+            for (idx, offset) in indices.into_iter().zip(offsets) {
+                produced[idx] = PartitionAndOffset { partition, offset };
+            }
+        }
+        Ok(produced)
+    }
+
+    /// Get or create the client for `partition`.
+    async fn partition_client(&self, partition: i32) -> Result<Arc<PartitionClient>> {
+        if let Some(client) = self.partitions.lock().clients.get(&partition) {
+            return Ok(Arc::clone(client));
+        }
+
+        let client = PartitionClient::new(
+            self.topic.clone(),
+            partition,
+            Arc::clone(&self.brokers),
+            self.unknown_topic_handling,
+            Arc::clone(&self.backoff_config),
+        )
+        .await?
+        .with_request_timeout(self.request_timeout);
+
+        // another task might have created a client in the meantime, use that one
+        let mut partitions = self.partitions.lock();
+        Ok(Arc::clone(
+            partitions
+                .clients
+                .entry(partition)
+                .or_insert_with(|| Arc::new(client)),
+        ))
+    }
+}
@@ -0,0 +1,694 @@
+//! Transactional producer.
+//!
+//! A [`TransactionalProducer`] writes records to multiple partitions (and optionally commits consumer group offsets)
+//! atomically: either all writes of a transaction become visible to `read_committed` consumers or none of them.
+//!
+//! # Usage
+//! ```no_run
+//! # async fn test() {
+//! use rskafka::{
+//!     client::{ClientBuilder, partition::Compression},
+//!     record::Record,
+//! };
+//! use chrono::{TimeZone, Utc};
+//! use std::collections::BTreeMap;
+//!
+//! let connection = "localhost:9093".to_owned();
+//! let client = ClientBuilder::new(vec![connection]).build().await.unwrap();
+//!
+//! // fences previous producers with the same transactional ID
+//! let producer = client
+//!     .transactional_producer("my_transactional_id")
+//!     .build()
+//!     .await
+//!     .unwrap();
+//!
+//! let record = Record {
+//!     key: None,
+//!     value: Some(b"hello kafka".to_vec()),
+//!     headers: BTreeMap::from([
+//!         ("foo".to_owned(), b"bar".to_vec()),
+//!     ]),
+//!     timestamp: Utc.timestamp_millis_opt(42).unwrap(),
+//! };
+//!
+//! producer.begin_transaction().await.unwrap();
+//! let res = async {
+//!     producer.produce("my_topic", 0, vec![record.clone()], Compression::default()).await?;
+//!     producer.produce("my_topic", 1, vec![record], Compression::default()).await?;
+//!     producer.commit_transaction().await
+//! }.await;
+//! if res.is_err() {
+//!     producer.abort_transaction().await.unwrap();
+//! }
+//! # }
+//! ```
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Cursor;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::{
+    backoff::{BackoffConfig, ErrorOrThrottle},
+    client::{
+        coordinator::{Coordinator, maybe_retry},
+        error::{Error, ProtocolError, RequestContext, Result},
+        group_offsets::CommittedOffset,
+        partition::{Compression, PartitionClient, UnknownTopicHandling},
+        producer_id::{PartitionSequence, ProducerIdAndEpoch, init_producer_id},
+    },
+    connection::{BrokerCache, BrokerConnector},
+    messenger::TimeoutOverride,
+    protocol::{
+        messages::{
+            AddOffsetsToTxnRequest, AddOffsetsToTxnResponse, AddPartitionsToTxnRequest,
+            AddPartitionsToTxnRequestTopic, AddPartitionsToTxnResponse, CoordinatorType,
+            EndTxnRequest, EndTxnResponse, ReadVersionedType, RequestBody, TxnOffsetCommitRequest,
+            TxnOffsetCommitRequestPartition, TxnOffsetCommitRequestTopic, TxnOffsetCommitResponse,
+            WriteVersionedType,
+        },
+        primitives::{Array, Boolean, Int16, Int32, Int64, NullableString, String_},
+    },
+    record::Record,
+    throttle::maybe_throttle,
+};
+
+/// Builder for [`TransactionalProducer`].
+#[derive(Debug)]
+pub struct TransactionalProducerBuilder {
+    brokers: Arc<BrokerConnector>,
+
+    backoff_config: Arc<BackoffConfig>,
+
+    transactional_id: String,
+
+    transaction_timeout: Duration,
+
+    request_timeout: TimeoutOverride,
+}
+
+impl TransactionalProducerBuilder {
+    pub(super) fn new(
+        brokers: Arc<BrokerConnector>,
+        backoff_config: Arc<BackoffConfig>,
+        transactional_id: String,
+    ) -> Self {
+        Self {
+            brokers,
+            backoff_config,
+            transactional_id,
+            // same default as the Java client
+            transaction_timeout: Duration::from_secs(60),
+            request_timeout: TimeoutOverride::Default,
+        }
+    }
+
+    /// Time after which the coordinator proactively aborts an open transaction.
+    ///
+    /// This must not exceed the `transaction.max.timeout.ms` setting of the brokers.
+    pub fn with_transaction_timeout(self, transaction_timeout: Duration) -> Self {
+        Self {
+            transaction_timeout,
+            ..self
+        }
+    }
+
+    /// Override the [request timeout](super::ClientBuilder::request_timeout) for requests sent by this producer.
+    ///
+    /// This applies to requests sent to the coordinators and to the
+    /// [partition clients](PartitionClient::with_request_timeout) that the producer uses. Defaults to
+    /// [`TimeoutOverride::Default`].
+    pub fn with_request_timeout(self, request_timeout: TimeoutOverride) -> Self {
+        Self {
+            request_timeout,
+            ..self
+        }
+    }
+
+    /// Obtain a producer ID for the transactional ID.
+    ///
+    /// This fences all previous producers that used the same transactional ID and aborts their open transaction.
+    pub async fn build(self) -> Result<TransactionalProducer> {
+        let transaction_timeout_ms = self
+            .transaction_timeout
+            .as_millis()
+            .try_into()
+            .unwrap_or(i32::MAX);
+        let coordinator = Coordinator::new(
+            self.transactional_id.clone(),
+            CoordinatorType::Transaction,
+            Arc::clone(&self.brokers),
+        )
+        .with_request_timeout(self.request_timeout);
+        let producer = init_producer_id(
+            &self.backoff_config,
+            &coordinator,
+            Some(&self.transactional_id),
+            transaction_timeout_ms,
+            None,
+            self.request_timeout,
+            || coordinator.request_context(),
+        )
+        .await?;
+
+        Ok(TransactionalProducer {
+            brokers: self.brokers,
+            backoff_config: self.backoff_config,
+            transactional_id: self.transactional_id,
+            transaction_timeout_ms,
+            request_timeout: self.request_timeout,
+            coordinator,
+            state: Mutex::new(State {
+                producer,
+                status: Status::Ready,
+                epoch_bump_required: false,
+                sequences: BTreeMap::new(),
+                partition_clients: BTreeMap::new(),
+                transaction_partitions: BTreeSet::new(),
+                transaction_groups: BTreeSet::new(),
+            }),
+        })
+    }
+}
+
+/// Status of the current transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    /// No transaction in progress.
+    Ready,
+
+    /// A transaction was started.
+    InTransaction,
+
+    /// An operation within the transaction failed, the transaction must be aborted.
+    AbortableError,
+
+    /// The producer was fenced or failed in a way it cannot recover from.
+    Fatal,
+}
+
+/// Topic name and partition ID.
+type TopicPartition = (String, i32);
+
+#[derive(Debug)]
+struct State {
+    producer: ProducerIdAndEpoch,
+
+    status: Status,
+
+    /// A produce request failed, so the sequence numbers might be out of sync with the brokers.
+    ///
+    /// The producer epoch is bumped once the transaction was aborted, which resets the sequence numbers.
+    epoch_bump_required: bool,
+
+    /// Next sequence number per partition.
+    sequences: BTreeMap<TopicPartition, i32>,
+
+    partition_clients: BTreeMap<TopicPartition, Arc<PartitionClient>>,
+
+    /// Partitions that were added to the current transaction.
+    transaction_partitions: BTreeSet<TopicPartition>,
+
+    /// Consumer groups that were added to the current transaction.
+    transaction_groups: BTreeSet<String>,
+}
+
+impl State {
+    fn ensure_in_transaction(&self) -> Result<()> {
+        match self.status {
+            Status::InTransaction => Ok(()),
+            Status::Ready => Err(Error::InvalidTransactionState(
+                "no transaction in progress".to_owned(),
+            )),
+            Status::AbortableError => Err(Error::InvalidTransactionState(
+                "transaction failed and must be aborted".to_owned(),
+            )),
+            Status::Fatal => Err(Error::InvalidTransactionState(
+                "producer was fenced or failed fatally".to_owned(),
+            )),
+        }
+    }
+
+    /// Record that an operation within the transaction failed.
+    fn fail(&mut self, e: Error) -> Error {
+        self.status = if is_fatal(&e) {
+            Status::Fatal
+        } else {
+            Status::AbortableError
+        };
+        e
+    }
+
+    fn reset_transaction(&mut self) {
+        self.transaction_partitions.clear();
+        self.transaction_groups.clear();
+        self.status = Status::Ready;
+    }
+}
+
+/// Errors after which the producer cannot continue.
+fn is_fatal(e: &Error) -> bool {
+    matches!(
+        e,
+        Error::ServerError {
+            protocol_error: ProtocolError::ProducerFenced
+                | ProtocolError::TransactionalIdAuthorizationFailed,
+            ..
+        }
+    )
+}
+
+/// Producer that writes records to multiple partitions within atomic transactions.
+///
+/// Must be constructed using [`TransactionalProducerBuilder`].
+///
+/// All operations are serialized. If a transaction is left open (e.g. because the producer is dropped), the coordinator
+/// aborts it after the transaction timeout or as soon as a new producer with the same transactional ID is built.
+///
+/// # Error Handling
+/// If an operation within a transaction fails, the transaction must be aborted using
+/// [`abort_transaction`](Self::abort_transaction). If the producer was fenced by another producer using the same
+/// transactional ID, all further operations fail with [`Error::InvalidTransactionState`] and a new producer must be
+/// built.
+#[derive(Debug)]
+pub struct TransactionalProducer {
+    brokers: Arc<BrokerConnector>,
+
+    backoff_config: Arc<BackoffConfig>,
+
+    transactional_id: String,
+
+    transaction_timeout_ms: i32,
+
+    request_timeout: TimeoutOverride,
+
+    /// Transaction coordinator.
+    coordinator: Coordinator,
+
+    state: Mutex<State>,
+}
+
+impl TransactionalProducer {
+    /// Transactional ID.
+    pub fn transactional_id(&self) -> &str {
+        &self.transactional_id
+    }
+
+    /// Start a new transaction.
+    pub async fn begin_transaction(&self) -> Result<()> {
+        let mut state = self.state.lock().await;
+        match state.status {
+            Status::Ready => {
+                state.status = Status::InTransaction;
+                Ok(())
+            }
+            Status::InTransaction => Err(Error::InvalidTransactionState(
+                "transaction already in progress".to_owned(),
+            )),
+            _ => state.ensure_in_transaction(),
+        }
+    }
+
+    /// Produce records to a partition as part of the current transaction.
+    ///
+    /// Returns the offsets of the records. They only become visible to `read_committed` consumers once the transaction
+    /// is committed.
+    pub async fn produce(
+        &self,
+        topic: &str,
+        partition: i32,
+        records: Vec<Record>,
+        compression: Compression,
+    ) -> Result<Vec<i64>> {
+        let mut state = self.state.lock().await;
+        state.ensure_in_transaction()?;
+
+        let key = (topic.to_owned(), partition);
+        let client = match state.partition_clients.get(&key) {
+            Some(client) => Arc::clone(client),
+            None => {
+                let client = Arc::new(
+                    PartitionClient::new(
+                        topic.to_owned(),
+                        partition,
+                        Arc::clone(&self.brokers),
+                        UnknownTopicHandling::Retry,
+                        Arc::clone(&self.backoff_config),
+                    )
+                    .await?
+                    .with_request_timeout(self.request_timeout),
+                );
+                state
+                    .partition_clients
+                    .insert(key.clone(), Arc::clone(&client));
+                client
+            }
+        };
+
+        if !state.transaction_partitions.contains(&key) {
+            if let Err(e) = self.add_partition(state.producer, topic, partition).await {
+                return Err(state.fail(e));
+            }
+            state.transaction_partitions.insert(key.clone());
+        }
+
+        let n = records.len() as i32;
+        let mut sequence = PartitionSequence {
+            producer: state.producer,
+            next_sequence: state.sequences.get(&key).copied().unwrap_or_default(),
+        };
+        match client
+            .produce_transactional(records, compression, &self.transactional_id, &sequence)
+            .await
+        {
+            Ok(offsets) => {
+                sequence.advance(n);
+                state.sequences.insert(key, sequence.next_sequence);
+                Ok(offsets)
+            }
+            Err(e) => {
+                // we do not know if the batch was written
+                state.epoch_bump_required = true;
+                Err(state.fail(e))
+            }
+        }
+    }
+
+    /// Commit consumer group offsets as part of the current transaction.
+    ///
+    /// This is used for "consume-transform-produce" pipelines: the offsets are only committed if the transaction is
+    /// committed.
+    pub async fn send_offsets_to_transaction(
+        &self,
+        group_id: &str,
+        offsets: &[CommittedOffset],
+    ) -> Result<()> {
+        let mut state = self.state.lock().await;
+        state.ensure_in_transaction()?;
+
+        if !state.transaction_groups.contains(group_id) {
+            let request = AddOffsetsToTxnRequest {
+                transactional_id: String_(self.transactional_id.clone()),
+                producer_id: Int64(state.producer.producer_id),
+                producer_epoch: Int16(state.producer.producer_epoch),
+                group_id: String_(group_id.to_owned()),
+            };
+            if let Err(e) = self
+                .request::<_, AddOffsetsToTxnResponse>(
+                    &self.coordinator,
+                    "add_offsets_to_txn",
+                    &request,
+                )
+                .await
+            {
+                return Err(state.fail(e));
+            }
+            state.transaction_groups.insert(group_id.to_owned());
+        }
+
+        let mut topics: BTreeMap<&str, Vec<TxnOffsetCommitRequestPartition>> = BTreeMap::new();
+        for offset in offsets {
+            topics.entry(offset.topic.as_str()).or_default().push(
+                TxnOffsetCommitRequestPartition {
+                    partition_index: Int32(offset.partition),
+                    committed_offset: Int64(offset.offset),
+                    committed_leader_epoch: None,
+                    committed_metadata: NullableString(offset.metadata.clone()),
+                },
+            );
+        }
+        let request = TxnOffsetCommitRequest {
+            transactional_id: String_(self.transactional_id.clone()),
+            group_id: String_(group_id.to_owned()),
+            producer_id: Int64(state.producer.producer_id),
+            producer_epoch: Int16(state.producer.producer_epoch),
+            topics: topics
+                .into_iter()
+                .map(|(topic, partitions)| TxnOffsetCommitRequestTopic {
+                    name: String_(topic.to_owned()),
+                    partitions,
+                })
+                .collect(),
+        };
+
+        // offsets are written by the group coordinator
+        let group_coordinator = Coordinator::new(
+            group_id.to_owned(),
+            CoordinatorType::Group,
+            Arc::clone(&self.brokers),
+        )
+        .with_request_timeout(self.request_timeout);
+        match self
+            .request::<_, TxnOffsetCommitResponse>(
+                &group_coordinator,
+                "txn_offset_commit",
+                &request,
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => Err(state.fail(e)),
+        }
+    }
+
+    /// Commit the current transaction.
+    ///
+    /// If this fails, the transaction must be [aborted](Self::abort_transaction).
+    pub async fn commit_transaction(&self) -> Result<()> {
+        let mut state = self.state.lock().await;
+        state.ensure_in_transaction()?;
+
+        if let Err(e) = self.end_transaction(&state, true).await {
+            return Err(state.fail(e));
+        }
+        state.reset_transaction();
+
+        Ok(())
+    }
+
+    /// Abort the current transaction.
+    ///
+    /// This also recovers from errors within the transaction. If required, the producer epoch is bumped afterwards so
+    /// that the sequence numbers start over.
+    pub async fn abort_transaction(&self) -> Result<()> {
+        let mut state = self.state.lock().await;
+        match state.status {
+            Status::InTransaction | Status::AbortableError => {}
+            _ => state.ensure_in_transaction()?,
+        }
+
+        match self.end_transaction(&state, false).await {
+            Ok(()) => {}
+            Err(
+                e @ Error::ServerError {
+                    protocol_error: ProtocolError::InvalidProducerEpoch,
+                    ..
+                },
+            ) => {
+                // The coordinator already aborted the transaction because it timed out and bumped the epoch (KIP-360).
+                // Recover by bumping the epoch ourselves.
+                warn!(
+                    e=%e,
+                    transactional_id = self.transactional_id.as_str(),
+                    "Transaction was aborted by coordinator",
+                );
+                state.epoch_bump_required = true;
+            }
+            Err(e) => return Err(state.fail(e)),
+        }
+
+        if state.epoch_bump_required {
+            // Bumping the epoch fails if we were fenced by another producer in the meantime.
+            let producer = init_producer_id(
+                &self.backoff_config,
+                &self.coordinator,
+                Some(&self.transactional_id),
+                self.transaction_timeout_ms,
+                Some(state.producer),
+                self.request_timeout,
+                || self.coordinator.request_context(),
+            )
+            .await
+            .inspect_err(|_| {
+                state.status = Status::Fatal;
+            })?;
+
+            state.producer = producer;
+            state.sequences.clear();
+            state.epoch_bump_required = false;
+        }
+
+        state.reset_transaction();
+        info!(
+            transactional_id = self.transactional_id.as_str(),
+            "Aborted transaction"
+        );
+
+        Ok(())
+    }
+
+    async fn add_partition(
+        &self,
+        producer: ProducerIdAndEpoch,
+        topic: &str,
+        partition: i32,
+    ) -> Result<()> {
+        let request = AddPartitionsToTxnRequest {
+            transactional_id: String_(self.transactional_id.clone()),
+            producer_id: Int64(producer.producer_id),
+            producer_epoch: Int16(producer.producer_epoch),
+            topics: vec![AddPartitionsToTxnRequestTopic {
+                name: String_(topic.to_owned()),
+                partitions: Array(Some(vec![Int32(partition)])),
+            }],
+        };
+        self.request::<_, AddPartitionsToTxnResponse>(
+            &self.coordinator,
+            "add_partitions_to_txn",
+            &request,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn end_transaction(&self, state: &State, committed: bool) -> Result<()> {
+        // the coordinator does not know about transactions that did not touch any partition or group
+        if state.transaction_partitions.is_empty() && state.transaction_groups.is_empty() {
+            return Ok(());
+        }
+
+        let request = EndTxnRequest {
+            transactional_id: String_(self.transactional_id.clone()),
+            producer_id: Int64(state.producer.producer_id),
+            producer_epoch: Int16(state.producer.producer_epoch),
+            committed: Boolean(committed),
+        };
+        self.request::<_, EndTxnResponse>(&self.coordinator, "end_txn", &request)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Send a request to a coordinator and check the response for errors.
+    async fn request<Req, Resp>(
+        &self,
+        coordinator: &Coordinator,
+        request_name: &str,
+        request: &Req,
+    ) -> Result<Resp>
+    where
+        Req: RequestBody<ResponseBody = Resp> + WriteVersionedType<Vec<u8>> + Send + Sync,
+        Resp: TxnResponse + ReadVersionedType<Cursor<Vec<u8>>> + Send,
+    {
+        maybe_retry(
+            &self.backoff_config,
+            coordinator,
+            request_name,
+            || async move {
+                let (broker, r#gen) = coordinator
+                    .get()
+                    .await
+                    .map_err(|e| ErrorOrThrottle::Error((e, None)))?;
+                let response = broker
+                    .request_with_timeout(request, broker.request_timeout(self.request_timeout))
+                    .await
+                    .map_err(|e| ErrorOrThrottle::Error((e.into(), Some(r#gen))))?;
+                maybe_throttle(Some(response.throttle_time_ms()))?;
+
+                match response.error(&self.transactional_id) {
+                    Some(e) => Err(ErrorOrThrottle::Error((e, Some(r#gen)))),
+                    None => Ok(response),
+                }
+            },
+        )
+        .await
+    }
+}
+
+/// Common fields of the responses sent by the transaction and group coordinators.
+trait TxnResponse {
+    fn throttle_time_ms(&self) -> Int32;
+
+    /// First error within the response.
+    fn error(&self, transactional_id: &str) -> Option<Error>;
+}
+
+fn server_error(protocol_error: ProtocolError, request: RequestContext) -> Error {
+    Error::ServerError {
+        protocol_error,
+        error_message: None,
+        request,
+        response: None,
+        is_virtual: false,
+    }
+}
+
+impl TxnResponse for AddPartitionsToTxnResponse {
+    fn throttle_time_ms(&self) -> Int32 {
+        self.throttle_time_ms
+    }
+
+    fn error(&self, _transactional_id: &str) -> Option<Error> {
+        self.results.iter().find_map(|topic| {
+            topic.results.iter().find_map(|partition| {
+                partition.error.map(|e| {
+                    server_error(
+                        e,
+                        RequestContext::Partition(
+                            topic.name.0.clone(),
+                            partition.partition_index.0,
+                        ),
+                    )
+                })
+            })
+        })
+    }
+}
+
+impl TxnResponse for AddOffsetsToTxnResponse {
+    fn throttle_time_ms(&self) -> Int32 {
+        self.throttle_time_ms
+    }
+
+    fn error(&self, transactional_id: &str) -> Option<Error> {
+        self.error
+            .map(|e| server_error(e, RequestContext::Transaction(transactional_id.to_owned())))
+    }
+}
+
+impl TxnResponse for EndTxnResponse {
+    fn throttle_time_ms(&self) -> Int32 {
+        self.throttle_time_ms
+    }
+
+    fn error(&self, transactional_id: &str) -> Option<Error> {
+        self.error
+            .map(|e| server_error(e, RequestContext::Transaction(transactional_id.to_owned())))
+    }
+}
+
+impl TxnResponse for TxnOffsetCommitResponse {
+    fn throttle_time_ms(&self) -> Int32 {
+        self.throttle_time_ms
+    }
+
+    fn error(&self, _transactional_id: &str) -> Option<Error> {
+        self.topics.iter().find_map(|topic| {
+            topic.partitions.iter().find_map(|partition| {
+                partition.error.map(|e| {
+                    server_error(
+                        e,
+                        RequestContext::Partition(
+                            topic.name.0.clone(),
+                            partition.partition_index.0,
+                        ),
+                    )
+                })
+            })
+        })
+    }
+}
@@ -10,10 +10,13 @@ use tracing::{debug, error, info, warn};
 
 use crate::backoff::ErrorOrThrottle;
 use crate::client::metadata_cache::MetadataCacheGeneration;
+use crate::connection::pool::ConnectionPool;
 use crate::connection::topology::{Broker, BrokerTopology};
 use crate::connection::transport::Transport;
-use crate::messenger::{Messenger, RequestError};
-use crate::protocol::messages::{MetadataRequest, MetadataRequestTopic, MetadataResponse};
+use crate::messenger::{Messenger, RequestError, RequestTimeoutConfig};
+use crate::protocol::messages::{
+    MetadataRequest, MetadataRequestTopic, MetadataResponse, MetadataResponseTopic,
+};
 use crate::protocol::primitives::String_;
 use crate::throttle::maybe_throttle;
 use crate::{
@@ -25,6 +28,7 @@ pub use self::transport::Error as TransportError;
 pub use self::transport::TlsConfig;
 pub use self::transport::{Credentials, OauthBearerCredentials, OauthCallback, SaslConfig};
 
+mod pool;
 mod topology;
 mod transport;
 
@@ -80,6 +84,7 @@ impl Display for MultiError {
 trait ConnectionHandler {
     type R: RequestHandler + Send + Sync;
 
+    #[allow(clippy::too_many_arguments, reason = "Method is internal")]
     fn connect(
         &self,
         client_id: Arc<str>,
@@ -88,6 +93,7 @@ trait ConnectionHandler {
         sasl_config: Option<SaslConfig>,
         max_message_size: usize,
         timeout: Option<Duration>,
+        timeout_config: RequestTimeoutConfig,
     ) -> impl Future<Output = Result<Arc<Self::R>>> + Send;
 }
 
@@ -151,6 +157,7 @@ impl ConnectionHandler for BrokerRepresentation {
         sasl_config: Option<SaslConfig>,
         max_message_size: usize,
         timeout: Option<Duration>,
+        timeout_config: RequestTimeoutConfig,
     ) -> Result<Arc<Self::R>> {
         let url = self.url();
         info!(
@@ -166,6 +173,7 @@ impl ConnectionHandler for BrokerRepresentation {
             })?;
 
         let mut messenger = Messenger::new(BufStream::new(transport), max_message_size, client_id);
+        messenger.set_timeout_config(timeout_config);
         messenger.sync_versions().await?;
         if let Some(sasl_config) = sasl_config {
             messenger.do_sasl(sasl_config).await?;
@@ -177,7 +185,7 @@ impl ConnectionHandler for BrokerRepresentation {
 /// Caches the broker topology and provides the ability to
 ///
 /// * Get a cached connection to an arbitrary broker
-/// * Obtain a connection to a specific broker
+/// * Obtain a pooled connection to a specific broker
 ///
 /// Maintains a list of brokers within the cluster and caches a connection to a broker
 pub struct BrokerConnector {
@@ -195,6 +203,9 @@ pub struct BrokerConnector {
     /// This one is used for metadata queries.
     cached_arbitrary_broker: Mutex<(Option<BrokerConnection>, BrokerCacheGeneration)>,
 
+    /// Connections to specific brokers, shared by all clients.
+    connection_pool: ConnectionPool<MessengerTransport>,
+
     /// A cache of [`MetadataResponse`].
     ///
     /// Used during leader discovery.
@@ -217,6 +228,12 @@ pub struct BrokerConnector {
 
     /// Timeout for connection attempts to the broker.
     connect_timeout: Option<Duration>,
+
+    /// Timeout for requests.
+    timeout_config: RequestTimeoutConfig,
+
+    /// Rack of the client, used to fetch from the closest replica.
+    client_rack: Option<Arc<str>>,
 }
 
 impl BrokerConnector {
@@ -230,12 +247,16 @@ impl BrokerConnector {
         max_message_size: usize,
         backoff_config: Arc<BackoffConfig>,
         connect_timeout: Option<Duration>,
+        timeout_config: RequestTimeoutConfig,
+        connections_per_broker: usize,
+        client_rack: Option<Arc<str>>,
     ) -> Self {
         Self {
             bootstrap_brokers,
             client_id,
             topology: Default::default(),
             cached_arbitrary_broker: Mutex::new((None, BrokerCacheGeneration::START)),
+            connection_pool: ConnectionPool::new(connections_per_broker),
             cached_metadata: Default::default(),
             backoff_config,
             tls_config,
@@ -243,9 +264,16 @@ impl BrokerConnector {
             sasl_config,
             max_message_size,
             connect_timeout,
+            timeout_config,
+            client_rack,
         }
     }
 
+    /// Rack of the client, if configured.
+    pub fn client_rack(&self) -> Option<&str> {
+        self.client_rack.as_deref()
+    }
+
     /// Fetch and cache metadata
     pub async fn refresh_metadata(&self) -> Result<()> {
         self.request_metadata(&MetadataLookupMode::ArbitraryBroker, None)
@@ -320,6 +348,19 @@ impl BrokerConnector {
         Ok((response, None))
     }
 
+    /// Generation of the cached metadata, which changes whenever the cache is updated.
+    pub(crate) fn cached_metadata_generation(&self) -> MetadataCacheGeneration {
+        self.cached_metadata.generation()
+    }
+
+    /// Cached metadata of `topic`, if any, and the generation of the cache it was read from.
+    pub(crate) fn cached_topic_metadata(
+        &self,
+        topic: &str,
+    ) -> (Option<MetadataResponseTopic>, MetadataCacheGeneration) {
+        self.cached_metadata.topic(topic)
+    }
+
     pub(crate) fn invalidate_metadata_cache(
         &self,
         reason: &'static str,
@@ -328,19 +369,28 @@ impl BrokerConnector {
         self.cached_metadata.invalidate(reason, r#gen)
     }
 
-    /// Returns a new connection to the broker with the provided id
+    /// Returns a connection to the broker with the provided id
+    ///
+    /// Connections are pooled and shared between all callers. A new connection is only established if the pooled one
+    /// is poisoned or the broker moved to a different address.
     pub async fn connect(&self, broker_id: i32) -> Result<Option<BrokerConnection>> {
         match self.topology.get_broker(broker_id).await {
             Some(broker) => {
-                let connection = BrokerRepresentation::Topology(broker)
-                    .connect(
-                        Arc::clone(&self.client_id),
-                        self.tls_config.clone(),
-                        self.socks5_proxy.clone(),
-                        self.sasl_config.clone(),
-                        self.max_message_size,
-                        self.connect_timeout,
-                    )
+                let connection = self
+                    .connection_pool
+                    .get(broker_id, broker.to_string(), || async move {
+                        BrokerRepresentation::Topology(broker)
+                            .connect(
+                                Arc::clone(&self.client_id),
+                                self.tls_config.clone(),
+                                self.socks5_proxy.clone(),
+                                self.sasl_config.clone(),
+                                self.max_message_size,
+                                self.connect_timeout,
+                                self.timeout_config,
+                            )
+                            .await
+                    })
                     .await?;
                 Ok(Some(connection))
             }
@@ -373,6 +423,7 @@ impl std::fmt::Debug for BrokerConnector {
             .field("bootstrap_brokers", &self.bootstrap_brokers)
             .field("topology", &self.topology)
             .field("cached_arbitrary_broker", &self.cached_arbitrary_broker)
+            .field("connection_pool", &self.connection_pool)
             .field("backoff_config", &self.backoff_config)
             .field("tls_config", &"...")
             .field("max_message_size", &self.max_message_size)
@@ -455,6 +506,7 @@ impl BrokerCache for &BrokerConnector {
             self.sasl_config.clone(),
             self.max_message_size,
             self.connect_timeout,
+            self.timeout_config,
         )
         .await?;
 
@@ -493,6 +545,7 @@ async fn connect_to_a_broker_with_retry<B>(
     sasl_config: Option<SaslConfig>,
     max_message_size: usize,
     connect_timeout: Option<Duration>,
+    timeout_config: RequestTimeoutConfig,
 ) -> Result<Arc<B::R>>
 where
     B: ConnectionHandler + Send + Sync,
@@ -513,6 +566,7 @@ where
                         sasl_config.clone(),
                         max_message_size,
                         connect_timeout,
+                        timeout_config,
                     )
                     .await;
 
@@ -567,9 +621,11 @@ where
 
                     ControlFlow::Break(Ok(response))
                 }
-                Err(e @ RequestError::Poisoned(_) | e @ RequestError::IO(_))
-                    if !matches!(metadata_mode, MetadataLookupMode::SpecificBroker(_)) =>
-                {
+                Err(
+                    e @ (RequestError::Poisoned(_)
+                    | RequestError::IO(_)
+                    | RequestError::Timeout { .. }),
+                ) if !matches!(metadata_mode, MetadataLookupMode::SpecificBroker(_)) => {
                     if let Some(r#gen) = cache_gen {
                         arbitrary_broker_cache
                             .invalidate(
@@ -825,6 +881,7 @@ mod tests {
             _sasl_config: Option<SaslConfig>,
             _max_message_size: usize,
             _connect_timeout: Option<Duration>,
+            _timeout_config: RequestTimeoutConfig,
         ) -> Result<Arc<Self::R>> {
             (self.conn)()
         }
@@ -854,6 +911,7 @@ mod tests {
             Default::default(),
             Default::default(),
             None,
+            Default::default(),
         )
         .await
         .unwrap();
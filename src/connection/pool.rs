@@ -0,0 +1,235 @@
+//! Connections to specific brokers that are shared by all clients.
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use parking_lot::Mutex;
+use tokio::sync::Mutex as AsyncMutex;
+use tracing::{debug, info};
+
+use super::{MessengerTransport, Result};
+
+/// A connection that can be shared by the [`ConnectionPool`].
+pub trait PooledConnection: Send + Sync {
+    /// The connection is broken and must be replaced.
+    fn is_poisoned(&self) -> bool;
+}
+
+impl PooledConnection for MessengerTransport {
+    fn is_poisoned(&self) -> bool {
+        self.is_poisoned()
+    }
+}
+
+/// Pool of connections, keyed by broker ID.
+///
+/// Requests are pipelined, so a connection can be used by many clients at the same time. Every broker gets up to
+/// `connections_per_broker` connections which are handed out round-robin.
+#[derive(Debug)]
+pub struct ConnectionPool<C> {
+    connections_per_broker: usize,
+    brokers: Mutex<HashMap<i32, Arc<BrokerConnections<C>>>>,
+}
+
+#[derive(Debug)]
+struct BrokerConnections<C> {
+    /// Slot that is used next.
+    next: AtomicUsize,
+
+    /// Established connections.
+    ///
+    /// The async lock ensures that concurrent callers wait for a connection attempt instead of starting their own.
+    slots: Vec<AsyncMutex<Option<Slot<C>>>>,
+}
+
+#[derive(Debug)]
+struct Slot<C> {
+    /// Address the connection was established to.
+    url: String,
+    connection: Arc<C>,
+}
+
+impl<C> ConnectionPool<C>
+where
+    C: PooledConnection,
+{
+    /// Create empty pool.
+    ///
+    /// # Panics
+    /// Panics if `connections_per_broker` is zero.
+    pub fn new(connections_per_broker: usize) -> Self {
+        assert!(
+            connections_per_broker > 0,
+            "need at least one connection per broker"
+        );
+
+        Self {
+            connections_per_broker,
+            brokers: Default::default(),
+        }
+    }
+
+    /// Get a connection to broker `broker_id` at `url`.
+    ///
+    /// A new connection is established using `connect` if the selected slot is empty, if its connection is poisoned or
+    /// if the broker moved to a different address.
+    pub async fn get<F, Fut>(&self, broker_id: i32, url: String, connect: F) -> Result<Arc<C>>
+    where
+        F: FnOnce() -> Fut + Send,
+        Fut: Future<Output = Result<Arc<C>>> + Send,
+    {
+        let broker = Arc::clone(self.brokers.lock().entry(broker_id).or_insert_with(|| {
+            Arc::new(BrokerConnections {
+                next: AtomicUsize::new(0),
+                slots: (0..self.connections_per_broker)
+                    .map(|_| AsyncMutex::new(None))
+                    .collect(),
+            })
+        }));
+        let idx = broker.next.fetch_add(1, Ordering::Relaxed) % broker.slots.len();
+
+        let mut slot = broker.slots[idx].lock().await;
+        match slot.as_ref() {
+            Some(s) if s.url != url => {
+                info!(
+                    broker_id,
+                    old = s.url.as_str(),
+                    new = url.as_str(),
+                    "Broker moved, replacing pooled connection",
+                );
+            }
+            Some(s) if s.connection.is_poisoned() => {
+                info!(
+                    broker_id,
+                    slot = idx,
+                    "Replacing poisoned pooled connection"
+                );
+            }
+            Some(s) => {
+                return Ok(Arc::clone(&s.connection));
+            }
+            None => {
+                debug!(broker_id, slot = idx, "Establishing pooled connection");
+            }
+        }
+
+        // drop broken connection before connecting, so a failed attempt does not leave it in the pool
+        *slot = None;
+        let connection = connect().await?;
+        *slot = Some(Slot {
+            url,
+            connection: Arc::clone(&connection),
+        });
+        Ok(connection)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+
+    use super::*;
+    use crate::connection::Error;
+
+    #[derive(Debug, Default)]
+    struct FakeConnection {
+        poisoned: AtomicBool,
+    }
+
+    impl PooledConnection for FakeConnection {
+        fn is_poisoned(&self) -> bool {
+            self.poisoned.load(Ordering::SeqCst)
+        }
+    }
+
+    async fn get(
+        pool: &ConnectionPool<FakeConnection>,
+        broker_id: i32,
+        url: &str,
+    ) -> Arc<FakeConnection> {
+        pool.get(broker_id, url.to_owned(), || async {
+            Ok(Arc::new(FakeConnection::default()))
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_reuse() {
+        let pool = ConnectionPool::new(1);
+
+        let c1 = get(&pool, 1, "a:1").await;
+        let c2 = get(&pool, 1, "a:1").await;
+        assert!(Arc::ptr_eq(&c1, &c2));
+
+        let c3 = get(&pool, 2, "b:1").await;
+        assert!(!Arc::ptr_eq(&c1, &c3));
+    }
+
+    #[tokio::test]
+    async fn test_round_robin() {
+        let pool = ConnectionPool::new(2);
+
+        let c1 = get(&pool, 1, "a:1").await;
+        let c2 = get(&pool, 1, "a:1").await;
+        assert!(!Arc::ptr_eq(&c1, &c2));
+
+        let c3 = get(&pool, 1, "a:1").await;
+        let c4 = get(&pool, 1, "a:1").await;
+        assert!(Arc::ptr_eq(&c1, &c3));
+        assert!(Arc::ptr_eq(&c2, &c4));
+    }
+
+    #[tokio::test]
+    async fn test_replace_poisoned() {
+        let pool = ConnectionPool::new(1);
+
+        let c1 = get(&pool, 1, "a:1").await;
+        c1.poisoned.store(true, Ordering::SeqCst);
+
+        let c2 = get(&pool, 1, "a:1").await;
+        assert!(!Arc::ptr_eq(&c1, &c2));
+        assert!(!c2.is_poisoned());
+
+        let c3 = get(&pool, 1, "a:1").await;
+        assert!(Arc::ptr_eq(&c2, &c3));
+    }
+
+    #[tokio::test]
+    async fn test_replace_moved_broker() {
+        let pool = ConnectionPool::new(1);
+
+        let c1 = get(&pool, 1, "a:1").await;
+        let c2 = get(&pool, 1, "a:2").await;
+        assert!(!Arc::ptr_eq(&c1, &c2));
+    }
+
+    #[tokio::test]
+    async fn test_failed_connect() {
+        let pool = ConnectionPool::new(1);
+
+        let c1 = get(&pool, 1, "a:1").await;
+        c1.poisoned.store(true, Ordering::SeqCst);
+
+        let err = pool
+            .get(1, "a:1".to_owned(), || async {
+                Err(Error::RetryFailed(
+                    crate::backoff::BackoffError::DeadlineExceded {
+                        deadline: Default::default(),
+                        source: "connect failed".into(),
+                    },
+                ))
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::RetryFailed(_)));
+
+        let c2 = get(&pool, 1, "a:1").await;
+        assert!(!Arc::ptr_eq(&c1, &c2));
+    }
+}
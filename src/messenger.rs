@@ -2,12 +2,13 @@ use std::{
     collections::HashMap,
     future::Future,
     io::Cursor,
-    ops::DerefMut,
+    ops::{Deref, DerefMut},
     sync::{
         Arc,
-        atomic::{AtomicI32, Ordering},
+        atomic::{AtomicI32, AtomicUsize, Ordering},
     },
     task::Poll,
+    time::Duration,
 };
 
 use futures::future::BoxFuture;
@@ -56,6 +57,15 @@ struct Response {
     data: Cursor<Vec<u8>>,
 }
 
+/// Serialized request, ready to be sent.
+#[derive(Debug)]
+struct EncodedRequest {
+    correlation_id: i32,
+    body_api_version: ApiVersion,
+    use_tagged_fields_in_response: bool,
+    buf: Vec<u8>,
+}
+
 #[derive(Debug)]
 struct ActiveRequest {
     channel: Sender<Result<Response, RequestError>>,
@@ -128,6 +138,12 @@ pub struct Messenger<RW> {
     /// Note that this and `stream_write` are separate struct to allow sending and receiving data concurrently.
     state: Arc<Mutex<MessengerState>>,
 
+    /// Timeout for requests, see [`RequestTimeoutConfig`].
+    timeout_config: RequestTimeoutConfig,
+
+    /// Number of requests that timed out since the last successful one.
+    consecutive_timeouts: AtomicUsize,
+
     /// Join handle for the background worker that fetches responses.
     join_handle: JoinHandle<()>,
 }
@@ -168,6 +184,59 @@ pub enum RequestError {
 
     #[error("Connection is poisoned: {0}")]
     Poisoned(Arc<RequestError>),
+
+    #[error("Request timed out after {timeout:?}: api_key={api_key:?}")]
+    Timeout { api_key: ApiKey, timeout: Duration },
+}
+
+/// How long to wait for responses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RequestTimeoutConfig {
+    /// Default time after which a request fails with [`RequestError::Timeout`].
+    ///
+    /// `None` waits forever.
+    pub timeout: Option<Duration>,
+
+    /// Poison the connection after this many consecutive requests timed out.
+    ///
+    /// A broker that stops answering might still keep the connection open. Poisoning it makes the connection caches
+    /// establish a new one. `None` never poisons the connection because of timeouts.
+    pub max_consecutive_timeouts: Option<usize>,
+}
+
+/// Overrides the [default request timeout](crate::client::ClientBuilder::request_timeout).
+///
+/// Clients like [`PartitionClient`](crate::client::partition::PartitionClient) accept an override for all of their
+/// requests, [`scope`](Self::scope) overrides the timeout for a single call.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimeoutOverride {
+    /// Use the default timeout.
+    #[default]
+    Default,
+
+    /// Wait forever.
+    Never,
+
+    /// Time out after the given duration.
+    After(Duration),
+}
+
+tokio::task_local! {
+    static SCOPED_TIMEOUT: TimeoutOverride;
+}
+
+impl TimeoutOverride {
+    /// Apply this override to all requests sent while `f` runs, e.g. a single call of a client method.
+    ///
+    /// The scoped override takes precedence over the override of the client and the default timeout.
+    /// [`Default`](Self::Default) keeps the override of the client. Requests sent by tasks that `f` spawns, like the
+    /// heartbeats of a [consumer group](crate::client::consumer_group), are not affected.
+    pub async fn scope<F>(self, f: F) -> F::Output
+    where
+        F: Future,
+    {
+        SCOPED_TIMEOUT.scope(self, f).await
+    }
 }
 
 #[derive(Error, Debug)]
@@ -300,15 +369,59 @@ where
             correlation_id: AtomicI32::new(0),
             version_ranges: HashMap::new(),
             state,
+            timeout_config: RequestTimeoutConfig::default(),
+            consecutive_timeouts: AtomicUsize::new(0),
             join_handle,
         }
     }
 
+    /// Set request timeout.
+    pub fn set_timeout_config(&mut self, timeout_config: RequestTimeoutConfig) {
+        self.timeout_config = timeout_config;
+    }
+
+    /// Request timeout to use with [`request_with_timeout`](Self::request_with_timeout).
+    ///
+    /// `timeout_override` is set by clients that allow overriding the [default timeout](RequestTimeoutConfig::timeout).
+    /// A [scoped override](TimeoutOverride::scope) takes precedence.
+    pub fn request_timeout(&self, timeout_override: TimeoutOverride) -> Option<Duration> {
+        let scoped = SCOPED_TIMEOUT
+            .try_with(|scoped| *scoped)
+            .unwrap_or_default();
+        let timeout_override = match scoped {
+            TimeoutOverride::Default => timeout_override,
+            scoped => scoped,
+        };
+        match timeout_override {
+            TimeoutOverride::Default => self.timeout_config.timeout,
+            TimeoutOverride::Never => None,
+            TimeoutOverride::After(timeout) => Some(timeout),
+        }
+    }
+
+    /// Same as [`request_timeout`](Self::request_timeout), extended by the time the broker may hold back the response
+    /// on purpose.
+    ///
+    /// Use this with [`request_with_timeout`](Self::request_with_timeout) for fetch and produce requests.
+    pub fn extended_request_timeout(
+        &self,
+        timeout_override: TimeoutOverride,
+        broker_wait: Duration,
+    ) -> Option<Duration> {
+        self.request_timeout(timeout_override)
+            .map(|t| t + broker_wait)
+    }
+
     #[cfg(feature = "unstable-fuzzing")]
     pub fn override_version_ranges(&mut self, ranges: HashMap<ApiKey, ApiVersionRange>) {
         self.set_version_ranges(ranges);
     }
 
+    /// Whether the connection broke and cannot be used for further requests.
+    pub fn is_poisoned(&self) -> bool {
+        matches!(*self.state.lock(), MessengerState::Poison(_))
+    }
+
     /// Set supported version range.
     fn set_version_ranges(&mut self, ranges: HashMap<ApiKey, ApiVersionRange>) {
         self.version_ranges = ranges;
@@ -319,58 +432,59 @@ where
         R: RequestBody + Send + WriteVersionedType<Vec<u8>>,
         R::ResponseBody: ReadVersionedType<Cursor<Vec<u8>>>,
     {
-        self.request_with_version_ranges(msg, &self.version_ranges)
+        self.request_with_timeout(msg, self.request_timeout(TimeoutOverride::Default))
             .await
     }
 
-    async fn request_with_version_ranges<R>(
+    /// Same as [`request`](Self::request) but overrides the [request timeout](Self::request_timeout).
+    ///
+    /// This is useful for requests that the broker may hold back on purpose, like fetch requests waiting for data.
+    pub async fn request_with_timeout<R>(
         &self,
         msg: R,
-        version_ranges: &HashMap<ApiKey, ApiVersionRange>,
+        timeout: Option<Duration>,
     ) -> Result<R::ResponseBody, RequestError>
     where
         R: RequestBody + Send + WriteVersionedType<Vec<u8>>,
         R::ResponseBody: ReadVersionedType<Cursor<Vec<u8>>>,
     {
-        let body_api_version = version_ranges
-            .get(&R::API_KEY)
-            .and_then(|range_server| match_versions(*range_server, R::API_VERSION_RANGE))
-            .ok_or(RequestError::NoVersionMatch {
-                api_key: R::API_KEY,
-            })?;
+        self.request_with_version_ranges(msg, &self.version_ranges, timeout)
+            .await
+    }
 
-        // determine if our request and response headers shall contain tagged fields. This system is borrowed from
-        // rdkafka ("flexver"), see:
-        // - https://github.com/edenhill/librdkafka/blob/2b76b65212e5efda213961d5f84e565038036270/src/rdkafka_request.c#L973
-        // - https://github.com/edenhill/librdkafka/blob/2b76b65212e5efda213961d5f84e565038036270/src/rdkafka_buf.c#L167-L174
-        let use_tagged_fields_in_request =
-            body_api_version >= R::FIRST_TAGGED_FIELD_IN_REQUEST_VERSION;
-        let use_tagged_fields_in_response =
-            body_api_version >= R::FIRST_TAGGED_FIELD_IN_RESPONSE_VERSION;
+    /// Send a request for which the broker will NOT send a response.
+    ///
+    /// This is only the case for produce requests with `acks = 0`. Since there is no response frame that could be
+    /// matched to the correlation ID, the request is never registered as active.
+    pub async fn request_without_response<R>(&self, msg: R) -> Result<(), RequestError>
+    where
+        R: RequestBody + Send + WriteVersionedType<Vec<u8>>,
+    {
+        let encoded = self.encode_request(msg, &self.version_ranges)?;
 
-        // Correlation ID so that we can de-multiplex the responses.
-        let correlation_id = self.correlation_id.fetch_add(1, Ordering::SeqCst);
+        if let MessengerState::Poison(e) = self.state.lock().deref() {
+            return Err(RequestError::Poisoned(Arc::clone(e)));
+        }
 
-        let header = RequestHeader {
-            request_api_key: R::API_KEY,
-            request_api_version: body_api_version,
-            correlation_id: Int32(correlation_id),
-            // Technically we don't need to send a client_id, but newer redpanda version fail to parse the message
-            // without it. See https://github.com/influxdata/rskafka/issues/169 .
-            client_id: Some(NullableString(Some(String::from(self.client_id.as_ref())))),
-            tagged_fields: Some(TaggedFields::default()),
-        };
-        let header_version = if use_tagged_fields_in_request {
-            ApiVersion(Int16(2))
-        } else {
-            ApiVersion(Int16(1))
-        };
+        self.send_message(encoded.buf).await
+    }
 
-        let mut buf = Vec::new();
-        header
-            .write_versioned(&mut buf, header_version)
-            .expect("Writing header to buffer should always work");
-        msg.write_versioned(&mut buf, body_api_version)?;
+    async fn request_with_version_ranges<R>(
+        &self,
+        msg: R,
+        version_ranges: &HashMap<ApiKey, ApiVersionRange>,
+        timeout: Option<Duration>,
+    ) -> Result<R::ResponseBody, RequestError>
+    where
+        R: RequestBody + Send + WriteVersionedType<Vec<u8>>,
+        R::ResponseBody: ReadVersionedType<Cursor<Vec<u8>>>,
+    {
+        let EncodedRequest {
+            correlation_id,
+            body_api_version,
+            use_tagged_fields_in_response,
+            buf,
+        } = self.encode_request(msg, version_ranges)?;
 
         let (tx, rx) = channel();
 
@@ -394,10 +508,21 @@ where
             }
         }
 
-        self.send_message(buf).await?;
-        cleanup_on_cancel.message_sent();
+        let send_and_receive = async {
+            self.send_message(buf).await?;
+            cleanup_on_cancel.message_sent();
 
-        let mut response = rx.await.expect("Who closed this channel?!")?;
+            rx.await.expect("Who closed this channel?!")
+        };
+        let response = match timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, send_and_receive).await {
+                Ok(response) => response,
+                Err(_) => return Err(self.handle_timeout(R::API_KEY, correlation_id, timeout)),
+            },
+            None => send_and_receive.await,
+        };
+        let mut response = response?;
+        self.consecutive_timeouts.store(0, Ordering::SeqCst);
         let body = R::ResponseBody::read_versioned(&mut response.data, body_api_version)?;
 
         // check if we fully consumed the message, otherwise there might be a bug in our protocol code
@@ -415,6 +540,99 @@ where
         Ok(body)
     }
 
+    /// Forget about a request that timed out and poison the connection if too many requests timed out in a row.
+    fn handle_timeout(
+        &self,
+        api_key: ApiKey,
+        correlation_id: i32,
+        timeout: Duration,
+    ) -> RequestError {
+        let mut state = self.state.lock();
+        if let MessengerState::RequestMap(map) = state.deref_mut() {
+            // a late response is ignored by the background worker
+            map.remove(&correlation_id);
+        }
+
+        let consecutive_timeouts = self.consecutive_timeouts.fetch_add(1, Ordering::SeqCst) + 1;
+        warn!(
+            ?api_key,
+            correlation_id,
+            ?timeout,
+            consecutive_timeouts,
+            "Request timed out",
+        );
+        if self
+            .timeout_config
+            .max_consecutive_timeouts
+            .is_some_and(|max| consecutive_timeouts >= max)
+        {
+            warn!(
+                consecutive_timeouts,
+                "Too many requests timed out, poisoning connection"
+            );
+            state.poison(RequestError::Timeout { api_key, timeout });
+        }
+
+        RequestError::Timeout { api_key, timeout }
+    }
+
+    /// Pick a version for the request, assign a correlation ID and serialize header and body.
+    fn encode_request<R>(
+        &self,
+        msg: R,
+        version_ranges: &HashMap<ApiKey, ApiVersionRange>,
+    ) -> Result<EncodedRequest, RequestError>
+    where
+        R: RequestBody + WriteVersionedType<Vec<u8>>,
+    {
+        let body_api_version = version_ranges
+            .get(&R::API_KEY)
+            .and_then(|range_server| match_versions(*range_server, R::API_VERSION_RANGE))
+            .ok_or(RequestError::NoVersionMatch {
+                api_key: R::API_KEY,
+            })?;
+
+        // determine if our request and response headers shall contain tagged fields. This system is borrowed from
+        // rdkafka ("flexver"), see:
+        // - https://github.com/edenhill/librdkafka/blob/2b76b65212e5efda213961d5f84e565038036270/src/rdkafka_request.c#L973
+        // - https://github.com/edenhill/librdkafka/blob/2b76b65212e5efda213961d5f84e565038036270/src/rdkafka_buf.c#L167-L174
+        let use_tagged_fields_in_request =
+            body_api_version >= R::FIRST_TAGGED_FIELD_IN_REQUEST_VERSION;
+        let use_tagged_fields_in_response =
+            body_api_version >= R::FIRST_TAGGED_FIELD_IN_RESPONSE_VERSION;
+
+        // Correlation ID so that we can de-multiplex the responses.
+        let correlation_id = self.correlation_id.fetch_add(1, Ordering::SeqCst);
+
+        let header = RequestHeader {
+            request_api_key: R::API_KEY,
+            request_api_version: body_api_version,
+            correlation_id: Int32(correlation_id),
+            // Technically we don't need to send a client_id, but newer redpanda version fail to parse the message
+            // without it. See https://github.com/influxdata/rskafka/issues/169 .
+            client_id: Some(NullableString(Some(String::from(self.client_id.as_ref())))),
+            tagged_fields: Some(TaggedFields::default()),
+        };
+        let header_version = if use_tagged_fields_in_request {
+            ApiVersion(Int16(2))
+        } else {
+            ApiVersion(Int16(1))
+        };
+
+        let mut buf = Vec::new();
+        header
+            .write_versioned(&mut buf, header_version)
+            .expect("Writing header to buffer should always work");
+        msg.write_versioned(&mut buf, body_api_version)?;
+
+        Ok(EncodedRequest {
+            correlation_id,
+            body_api_version,
+            use_tagged_fields_in_response,
+            buf,
+        })
+    }
+
     async fn send_message(&self, msg: Vec<u8>) -> Result<(), RequestError> {
         match self.send_message_inner(msg).await {
             Ok(()) => Ok(()),
@@ -465,7 +683,11 @@ where
 
             'throttle: loop {
                 match self
-                    .request_with_version_ranges(&body, &version_ranges)
+                    .request_with_version_ranges(
+                        &body,
+                        &version_ranges,
+                        self.timeout_config.timeout,
+                    )
                     .await
                 {
                     Ok(response) => {
@@ -1112,6 +1334,7 @@ mod tests {
             .await
             .unwrap_err();
         assert_matches!(err, RequestError::Poisoned(_));
+        assert!(messenger.is_poisoned());
     }
 
     #[tokio::test]
@@ -1188,6 +1411,152 @@ mod tests {
         assert_eq!(actual, resp);
     }
 
+    #[tokio::test]
+    async fn test_request_without_response() {
+        let (sim, rx) = MessageSimulator::new();
+        let mut messenger = Messenger::new(rx, 1_000, Arc::from(DEFAULT_CLIENT_ID));
+        messenger.set_version_ranges(HashMap::from([(
+            ApiKey::ListOffsets,
+            ListOffsetsRequest::API_VERSION_RANGE,
+        )]));
+
+        sim.consume();
+        messenger
+            .request_without_response(ListOffsetsRequest {
+                replica_id: NORMAL_CONSUMER,
+                isolation_level: None,
+                topics: vec![],
+            })
+            .await
+            .unwrap();
+
+        // request is not waiting for a response
+        assert_matches!(
+            messenger.state.lock().deref(),
+            MessengerState::RequestMap(map) if map.is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout() {
+        let (sim, rx) = MessageSimulator::new();
+        let mut messenger = Messenger::new(rx, 1_000, Arc::from(DEFAULT_CLIENT_ID));
+        messenger.set_version_ranges(HashMap::from([(
+            ApiKey::ListOffsets,
+            ListOffsetsRequest::API_VERSION_RANGE,
+        )]));
+        messenger.set_timeout_config(RequestTimeoutConfig {
+            timeout: Some(Duration::from_millis(10)),
+            max_consecutive_timeouts: Some(3),
+        });
+        let request = ListOffsetsRequest {
+            replica_id: NORMAL_CONSUMER,
+            isolation_level: None,
+            topics: vec![],
+        };
+
+        // broker reads the request but never answers
+        sim.consume();
+        let err = messenger.request(&request).await.unwrap_err();
+        assert_matches!(
+            err,
+            RequestError::Timeout {
+                api_key: ApiKey::ListOffsets,
+                ..
+            }
+        );
+        assert_matches!(
+            messenger.state.lock().deref(),
+            MessengerState::RequestMap(map) if map.is_empty()
+        );
+        assert!(!messenger.is_poisoned());
+
+        // override per request
+        sim.consume();
+        let err = messenger
+            .request_with_timeout(&request, Some(Duration::from_millis(20)))
+            .await
+            .unwrap_err();
+        assert_matches!(
+            err,
+            RequestError::Timeout { timeout, .. } if timeout == Duration::from_millis(20)
+        );
+
+        // override per call
+        sim.consume();
+        let err = TimeoutOverride::After(Duration::from_millis(30))
+            .scope(messenger.request(&request))
+            .await
+            .unwrap_err();
+        assert_matches!(
+            err,
+            RequestError::Timeout { timeout, .. } if timeout == Duration::from_millis(30)
+        );
+
+        // third timeout in a row poisons the connection
+        assert!(messenger.is_poisoned());
+        let err = messenger.request(&request).await.unwrap_err();
+        assert_matches!(err, RequestError::Poisoned(e) if matches!(*e, RequestError::Timeout { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout_override() {
+        let (_sim, rx) = MessageSimulator::new();
+        let mut messenger = Messenger::new(rx, 1_000, Arc::from(DEFAULT_CLIENT_ID));
+        messenger.set_timeout_config(RequestTimeoutConfig {
+            timeout: Some(Duration::from_secs(1)),
+            max_consecutive_timeouts: None,
+        });
+
+        let two_secs = TimeoutOverride::After(Duration::from_secs(2));
+        assert_eq!(
+            messenger.request_timeout(TimeoutOverride::Default),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(
+            messenger.request_timeout(two_secs),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(messenger.request_timeout(TimeoutOverride::Never), None);
+
+        let wait = Duration::from_secs(5);
+        assert_eq!(
+            messenger.extended_request_timeout(TimeoutOverride::Default, wait),
+            Some(Duration::from_secs(6))
+        );
+        assert_eq!(
+            messenger.extended_request_timeout(two_secs, wait),
+            Some(Duration::from_secs(7))
+        );
+        assert_eq!(
+            messenger.extended_request_timeout(TimeoutOverride::Never, wait),
+            None
+        );
+
+        // scoped overrides take precedence over the client override
+        TimeoutOverride::Never
+            .scope(async {
+                assert_eq!(messenger.request_timeout(two_secs), None);
+            })
+            .await;
+        TimeoutOverride::After(Duration::from_secs(3))
+            .scope(async {
+                assert_eq!(
+                    messenger.request_timeout(TimeoutOverride::Never),
+                    Some(Duration::from_secs(3))
+                );
+            })
+            .await;
+        TimeoutOverride::Default
+            .scope(async {
+                assert_eq!(
+                    messenger.request_timeout(two_secs),
+                    Some(Duration::from_secs(2))
+                );
+            })
+            .await;
+    }
+
     #[tokio::test]
     async fn test_cancel_request() {
         // Use a "virtual" network between a simulated broker and a client. The network is intercepted in the middle to
@@ -0,0 +1,164 @@
+use std::io::{Read, Write};
+
+use crate::protocol::{
+    api_key::ApiKey,
+    api_version::{ApiVersion, ApiVersionRange},
+    error::Error,
+    messages::{read_versioned_array, write_versioned_array},
+    primitives::{Array, Int16, Int32, Int64, String_},
+    traits::{ReadType, WriteType},
+};
+
+use super::{
+    ReadVersionedError, ReadVersionedType, RequestBody, WriteVersionedError, WriteVersionedType,
+};
+
+#[derive(Debug)]
+pub struct AddPartitionsToTxnRequestTopic {
+    /// The name of the topic.
+    pub name: String_,
+
+    /// The partition indexes to add to the transaction.
+    pub partitions: Array<Int32>,
+}
+
+impl<W> WriteVersionedType<W> for AddPartitionsToTxnRequestTopic
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 2);
+
+        self.name.write(writer)?;
+        self.partitions.write(writer)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct AddPartitionsToTxnRequest {
+    /// The transactional id corresponding to the transaction.
+    pub transactional_id: String_,
+
+    /// Current producer id in use by the transactional id.
+    pub producer_id: Int64,
+
+    /// Current epoch associated with the producer id.
+    pub producer_epoch: Int16,
+
+    /// The partitions to add to the transaction.
+    pub topics: Vec<AddPartitionsToTxnRequestTopic>,
+}
+
+impl<W> WriteVersionedType<W> for AddPartitionsToTxnRequest
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 2);
+
+        self.transactional_id.write(writer)?;
+        self.producer_id.write(writer)?;
+        self.producer_epoch.write(writer)?;
+        write_versioned_array(writer, version, Some(self.topics.as_slice()))?;
+
+        Ok(())
+    }
+}
+
+impl RequestBody for AddPartitionsToTxnRequest {
+    type ResponseBody = AddPartitionsToTxnResponse;
+
+    const API_KEY: ApiKey = ApiKey::AddPartitionsToTxn;
+
+    /// Version 3 introduces tagged fields, so stay below that for now.
+    const API_VERSION_RANGE: ApiVersionRange =
+        ApiVersionRange::new(ApiVersion(Int16(0)), ApiVersion(Int16(2)));
+
+    const FIRST_TAGGED_FIELD_IN_REQUEST_VERSION: ApiVersion = ApiVersion(Int16(3));
+}
+
+#[derive(Debug)]
+#[allow(missing_copy_implementations)]
+pub struct AddPartitionsToTxnPartitionResult {
+    /// The partition index.
+    pub partition_index: Int32,
+
+    /// The response error code.
+    pub error: Option<Error>,
+}
+
+impl<R> ReadVersionedType<R> for AddPartitionsToTxnPartitionResult
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 2);
+
+        Ok(Self {
+            partition_index: Int32::read(reader)?,
+            error: Error::new(Int16::read(reader)?.0),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct AddPartitionsToTxnTopicResult {
+    /// The topic name.
+    pub name: String_,
+
+    /// The results for each partition.
+    pub results: Vec<AddPartitionsToTxnPartitionResult>,
+}
+
+impl<R> ReadVersionedType<R> for AddPartitionsToTxnTopicResult
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 2);
+
+        Ok(Self {
+            name: String_::read(reader)?,
+            results: read_versioned_array(reader, version)?.unwrap_or_default(),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct AddPartitionsToTxnResponse {
+    /// The duration in milliseconds for which the request was throttled due to a quota violation, or zero if the
+    /// request did not violate any quota.
+    pub throttle_time_ms: Int32,
+
+    /// The results for each topic.
+    pub results: Vec<AddPartitionsToTxnTopicResult>,
+}
+
+impl<R> ReadVersionedType<R> for AddPartitionsToTxnResponse
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 2);
+
+        Ok(Self {
+            throttle_time_ms: Int32::read(reader)?,
+            results: read_versioned_array(reader, version)?.unwrap_or_default(),
+        })
+    }
+}
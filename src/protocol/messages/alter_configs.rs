@@ -0,0 +1,277 @@
+use std::io::{Read, Write};
+
+use super::{
+    ReadVersionedError, ReadVersionedType, RequestBody, WriteVersionedError, WriteVersionedType,
+};
+use crate::protocol::api_version::ApiVersionRange;
+use crate::protocol::error::Error;
+use crate::protocol::messages::{
+    read_compact_versioned_array, read_versioned_array, write_compact_versioned_array,
+    write_versioned_array,
+};
+use crate::protocol::{
+    api_key::ApiKey,
+    api_version::ApiVersion,
+    primitives::*,
+    traits::{ReadType, WriteType},
+};
+
+#[derive(Debug)]
+pub struct AlterConfigsRequest {
+    /// The updates for each resource.
+    pub resources: Vec<AlterConfigsResource>,
+
+    /// True if we should validate the request, but not change the configurations.
+    pub validate_only: Boolean,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 2
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl RequestBody for AlterConfigsRequest {
+    type ResponseBody = AlterConfigsResponse;
+
+    const API_KEY: ApiKey = ApiKey::AlterConfigs;
+
+    /// All versions.
+    const API_VERSION_RANGE: ApiVersionRange =
+        ApiVersionRange::new(ApiVersion(Int16(0)), ApiVersion(Int16(2)));
+
+    const FIRST_TAGGED_FIELD_IN_REQUEST_VERSION: ApiVersion = ApiVersion(Int16(2));
+}
+
+impl<W> WriteVersionedType<W> for AlterConfigsRequest
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 2);
+
+        if v >= 2 {
+            write_compact_versioned_array(writer, version, Some(self.resources.as_slice()))?;
+        } else {
+            write_versioned_array(writer, version, Some(self.resources.as_slice()))?;
+        }
+        self.validate_only.write(writer)?;
+
+        if v >= 2 {
+            match self.tagged_fields.as_ref() {
+                Some(tagged_fields) => {
+                    tagged_fields.write(writer)?;
+                }
+                None => {
+                    TaggedFields::default().write(writer)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct AlterConfigsResource {
+    /// The resource type.
+    pub resource_type: Int8,
+
+    /// The resource name.
+    pub resource_name: String_,
+
+    /// The configurations.
+    ///
+    /// Configurations that are not listed here are reset to their defaults.
+    pub configs: Vec<AlterableConfig>,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 2
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<W> WriteVersionedType<W> for AlterConfigsResource
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 2);
+
+        self.resource_type.write(writer)?;
+
+        if v >= 2 {
+            CompactStringRef(&self.resource_name.0).write(writer)?;
+            write_compact_versioned_array(writer, version, Some(self.configs.as_slice()))?;
+        } else {
+            self.resource_name.write(writer)?;
+            write_versioned_array(writer, version, Some(self.configs.as_slice()))?;
+        }
+
+        if v >= 2 {
+            match self.tagged_fields.as_ref() {
+                Some(tagged_fields) => {
+                    tagged_fields.write(writer)?;
+                }
+                None => {
+                    TaggedFields::default().write(writer)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct AlterableConfig {
+    /// The configuration key name.
+    pub name: String_,
+
+    /// The value to set for the configuration key.
+    pub value: NullableString,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 2
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<W> WriteVersionedType<W> for AlterableConfig
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 2);
+
+        if v >= 2 {
+            CompactStringRef(&self.name.0).write(writer)?;
+            CompactNullableStringRef(self.value.0.as_deref()).write(writer)?;
+        } else {
+            self.name.write(writer)?;
+            self.value.write(writer)?;
+        }
+
+        if v >= 2 {
+            match self.tagged_fields.as_ref() {
+                Some(tagged_fields) => {
+                    tagged_fields.write(writer)?;
+                }
+                None => {
+                    TaggedFields::default().write(writer)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct AlterConfigsResponse {
+    /// The duration in milliseconds for which the request was throttled due to a quota violation, or zero if the
+    /// request did not violate any quota.
+    pub throttle_time_ms: Int32,
+
+    /// The responses for each resource.
+    pub responses: Vec<AlterConfigsResourceResponse>,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 2
+    #[allow(dead_code)]
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<R> ReadVersionedType<R> for AlterConfigsResponse
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 2);
+
+        let throttle_time_ms = Int32::read(reader)?;
+        let responses = if v >= 2 {
+            read_compact_versioned_array(reader, version)?.unwrap_or_default()
+        } else {
+            read_versioned_array(reader, version)?.unwrap_or_default()
+        };
+        let tagged_fields = (v >= 2).then(|| TaggedFields::read(reader)).transpose()?;
+
+        Ok(Self {
+            throttle_time_ms,
+            responses,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct AlterConfigsResourceResponse {
+    /// The resource error, or zero if there was no error.
+    pub error: Option<Error>,
+
+    /// The resource error message, or null if there was no error.
+    pub error_message: NullableString,
+
+    /// The resource type.
+    #[allow(dead_code)]
+    pub resource_type: Int8,
+
+    /// The resource name.
+    #[allow(dead_code)]
+    pub resource_name: String_,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 2
+    #[allow(dead_code)]
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<R> ReadVersionedType<R> for AlterConfigsResourceResponse
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 2);
+
+        let error = Error::new(Int16::read(reader)?.0);
+        let error_message = if v >= 2 {
+            NullableString(CompactNullableString::read(reader)?.0)
+        } else {
+            NullableString::read(reader)?
+        };
+        let resource_type = Int8::read(reader)?;
+        let resource_name = if v >= 2 {
+            String_(CompactString::read(reader)?.0)
+        } else {
+            String_::read(reader)?
+        };
+        let tagged_fields = (v >= 2).then(|| TaggedFields::read(reader)).transpose()?;
+
+        Ok(Self {
+            error,
+            error_message,
+            resource_type,
+            resource_name,
+            tagged_fields,
+        })
+    }
+}
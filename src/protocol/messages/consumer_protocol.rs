@@ -0,0 +1,198 @@
+//! Embedded consumer protocol that is transported as opaque bytes within the group membership messages.
+//!
+//! The metadata of a [`JoinGroupRequestProtocol`](super::JoinGroupRequestProtocol) holds a
+//! [`ConsumerProtocolSubscription`] and the assignments within [`SyncGroupRequest`](super::SyncGroupRequest) /
+//! [`SyncGroupResponse`](super::SyncGroupResponse) hold [`ConsumerProtocolAssignment`]s.
+//!
+//! Newer versions of these structures only append fields, so we always write version 0 and ignore trailing data when
+//! reading data written by other clients.
+//!
+//! # References
+//! - <https://cwiki.apache.org/confluence/display/KAFKA/A+Guide+To+The+Kafka+Protocol#AGuideToTheKafkaProtocol-GroupMembershipAPI>
+//! - <https://github.com/apache/kafka/blob/trunk/clients/src/main/resources/common/message/ConsumerProtocolSubscription.json>
+//! - <https://github.com/apache/kafka/blob/trunk/clients/src/main/resources/common/message/ConsumerProtocolAssignment.json>
+
+use std::io::{Read, Write};
+
+use crate::protocol::{
+    primitives::{Array, Int16, Int32, NullableBytes, String_},
+    traits::{ReadError, ReadType, WriteError, WriteType},
+};
+
+/// Protocol type used by consumer groups.
+pub const CONSUMER_PROTOCOL_TYPE: &str = "consumer";
+
+/// Version that we write.
+const CONSUMER_PROTOCOL_VERSION: Int16 = Int16(0);
+
+/// Read the version header and check that we can handle it.
+fn read_version<R: Read>(reader: &mut R) -> Result<(), ReadError> {
+    let version = Int16::read(reader)?;
+    if version.0 < 0 {
+        return Err(ReadError::Malformed(
+            format!("Invalid consumer protocol version: {}", version.0).into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Skip fields that were added in newer versions.
+fn skip_trailing<R: Read>(reader: &mut R) -> Result<(), ReadError> {
+    std::io::copy(reader, &mut std::io::sink())?;
+    Ok(())
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+pub struct ConsumerProtocolSubscription {
+    /// The topics that the member wants to consume.
+    pub topics: Array<String_>,
+
+    /// Opaque user data.
+    pub user_data: NullableBytes,
+}
+
+impl<R> ReadType<R> for ConsumerProtocolSubscription
+where
+    R: Read,
+{
+    fn read(reader: &mut R) -> Result<Self, ReadError> {
+        read_version(reader)?;
+        let topics = Array::read(reader)?;
+        let user_data = NullableBytes::read(reader)?;
+        skip_trailing(reader)?;
+
+        Ok(Self { topics, user_data })
+    }
+}
+
+impl<W> WriteType<W> for ConsumerProtocolSubscription
+where
+    W: Write,
+{
+    fn write(&self, writer: &mut W) -> Result<(), WriteError> {
+        CONSUMER_PROTOCOL_VERSION.write(writer)?;
+        self.topics.write(writer)?;
+        self.user_data.write(writer)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+pub struct ConsumerProtocolAssignmentTopic {
+    /// The topic name.
+    pub topic: String_,
+
+    /// The partitions assigned to the member.
+    pub partitions: Array<Int32>,
+}
+
+impl<R> ReadType<R> for ConsumerProtocolAssignmentTopic
+where
+    R: Read,
+{
+    fn read(reader: &mut R) -> Result<Self, ReadError> {
+        Ok(Self {
+            topic: String_::read(reader)?,
+            partitions: Array::read(reader)?,
+        })
+    }
+}
+
+impl<W> WriteType<W> for ConsumerProtocolAssignmentTopic
+where
+    W: Write,
+{
+    fn write(&self, writer: &mut W) -> Result<(), WriteError> {
+        self.topic.write(writer)?;
+        self.partitions.write(writer)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+pub struct ConsumerProtocolAssignment {
+    /// The partitions assigned to the member.
+    pub assigned_partitions: Array<ConsumerProtocolAssignmentTopic>,
+
+    /// Opaque user data.
+    pub user_data: NullableBytes,
+}
+
+impl<R> ReadType<R> for ConsumerProtocolAssignment
+where
+    R: Read,
+{
+    fn read(reader: &mut R) -> Result<Self, ReadError> {
+        read_version(reader)?;
+        let assigned_partitions = Array::read(reader)?;
+        let user_data = NullableBytes::read(reader)?;
+        skip_trailing(reader)?;
+
+        Ok(Self {
+            assigned_partitions,
+            user_data,
+        })
+    }
+}
+
+impl<W> WriteType<W> for ConsumerProtocolAssignment
+where
+    W: Write,
+{
+    fn write(&self, writer: &mut W) -> Result<(), WriteError> {
+        CONSUMER_PROTOCOL_VERSION.write(writer)?;
+        self.assigned_partitions.write(writer)?;
+        self.user_data.write(writer)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::protocol::test_utils::test_roundtrip;
+
+    use super::*;
+
+    test_roundtrip!(
+        ConsumerProtocolSubscription,
+        test_consumer_protocol_subscription_roundtrip
+    );
+
+    test_roundtrip!(
+        ConsumerProtocolAssignment,
+        test_consumer_protocol_assignment_roundtrip
+    );
+
+    #[test]
+    fn test_subscription_ignores_newer_fields() {
+        let mut buf = Cursor::new(Vec::<u8>::new());
+        // version 1
+        Int16(1).write(&mut buf).unwrap();
+        Array(Some(vec![String_("foo".to_owned())]))
+            .write(&mut buf)
+            .unwrap();
+        NullableBytes(None).write(&mut buf).unwrap();
+        // owned partitions
+        Array(Some(vec![ConsumerProtocolAssignmentTopic {
+            topic: String_("foo".to_owned()),
+            partitions: Array(Some(vec![Int32(1)])),
+        }]))
+        .write(&mut buf)
+        .unwrap();
+
+        buf.set_position(0);
+        let subscription = ConsumerProtocolSubscription::read(&mut buf).unwrap();
+        assert_eq!(
+            subscription,
+            ConsumerProtocolSubscription {
+                topics: Array(Some(vec![String_("foo".to_owned())])),
+                user_data: NullableBytes(None),
+            }
+        );
+    }
+}
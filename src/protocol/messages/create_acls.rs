@@ -0,0 +1,242 @@
+use std::io::{Read, Write};
+
+use super::{
+    ReadVersionedError, ReadVersionedType, RequestBody, WriteVersionedError, WriteVersionedType,
+};
+use crate::protocol::api_version::ApiVersionRange;
+use crate::protocol::error::Error;
+use crate::protocol::messages::{
+    read_compact_versioned_array, read_versioned_array, write_compact_versioned_array,
+    write_versioned_array,
+};
+use crate::protocol::{
+    api_key::ApiKey,
+    api_version::ApiVersion,
+    primitives::*,
+    traits::{ReadType, WriteType},
+};
+
+#[derive(Debug)]
+pub struct CreateAclsRequest {
+    /// The ACLs that we want to create.
+    pub creations: Vec<AclCreation>,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 2
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl RequestBody for CreateAclsRequest {
+    type ResponseBody = CreateAclsResponse;
+
+    const API_KEY: ApiKey = ApiKey::CreateAcls;
+
+    /// All versions.
+    const API_VERSION_RANGE: ApiVersionRange =
+        ApiVersionRange::new(ApiVersion(Int16(0)), ApiVersion(Int16(3)));
+
+    const FIRST_TAGGED_FIELD_IN_REQUEST_VERSION: ApiVersion = ApiVersion(Int16(2));
+}
+
+impl<W> WriteVersionedType<W> for CreateAclsRequest
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 3);
+
+        if v >= 2 {
+            write_compact_versioned_array(writer, version, Some(self.creations.as_slice()))?;
+        } else {
+            write_versioned_array(writer, version, Some(self.creations.as_slice()))?;
+        }
+
+        if v >= 2 {
+            match self.tagged_fields.as_ref() {
+                Some(tagged_fields) => {
+                    tagged_fields.write(writer)?;
+                }
+                None => {
+                    TaggedFields::default().write(writer)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct AclCreation {
+    /// The type of the resource.
+    pub resource_type: Int8,
+
+    /// The resource name for the ACL.
+    pub resource_name: String_,
+
+    /// The pattern type for the ACL.
+    ///
+    /// Version 0 only supports literal patterns. Defaults to `3` (literal).
+    ///
+    /// Added in version 1
+    pub resource_pattern_type: Option<Int8>,
+
+    /// The principal for the ACL.
+    pub principal: String_,
+
+    /// The host for the ACL.
+    pub host: String_,
+
+    /// The operation type for the ACL (read, write, etc.).
+    pub operation: Int8,
+
+    /// The permission type for the ACL (allow, deny, etc.).
+    pub permission_type: Int8,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 2
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<W> WriteVersionedType<W> for AclCreation
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 3);
+
+        self.resource_type.write(writer)?;
+
+        if v >= 2 {
+            CompactStringRef(&self.resource_name.0).write(writer)?;
+        } else {
+            self.resource_name.write(writer)?;
+        }
+
+        if v >= 1 {
+            self.resource_pattern_type
+                .unwrap_or(Int8(3))
+                .write(writer)?;
+        } else if !matches!(self.resource_pattern_type, None | Some(Int8(3))) {
+            return Err(WriteVersionedError::FieldNotAvailable {
+                version,
+                field: "resource_pattern_type".to_string(),
+            });
+        }
+
+        if v >= 2 {
+            CompactStringRef(&self.principal.0).write(writer)?;
+            CompactStringRef(&self.host.0).write(writer)?;
+        } else {
+            self.principal.write(writer)?;
+            self.host.write(writer)?;
+        }
+
+        self.operation.write(writer)?;
+        self.permission_type.write(writer)?;
+
+        if v >= 2 {
+            match self.tagged_fields.as_ref() {
+                Some(tagged_fields) => {
+                    tagged_fields.write(writer)?;
+                }
+                None => {
+                    TaggedFields::default().write(writer)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct CreateAclsResponse {
+    /// The duration in milliseconds for which the request was throttled due to a quota violation, or zero if the
+    /// request did not violate any quota.
+    pub throttle_time_ms: Int32,
+
+    /// The results for each ACL creation, in the order of the request.
+    pub results: Vec<AclCreationResult>,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 2
+    #[allow(dead_code)]
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<R> ReadVersionedType<R> for CreateAclsResponse
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 3);
+
+        let throttle_time_ms = Int32::read(reader)?;
+        let results = if v >= 2 {
+            read_compact_versioned_array(reader, version)?.unwrap_or_default()
+        } else {
+            read_versioned_array(reader, version)?.unwrap_or_default()
+        };
+        let tagged_fields = (v >= 2).then(|| TaggedFields::read(reader)).transpose()?;
+
+        Ok(Self {
+            throttle_time_ms,
+            results,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct AclCreationResult {
+    /// The result error, or zero if there was no error.
+    pub error: Option<Error>,
+
+    /// The result message, or null if there was no error.
+    pub error_message: NullableString,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 2
+    #[allow(dead_code)]
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<R> ReadVersionedType<R> for AclCreationResult
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 3);
+
+        let error = Error::new(Int16::read(reader)?.0);
+        let error_message = if v >= 2 {
+            NullableString(CompactNullableString::read(reader)?.0)
+        } else {
+            NullableString::read(reader)?
+        };
+        let tagged_fields = (v >= 2).then(|| TaggedFields::read(reader)).transpose()?;
+
+        Ok(Self {
+            error,
+            error_message,
+            tagged_fields,
+        })
+    }
+}
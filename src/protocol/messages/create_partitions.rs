@@ -0,0 +1,271 @@
+use std::io::{Read, Write};
+
+use super::{
+    ReadVersionedError, ReadVersionedType, RequestBody, WriteVersionedError, WriteVersionedType,
+};
+use crate::protocol::api_version::ApiVersionRange;
+use crate::protocol::error::Error;
+use crate::protocol::messages::{
+    read_compact_versioned_array, read_versioned_array, write_compact_versioned_array,
+    write_versioned_array,
+};
+use crate::protocol::{
+    api_key::ApiKey,
+    api_version::ApiVersion,
+    primitives::*,
+    traits::{ReadType, WriteType},
+};
+
+#[derive(Debug)]
+pub struct CreatePartitionsRequest {
+    /// Each topic that we want to create new partitions inside.
+    pub topics: Vec<CreatePartitionsTopic>,
+
+    /// The time in ms to wait for the partitions to be created.
+    pub timeout_ms: Int32,
+
+    /// If true, then validate the request, but don't actually increase the number of partitions.
+    pub validate_only: Boolean,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 2
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl RequestBody for CreatePartitionsRequest {
+    type ResponseBody = CreatePartitionsResponse;
+
+    const API_KEY: ApiKey = ApiKey::CreatePartitions;
+
+    /// All versions.
+    const API_VERSION_RANGE: ApiVersionRange =
+        ApiVersionRange::new(ApiVersion(Int16(0)), ApiVersion(Int16(3)));
+
+    const FIRST_TAGGED_FIELD_IN_REQUEST_VERSION: ApiVersion = ApiVersion(Int16(2));
+}
+
+impl<W> WriteVersionedType<W> for CreatePartitionsRequest
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 3);
+
+        if v >= 2 {
+            write_compact_versioned_array(writer, version, Some(self.topics.as_slice()))?;
+        } else {
+            write_versioned_array(writer, version, Some(self.topics.as_slice()))?;
+        }
+        self.timeout_ms.write(writer)?;
+        self.validate_only.write(writer)?;
+
+        if v >= 2 {
+            match self.tagged_fields.as_ref() {
+                Some(tagged_fields) => {
+                    tagged_fields.write(writer)?;
+                }
+                None => {
+                    TaggedFields::default().write(writer)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct CreatePartitionsTopic {
+    /// The topic name.
+    pub name: String_,
+
+    /// The new partition count.
+    pub count: Int32,
+
+    /// The new partition assignments, or null to use the automatic assignment.
+    pub assignments: Option<Vec<CreatePartitionsAssignment>>,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 2
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<W> WriteVersionedType<W> for CreatePartitionsTopic
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 3);
+
+        if v >= 2 {
+            CompactStringRef(&self.name.0).write(writer)?
+        } else {
+            self.name.write(writer)?;
+        }
+
+        self.count.write(writer)?;
+
+        if v >= 2 {
+            write_compact_versioned_array(writer, version, self.assignments.as_deref())?;
+        } else {
+            write_versioned_array(writer, version, self.assignments.as_deref())?;
+        }
+
+        if v >= 2 {
+            match self.tagged_fields.as_ref() {
+                Some(tagged_fields) => {
+                    tagged_fields.write(writer)?;
+                }
+                None => {
+                    TaggedFields::default().write(writer)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct CreatePartitionsAssignment {
+    /// The assigned broker IDs.
+    pub broker_ids: Array<Int32>,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 2
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<W> WriteVersionedType<W> for CreatePartitionsAssignment
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 3);
+
+        if v >= 2 {
+            CompactArrayRef(self.broker_ids.0.as_deref()).write(writer)?;
+        } else {
+            self.broker_ids.write(writer)?;
+        }
+
+        if v >= 2 {
+            match self.tagged_fields.as_ref() {
+                Some(tagged_fields) => {
+                    tagged_fields.write(writer)?;
+                }
+                None => {
+                    TaggedFields::default().write(writer)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct CreatePartitionsResponse {
+    /// The duration in milliseconds for which the request was throttled due to a quota violation, or zero if the
+    /// request did not violate any quota.
+    pub throttle_time_ms: Int32,
+
+    /// The partition creation results for each topic.
+    pub results: Vec<CreatePartitionsTopicResult>,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 2
+    #[allow(dead_code)]
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<R> ReadVersionedType<R> for CreatePartitionsResponse
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 3);
+
+        let throttle_time_ms = Int32::read(reader)?;
+        let results = if v >= 2 {
+            read_compact_versioned_array(reader, version)?.unwrap_or_default()
+        } else {
+            read_versioned_array(reader, version)?.unwrap_or_default()
+        };
+        let tagged_fields = (v >= 2).then(|| TaggedFields::read(reader)).transpose()?;
+
+        Ok(Self {
+            throttle_time_ms,
+            results,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct CreatePartitionsTopicResult {
+    /// The topic name.
+    pub name: String_,
+
+    /// The result error, or zero if there was no error.
+    pub error: Option<Error>,
+
+    /// The result message, or null if there was no error.
+    pub error_message: NullableString,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 2
+    #[allow(dead_code)]
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<R> ReadVersionedType<R> for CreatePartitionsTopicResult
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 3);
+
+        let name = if v >= 2 {
+            String_(CompactString::read(reader)?.0)
+        } else {
+            String_::read(reader)?
+        };
+        let error = Error::new(Int16::read(reader)?.0);
+        let error_message = if v >= 2 {
+            NullableString(CompactNullableString::read(reader)?.0)
+        } else {
+            NullableString::read(reader)?
+        };
+        let tagged_fields = (v >= 2).then(|| TaggedFields::read(reader)).transpose()?;
+
+        Ok(Self {
+            name,
+            error,
+            error_message,
+            tagged_fields,
+        })
+    }
+}
@@ -0,0 +1,335 @@
+use std::io::{Read, Write};
+
+use super::{
+    ReadVersionedError, ReadVersionedType, RequestBody, WriteVersionedError, WriteVersionedType,
+};
+use crate::protocol::api_version::ApiVersionRange;
+use crate::protocol::error::Error;
+use crate::protocol::messages::{
+    read_compact_versioned_array, read_versioned_array, write_compact_versioned_array,
+    write_versioned_array,
+};
+use crate::protocol::{
+    api_key::ApiKey,
+    api_version::ApiVersion,
+    primitives::*,
+    traits::{ReadType, WriteType},
+};
+
+#[derive(Debug)]
+pub struct DeleteAclsRequest {
+    /// The filters to use when deleting ACLs.
+    pub filters: Vec<DeleteAclsFilter>,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 2
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl RequestBody for DeleteAclsRequest {
+    type ResponseBody = DeleteAclsResponse;
+
+    const API_KEY: ApiKey = ApiKey::DeleteAcls;
+
+    /// All versions.
+    const API_VERSION_RANGE: ApiVersionRange =
+        ApiVersionRange::new(ApiVersion(Int16(0)), ApiVersion(Int16(3)));
+
+    const FIRST_TAGGED_FIELD_IN_REQUEST_VERSION: ApiVersion = ApiVersion(Int16(2));
+}
+
+impl<W> WriteVersionedType<W> for DeleteAclsRequest
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 3);
+
+        if v >= 2 {
+            write_compact_versioned_array(writer, version, Some(self.filters.as_slice()))?;
+        } else {
+            write_versioned_array(writer, version, Some(self.filters.as_slice()))?;
+        }
+
+        if v >= 2 {
+            match self.tagged_fields.as_ref() {
+                Some(tagged_fields) => {
+                    tagged_fields.write(writer)?;
+                }
+                None => {
+                    TaggedFields::default().write(writer)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct DeleteAclsFilter {
+    /// The resource type.
+    pub resource_type_filter: Int8,
+
+    /// The resource name, or null to match any resource name.
+    pub resource_name_filter: NullableString,
+
+    /// The pattern type.
+    ///
+    /// Version 0 only supports literal patterns. Defaults to `3` (literal).
+    ///
+    /// Added in version 1
+    pub pattern_type_filter: Option<Int8>,
+
+    /// The principal filter, or null to accept all principals.
+    pub principal_filter: NullableString,
+
+    /// The host filter, or null to accept all hosts.
+    pub host_filter: NullableString,
+
+    /// The ACL operation.
+    pub operation: Int8,
+
+    /// The permission type.
+    pub permission_type: Int8,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 2
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<W> WriteVersionedType<W> for DeleteAclsFilter
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 3);
+
+        self.resource_type_filter.write(writer)?;
+
+        if v >= 2 {
+            CompactNullableStringRef(self.resource_name_filter.0.as_deref()).write(writer)?;
+        } else {
+            self.resource_name_filter.write(writer)?;
+        }
+
+        if v >= 1 {
+            self.pattern_type_filter.unwrap_or(Int8(3)).write(writer)?;
+        } else if !matches!(self.pattern_type_filter, None | Some(Int8(3))) {
+            return Err(WriteVersionedError::FieldNotAvailable {
+                version,
+                field: "pattern_type_filter".to_string(),
+            });
+        }
+
+        if v >= 2 {
+            CompactNullableStringRef(self.principal_filter.0.as_deref()).write(writer)?;
+            CompactNullableStringRef(self.host_filter.0.as_deref()).write(writer)?;
+        } else {
+            self.principal_filter.write(writer)?;
+            self.host_filter.write(writer)?;
+        }
+
+        self.operation.write(writer)?;
+        self.permission_type.write(writer)?;
+
+        if v >= 2 {
+            match self.tagged_fields.as_ref() {
+                Some(tagged_fields) => {
+                    tagged_fields.write(writer)?;
+                }
+                None => {
+                    TaggedFields::default().write(writer)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct DeleteAclsResponse {
+    /// The duration in milliseconds for which the request was throttled due to a quota violation, or zero if the
+    /// request did not violate any quota.
+    pub throttle_time_ms: Int32,
+
+    /// The results for each filter, in the order of the request.
+    pub filter_results: Vec<DeleteAclsFilterResult>,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 2
+    #[allow(dead_code)]
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<R> ReadVersionedType<R> for DeleteAclsResponse
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 3);
+
+        let throttle_time_ms = Int32::read(reader)?;
+        let filter_results = if v >= 2 {
+            read_compact_versioned_array(reader, version)?.unwrap_or_default()
+        } else {
+            read_versioned_array(reader, version)?.unwrap_or_default()
+        };
+        let tagged_fields = (v >= 2).then(|| TaggedFields::read(reader)).transpose()?;
+
+        Ok(Self {
+            throttle_time_ms,
+            filter_results,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct DeleteAclsFilterResult {
+    /// The error code, or 0 if the filter succeeded.
+    pub error: Option<Error>,
+
+    /// The error message, or null if the filter succeeded.
+    pub error_message: NullableString,
+
+    /// The ACLs which matched this filter.
+    pub matching_acls: Vec<DeleteAclsMatchingAcl>,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 2
+    #[allow(dead_code)]
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<R> ReadVersionedType<R> for DeleteAclsFilterResult
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 3);
+
+        let error = Error::new(Int16::read(reader)?.0);
+        let error_message = if v >= 2 {
+            NullableString(CompactNullableString::read(reader)?.0)
+        } else {
+            NullableString::read(reader)?
+        };
+        let matching_acls = if v >= 2 {
+            read_compact_versioned_array(reader, version)?.unwrap_or_default()
+        } else {
+            read_versioned_array(reader, version)?.unwrap_or_default()
+        };
+        let tagged_fields = (v >= 2).then(|| TaggedFields::read(reader)).transpose()?;
+
+        Ok(Self {
+            error,
+            error_message,
+            matching_acls,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct DeleteAclsMatchingAcl {
+    /// The deletion error code, or 0 if the deletion succeeded.
+    pub error: Option<Error>,
+
+    /// The deletion error message, or null if the deletion succeeded.
+    pub error_message: NullableString,
+
+    /// The ACL resource type.
+    pub resource_type: Int8,
+
+    /// The ACL resource name.
+    pub resource_name: String_,
+
+    /// The ACL resource pattern type.
+    ///
+    /// Added in version 1
+    pub pattern_type: Option<Int8>,
+
+    /// The ACL principal.
+    pub principal: String_,
+
+    /// The ACL host.
+    pub host: String_,
+
+    /// The ACL operation.
+    pub operation: Int8,
+
+    /// The ACL permission type.
+    pub permission_type: Int8,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 2
+    #[allow(dead_code)]
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<R> ReadVersionedType<R> for DeleteAclsMatchingAcl
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 3);
+
+        let error = Error::new(Int16::read(reader)?.0);
+        let error_message = if v >= 2 {
+            NullableString(CompactNullableString::read(reader)?.0)
+        } else {
+            NullableString::read(reader)?
+        };
+        let resource_type = Int8::read(reader)?;
+        let resource_name = if v >= 2 {
+            String_(CompactString::read(reader)?.0)
+        } else {
+            String_::read(reader)?
+        };
+        let pattern_type = (v >= 1).then(|| Int8::read(reader)).transpose()?;
+        let (principal, host) = if v >= 2 {
+            (
+                String_(CompactString::read(reader)?.0),
+                String_(CompactString::read(reader)?.0),
+            )
+        } else {
+            (String_::read(reader)?, String_::read(reader)?)
+        };
+        let operation = Int8::read(reader)?;
+        let permission_type = Int8::read(reader)?;
+        let tagged_fields = (v >= 2).then(|| TaggedFields::read(reader)).transpose()?;
+
+        Ok(Self {
+            error,
+            error_message,
+            resource_type,
+            resource_name,
+            pattern_type,
+            principal,
+            host,
+            operation,
+            permission_type,
+            tagged_fields,
+        })
+    }
+}
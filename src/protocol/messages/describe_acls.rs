@@ -0,0 +1,274 @@
+use std::io::{Read, Write};
+
+use super::{
+    ReadVersionedError, ReadVersionedType, RequestBody, WriteVersionedError, WriteVersionedType,
+};
+use crate::protocol::api_version::ApiVersionRange;
+use crate::protocol::error::Error;
+use crate::protocol::messages::{read_compact_versioned_array, read_versioned_array};
+use crate::protocol::{
+    api_key::ApiKey,
+    api_version::ApiVersion,
+    primitives::*,
+    traits::{ReadType, WriteType},
+};
+
+#[derive(Debug)]
+pub struct DescribeAclsRequest {
+    /// The resource type.
+    pub resource_type_filter: Int8,
+
+    /// The resource name, or null to match any resource name.
+    pub resource_name_filter: NullableString,
+
+    /// The resource pattern to match.
+    ///
+    /// Version 0 only supports literal patterns. Defaults to `3` (literal).
+    ///
+    /// Added in version 1
+    pub pattern_type_filter: Option<Int8>,
+
+    /// The principal to match, or null to match any principal.
+    pub principal_filter: NullableString,
+
+    /// The host to match, or null to match any host.
+    pub host_filter: NullableString,
+
+    /// The operation to match.
+    pub operation: Int8,
+
+    /// The permission type to match.
+    pub permission_type: Int8,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 2
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl RequestBody for DescribeAclsRequest {
+    type ResponseBody = DescribeAclsResponse;
+
+    const API_KEY: ApiKey = ApiKey::DescribeAcls;
+
+    /// All versions.
+    const API_VERSION_RANGE: ApiVersionRange =
+        ApiVersionRange::new(ApiVersion(Int16(0)), ApiVersion(Int16(3)));
+
+    const FIRST_TAGGED_FIELD_IN_REQUEST_VERSION: ApiVersion = ApiVersion(Int16(2));
+}
+
+impl<W> WriteVersionedType<W> for DescribeAclsRequest
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 3);
+
+        self.resource_type_filter.write(writer)?;
+
+        if v >= 2 {
+            CompactNullableStringRef(self.resource_name_filter.0.as_deref()).write(writer)?;
+        } else {
+            self.resource_name_filter.write(writer)?;
+        }
+
+        if v >= 1 {
+            self.pattern_type_filter.unwrap_or(Int8(3)).write(writer)?;
+        } else if !matches!(self.pattern_type_filter, None | Some(Int8(3))) {
+            return Err(WriteVersionedError::FieldNotAvailable {
+                version,
+                field: "pattern_type_filter".to_string(),
+            });
+        }
+
+        if v >= 2 {
+            CompactNullableStringRef(self.principal_filter.0.as_deref()).write(writer)?;
+            CompactNullableStringRef(self.host_filter.0.as_deref()).write(writer)?;
+        } else {
+            self.principal_filter.write(writer)?;
+            self.host_filter.write(writer)?;
+        }
+
+        self.operation.write(writer)?;
+        self.permission_type.write(writer)?;
+
+        if v >= 2 {
+            match self.tagged_fields.as_ref() {
+                Some(tagged_fields) => {
+                    tagged_fields.write(writer)?;
+                }
+                None => {
+                    TaggedFields::default().write(writer)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct DescribeAclsResponse {
+    /// The duration in milliseconds for which the request was throttled due to a quota violation, or zero if the
+    /// request did not violate any quota.
+    pub throttle_time_ms: Int32,
+
+    /// The error code, or 0 if there was no error.
+    pub error: Option<Error>,
+
+    /// The error message, or null if there was no error.
+    pub error_message: NullableString,
+
+    /// Each resource that is referenced in an ACL.
+    pub resources: Vec<DescribeAclsResource>,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 2
+    #[allow(dead_code)]
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<R> ReadVersionedType<R> for DescribeAclsResponse
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 3);
+
+        let throttle_time_ms = Int32::read(reader)?;
+        let error = Error::new(Int16::read(reader)?.0);
+        let error_message = if v >= 2 {
+            NullableString(CompactNullableString::read(reader)?.0)
+        } else {
+            NullableString::read(reader)?
+        };
+        let resources = if v >= 2 {
+            read_compact_versioned_array(reader, version)?.unwrap_or_default()
+        } else {
+            read_versioned_array(reader, version)?.unwrap_or_default()
+        };
+        let tagged_fields = (v >= 2).then(|| TaggedFields::read(reader)).transpose()?;
+
+        Ok(Self {
+            throttle_time_ms,
+            error,
+            error_message,
+            resources,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct DescribeAclsResource {
+    /// The resource type.
+    pub resource_type: Int8,
+
+    /// The resource name.
+    pub resource_name: String_,
+
+    /// The resource pattern type.
+    ///
+    /// Added in version 1
+    pub pattern_type: Option<Int8>,
+
+    /// The ACLs.
+    pub acls: Vec<AclDescription>,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 2
+    #[allow(dead_code)]
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<R> ReadVersionedType<R> for DescribeAclsResource
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 3);
+
+        let resource_type = Int8::read(reader)?;
+        let resource_name = if v >= 2 {
+            String_(CompactString::read(reader)?.0)
+        } else {
+            String_::read(reader)?
+        };
+        let pattern_type = (v >= 1).then(|| Int8::read(reader)).transpose()?;
+        let acls = if v >= 2 {
+            read_compact_versioned_array(reader, version)?.unwrap_or_default()
+        } else {
+            read_versioned_array(reader, version)?.unwrap_or_default()
+        };
+        let tagged_fields = (v >= 2).then(|| TaggedFields::read(reader)).transpose()?;
+
+        Ok(Self {
+            resource_type,
+            resource_name,
+            pattern_type,
+            acls,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct AclDescription {
+    /// The ACL principal.
+    pub principal: String_,
+
+    /// The ACL host.
+    pub host: String_,
+
+    /// The ACL operation.
+    pub operation: Int8,
+
+    /// The ACL permission type.
+    pub permission_type: Int8,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 2
+    #[allow(dead_code)]
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<R> ReadVersionedType<R> for AclDescription
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 3);
+
+        let (principal, host) = if v >= 2 {
+            (
+                String_(CompactString::read(reader)?.0),
+                String_(CompactString::read(reader)?.0),
+            )
+        } else {
+            (String_::read(reader)?, String_::read(reader)?)
+        };
+        let operation = Int8::read(reader)?;
+        let permission_type = Int8::read(reader)?;
+        let tagged_fields = (v >= 2).then(|| TaggedFields::read(reader)).transpose()?;
+
+        Ok(Self {
+            principal,
+            host,
+            operation,
+            permission_type,
+            tagged_fields,
+        })
+    }
+}
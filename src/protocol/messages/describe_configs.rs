@@ -0,0 +1,403 @@
+use std::io::{Read, Write};
+
+use super::{
+    ReadVersionedError, ReadVersionedType, RequestBody, WriteVersionedError, WriteVersionedType,
+};
+use crate::protocol::api_version::ApiVersionRange;
+use crate::protocol::error::Error;
+use crate::protocol::messages::{
+    read_compact_versioned_array, read_versioned_array, write_compact_versioned_array,
+    write_versioned_array,
+};
+use crate::protocol::{
+    api_key::ApiKey,
+    api_version::ApiVersion,
+    primitives::*,
+    traits::{ReadType, WriteType},
+};
+
+#[derive(Debug)]
+pub struct DescribeConfigsRequest {
+    /// The resources whose configurations we want to describe.
+    pub resources: Vec<DescribeConfigsRequestResource>,
+
+    /// True if we should include all synonyms.
+    ///
+    /// Added in version 1
+    pub include_synonyms: Option<Boolean>,
+
+    /// True if we should include configuration documentation.
+    ///
+    /// Added in version 3
+    pub include_documentation: Option<Boolean>,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 4
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl RequestBody for DescribeConfigsRequest {
+    type ResponseBody = DescribeConfigsResponse;
+
+    const API_KEY: ApiKey = ApiKey::DescribeConfigs;
+
+    /// All versions.
+    const API_VERSION_RANGE: ApiVersionRange =
+        ApiVersionRange::new(ApiVersion(Int16(0)), ApiVersion(Int16(4)));
+
+    const FIRST_TAGGED_FIELD_IN_REQUEST_VERSION: ApiVersion = ApiVersion(Int16(4));
+}
+
+impl<W> WriteVersionedType<W> for DescribeConfigsRequest
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 4);
+
+        if v >= 4 {
+            write_compact_versioned_array(writer, version, Some(self.resources.as_slice()))?;
+        } else {
+            write_versioned_array(writer, version, Some(self.resources.as_slice()))?;
+        }
+
+        if v >= 1 {
+            self.include_synonyms
+                .unwrap_or(Boolean(false))
+                .write(writer)?;
+        }
+        if v >= 3 {
+            self.include_documentation
+                .unwrap_or(Boolean(false))
+                .write(writer)?;
+        }
+
+        if v >= 4 {
+            match self.tagged_fields.as_ref() {
+                Some(tagged_fields) => {
+                    tagged_fields.write(writer)?;
+                }
+                None => {
+                    TaggedFields::default().write(writer)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct DescribeConfigsRequestResource {
+    /// The resource type.
+    pub resource_type: Int8,
+
+    /// The resource name.
+    pub resource_name: String_,
+
+    /// The configuration keys to list, or null to list all configuration keys.
+    pub configuration_keys: Option<Vec<String_>>,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 4
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<W> WriteVersionedType<W> for DescribeConfigsRequestResource
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 4);
+
+        self.resource_type.write(writer)?;
+
+        if v >= 4 {
+            CompactStringRef(&self.resource_name.0).write(writer)?;
+            let keys = self.configuration_keys.as_ref().map(|keys| {
+                keys.iter()
+                    .map(|k| CompactStringRef(&k.0))
+                    .collect::<Vec<_>>()
+            });
+            CompactArrayRef(keys.as_deref()).write(writer)?;
+        } else {
+            self.resource_name.write(writer)?;
+            ArrayRef(self.configuration_keys.as_deref()).write(writer)?;
+        }
+
+        if v >= 4 {
+            match self.tagged_fields.as_ref() {
+                Some(tagged_fields) => {
+                    tagged_fields.write(writer)?;
+                }
+                None => {
+                    TaggedFields::default().write(writer)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct DescribeConfigsResponse {
+    /// The duration in milliseconds for which the request was throttled due to a quota violation, or zero if the
+    /// request did not violate any quota.
+    pub throttle_time_ms: Int32,
+
+    /// The results for each resource.
+    pub results: Vec<DescribeConfigsResponseResource>,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 4
+    #[allow(dead_code)]
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<R> ReadVersionedType<R> for DescribeConfigsResponse
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 4);
+
+        let throttle_time_ms = Int32::read(reader)?;
+        let results = if v >= 4 {
+            read_compact_versioned_array(reader, version)?.unwrap_or_default()
+        } else {
+            read_versioned_array(reader, version)?.unwrap_or_default()
+        };
+        let tagged_fields = (v >= 4).then(|| TaggedFields::read(reader)).transpose()?;
+
+        Ok(Self {
+            throttle_time_ms,
+            results,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct DescribeConfigsResponseResource {
+    /// The error code, or 0 if we were able to successfully describe the configurations.
+    pub error: Option<Error>,
+
+    /// The error message, or null if we were able to successfully describe the configurations.
+    pub error_message: NullableString,
+
+    /// The resource type.
+    pub resource_type: Int8,
+
+    /// The resource name.
+    pub resource_name: String_,
+
+    /// Each listed configuration.
+    pub configs: Vec<DescribeConfigsResponseConfig>,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 4
+    #[allow(dead_code)]
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<R> ReadVersionedType<R> for DescribeConfigsResponseResource
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 4);
+
+        let error = Error::new(Int16::read(reader)?.0);
+        let error_message = if v >= 4 {
+            NullableString(CompactNullableString::read(reader)?.0)
+        } else {
+            NullableString::read(reader)?
+        };
+        let resource_type = Int8::read(reader)?;
+        let resource_name = if v >= 4 {
+            String_(CompactString::read(reader)?.0)
+        } else {
+            String_::read(reader)?
+        };
+        let configs = if v >= 4 {
+            read_compact_versioned_array(reader, version)?.unwrap_or_default()
+        } else {
+            read_versioned_array(reader, version)?.unwrap_or_default()
+        };
+        let tagged_fields = (v >= 4).then(|| TaggedFields::read(reader)).transpose()?;
+
+        Ok(Self {
+            error,
+            error_message,
+            resource_type,
+            resource_name,
+            configs,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct DescribeConfigsResponseConfig {
+    /// The configuration name.
+    pub name: String_,
+
+    /// The configuration value.
+    pub value: NullableString,
+
+    /// True if the configuration is read-only.
+    pub read_only: Boolean,
+
+    /// True if the configuration is not set.
+    ///
+    /// Removed in version 1
+    pub is_default: Option<Boolean>,
+
+    /// The configuration source.
+    ///
+    /// Added in version 1
+    pub config_source: Option<Int8>,
+
+    /// True if this configuration is sensitive.
+    pub is_sensitive: Boolean,
+
+    /// The synonyms for this configuration key.
+    ///
+    /// Added in version 1
+    pub synonyms: Vec<DescribeConfigsResponseSynonym>,
+
+    /// The configuration data type.
+    ///
+    /// Added in version 3
+    #[allow(dead_code)]
+    pub config_type: Option<Int8>,
+
+    /// The configuration documentation.
+    ///
+    /// Added in version 3
+    #[allow(dead_code)]
+    pub documentation: Option<NullableString>,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 4
+    #[allow(dead_code)]
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<R> ReadVersionedType<R> for DescribeConfigsResponseConfig
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 4);
+
+        let name = if v >= 4 {
+            String_(CompactString::read(reader)?.0)
+        } else {
+            String_::read(reader)?
+        };
+        let value = if v >= 4 {
+            NullableString(CompactNullableString::read(reader)?.0)
+        } else {
+            NullableString::read(reader)?
+        };
+        let read_only = Boolean::read(reader)?;
+        let is_default = (v == 0).then(|| Boolean::read(reader)).transpose()?;
+        let config_source = (v >= 1).then(|| Int8::read(reader)).transpose()?;
+        let is_sensitive = Boolean::read(reader)?;
+        let synonyms = if v >= 4 {
+            read_compact_versioned_array(reader, version)?.unwrap_or_default()
+        } else if v >= 1 {
+            read_versioned_array(reader, version)?.unwrap_or_default()
+        } else {
+            vec![]
+        };
+        let config_type = (v >= 3).then(|| Int8::read(reader)).transpose()?;
+        let documentation = match v {
+            0..=2 => None,
+            3 => Some(NullableString::read(reader)?),
+            _ => Some(NullableString(CompactNullableString::read(reader)?.0)),
+        };
+        let tagged_fields = (v >= 4).then(|| TaggedFields::read(reader)).transpose()?;
+
+        Ok(Self {
+            name,
+            value,
+            read_only,
+            is_default,
+            config_source,
+            is_sensitive,
+            synonyms,
+            config_type,
+            documentation,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct DescribeConfigsResponseSynonym {
+    /// The synonym name.
+    pub name: String_,
+
+    /// The synonym value.
+    pub value: NullableString,
+
+    /// The synonym source.
+    pub source: Int8,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 4
+    #[allow(dead_code)]
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<R> ReadVersionedType<R> for DescribeConfigsResponseSynonym
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!((1..=4).contains(&v));
+
+        let name = if v >= 4 {
+            String_(CompactString::read(reader)?.0)
+        } else {
+            String_::read(reader)?
+        };
+        let value = if v >= 4 {
+            NullableString(CompactNullableString::read(reader)?.0)
+        } else {
+            NullableString::read(reader)?
+        };
+        let source = Int8::read(reader)?;
+        let tagged_fields = (v >= 4).then(|| TaggedFields::read(reader)).transpose()?;
+
+        Ok(Self {
+            name,
+            value,
+            source,
+            tagged_fields,
+        })
+    }
+}
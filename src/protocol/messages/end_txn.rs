@@ -0,0 +1,87 @@
+use std::io::{Read, Write};
+
+use crate::protocol::{
+    api_key::ApiKey,
+    api_version::{ApiVersion, ApiVersionRange},
+    error::Error,
+    primitives::{Boolean, Int16, Int32, Int64, String_},
+    traits::{ReadType, WriteType},
+};
+
+use super::{
+    ReadVersionedError, ReadVersionedType, RequestBody, WriteVersionedError, WriteVersionedType,
+};
+
+#[derive(Debug)]
+pub struct EndTxnRequest {
+    /// The ID of the transaction to end.
+    pub transactional_id: String_,
+
+    /// The producer ID.
+    pub producer_id: Int64,
+
+    /// The current epoch associated with the producer.
+    pub producer_epoch: Int16,
+
+    /// True if the transaction was committed, false if it was aborted.
+    pub committed: Boolean,
+}
+
+impl<W> WriteVersionedType<W> for EndTxnRequest
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 2);
+
+        self.transactional_id.write(writer)?;
+        self.producer_id.write(writer)?;
+        self.producer_epoch.write(writer)?;
+        self.committed.write(writer)?;
+
+        Ok(())
+    }
+}
+
+impl RequestBody for EndTxnRequest {
+    type ResponseBody = EndTxnResponse;
+
+    const API_KEY: ApiKey = ApiKey::EndTxn;
+
+    /// Version 3 introduces tagged fields, so stay below that for now.
+    const API_VERSION_RANGE: ApiVersionRange =
+        ApiVersionRange::new(ApiVersion(Int16(0)), ApiVersion(Int16(2)));
+
+    const FIRST_TAGGED_FIELD_IN_REQUEST_VERSION: ApiVersion = ApiVersion(Int16(3));
+}
+
+#[derive(Debug)]
+#[allow(missing_copy_implementations)]
+pub struct EndTxnResponse {
+    /// The duration in milliseconds for which the request was throttled due to a quota violation, or zero if the
+    /// request did not violate any quota.
+    pub throttle_time_ms: Int32,
+
+    /// The error code, or 0 if there was no error.
+    pub error: Option<Error>,
+}
+
+impl<R> ReadVersionedType<R> for EndTxnResponse
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 2);
+
+        Ok(Self {
+            throttle_time_ms: Int32::read(reader)?,
+            error: Error::new(Int16::read(reader)?.0),
+        })
+    }
+}
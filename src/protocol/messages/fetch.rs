@@ -5,7 +5,7 @@ use crate::protocol::{
     api_version::{ApiVersion, ApiVersionRange},
     error::Error as ApiError,
     messages::{IsolationLevel, read_versioned_array, write_versioned_array},
-    primitives::{Int8, Int16, Int32, Int64, Records, String_},
+    primitives::{ArrayRef, Int8, Int16, Int32, Int64, Records, String_},
     traits::{ReadType, WriteType},
 };
 
@@ -19,9 +19,23 @@ pub struct FetchRequestPartition {
     /// The partition index.
     pub partition: Int32,
 
+    /// The current leader epoch of the partition.
+    ///
+    /// Defaults to `-1`, i.e. the leader epoch is not checked.
+    ///
+    /// Added in version 9.
+    pub current_leader_epoch: Option<Int32>,
+
     /// The message offset.
     pub fetch_offset: Int64,
 
+    /// The earliest available offset of the follower replica.
+    ///
+    /// The field is only used when the request is sent by the follower. Defaults to `-1`.
+    ///
+    /// Added in version 5.
+    pub log_start_offset: Option<Int64>,
+
     /// The maximum bytes to fetch from this partition.
     ///
     /// See KIP-74 for cases where this limit may not be honored.
@@ -38,10 +52,18 @@ where
         version: ApiVersion,
     ) -> Result<(), WriteVersionedError> {
         let v = version.0.0;
-        assert!(v <= 4);
+        assert!(v <= 11);
 
         self.partition.write(writer)?;
+        if v >= 9 {
+            self.current_leader_epoch
+                .unwrap_or(Int32(-1))
+                .write(writer)?;
+        }
         self.fetch_offset.write(writer)?;
+        if v >= 5 {
+            self.log_start_offset.unwrap_or(Int64(-1)).write(writer)?;
+        }
         self.partition_max_bytes.write(writer)?;
 
         Ok(())
@@ -67,7 +89,7 @@ where
         version: ApiVersion,
     ) -> Result<(), WriteVersionedError> {
         let v = version.0.0;
-        assert!(v <= 4);
+        assert!(v <= 11);
 
         self.topic.write(writer)?;
         write_versioned_array(writer, version, Some(&self.partitions))?;
@@ -76,6 +98,34 @@ where
     }
 }
 
+#[derive(Debug)]
+pub struct FetchRequestForgottenTopic {
+    /// The topic name.
+    pub topic: String_,
+
+    /// The partitions indexes to forget.
+    pub partitions: Vec<Int32>,
+}
+
+impl<W> WriteVersionedType<W> for FetchRequestForgottenTopic
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!((7..=11).contains(&v));
+
+        self.topic.write(writer)?;
+        ArrayRef(Some(&self.partitions)).write(writer)?;
+
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 pub struct FetchRequest {
     /// The broker ID of the follower, of -1 if this request is from a consumer.
@@ -109,8 +159,45 @@ pub struct FetchRequest {
     /// [KIP-98]: https://cwiki.apache.org/confluence/display/KAFKA/KIP-98+-+Exactly+Once+Delivery+and+Transactional+Messaging
     pub isolation_level: Option<IsolationLevel>,
 
+    /// The fetch session ID.
+    ///
+    /// Defaults to `0`, i.e. no session.
+    ///
+    /// Added in version 7.
+    ///
+    /// See [KIP-227].
+    ///
+    /// [KIP-227]: https://cwiki.apache.org/confluence/display/KAFKA/KIP-227%3A+Introduce+Incremental+FetchRequests+to+Increase+Partition+Scalability
+    pub session_id: Option<Int32>,
+
+    /// The fetch session epoch, which is used for ordering requests in a session.
+    ///
+    /// `0` creates a new session, `-1` closes the session identified by [`session_id`](Self::session_id) (or
+    /// requests a full fetch without a session if there is none). Defaults to `-1`.
+    ///
+    /// Added in version 7.
+    pub session_epoch: Option<Int32>,
+
     /// The topics to fetch.
+    ///
+    /// Within an incremental fetch session, only partitions that were added to the session or whose fetch
+    /// parameters changed are listed.
     pub topics: Vec<FetchRequestTopic>,
+
+    /// In an incremental fetch request, the partitions to remove from the session.
+    ///
+    /// Added in version 7.
+    pub forgotten_topics_data: Vec<FetchRequestForgottenTopic>,
+
+    /// Rack ID of the consumer making this request.
+    ///
+    /// Allows the leader to point the consumer to a replica in the same rack, see [KIP-392]. Defaults to an empty
+    /// string.
+    ///
+    /// Added in version 11.
+    ///
+    /// [KIP-392]: https://cwiki.apache.org/confluence/display/KAFKA/KIP-392%3A+Allow+consumers+to+fetch+from+closest+replica
+    pub rack_id: Option<String_>,
 }
 
 impl<W> WriteVersionedType<W> for FetchRequest
@@ -123,7 +210,7 @@ where
         version: ApiVersion,
     ) -> Result<(), WriteVersionedError> {
         let v = version.0.0;
-        assert!(v <= 4);
+        assert!(v <= 11);
 
         self.replica_id.write(writer)?;
         self.max_wait_ms.write(writer)?;
@@ -140,8 +227,24 @@ where
             level.write(writer)?;
         }
 
+        if v >= 7 {
+            self.session_id.unwrap_or(Int32(0)).write(writer)?;
+            self.session_epoch.unwrap_or(Int32(-1)).write(writer)?;
+        }
+
         write_versioned_array(writer, version, Some(&self.topics))?;
 
+        if v >= 7 {
+            write_versioned_array(writer, version, Some(&self.forgotten_topics_data))?;
+        }
+
+        if v >= 11 {
+            match &self.rack_id {
+                Some(rack_id) => rack_id.write(writer)?,
+                None => String_(String::new()).write(writer)?,
+            }
+        }
+
         Ok(())
     }
 }
@@ -158,7 +261,7 @@ impl RequestBody for FetchRequest {
     ///
     /// [KIP-98]: https://cwiki.apache.org/confluence/display/KAFKA/KIP-98+-+Exactly+Once+Delivery+and+Transactional+Messaging
     const API_VERSION_RANGE: ApiVersionRange =
-        ApiVersionRange::new(ApiVersion(Int16(4)), ApiVersion(Int16(4)));
+        ApiVersionRange::new(ApiVersion(Int16(4)), ApiVersion(Int16(11)));
 
     const FIRST_TAGGED_FIELD_IN_REQUEST_VERSION: ApiVersion = ApiVersion(Int16(12));
 }
@@ -179,7 +282,7 @@ where
 {
     fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
         let v = version.0.0;
-        assert!(4 <= v && v <= 4);
+        assert!((4..=11).contains(&v));
 
         Ok(Self {
             producer_id: Int64::read(reader)?,
@@ -207,11 +310,22 @@ pub struct FetchResponsePartition {
     /// Added in version 4.
     pub last_stable_offset: Option<Int64>,
 
+    /// The current log start offset.
+    ///
+    /// Added in version 5.
+    #[allow(dead_code)]
+    pub log_start_offset: Option<Int64>,
+
     /// The aborted transactions.
     ///
     /// Added in version 4.
     pub aborted_transactions: Vec<FetchResponseAbortedTransaction>,
 
+    /// The preferred read replica for the consumer to use on its next fetch request, or `-1`.
+    ///
+    /// Added in version 11.
+    pub preferred_read_replica: Option<Int32>,
+
     /// The record data.
     pub records: Records,
 }
@@ -222,18 +336,20 @@ where
 {
     fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
         let v = version.0.0;
-        assert!(v <= 4);
+        assert!(v <= 11);
 
         Ok(Self {
             partition_index: Int32::read(reader)?,
             error_code: ApiError::new(Int16::read(reader)?.0),
             high_watermark: Int64::read(reader)?,
             last_stable_offset: (v >= 4).then(|| Int64::read(reader)).transpose()?,
+            log_start_offset: (v >= 5).then(|| Int64::read(reader)).transpose()?,
             aborted_transactions: (v >= 4)
                 .then(|| read_versioned_array(reader, version))
                 .transpose()?
                 .flatten()
                 .unwrap_or_default(),
+            preferred_read_replica: (v >= 11).then(|| Int32::read(reader)).transpose()?,
             records: Records::read(reader)?,
         })
     }
@@ -254,7 +370,7 @@ where
 {
     fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
         let v = version.0.0;
-        assert!(v <= 4);
+        assert!(v <= 11);
 
         Ok(Self {
             topic: String_::read(reader)?,
@@ -270,7 +386,19 @@ pub struct FetchResponse {
     /// Added in version 1.
     pub throttle_time_ms: Option<Int32>,
 
+    /// The top level response error code.
+    ///
+    /// Added in version 7.
+    pub error_code: Option<ApiError>,
+
+    /// The fetch session ID, or `0` if this is not part of a fetch session.
+    ///
+    /// Added in version 7.
+    pub session_id: Option<Int32>,
+
     /// The response topics.
+    ///
+    /// For incremental fetch requests, only partitions with changes (e.g. new records or errors) are listed.
     pub responses: Vec<FetchResponseTopic>,
 }
 
@@ -280,10 +408,15 @@ where
 {
     fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
         let v = version.0.0;
-        assert!(v <= 4);
+        assert!(v <= 11);
 
         Ok(Self {
             throttle_time_ms: (v >= 1).then(|| Int32::read(reader)).transpose()?,
+            error_code: (v >= 7)
+                .then(|| Int16::read(reader))
+                .transpose()?
+                .and_then(|e| ApiError::new(e.0)),
+            session_id: (v >= 7).then(|| Int32::read(reader)).transpose()?,
             responses: read_versioned_array(reader, version)?.unwrap_or_default(),
         })
     }
@@ -0,0 +1,140 @@
+use std::io::{Read, Write};
+
+use crate::protocol::{
+    api_key::ApiKey,
+    api_version::{ApiVersion, ApiVersionRange},
+    error::Error,
+    primitives::{Int8, Int16, Int32, NullableString, String_},
+    traits::{ReadType, WriteType},
+};
+
+use super::{
+    ReadVersionedError, ReadVersionedType, RequestBody, WriteVersionedError, WriteVersionedType,
+};
+
+/// Coordinator type used in [`FindCoordinatorRequest`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoordinatorType {
+    /// Consumer group coordinator.
+    Group,
+
+    /// Transaction coordinator.
+    Transaction,
+}
+
+impl From<CoordinatorType> for Int8 {
+    fn from(t: CoordinatorType) -> Self {
+        match t {
+            CoordinatorType::Group => Self(0),
+            CoordinatorType::Transaction => Self(1),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct FindCoordinatorRequest {
+    /// The coordinator key.
+    ///
+    /// This is the group ID for group coordinators and the transactional ID for transaction coordinators.
+    pub key: String_,
+
+    /// The coordinator key type.
+    ///
+    /// Added in version 1.
+    pub key_type: Option<CoordinatorType>,
+}
+
+impl<W> WriteVersionedType<W> for FindCoordinatorRequest
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 2);
+
+        self.key.write(writer)?;
+
+        if v >= 1 {
+            // defaults to "group"
+            Int8::from(self.key_type.unwrap_or(CoordinatorType::Group)).write(writer)?;
+        } else if matches!(self.key_type, Some(CoordinatorType::Transaction)) {
+            return Err(WriteVersionedError::FieldNotAvailable {
+                version,
+                field: "key_type".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl RequestBody for FindCoordinatorRequest {
+    type ResponseBody = FindCoordinatorResponse;
+
+    const API_KEY: ApiKey = ApiKey::FindCoordinator;
+
+    /// Version 3 introduces tagged fields, so stay below that for now.
+    const API_VERSION_RANGE: ApiVersionRange =
+        ApiVersionRange::new(ApiVersion(Int16(0)), ApiVersion(Int16(2)));
+
+    const FIRST_TAGGED_FIELD_IN_REQUEST_VERSION: ApiVersion = ApiVersion(Int16(3));
+}
+
+#[derive(Debug)]
+pub struct FindCoordinatorResponse {
+    /// The duration in milliseconds for which the request was throttled due to a quota violation, or zero if the
+    /// request did not violate any quota.
+    ///
+    /// Added in version 1.
+    #[allow(dead_code)]
+    pub throttle_time_ms: Option<Int32>,
+
+    /// The error code, or 0 if there was no error.
+    pub error: Option<Error>,
+
+    /// The error message, or null if there was no error.
+    ///
+    /// Added in version 1.
+    pub error_message: Option<NullableString>,
+
+    /// The node id.
+    pub node_id: Int32,
+
+    /// The host name.
+    #[allow(dead_code)]
+    pub host: String_,
+
+    /// The port.
+    #[allow(dead_code)]
+    pub port: Int32,
+}
+
+impl<R> ReadVersionedType<R> for FindCoordinatorResponse
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 2);
+
+        let throttle_time_ms = (v >= 1).then(|| Int32::read(reader)).transpose()?;
+        let error = Error::new(Int16::read(reader)?.0);
+        let error_message = (v >= 1).then(|| NullableString::read(reader)).transpose()?;
+        let node_id = Int32::read(reader)?;
+        let host = String_::read(reader)?;
+        let port = Int32::read(reader)?;
+
+        Ok(Self {
+            throttle_time_ms,
+            error,
+            error_message,
+            node_id,
+            host,
+            port,
+        })
+    }
+}
@@ -0,0 +1,106 @@
+use std::io::{Read, Write};
+
+use crate::protocol::{
+    api_key::ApiKey,
+    api_version::{ApiVersion, ApiVersionRange},
+    error::Error,
+    primitives::{Int16, Int32, NullableString, String_},
+    traits::{ReadType, WriteType},
+};
+
+use super::{
+    ReadVersionedError, ReadVersionedType, RequestBody, WriteVersionedError, WriteVersionedType,
+};
+
+#[derive(Debug)]
+pub struct HeartbeatRequest {
+    /// The group ID.
+    pub group_id: String_,
+
+    /// The generation of the group.
+    pub generation_id: Int32,
+
+    /// The member ID.
+    pub member_id: String_,
+
+    /// The unique identifier of the consumer instance provided by end user.
+    ///
+    /// Added in version 3.
+    pub group_instance_id: Option<NullableString>,
+}
+
+impl<W> WriteVersionedType<W> for HeartbeatRequest
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 3);
+
+        self.group_id.write(writer)?;
+        self.generation_id.write(writer)?;
+        self.member_id.write(writer)?;
+
+        if v >= 3 {
+            match self.group_instance_id.as_ref() {
+                Some(group_instance_id) => {
+                    group_instance_id.write(writer)?;
+                }
+                None => {
+                    NullableString::default().write(writer)?;
+                }
+            }
+        } else if matches!(self.group_instance_id, Some(NullableString(Some(_)))) {
+            return Err(WriteVersionedError::FieldNotAvailable {
+                version,
+                field: "group_instance_id".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl RequestBody for HeartbeatRequest {
+    type ResponseBody = HeartbeatResponse;
+
+    const API_KEY: ApiKey = ApiKey::Heartbeat;
+
+    /// Version 4 introduces tagged fields, so stay below that for now.
+    const API_VERSION_RANGE: ApiVersionRange =
+        ApiVersionRange::new(ApiVersion(Int16(0)), ApiVersion(Int16(3)));
+
+    const FIRST_TAGGED_FIELD_IN_REQUEST_VERSION: ApiVersion = ApiVersion(Int16(4));
+}
+
+#[derive(Debug)]
+#[allow(missing_copy_implementations)]
+pub struct HeartbeatResponse {
+    /// The duration in milliseconds for which the request was throttled due to a quota violation, or zero if the
+    /// request did not violate any quota.
+    ///
+    /// Added in version 1.
+    pub throttle_time_ms: Option<Int32>,
+
+    /// The error code, or 0 if there was no error.
+    pub error: Option<Error>,
+}
+
+impl<R> ReadVersionedType<R> for HeartbeatResponse
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 3);
+
+        Ok(Self {
+            throttle_time_ms: (v >= 1).then(|| Int32::read(reader)).transpose()?,
+            error: Error::new(Int16::read(reader)?.0),
+        })
+    }
+}
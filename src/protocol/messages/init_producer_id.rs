@@ -0,0 +1,143 @@
+use std::io::{Read, Write};
+
+use crate::protocol::{
+    api_key::ApiKey,
+    api_version::{ApiVersion, ApiVersionRange},
+    error::Error,
+    primitives::{CompactNullableStringRef, Int16, Int32, Int64, NullableString, TaggedFields},
+    traits::{ReadType, WriteType},
+};
+
+use super::{
+    ReadVersionedError, ReadVersionedType, RequestBody, WriteVersionedError, WriteVersionedType,
+};
+
+#[derive(Debug)]
+pub struct InitProducerIdRequest {
+    /// The transactional ID, or `None` if the producer is not transactional.
+    pub transactional_id: NullableString,
+
+    /// The time in ms to wait before aborting idle transactions sent by this producer.
+    ///
+    /// This is only relevant if a transactional ID has been defined.
+    pub transaction_timeout_ms: Int32,
+
+    /// The producer ID.
+    ///
+    /// This is used to disambiguate requests if a transactional ID is reused following its expiration. Use `-1` if
+    /// there is no existing producer ID.
+    ///
+    /// Added in version 3.
+    pub producer_id: Option<Int64>,
+
+    /// The producer's current epoch.
+    ///
+    /// This will be checked against the producer epoch on the broker, and the request will return an error if they do
+    /// not match. Use `-1` if there is no existing producer ID.
+    ///
+    /// Added in version 3.
+    pub producer_epoch: Option<Int16>,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 2.
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<W> WriteVersionedType<W> for InitProducerIdRequest
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 4);
+
+        if v >= 2 {
+            CompactNullableStringRef(self.transactional_id.0.as_deref()).write(writer)?;
+        } else {
+            self.transactional_id.write(writer)?;
+        }
+        self.transaction_timeout_ms.write(writer)?;
+
+        if v >= 3 {
+            self.producer_id.unwrap_or(Int64(-1)).write(writer)?;
+            self.producer_epoch.unwrap_or(Int16(-1)).write(writer)?;
+        } else if self.producer_id.is_some_and(|id| id.0 != -1) {
+            return Err(WriteVersionedError::FieldNotAvailable {
+                version,
+                field: "producer_id".to_string(),
+            });
+        }
+
+        if v >= 2 {
+            match self.tagged_fields.as_ref() {
+                Some(tagged_fields) => {
+                    tagged_fields.write(writer)?;
+                }
+                None => {
+                    TaggedFields::default().write(writer)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl RequestBody for InitProducerIdRequest {
+    type ResponseBody = InitProducerIdResponse;
+
+    const API_KEY: ApiKey = ApiKey::InitProducerId;
+
+    /// Version 3 is required to bump the epoch of an existing producer ID ([KIP-360]).
+    ///
+    /// [KIP-360]: https://cwiki.apache.org/confluence/display/KAFKA/KIP-360%3A+Improve+reliability+of+idempotent%2Ftransactional+producer
+    const API_VERSION_RANGE: ApiVersionRange =
+        ApiVersionRange::new(ApiVersion(Int16(0)), ApiVersion(Int16(4)));
+
+    const FIRST_TAGGED_FIELD_IN_REQUEST_VERSION: ApiVersion = ApiVersion(Int16(2));
+}
+
+#[derive(Debug)]
+pub struct InitProducerIdResponse {
+    /// The duration in milliseconds for which the request was throttled due to a quota violation, or zero if the
+    /// request did not violate any quota.
+    pub throttle_time_ms: Int32,
+
+    /// The error code, or 0 if there was no error.
+    pub error: Option<Error>,
+
+    /// The current producer ID.
+    pub producer_id: Int64,
+
+    /// The current epoch associated with the producer ID.
+    pub producer_epoch: Int16,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 2.
+    #[allow(dead_code)]
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<R> ReadVersionedType<R> for InitProducerIdResponse
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 4);
+
+        Ok(Self {
+            throttle_time_ms: Int32::read(reader)?,
+            error: Error::new(Int16::read(reader)?.0),
+            producer_id: Int64::read(reader)?,
+            producer_epoch: Int16::read(reader)?,
+            tagged_fields: (v >= 2).then(|| TaggedFields::read(reader)).transpose()?,
+        })
+    }
+}
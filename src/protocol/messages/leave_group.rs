@@ -0,0 +1,81 @@
+use std::io::{Read, Write};
+
+use crate::protocol::{
+    api_key::ApiKey,
+    api_version::{ApiVersion, ApiVersionRange},
+    error::Error,
+    primitives::{Int16, Int32, String_},
+    traits::{ReadType, WriteType},
+};
+
+use super::{
+    ReadVersionedError, ReadVersionedType, RequestBody, WriteVersionedError, WriteVersionedType,
+};
+
+#[derive(Debug)]
+pub struct LeaveGroupRequest {
+    /// The ID of the group to leave.
+    pub group_id: String_,
+
+    /// The member ID to remove from the group.
+    pub member_id: String_,
+}
+
+impl<W> WriteVersionedType<W> for LeaveGroupRequest
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 2);
+
+        self.group_id.write(writer)?;
+        self.member_id.write(writer)?;
+
+        Ok(())
+    }
+}
+
+impl RequestBody for LeaveGroupRequest {
+    type ResponseBody = LeaveGroupResponse;
+
+    const API_KEY: ApiKey = ApiKey::LeaveGroup;
+
+    /// Version 3 replaces the single member with a batch of members, which we don't need.
+    const API_VERSION_RANGE: ApiVersionRange =
+        ApiVersionRange::new(ApiVersion(Int16(0)), ApiVersion(Int16(2)));
+
+    const FIRST_TAGGED_FIELD_IN_REQUEST_VERSION: ApiVersion = ApiVersion(Int16(4));
+}
+
+#[derive(Debug)]
+#[allow(missing_copy_implementations)]
+pub struct LeaveGroupResponse {
+    /// The duration in milliseconds for which the request was throttled due to a quota violation, or zero if the
+    /// request did not violate any quota.
+    ///
+    /// Added in version 1.
+    pub throttle_time_ms: Option<Int32>,
+
+    /// The error code, or 0 if there was no error.
+    pub error: Option<Error>,
+}
+
+impl<R> ReadVersionedType<R> for LeaveGroupResponse
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 2);
+
+        Ok(Self {
+            throttle_time_ms: (v >= 1).then(|| Int32::read(reader)).transpose()?,
+            error: Error::new(Int16::read(reader)?.0),
+        })
+    }
+}
@@ -32,9 +32,9 @@ impl RequestBody for MetadataRequest {
 
     const API_KEY: ApiKey = ApiKey::Metadata;
 
-    /// At the time of writing this is the same subset supported by rdkafka
+    /// All versions before the flexible encoding and the authorized operations (version 8).
     const API_VERSION_RANGE: ApiVersionRange =
-        ApiVersionRange::new(ApiVersion(Int16(0)), ApiVersion(Int16(4)));
+        ApiVersionRange::new(ApiVersion(Int16(0)), ApiVersion(Int16(7)));
 
     const FIRST_TAGGED_FIELD_IN_REQUEST_VERSION: ApiVersion = ApiVersion(Int16(9));
 }
@@ -49,7 +49,7 @@ where
         version: ApiVersion,
     ) -> Result<(), WriteVersionedError> {
         let v = version.0.0;
-        assert!(v <= 4);
+        assert!(v <= 7);
 
         if v < 4 && self.allow_auto_topic_creation.is_some() {
             return Err(WriteVersionedError::FieldNotAvailable {
@@ -85,7 +85,7 @@ where
         writer: &mut W,
         version: ApiVersion,
     ) -> Result<(), WriteVersionedError> {
-        assert!(version.0.0 <= 4);
+        assert!(version.0.0 <= 7);
         Ok(self.name.write(writer)?)
     }
 }
@@ -121,7 +121,7 @@ where
 {
     fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
         let v = version.0.0;
-        assert!(v <= 4);
+        assert!(v <= 7);
 
         let throttle_time_ms = (v >= 3).then(|| Int32::read(reader)).transpose()?;
         let brokers = read_versioned_array(reader, version)?.unwrap_or_default();
@@ -157,7 +157,7 @@ where
 {
     fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
         let v = version.0.0;
-        assert!(v <= 4);
+        assert!(v <= 7);
 
         let node_id = Int32::read(reader)?;
         let host = String_::read(reader)?;
@@ -191,7 +191,7 @@ where
 {
     fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
         let v = version.0.0;
-        assert!(v <= 4);
+        assert!(v <= 7);
 
         let error = Error::new(Int16::read(reader)?.0);
         let name = String_::read(reader)?;
@@ -215,10 +215,18 @@ pub struct MetadataResponsePartition {
     pub partition_index: Int32,
     /// The ID of the leader broker
     pub leader_id: Int32,
+    /// The leader epoch of this partition
+    ///
+    /// Added in version 7
+    pub leader_epoch: Option<Int32>,
     /// The set of all nodes that host this partition
     pub replica_nodes: Array<Int32>,
     /// The set of all nodes that are in sync with the leader for this partition
     pub isr_nodes: Array<Int32>,
+    /// The set of offline replicas of this partition
+    ///
+    /// Added in version 5
+    pub offline_replicas: Option<Array<Int32>>,
 }
 
 impl<R> ReadVersionedType<R> for MetadataResponsePartition
@@ -227,14 +235,24 @@ where
 {
     fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
         let v = version.0.0;
-        assert!(v <= 4);
+        assert!(v <= 7);
+
+        let error = Error::new(Int16::read(reader)?.0);
+        let partition_index = Int32::read(reader)?;
+        let leader_id = Int32::read(reader)?;
+        let leader_epoch = (v >= 7).then(|| Int32::read(reader)).transpose()?;
+        let replica_nodes = Array::read(reader)?;
+        let isr_nodes = Array::read(reader)?;
+        let offline_replicas = (v >= 5).then(|| Array::read(reader)).transpose()?;
 
         Ok(Self {
-            error: Error::new(Int16::read(reader)?.0),
-            partition_index: Int32::read(reader)?,
-            leader_id: Int32::read(reader)?,
-            replica_nodes: Array::read(reader)?,
-            isr_nodes: Array::read(reader)?,
+            error,
+            partition_index,
+            leader_id,
+            leader_epoch,
+            replica_nodes,
+            isr_nodes,
+            offline_replicas,
         })
     }
 }
@@ -16,28 +16,70 @@ use super::{
     vec_builder::VecBuilder,
 };
 
+mod add_offsets_to_txn;
+pub use add_offsets_to_txn::*;
+mod add_partitions_to_txn;
+pub use add_partitions_to_txn::*;
+mod alter_configs;
+pub use alter_configs::*;
 mod api_versions;
 pub use api_versions::*;
 mod constants;
 pub use constants::*;
+mod consumer_protocol;
+pub use consumer_protocol::*;
+mod create_acls;
+pub use create_acls::*;
+mod create_partitions;
+pub use create_partitions::*;
 mod create_topics;
 pub use create_topics::*;
+mod delete_acls;
+pub use delete_acls::*;
 mod delete_records;
 pub use delete_records::*;
 mod delete_topics;
 pub use delete_topics::*;
+mod describe_acls;
+pub use describe_acls::*;
+mod describe_configs;
+pub use describe_configs::*;
+mod end_txn;
+pub use end_txn::*;
 mod fetch;
 pub use fetch::*;
+mod find_coordinator;
+pub use find_coordinator::*;
 mod header;
 pub use header::*;
+mod heartbeat;
+pub use heartbeat::*;
+mod incremental_alter_configs;
+pub use incremental_alter_configs::*;
+mod init_producer_id;
+pub use init_producer_id::*;
+mod join_group;
+pub use join_group::*;
+mod leave_group;
+pub use leave_group::*;
 mod list_offsets;
 pub use list_offsets::*;
 mod metadata;
 pub use metadata::*;
+mod offset_commit;
+pub use offset_commit::*;
+mod offset_fetch;
+pub use offset_fetch::*;
+mod offset_for_leader_epoch;
+pub use offset_for_leader_epoch::*;
 mod produce;
 pub use produce::*;
 mod sasl_msg;
 pub use sasl_msg::*;
+mod sync_group;
+pub use sync_group::*;
+mod txn_offset_commit;
+pub use txn_offset_commit::*;
 #[cfg(test)]
 mod test_utils;
 
@@ -0,0 +1,272 @@
+use std::io::{Read, Write};
+
+use crate::protocol::{
+    api_key::ApiKey,
+    api_version::{ApiVersion, ApiVersionRange},
+    error::Error,
+    messages::{read_versioned_array, write_versioned_array},
+    primitives::{Int16, Int32, Int64, NullableString, String_},
+    traits::{ReadType, WriteType},
+};
+
+use super::{
+    ReadVersionedError, ReadVersionedType, RequestBody, WriteVersionedError, WriteVersionedType,
+};
+
+#[derive(Debug)]
+pub struct OffsetCommitRequestPartition {
+    /// The partition index.
+    pub partition_index: Int32,
+
+    /// The message offset to be committed.
+    pub committed_offset: Int64,
+
+    /// The leader epoch of this partition.
+    ///
+    /// Added in version 6.
+    pub committed_leader_epoch: Option<Int32>,
+
+    /// The timestamp of the commit.
+    ///
+    /// Only used in version 1.
+    pub commit_timestamp: Option<Int64>,
+
+    /// Any associated metadata the client wants to keep.
+    pub committed_metadata: NullableString,
+}
+
+impl<W> WriteVersionedType<W> for OffsetCommitRequestPartition
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 7);
+
+        self.partition_index.write(writer)?;
+        self.committed_offset.write(writer)?;
+
+        if v >= 6 {
+            // defaults to "unknown"
+            self.committed_leader_epoch
+                .unwrap_or(Int32(-1))
+                .write(writer)?;
+        }
+
+        if v == 1 {
+            // defaults to "use broker time"
+            self.commit_timestamp.unwrap_or(Int64(-1)).write(writer)?;
+        }
+
+        self.committed_metadata.write(writer)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct OffsetCommitRequestTopic {
+    /// The topic name.
+    pub name: String_,
+
+    /// Each partition to commit offsets for.
+    pub partitions: Vec<OffsetCommitRequestPartition>,
+}
+
+impl<W> WriteVersionedType<W> for OffsetCommitRequestTopic
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 7);
+
+        self.name.write(writer)?;
+        write_versioned_array(writer, version, Some(self.partitions.as_slice()))?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct OffsetCommitRequest {
+    /// The unique group identifier.
+    pub group_id: String_,
+
+    /// The generation of the group.
+    ///
+    /// Use `-1` if the client is not a member of the group.
+    ///
+    /// Added in version 1.
+    pub generation_id: Option<Int32>,
+
+    /// The member ID assigned by the group coordinator.
+    ///
+    /// Use an empty string if the client is not a member of the group.
+    ///
+    /// Added in version 1.
+    pub member_id: Option<String_>,
+
+    /// The unique identifier of the consumer instance provided by end user.
+    ///
+    /// Added in version 7.
+    pub group_instance_id: Option<NullableString>,
+
+    /// The time period in ms to retain the offset.
+    ///
+    /// Only used in versions 2 to 4.
+    pub retention_time_ms: Option<Int64>,
+
+    /// The topics to commit offsets for.
+    pub topics: Vec<OffsetCommitRequestTopic>,
+}
+
+impl<W> WriteVersionedType<W> for OffsetCommitRequest
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 7);
+
+        self.group_id.write(writer)?;
+
+        if v >= 1 {
+            // defaults to "not a member"
+            self.generation_id.unwrap_or(Int32(-1)).write(writer)?;
+            match self.member_id.as_ref() {
+                Some(member_id) => {
+                    member_id.write(writer)?;
+                }
+                None => {
+                    String_(String::new()).write(writer)?;
+                }
+            }
+        }
+
+        if v >= 7 {
+            match self.group_instance_id.as_ref() {
+                Some(group_instance_id) => {
+                    group_instance_id.write(writer)?;
+                }
+                None => {
+                    NullableString::default().write(writer)?;
+                }
+            }
+        } else if matches!(self.group_instance_id, Some(NullableString(Some(_)))) {
+            return Err(WriteVersionedError::FieldNotAvailable {
+                version,
+                field: "group_instance_id".to_string(),
+            });
+        }
+
+        if (2..=4).contains(&v) {
+            // defaults to "use broker setting"
+            self.retention_time_ms.unwrap_or(Int64(-1)).write(writer)?;
+        }
+
+        write_versioned_array(writer, version, Some(self.topics.as_slice()))?;
+
+        Ok(())
+    }
+}
+
+impl RequestBody for OffsetCommitRequest {
+    type ResponseBody = OffsetCommitResponse;
+
+    const API_KEY: ApiKey = ApiKey::OffsetCommit;
+
+    /// Version 8 introduces tagged fields, so stay below that for now.
+    const API_VERSION_RANGE: ApiVersionRange =
+        ApiVersionRange::new(ApiVersion(Int16(0)), ApiVersion(Int16(7)));
+
+    const FIRST_TAGGED_FIELD_IN_REQUEST_VERSION: ApiVersion = ApiVersion(Int16(8));
+}
+
+#[derive(Debug)]
+#[allow(missing_copy_implementations)]
+pub struct OffsetCommitResponsePartition {
+    /// The partition index.
+    pub partition_index: Int32,
+
+    /// The error code, or 0 if there was no error.
+    pub error: Option<Error>,
+}
+
+impl<R> ReadVersionedType<R> for OffsetCommitResponsePartition
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 7);
+
+        Ok(Self {
+            partition_index: Int32::read(reader)?,
+            error: Error::new(Int16::read(reader)?.0),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct OffsetCommitResponseTopic {
+    /// The topic name.
+    pub name: String_,
+
+    /// The responses for each partition in the topic.
+    pub partitions: Vec<OffsetCommitResponsePartition>,
+}
+
+impl<R> ReadVersionedType<R> for OffsetCommitResponseTopic
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 7);
+
+        Ok(Self {
+            name: String_::read(reader)?,
+            partitions: read_versioned_array(reader, version)?.unwrap_or_default(),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct OffsetCommitResponse {
+    /// The duration in milliseconds for which the request was throttled due to a quota violation, or zero if the
+    /// request did not violate any quota.
+    ///
+    /// Added in version 3.
+    pub throttle_time_ms: Option<Int32>,
+
+    /// The responses for each topic.
+    pub topics: Vec<OffsetCommitResponseTopic>,
+}
+
+impl<R> ReadVersionedType<R> for OffsetCommitResponse
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 7);
+
+        Ok(Self {
+            throttle_time_ms: (v >= 3).then(|| Int32::read(reader)).transpose()?,
+            topics: read_versioned_array(reader, version)?.unwrap_or_default(),
+        })
+    }
+}
@@ -0,0 +1,195 @@
+use std::io::{Read, Write};
+
+use crate::protocol::{
+    api_key::ApiKey,
+    api_version::{ApiVersion, ApiVersionRange},
+    error::Error,
+    messages::{read_versioned_array, write_versioned_array},
+    primitives::{Array, Int16, Int32, Int64, NullableString, String_},
+    traits::{ReadType, WriteType},
+};
+
+use super::{
+    ReadVersionedError, ReadVersionedType, RequestBody, WriteVersionedError, WriteVersionedType,
+};
+
+#[derive(Debug)]
+pub struct OffsetFetchRequestTopic {
+    /// The topic name.
+    pub name: String_,
+
+    /// The partition indexes we would like to fetch offsets for.
+    pub partition_indexes: Array<Int32>,
+}
+
+impl<W> WriteVersionedType<W> for OffsetFetchRequestTopic
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 5);
+
+        self.name.write(writer)?;
+        self.partition_indexes.write(writer)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct OffsetFetchRequest {
+    /// The group to fetch offsets for.
+    pub group_id: String_,
+
+    /// Each topic we would like to fetch offsets for.
+    ///
+    /// Starting with version 2 this can be `None` to fetch offsets for all topics.
+    pub topics: Option<Vec<OffsetFetchRequestTopic>>,
+}
+
+impl<W> WriteVersionedType<W> for OffsetFetchRequest
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 5);
+
+        self.group_id.write(writer)?;
+
+        if v < 2 && self.topics.is_none() {
+            return Err(WriteVersionedError::FieldNotAvailable {
+                version,
+                field: "topics=null".to_string(),
+            });
+        }
+        write_versioned_array(writer, version, self.topics.as_deref())?;
+
+        Ok(())
+    }
+}
+
+impl RequestBody for OffsetFetchRequest {
+    type ResponseBody = OffsetFetchResponse;
+
+    const API_KEY: ApiKey = ApiKey::OffsetFetch;
+
+    /// Version 6 introduces tagged fields, so stay below that for now.
+    const API_VERSION_RANGE: ApiVersionRange =
+        ApiVersionRange::new(ApiVersion(Int16(0)), ApiVersion(Int16(5)));
+
+    const FIRST_TAGGED_FIELD_IN_REQUEST_VERSION: ApiVersion = ApiVersion(Int16(6));
+}
+
+#[derive(Debug)]
+pub struct OffsetFetchResponsePartition {
+    /// The partition index.
+    pub partition_index: Int32,
+
+    /// The committed message offset, or `-1` if there is no committed offset.
+    pub committed_offset: Int64,
+
+    /// The leader epoch.
+    ///
+    /// Added in version 5.
+    #[allow(dead_code)]
+    pub committed_leader_epoch: Option<Int32>,
+
+    /// The partition metadata.
+    pub metadata: NullableString,
+
+    /// The error code, or 0 if there was no error.
+    pub error: Option<Error>,
+}
+
+impl<R> ReadVersionedType<R> for OffsetFetchResponsePartition
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 5);
+
+        Ok(Self {
+            partition_index: Int32::read(reader)?,
+            committed_offset: Int64::read(reader)?,
+            committed_leader_epoch: (v >= 5).then(|| Int32::read(reader)).transpose()?,
+            metadata: NullableString::read(reader)?,
+            error: Error::new(Int16::read(reader)?.0),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct OffsetFetchResponseTopic {
+    /// The topic name.
+    pub name: String_,
+
+    /// The responses per partition.
+    pub partitions: Vec<OffsetFetchResponsePartition>,
+}
+
+impl<R> ReadVersionedType<R> for OffsetFetchResponseTopic
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 5);
+
+        Ok(Self {
+            name: String_::read(reader)?,
+            partitions: read_versioned_array(reader, version)?.unwrap_or_default(),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct OffsetFetchResponse {
+    /// The duration in milliseconds for which the request was throttled due to a quota violation, or zero if the
+    /// request did not violate any quota.
+    ///
+    /// Added in version 3.
+    pub throttle_time_ms: Option<Int32>,
+
+    /// The responses per topic.
+    pub topics: Vec<OffsetFetchResponseTopic>,
+
+    /// The top-level error code, or 0 if there was no error.
+    ///
+    /// Added in version 2.
+    pub error: Option<Error>,
+}
+
+impl<R> ReadVersionedType<R> for OffsetFetchResponse
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 5);
+
+        let throttle_time_ms = (v >= 3).then(|| Int32::read(reader)).transpose()?;
+        let topics = read_versioned_array(reader, version)?.unwrap_or_default();
+        let error = if v >= 2 {
+            Error::new(Int16::read(reader)?.0)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            throttle_time_ms,
+            topics,
+            error,
+        })
+    }
+}
@@ -0,0 +1,328 @@
+use std::io::{Read, Write};
+
+use crate::protocol::{
+    api_key::ApiKey,
+    api_version::{ApiVersion, ApiVersionRange},
+    error::Error,
+    messages::{
+        read_compact_versioned_array, read_versioned_array, write_compact_versioned_array,
+        write_versioned_array,
+    },
+    primitives::{CompactString, CompactStringRef, Int16, Int32, Int64, String_, TaggedFields},
+    traits::{ReadType, WriteType},
+};
+
+use super::{
+    ReadVersionedError, ReadVersionedType, RequestBody, WriteVersionedError, WriteVersionedType,
+};
+
+#[derive(Debug)]
+pub struct OffsetForLeaderEpochRequestPartition {
+    /// The partition index.
+    pub partition: Int32,
+
+    /// An epoch used to fence consumers/replicas with old metadata.
+    ///
+    /// If the epoch provided by the client is larger than the current epoch known to the broker, then the
+    /// `UnknownLeaderEpoch` error code will be returned. If the provided epoch is smaller, then the `FencedLeaderEpoch`
+    /// error code will be returned. Defaults to `-1`, i.e. the leader epoch is not checked.
+    ///
+    /// Added in version 2.
+    pub current_leader_epoch: Option<Int32>,
+
+    /// The epoch to look up an offset for.
+    pub leader_epoch: Int32,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 4.
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<W> WriteVersionedType<W> for OffsetForLeaderEpochRequestPartition
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 4);
+
+        self.partition.write(writer)?;
+        if v >= 2 {
+            self.current_leader_epoch
+                .unwrap_or(Int32(-1))
+                .write(writer)?;
+        }
+        self.leader_epoch.write(writer)?;
+
+        if v >= 4 {
+            match self.tagged_fields.as_ref() {
+                Some(tagged_fields) => {
+                    tagged_fields.write(writer)?;
+                }
+                None => {
+                    TaggedFields::default().write(writer)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct OffsetForLeaderEpochRequestTopic {
+    /// The topic name.
+    pub topic: String_,
+
+    /// Each partition to get offsets for.
+    pub partitions: Vec<OffsetForLeaderEpochRequestPartition>,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 4.
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<W> WriteVersionedType<W> for OffsetForLeaderEpochRequestTopic
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 4);
+
+        if v >= 4 {
+            CompactStringRef(&self.topic.0).write(writer)?;
+            write_compact_versioned_array(writer, version, Some(self.partitions.as_slice()))?;
+        } else {
+            self.topic.write(writer)?;
+            write_versioned_array(writer, version, Some(self.partitions.as_slice()))?;
+        }
+
+        if v >= 4 {
+            match self.tagged_fields.as_ref() {
+                Some(tagged_fields) => {
+                    tagged_fields.write(writer)?;
+                }
+                None => {
+                    TaggedFields::default().write(writer)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct OffsetForLeaderEpochRequest {
+    /// The broker ID of the follower, or `-1` if this request is from a consumer.
+    ///
+    /// Defaults to `-1`.
+    ///
+    /// Added in version 3.
+    pub replica_id: Option<Int32>,
+
+    /// Each topic to get offsets for.
+    pub topics: Vec<OffsetForLeaderEpochRequestTopic>,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 4.
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<W> WriteVersionedType<W> for OffsetForLeaderEpochRequest
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 4);
+
+        if v >= 3 {
+            self.replica_id.unwrap_or(Int32(-1)).write(writer)?;
+        }
+
+        if v >= 4 {
+            write_compact_versioned_array(writer, version, Some(self.topics.as_slice()))?;
+        } else {
+            write_versioned_array(writer, version, Some(self.topics.as_slice()))?;
+        }
+
+        if v >= 4 {
+            match self.tagged_fields.as_ref() {
+                Some(tagged_fields) => {
+                    tagged_fields.write(writer)?;
+                }
+                None => {
+                    TaggedFields::default().write(writer)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl RequestBody for OffsetForLeaderEpochRequest {
+    type ResponseBody = OffsetForLeaderEpochResponse;
+
+    const API_KEY: ApiKey = ApiKey::OffsetForLeaderEpoch;
+
+    /// All versions.
+    const API_VERSION_RANGE: ApiVersionRange =
+        ApiVersionRange::new(ApiVersion(Int16(0)), ApiVersion(Int16(4)));
+
+    const FIRST_TAGGED_FIELD_IN_REQUEST_VERSION: ApiVersion = ApiVersion(Int16(4));
+}
+
+#[derive(Debug)]
+pub struct OffsetForLeaderEpochResponsePartition {
+    /// The error code, or 0 if there was no error.
+    pub error: Option<Error>,
+
+    /// The partition index.
+    pub partition: Int32,
+
+    /// The leader epoch of the partition, i.e. the largest epoch that is smaller than or equal to the requested one.
+    ///
+    /// `-1` if the broker does not know such an epoch.
+    ///
+    /// Added in version 1.
+    pub leader_epoch: Option<Int32>,
+
+    /// The end offset of the epoch, i.e. the start offset of the next epoch or the log end offset if this is the
+    /// current epoch.
+    ///
+    /// `-1` if the broker does not know the epoch.
+    pub end_offset: Int64,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 4.
+    #[allow(dead_code)]
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<R> ReadVersionedType<R> for OffsetForLeaderEpochResponsePartition
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 4);
+
+        let error = Error::new(Int16::read(reader)?.0);
+        let partition = Int32::read(reader)?;
+        let leader_epoch = (v >= 1).then(|| Int32::read(reader)).transpose()?;
+        let end_offset = Int64::read(reader)?;
+        let tagged_fields = (v >= 4).then(|| TaggedFields::read(reader)).transpose()?;
+
+        Ok(Self {
+            error,
+            partition,
+            leader_epoch,
+            end_offset,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct OffsetForLeaderEpochResponseTopic {
+    /// The topic name.
+    pub topic: String_,
+
+    /// Each partition in the topic we fetched offsets for.
+    pub partitions: Vec<OffsetForLeaderEpochResponsePartition>,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 4.
+    #[allow(dead_code)]
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<R> ReadVersionedType<R> for OffsetForLeaderEpochResponseTopic
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 4);
+
+        let topic = if v >= 4 {
+            String_(CompactString::read(reader)?.0)
+        } else {
+            String_::read(reader)?
+        };
+        let partitions = if v >= 4 {
+            read_compact_versioned_array(reader, version)?.unwrap_or_default()
+        } else {
+            read_versioned_array(reader, version)?.unwrap_or_default()
+        };
+        let tagged_fields = (v >= 4).then(|| TaggedFields::read(reader)).transpose()?;
+
+        Ok(Self {
+            topic,
+            partitions,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct OffsetForLeaderEpochResponse {
+    /// The duration in milliseconds for which the request was throttled due to a quota violation, or zero if the
+    /// request did not violate any quota.
+    ///
+    /// Added in version 2.
+    pub throttle_time_ms: Option<Int32>,
+
+    /// Each topic we fetched offsets for.
+    pub topics: Vec<OffsetForLeaderEpochResponseTopic>,
+
+    /// The tagged fields.
+    ///
+    /// Added in version 4.
+    #[allow(dead_code)]
+    pub tagged_fields: Option<TaggedFields>,
+}
+
+impl<R> ReadVersionedType<R> for OffsetForLeaderEpochResponse
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 4);
+
+        let throttle_time_ms = (v >= 2).then(|| Int32::read(reader)).transpose()?;
+        let topics = if v >= 4 {
+            read_compact_versioned_array(reader, version)?.unwrap_or_default()
+        } else {
+            read_versioned_array(reader, version)?.unwrap_or_default()
+        };
+        let tagged_fields = (v >= 4).then(|| TaggedFields::read(reader)).transpose()?;
+
+        Ok(Self {
+            throttle_time_ms,
+            topics,
+            tagged_fields,
+        })
+    }
+}
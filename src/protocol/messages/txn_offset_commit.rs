@@ -0,0 +1,213 @@
+use std::io::{Read, Write};
+
+use crate::protocol::{
+    api_key::ApiKey,
+    api_version::{ApiVersion, ApiVersionRange},
+    error::Error,
+    messages::{read_versioned_array, write_versioned_array},
+    primitives::{Int16, Int32, Int64, NullableString, String_},
+    traits::{ReadType, WriteType},
+};
+
+use super::{
+    ReadVersionedError, ReadVersionedType, RequestBody, WriteVersionedError, WriteVersionedType,
+};
+
+#[derive(Debug)]
+pub struct TxnOffsetCommitRequestPartition {
+    /// The index of the partition within the topic.
+    pub partition_index: Int32,
+
+    /// The message offset to be committed.
+    pub committed_offset: Int64,
+
+    /// The leader epoch of the last consumed record.
+    ///
+    /// Added in version 2.
+    pub committed_leader_epoch: Option<Int32>,
+
+    /// Any associated metadata the client wants to keep.
+    pub committed_metadata: NullableString,
+}
+
+impl<W> WriteVersionedType<W> for TxnOffsetCommitRequestPartition
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 2);
+
+        self.partition_index.write(writer)?;
+        self.committed_offset.write(writer)?;
+
+        if v >= 2 {
+            // defaults to "unknown"
+            self.committed_leader_epoch
+                .unwrap_or(Int32(-1))
+                .write(writer)?;
+        }
+
+        self.committed_metadata.write(writer)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct TxnOffsetCommitRequestTopic {
+    /// The topic name.
+    pub name: String_,
+
+    /// The partitions inside the topic that we want to commit offsets for.
+    pub partitions: Vec<TxnOffsetCommitRequestPartition>,
+}
+
+impl<W> WriteVersionedType<W> for TxnOffsetCommitRequestTopic
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 2);
+
+        self.name.write(writer)?;
+        write_versioned_array(writer, version, Some(self.partitions.as_slice()))?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct TxnOffsetCommitRequest {
+    /// The ID of the transaction.
+    pub transactional_id: String_,
+
+    /// The ID of the group.
+    pub group_id: String_,
+
+    /// The current producer ID in use by the transactional ID.
+    pub producer_id: Int64,
+
+    /// The current epoch associated with the producer ID.
+    pub producer_epoch: Int16,
+
+    /// Each topic that we want to commit offsets for.
+    pub topics: Vec<TxnOffsetCommitRequestTopic>,
+}
+
+impl<W> WriteVersionedType<W> for TxnOffsetCommitRequest
+where
+    W: Write,
+{
+    fn write_versioned(
+        &self,
+        writer: &mut W,
+        version: ApiVersion,
+    ) -> Result<(), WriteVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 2);
+
+        self.transactional_id.write(writer)?;
+        self.group_id.write(writer)?;
+        self.producer_id.write(writer)?;
+        self.producer_epoch.write(writer)?;
+        write_versioned_array(writer, version, Some(self.topics.as_slice()))?;
+
+        Ok(())
+    }
+}
+
+impl RequestBody for TxnOffsetCommitRequest {
+    type ResponseBody = TxnOffsetCommitResponse;
+
+    const API_KEY: ApiKey = ApiKey::TxnOffsetCommit;
+
+    /// Version 3 introduces tagged fields, so stay below that for now.
+    const API_VERSION_RANGE: ApiVersionRange =
+        ApiVersionRange::new(ApiVersion(Int16(0)), ApiVersion(Int16(2)));
+
+    const FIRST_TAGGED_FIELD_IN_REQUEST_VERSION: ApiVersion = ApiVersion(Int16(3));
+}
+
+#[derive(Debug)]
+#[allow(missing_copy_implementations)]
+pub struct TxnOffsetCommitResponsePartition {
+    /// The partition index.
+    pub partition_index: Int32,
+
+    /// The error code, or 0 if there was no error.
+    pub error: Option<Error>,
+}
+
+impl<R> ReadVersionedType<R> for TxnOffsetCommitResponsePartition
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 2);
+
+        Ok(Self {
+            partition_index: Int32::read(reader)?,
+            error: Error::new(Int16::read(reader)?.0),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct TxnOffsetCommitResponseTopic {
+    /// The topic name.
+    pub name: String_,
+
+    /// The responses for each partition in the topic.
+    pub partitions: Vec<TxnOffsetCommitResponsePartition>,
+}
+
+impl<R> ReadVersionedType<R> for TxnOffsetCommitResponseTopic
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 2);
+
+        Ok(Self {
+            name: String_::read(reader)?,
+            partitions: read_versioned_array(reader, version)?.unwrap_or_default(),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct TxnOffsetCommitResponse {
+    /// The duration in milliseconds for which the request was throttled due to a quota violation, or zero if the
+    /// request did not violate any quota.
+    pub throttle_time_ms: Int32,
+
+    /// The responses for each topic.
+    pub topics: Vec<TxnOffsetCommitResponseTopic>,
+}
+
+impl<R> ReadVersionedType<R> for TxnOffsetCommitResponse
+where
+    R: Read,
+{
+    fn read_versioned(reader: &mut R, version: ApiVersion) -> Result<Self, ReadVersionedError> {
+        let v = version.0.0;
+        assert!(v <= 2);
+
+        Ok(Self {
+            throttle_time_ms: Int32::read(reader)?,
+            topics: read_versioned_array(reader, version)?.unwrap_or_default(),
+        })
+    }
+}
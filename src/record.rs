@@ -2,6 +2,8 @@ use std::collections::BTreeMap;
 
 use chrono::{DateTime, Utc};
 
+use crate::client::partition::Compression;
+
 /// High-level record.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Record {
@@ -31,6 +33,66 @@ pub struct RecordAndOffset {
     pub offset: i64,
 }
 
+/// How the timestamps of a [`RecordBatch`] were assigned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampType {
+    /// Timestamps were set by the producer.
+    CreateTime,
+
+    /// Timestamps were set by the broker when the batch was appended to the log.
+    LogAppendTime,
+}
+
+/// Metadata of a [`RecordBatch`], as written by the producer and the broker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordBatchMetadata {
+    /// Offset of the first record of the batch.
+    ///
+    /// This may be smaller than the offset of the first record in [`RecordBatch::records`] if the batch was fetched
+    /// from an offset within the batch or if records were removed by compaction.
+    pub base_offset: i64,
+
+    /// Offset of the last record of the batch.
+    pub last_offset: i64,
+
+    /// Epoch of the partition leader that appended the batch.
+    pub partition_leader_epoch: i32,
+
+    /// Timestamp of the first record.
+    pub first_timestamp: DateTime<Utc>,
+
+    /// Largest timestamp of all records.
+    pub max_timestamp: DateTime<Utc>,
+
+    /// Producer ID, `-1` if the producer was neither idempotent nor transactional.
+    pub producer_id: i64,
+
+    /// Producer epoch, `-1` if the producer was neither idempotent nor transactional.
+    pub producer_epoch: i16,
+
+    /// Sequence number of the first record, `-1` if the producer was neither idempotent nor transactional.
+    pub base_sequence: i32,
+
+    /// Compression that the batch was stored with.
+    pub compression: Compression,
+
+    /// Whether the timestamps were set by the producer or by the broker.
+    pub timestamp_type: TimestampType,
+
+    /// The batch was written as part of a transaction.
+    pub is_transactional: bool,
+}
+
+/// Records of a single record batch, together with the batch metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordBatch {
+    /// Metadata of the batch.
+    pub metadata: RecordBatchMetadata,
+
+    /// Records of the batch, ordered by offset.
+    pub records: Vec<RecordAndOffset>,
+}
+
 #[cfg(test)]
 mod tests {
     use chrono::TimeZone;
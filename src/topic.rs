@@ -1,7 +1,63 @@
 use std::collections::BTreeSet;
 
+use crate::client::error::ProtocolError;
+
 #[derive(Debug)]
 pub struct Topic {
     pub name: String,
     pub partitions: BTreeSet<i32>,
 }
+
+/// Detailed state of a topic, see [`Client::describe_topics`](crate::client::Client::describe_topics).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TopicDescription {
+    /// Topic name.
+    pub name: String,
+
+    /// The topic is used by the brokers themselves, e.g. `__consumer_offsets`.
+    pub is_internal: bool,
+
+    /// Why the topic could not be described, e.g. [`ProtocolError::UnknownTopicOrPartition`].
+    pub error: Option<ProtocolError>,
+
+    /// Partitions, ordered by partition ID.
+    pub partitions: Vec<PartitionDescription>,
+}
+
+/// Detailed state of a partition, see [`TopicDescription::partitions`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartitionDescription {
+    /// Partition ID.
+    pub partition: i32,
+
+    /// Broker ID of the leader, `None` if the partition has no leader.
+    pub leader: Option<i32>,
+
+    /// Leader epoch, if reported by the broker.
+    ///
+    /// Note: this requires broker version >= 2.1.0 (KIP-320).
+    pub leader_epoch: Option<i32>,
+
+    /// Broker IDs of all replicas, the first one is the preferred leader.
+    pub replicas: Vec<i32>,
+
+    /// Broker IDs of the replicas that are in sync with the leader.
+    pub isr: Vec<i32>,
+
+    /// Broker IDs of the replicas that are offline.
+    ///
+    /// Note: this requires broker version >= 1.0.0 (KIP-112), older brokers never report offline replicas.
+    pub offline_replicas: Vec<i32>,
+
+    /// Partition error, e.g. [`ProtocolError::LeaderNotAvailable`].
+    ///
+    /// Note that the broker may still report the replicas of a partition with an error.
+    pub error: Option<ProtocolError>,
+}
+
+impl PartitionDescription {
+    /// Not all replicas are in sync with the leader.
+    pub fn is_under_replicated(&self) -> bool {
+        self.isr.len() < self.replicas.len()
+    }
+}
@@ -4,10 +4,16 @@ use rskafka::{
     BackoffConfig,
     client::{
         ClientBuilder,
+        acl::{
+            AclBinding, AclBindingFilter, AclOperation, AclPatternType, AclPermissionType,
+            AclResourceType,
+        },
+        controller::{ConfigChanges, ConfigResource, ConfigSource, NewPartitions, NewTopic},
         error::{Error as ClientError, ProtocolError, ServerErrorResponse},
-        partition::{Compression, OffsetAt, UnknownTopicHandling},
+        partition::{Acks, Compression, EpochEndOffset, OffsetAt, UnknownTopicHandling},
     },
-    record::{Record, RecordAndOffset},
+    record::{Record, RecordAndOffset, TimestampType},
+    topic::Topic,
 };
 use std::{collections::BTreeMap, env, str::FromStr, sync::Arc, time::Duration};
 
@@ -140,6 +146,548 @@ async fn test_topic_crud() {
     .unwrap();
 }
 
+#[tokio::test]
+async fn test_create_topics() {
+    maybe_start_logging();
+
+    let test_cfg = maybe_skip_kafka_integration!();
+    let client = ClientBuilder::new(test_cfg.bootstrap_brokers)
+        .build()
+        .await
+        .unwrap();
+    let controller_client = client.controller_client().unwrap();
+
+    let existing = random_topic_name();
+    controller_client
+        .create_topic(&existing, 1, 1, 5_000)
+        .await
+        .unwrap();
+
+    // dry run
+    let validated = random_topic_name();
+    let results = controller_client
+        .create_topics(
+            vec![
+                NewTopic::new(&validated)
+                    .with_num_partitions(1)
+                    .with_replication_factor(1),
+            ],
+            5_000,
+            true,
+        )
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].name, validated);
+    results[0].result.as_ref().unwrap();
+
+    let with_configs = random_topic_name();
+    let with_assignments = random_topic_name();
+    let results = controller_client
+        .create_topics(
+            vec![
+                NewTopic::new(&with_configs)
+                    .with_num_partitions(2)
+                    .with_replication_factor(1)
+                    .with_config("retention.ms", "3600000")
+                    .with_config("cleanup.policy", "compact"),
+                NewTopic::new(&existing)
+                    .with_num_partitions(1)
+                    .with_replication_factor(1),
+                NewTopic::new(&with_assignments)
+                    .with_replica_assignment(0, vec![0])
+                    .with_replica_assignment(1, vec![1]),
+            ],
+            5_000,
+            false,
+        )
+        .await
+        .unwrap();
+    let names = results.iter().map(|r| r.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            with_configs.as_str(),
+            existing.as_str(),
+            with_assignments.as_str()
+        ]
+    );
+
+    let created = results[0].result.as_ref().unwrap();
+    if matches!(test_cfg.broker_impl, BrokerImpl::Kafka) {
+        assert_eq!(created.num_partitions, Some(2));
+        assert_eq!(created.replication_factor, Some(1));
+        let retention = created
+            .configs
+            .iter()
+            .find(|config| config.name == "retention.ms")
+            .unwrap();
+        assert_eq!(retention.value.as_deref(), Some("3600000"));
+        assert_eq!(retention.source, ConfigSource::DynamicTopic);
+    }
+    assert_matches!(
+        results[1].result,
+        Err(ClientError::ServerError {
+            protocol_error: ProtocolError::TopicAlreadyExists,
+            ..
+        })
+    );
+    results[2].result.as_ref().unwrap();
+
+    // might take a while to converge
+    tokio::time::timeout(TEST_TIMEOUT, async {
+        loop {
+            let topics = client.list_topics().await.unwrap();
+            if let Some(topic) = topics.iter().find(|t| t.name == with_assignments) {
+                assert_eq!(topic.partitions.len(), 2);
+                assert!(topics.iter().all(|t| t.name != validated));
+                return;
+            }
+
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_create_partitions() {
+    maybe_start_logging();
+
+    let test_cfg = maybe_skip_kafka_integration!();
+    let topic_name = random_topic_name();
+
+    let client = ClientBuilder::new(test_cfg.bootstrap_brokers)
+        .build()
+        .await
+        .unwrap();
+    let controller_client = client.controller_client().unwrap();
+    controller_client
+        .create_topic(&topic_name, 1, 1, 5_000)
+        .await
+        .unwrap();
+
+    let n_partitions = |topics: Vec<Topic>| {
+        topics
+            .into_iter()
+            .find(|t| t.name == topic_name)
+            .map(|t| t.partitions.len())
+    };
+
+    let producer = client
+        .topic_producer(&topic_name, UnknownTopicHandling::Error)
+        .build()
+        .await
+        .unwrap();
+    assert_eq!(producer.num_partitions(), 1);
+
+    // dry run
+    controller_client
+        .create_partitions(NewPartitions::new(&topic_name, 2), 5_000, true)
+        .await
+        .unwrap();
+    assert_eq!(n_partitions(client.list_topics().await.unwrap()), Some(1));
+
+    controller_client
+        .create_partitions(
+            NewPartitions::new(&topic_name, 3)
+                .with_assignment(vec![1])
+                .with_assignment(vec![2]),
+            5_000,
+            false,
+        )
+        .await
+        .unwrap();
+    assert_eq!(n_partitions(client.list_topics().await.unwrap()), Some(3));
+
+    // existing producers pick up the new partitions
+    assert_eq!(producer.num_partitions(), 3);
+
+    // new partitions are usable right away
+    let partition_client = client
+        .partition_client(&topic_name, 2, UnknownTopicHandling::Error)
+        .await
+        .unwrap();
+    partition_client
+        .produce(vec![record(b"x")], Compression::NoCompression)
+        .await
+        .unwrap();
+
+    // partitions cannot be removed
+    let err = controller_client
+        .create_partitions(NewPartitions::new(&topic_name, 2), 5_000, false)
+        .await
+        .unwrap_err();
+    assert_matches!(
+        err,
+        ClientError::ServerError {
+            protocol_error: ProtocolError::InvalidPartitions,
+            ..
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_configs() {
+    maybe_start_logging();
+
+    let test_cfg = maybe_skip_kafka_integration!();
+    let topic_name = random_topic_name();
+
+    let client = ClientBuilder::new(test_cfg.bootstrap_brokers)
+        .build()
+        .await
+        .unwrap();
+    let controller_client = client.controller_client().unwrap();
+    controller_client
+        .create_topic(&topic_name, 1, 1, 5_000)
+        .await
+        .unwrap();
+    let topic = ConfigResource::Topic(topic_name.clone());
+
+    let describe = |name: &'static str| {
+        let controller_client = &controller_client;
+        let topic = topic.clone();
+        async move {
+            let result = controller_client
+                .describe_configs(vec![topic])
+                .await
+                .unwrap()
+                .pop()
+                .unwrap();
+            result
+                .result
+                .unwrap()
+                .into_iter()
+                .find(|config| config.name == name)
+                .unwrap()
+        }
+    };
+
+    let retention = describe("retention.ms").await;
+    assert_ne!(retention.source, ConfigSource::DynamicTopic);
+    assert!(!retention.is_sensitive);
+
+    // dry run
+    let results = controller_client
+        .incremental_alter_configs(
+            vec![ConfigChanges::new(topic.clone()).with_set("retention.ms", "3600000")],
+            true,
+        )
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    results[0].result.as_ref().unwrap();
+    assert_eq!(describe("retention.ms").await.value, retention.value);
+
+    let unknown = ConfigResource::Topic(random_topic_name());
+    let results = controller_client
+        .incremental_alter_configs(
+            vec![
+                ConfigChanges::new(topic.clone()).with_set("retention.ms", "3600000"),
+                ConfigChanges::new(unknown.clone()).with_set("retention.ms", "3600000"),
+            ],
+            false,
+        )
+        .await
+        .unwrap();
+    assert_eq!(results[0].resource, topic);
+    results[0].result.as_ref().unwrap();
+    assert_eq!(results[1].resource, unknown);
+    assert_matches!(
+        results[1].result,
+        Err(ClientError::ServerError {
+            protocol_error: ProtocolError::UnknownTopicOrPartition,
+            ..
+        })
+    );
+
+    let retention = describe("retention.ms").await;
+    assert_eq!(retention.value.as_deref(), Some("3600000"));
+    assert_eq!(retention.source, ConfigSource::DynamicTopic);
+
+    controller_client
+        .incremental_alter_configs(
+            vec![ConfigChanges::new(topic.clone()).with_delete("retention.ms")],
+            false,
+        )
+        .await
+        .unwrap()
+        .pop()
+        .unwrap()
+        .result
+        .unwrap();
+    assert_ne!(
+        describe("retention.ms").await.source,
+        ConfigSource::DynamicTopic
+    );
+
+    // Redpanda does not support list operations and broker configs the same way
+    if test_cfg.broker_impl != BrokerImpl::Kafka {
+        return;
+    }
+
+    controller_client
+        .incremental_alter_configs(
+            vec![ConfigChanges::new(topic.clone()).with_append("cleanup.policy", "compact")],
+            false,
+        )
+        .await
+        .unwrap()
+        .pop()
+        .unwrap()
+        .result
+        .unwrap();
+    assert_eq!(
+        describe("cleanup.policy").await.value.as_deref(),
+        Some("delete,compact")
+    );
+
+    // legacy API replaces the whole configuration
+    controller_client
+        .alter_configs(
+            topic.clone(),
+            BTreeMap::from([("retention.ms".to_owned(), "7200000".to_owned())]),
+            false,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        describe("retention.ms").await.value.as_deref(),
+        Some("7200000")
+    );
+    assert_ne!(
+        describe("cleanup.policy").await.source,
+        ConfigSource::DynamicTopic
+    );
+
+    let results = controller_client
+        .describe_configs(vec![ConfigResource::Broker(1), ConfigResource::Broker(2)])
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 2);
+    for result in results {
+        let configs = result.result.unwrap();
+        let retention = configs
+            .iter()
+            .find(|config| config.name == "log.retention.ms")
+            .unwrap();
+        assert!(!retention.read_only);
+    }
+}
+
+#[tokio::test]
+async fn test_acls() {
+    maybe_start_logging();
+
+    let test_cfg = maybe_skip_kafka_integration!();
+    let client = ClientBuilder::new(test_cfg.bootstrap_brokers)
+        .build()
+        .await
+        .unwrap();
+    let controller_client = client.controller_client().unwrap();
+
+    let principal = format!("User:{}", random_topic_name());
+    let literal = AclBinding::new(
+        AclResourceType::Topic,
+        random_topic_name(),
+        &principal,
+        AclOperation::Read,
+        AclPermissionType::Allow,
+    );
+    let prefixed = AclBinding::new(
+        AclResourceType::Group,
+        "group_",
+        &principal,
+        AclOperation::Describe,
+        AclPermissionType::Deny,
+    )
+    .with_pattern_type(AclPatternType::Prefixed);
+
+    let results = controller_client
+        .create_acls(vec![literal.clone(), prefixed.clone()])
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].binding, literal);
+    assert_eq!(results[1].binding, prefixed);
+
+    // the test cluster may not have an authorizer
+    if let Err(ClientError::ServerError {
+        protocol_error: ProtocolError::SecurityDisabled,
+        ..
+    }) = &results[0].result
+    {
+        for result in results {
+            assert_matches!(
+                result.result,
+                Err(ClientError::ServerError {
+                    protocol_error: ProtocolError::SecurityDisabled,
+                    ..
+                })
+            );
+        }
+        return;
+    }
+    for result in results {
+        result.result.unwrap();
+    }
+
+    let by_principal = AclBindingFilter {
+        principal: Some(principal.clone()),
+        ..AclBindingFilter::any()
+    };
+    let mut acls = controller_client
+        .describe_acls(by_principal.clone())
+        .await
+        .unwrap();
+    acls.sort();
+    let mut expected = vec![literal.clone(), prefixed.clone()];
+    expected.sort();
+    assert_eq!(acls, expected);
+
+    // "match" finds the prefixed ACL for a concrete group name
+    let acls = controller_client
+        .describe_acls(AclBindingFilter {
+            resource_type: AclResourceType::Group,
+            resource_name: Some("group_1".to_owned()),
+            pattern_type: AclPatternType::Match,
+            ..by_principal.clone()
+        })
+        .await
+        .unwrap();
+    assert_eq!(acls, vec![prefixed.clone()]);
+
+    let results = controller_client
+        .delete_acls(vec![AclBindingFilter::from(literal.clone())])
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    let deleted = results
+        .into_iter()
+        .next()
+        .unwrap()
+        .result
+        .unwrap()
+        .into_iter()
+        .map(|acl| {
+            acl.result.unwrap();
+            acl.binding
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(deleted, vec![literal]);
+
+    let acls = controller_client
+        .describe_acls(by_principal.clone())
+        .await
+        .unwrap();
+    assert_eq!(acls, vec![prefixed]);
+
+    controller_client
+        .delete_acls(vec![by_principal])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_describe_topics() {
+    maybe_start_logging();
+
+    let test_cfg = maybe_skip_kafka_integration!();
+    let topic_name = random_topic_name();
+
+    let client = ClientBuilder::new(test_cfg.bootstrap_brokers)
+        .build()
+        .await
+        .unwrap();
+    let controller_client = client.controller_client().unwrap();
+    controller_client
+        .create_topics(
+            vec![
+                NewTopic::new(&topic_name)
+                    .with_replica_assignment(0, vec![0, 1])
+                    .with_replica_assignment(1, vec![1, 2]),
+            ],
+            5_000,
+            false,
+        )
+        .await
+        .unwrap()
+        .pop()
+        .unwrap()
+        .result
+        .unwrap();
+
+    // leaders might take a while to be elected and replicas to catch up
+    let topic = tokio::time::timeout(TEST_TIMEOUT, async {
+        loop {
+            let topic = client
+                .describe_topics(Some(vec![topic_name.clone()]), false)
+                .await
+                .unwrap()
+                .pop()
+                .unwrap();
+            if topic.error.is_none()
+                && topic.partitions.len() == 2
+                && topic
+                    .partitions
+                    .iter()
+                    .all(|p| p.leader.is_some() && !p.is_under_replicated())
+            {
+                return topic;
+            }
+
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(topic.name, topic_name);
+    assert!(!topic.is_internal);
+    let replicas = topic
+        .partitions
+        .iter()
+        .map(|p| (p.partition, p.replicas.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(replicas, vec![(0, vec![0, 1]), (1, vec![1, 2])]);
+    for partition in &topic.partitions {
+        assert!(partition.replicas.contains(&partition.leader.unwrap()));
+        assert!(partition.offline_replicas.is_empty());
+        assert!(partition.error.is_none());
+    }
+    if test_cfg.broker_impl == BrokerImpl::Kafka {
+        assert!(topic.partitions.iter().all(|p| p.leader_epoch.is_some()));
+    }
+
+    // requested topics are returned in order, with errors for unknown topics
+    let unknown = random_topic_name();
+    let topics = client
+        .describe_topics(Some(vec![unknown.clone(), topic_name.clone()]), false)
+        .await
+        .unwrap();
+    assert_eq!(topics.len(), 2);
+    assert_eq!(topics[0].name, unknown);
+    assert_eq!(
+        topics[0].error,
+        Some(ProtocolError::UnknownTopicOrPartition)
+    );
+    assert_eq!(topics[1].name, topic_name);
+
+    // all topics
+    let topics = client.describe_topics(None, false).await.unwrap();
+    assert!(topics.iter().all(|t| !t.is_internal));
+    assert!(topics.iter().any(|t| t.name == topic_name));
+    let names = topics.iter().map(|t| t.name.clone()).collect::<Vec<_>>();
+    let mut sorted = names.clone();
+    sorted.sort();
+    assert_eq!(names, sorted);
+
+    let with_internal = client.describe_topics(None, true).await.unwrap();
+    assert!(with_internal.len() >= topics.len());
+}
+
 #[tokio::test]
 async fn test_partition_client() {
     maybe_start_logging();
@@ -589,6 +1137,319 @@ async fn test_consume_midbatch() {
     );
 }
 
+#[tokio::test]
+async fn test_consume_client_rack() {
+    maybe_start_logging();
+
+    let test_cfg = maybe_skip_kafka_integration!();
+    let topic_name = random_topic_name();
+
+    let client = ClientBuilder::new(test_cfg.bootstrap_brokers.clone())
+        .build()
+        .await
+        .unwrap();
+    let controller_client = client.controller_client().unwrap();
+    controller_client
+        .create_topic(&topic_name, 1, 3, 5_000)
+        .await
+        .unwrap();
+
+    let partition_client = client
+        .partition_client(&topic_name, 0, UnknownTopicHandling::Retry)
+        .await
+        .unwrap();
+    let record = record(b"x");
+    partition_client
+        .produce(vec![record.clone()], Compression::NoCompression)
+        .await
+        .unwrap();
+
+    // The Kafka test setup places every broker in its own rack, so at least one of the clients is redirected to a
+    // follower. Followers might not know the latest high watermark yet, so retry until the record is visible.
+    for rack in ["rack-0", "rack-1", "rack-2"] {
+        let client = ClientBuilder::new(test_cfg.bootstrap_brokers.clone())
+            .client_rack(rack)
+            .build()
+            .await
+            .unwrap();
+        let partition_client = client
+            .partition_client(&topic_name, 0, UnknownTopicHandling::Retry)
+            .await
+            .unwrap();
+
+        let records = tokio::time::timeout(TEST_TIMEOUT, async {
+            loop {
+                let (records, _watermark) = partition_client
+                    .fetch_records(0, 1..10_000, 100)
+                    .await
+                    .unwrap();
+                if !records.is_empty() {
+                    return records;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            records,
+            vec![RecordAndOffset {
+                record: record.clone(),
+                offset: 0,
+            }],
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_produce_idempotent() {
+    maybe_start_logging();
+
+    let test_cfg = maybe_skip_kafka_integration!();
+    let topic_name = random_topic_name();
+
+    let client = ClientBuilder::new(test_cfg.bootstrap_brokers)
+        .build()
+        .await
+        .unwrap();
+    let controller_client = client.controller_client().unwrap();
+    controller_client
+        .create_topic(&topic_name, 1, 1, 5_000)
+        .await
+        .unwrap();
+
+    let partition_client = Arc::new(
+        client
+            .partition_client(&topic_name, 0, UnknownTopicHandling::Retry)
+            .await
+            .unwrap()
+            .with_idempotence(true),
+    );
+
+    let record_1 = record(b"x");
+    let record_2 = record(b"y");
+    let record_3 = record(b"z");
+
+    let offsets = partition_client
+        .produce(
+            vec![record_1.clone(), record_2.clone()],
+            Compression::NoCompression,
+        )
+        .await
+        .unwrap();
+    assert_eq!(offsets, vec![0, 1]);
+
+    // concurrent calls are serialized
+    let (offsets_a, offsets_b) = futures::join!(
+        partition_client.produce(vec![record_3.clone()], Compression::NoCompression),
+        partition_client.produce(vec![record_3.clone()], Compression::NoCompression),
+    );
+    let mut offsets = [offsets_a.unwrap(), offsets_b.unwrap()].concat();
+    offsets.sort_unstable();
+    assert_eq!(offsets, vec![2, 3]);
+
+    // idempotence requires acks = all
+    let err = partition_client
+        .produce_with_acks(
+            vec![record_3.clone()],
+            Compression::NoCompression,
+            Acks::Leader,
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap_err();
+    assert_matches!(err, ClientError::InvalidConfig(_));
+
+    let (records, _watermark) = partition_client
+        .fetch_records(0, 1..10_000, 1_000)
+        .await
+        .unwrap();
+    let records: Vec<_> = records.into_iter().map(|r| r.record).collect();
+    assert_eq!(
+        records,
+        vec![record_1, record_2, record_3.clone(), record_3]
+    );
+}
+
+#[tokio::test]
+async fn test_fetch_record_batches() {
+    maybe_start_logging();
+
+    let test_cfg = maybe_skip_kafka_integration!();
+    let topic_name = random_topic_name();
+
+    let client = ClientBuilder::new(test_cfg.bootstrap_brokers)
+        .build()
+        .await
+        .unwrap();
+    let controller_client = client.controller_client().unwrap();
+    controller_client
+        .create_topic(&topic_name, 1, 1, 5_000)
+        .await
+        .unwrap();
+
+    let partition_client = client
+        .partition_client(&topic_name, 0, UnknownTopicHandling::Retry)
+        .await
+        .unwrap()
+        .with_idempotence(true);
+
+    let record_1 = record(b"x");
+    let record_2 = record(b"y");
+    let record_3 = record(b"z");
+    partition_client
+        .produce(
+            vec![record_1.clone(), record_2.clone()],
+            Compression::NoCompression,
+        )
+        .await
+        .unwrap();
+    partition_client
+        .produce(vec![record_3.clone()], Compression::NoCompression)
+        .await
+        .unwrap();
+
+    // start in the middle of the first batch
+    let (batches, watermark) = partition_client
+        .fetch_record_batches(1, 1..10_000, 1_000)
+        .await
+        .unwrap();
+    assert_eq!(watermark, 3);
+    assert_eq!(batches.len(), 2);
+
+    let (batch_1, batch_2) = (&batches[0], &batches[1]);
+    assert_eq!(batch_1.metadata.base_offset, 0);
+    assert_eq!(batch_1.metadata.last_offset, 1);
+    assert_eq!(
+        batch_1.records,
+        vec![RecordAndOffset {
+            record: record_2,
+            offset: 1,
+        }],
+    );
+    assert_eq!(batch_2.metadata.base_offset, 2);
+    assert_eq!(batch_2.metadata.last_offset, 2);
+    assert_eq!(
+        batch_2.records,
+        vec![RecordAndOffset {
+            record: record_3,
+            offset: 2,
+        }],
+    );
+
+    // idempotent producer
+    for batch in &batches {
+        assert!(batch.metadata.producer_id >= 0);
+        assert_eq!(batch.metadata.compression, Compression::NoCompression);
+        assert_eq!(batch.metadata.timestamp_type, TimestampType::CreateTime);
+        assert!(!batch.metadata.is_transactional);
+    }
+    assert_eq!(batch_1.metadata.producer_id, batch_2.metadata.producer_id);
+    assert_eq!(batch_1.metadata.base_sequence, 0);
+    assert_eq!(batch_2.metadata.base_sequence, 2);
+}
+
+#[tokio::test]
+async fn test_get_epoch_end_offset() {
+    maybe_start_logging();
+
+    let test_cfg = maybe_skip_kafka_integration!();
+    let topic_name = random_topic_name();
+
+    let client = ClientBuilder::new(test_cfg.bootstrap_brokers)
+        .build()
+        .await
+        .unwrap();
+    let controller_client = client.controller_client().unwrap();
+    controller_client
+        .create_topic(&topic_name, 1, 1, 5_000)
+        .await
+        .unwrap();
+
+    let partition_client = client
+        .partition_client(&topic_name, 0, UnknownTopicHandling::Retry)
+        .await
+        .unwrap();
+    partition_client
+        .produce(vec![record(b"x"), record(b"y")], Compression::NoCompression)
+        .await
+        .unwrap();
+
+    let (batches, _watermark) = partition_client
+        .fetch_record_batches(0, 1..10_000, 1_000)
+        .await
+        .unwrap();
+    let leader_epoch = batches[0].metadata.partition_leader_epoch;
+    assert!(leader_epoch >= 0);
+
+    // the current epoch ends at the end of the log
+    let epoch_end = partition_client
+        .get_epoch_end_offset(leader_epoch)
+        .await
+        .unwrap();
+    assert_eq!(
+        epoch_end,
+        EpochEndOffset {
+            leader_epoch: Some(leader_epoch),
+            end_offset: Some(2),
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_produce_acks() {
+    maybe_start_logging();
+
+    let test_cfg = maybe_skip_kafka_integration!();
+    let topic_name = random_topic_name();
+
+    let client = ClientBuilder::new(test_cfg.bootstrap_brokers)
+        .build()
+        .await
+        .unwrap();
+    let controller_client = client.controller_client().unwrap();
+    controller_client
+        .create_topic(&topic_name, 1, 1, 5_000)
+        .await
+        .unwrap();
+
+    let partition_client = client
+        .partition_client(&topic_name, 0, UnknownTopicHandling::Retry)
+        .await
+        .unwrap()
+        .with_acks(Acks::Leader)
+        .with_produce_timeout(Duration::from_secs(5));
+
+    let record_1 = record(b"x");
+    let record_2 = record(b"y");
+    let record_3 = record(b"z");
+
+    let offsets = partition_client
+        .produce(vec![record_1.clone()], Compression::NoCompression)
+        .await
+        .unwrap();
+    assert_eq!(offsets, vec![0]);
+
+    // fire and forget: offsets are unknown
+    let offsets = partition_client
+        .produce_with_acks(
+            vec![record_2.clone(), record_3.clone()],
+            Compression::NoCompression,
+            Acks::None,
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+    assert_eq!(offsets, vec![-1, -1]);
+
+    // the connection stays usable and requests are processed in order, so the records are visible now
+    let (records, _watermark) = partition_client
+        .fetch_records(0, 1..10_000, 1_000)
+        .await
+        .unwrap();
+    let records: Vec<_> = records.into_iter().map(|r| r.record).collect();
+    assert_eq!(records, vec![record_1, record_2, record_3]);
+}
+
 #[tokio::test]
 async fn test_delete_records() {
     maybe_start_logging();
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -11,8 +12,11 @@ use rskafka::record::Record;
 use rskafka::{
     client::{
         ClientBuilder,
-        consumer::{StartOffset, StreamConsumer, StreamConsumerBuilder},
+        consumer::{
+            CommittedFallback, OffsetReset, StartOffset, StreamConsumer, StreamConsumerBuilder,
+        },
         error::{Error, ProtocolError},
+        group_offsets::CommittedOffset,
         partition::{Compression, UnknownTopicHandling},
     },
     record::RecordAndOffset,
@@ -321,6 +325,117 @@ async fn test_stream_consumer_start_at_earliest_after_deletion() {
     assert_stream_pending(&mut stream).await;
 }
 
+#[tokio::test]
+async fn test_stream_consumer_offset_reset() {
+    maybe_start_logging();
+
+    let test_cfg = maybe_skip_kafka_integration!();
+    let client = ClientBuilder::new(test_cfg.bootstrap_brokers)
+        .build()
+        .await
+        .unwrap();
+    let controller_client = client.controller_client().unwrap();
+
+    let topic = random_topic_name();
+    controller_client
+        .create_topic(&topic, 1, 1, 5_000)
+        .await
+        .unwrap();
+
+    let record_1 = record(b"x");
+    let record_2 = record(b"y");
+
+    let partition_client = Arc::new(
+        client
+            .partition_client(&topic, 0, UnknownTopicHandling::Retry)
+            .await
+            .unwrap(),
+    );
+    partition_client
+        .produce(
+            vec![record_1.clone(), record_2.clone()],
+            Compression::NoCompression,
+        )
+        .await
+        .unwrap();
+
+    partition_client.delete_records(1, 1_000).await.unwrap();
+
+    // offset 0 was deleted, so the consumer restarts from the earliest available offset
+    let mut stream = StreamConsumerBuilder::new(Arc::clone(&partition_client), StartOffset::At(0))
+        .with_max_wait_ms(50)
+        .with_offset_reset(OffsetReset::Earliest)
+        .build();
+    let (record_and_offset, _) = assert_ok(timeout(TEST_TIMEOUT, stream.next()).await);
+    assert_eq!(record_and_offset.record, record_2);
+    assert_eq!(record_and_offset.offset, 1);
+
+    // `Error` policy returns the error to the caller
+    let mut stream = StreamConsumerBuilder::new(Arc::clone(&partition_client), StartOffset::At(0))
+        .with_max_wait_ms(50)
+        .with_offset_reset(OffsetReset::Error)
+        .build();
+    let err = timeout(TEST_TIMEOUT, stream.next())
+        .await
+        .expect("no timeout")
+        .expect("some records")
+        .unwrap_err();
+    assert_matches!(
+        err,
+        Error::ServerError {
+            protocol_error: ProtocolError::OffsetOutOfRange,
+            ..
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_stream_consumer_prefetch() {
+    maybe_start_logging();
+
+    let test_cfg = maybe_skip_kafka_integration!();
+    let client = ClientBuilder::new(test_cfg.bootstrap_brokers)
+        .build()
+        .await
+        .unwrap();
+    let controller_client = client.controller_client().unwrap();
+
+    let topic = random_topic_name();
+    controller_client
+        .create_topic(&topic, 1, 1, 5_000)
+        .await
+        .unwrap();
+
+    let partition_client = Arc::new(
+        client
+            .partition_client(&topic, 0, UnknownTopicHandling::Retry)
+            .await
+            .unwrap(),
+    );
+
+    // one batch per record
+    let records: Vec<_> = (0..10u8).map(|i| record(&[i])).collect();
+    for record in &records {
+        partition_client
+            .produce(vec![record.clone()], Compression::NoCompression)
+            .await
+            .unwrap();
+    }
+
+    let mut stream =
+        StreamConsumerBuilder::new(Arc::clone(&partition_client), StartOffset::Earliest)
+            .with_max_wait_ms(50)
+            .with_max_batch_size(1)
+            .with_prefetch_bytes(1_000_000)
+            .build();
+    for (offset, record) in records.into_iter().enumerate() {
+        let (record_and_offset, _) = assert_ok(timeout(TEST_TIMEOUT, stream.next()).await);
+        assert_eq!(record_and_offset.record, record);
+        assert_eq!(record_and_offset.offset, offset as i64);
+    }
+    assert_stream_pending(&mut stream).await;
+}
+
 #[tokio::test]
 async fn test_stream_consumer_start_at_latest() {
     maybe_start_logging();
@@ -472,6 +587,276 @@ async fn test_stream_consumer_start_timestamp_based_offset() {
     assert_stream_pending(&mut stream).await;
 }
 
+#[tokio::test]
+async fn test_stream_consumer_start_at_timestamp() {
+    maybe_start_logging();
+
+    let test_cfg = maybe_skip_kafka_integration!();
+    let client = ClientBuilder::new(test_cfg.bootstrap_brokers)
+        .build()
+        .await
+        .unwrap();
+    let controller_client = client.controller_client().unwrap();
+
+    let topic = random_topic_name();
+    controller_client
+        .create_topic(&topic, 1, 1, 5_000)
+        .await
+        .unwrap();
+
+    let partition_client = Arc::new(
+        client
+            .partition_client(&topic, 0, UnknownTopicHandling::Retry)
+            .await
+            .unwrap(),
+    );
+    let ts = Utc.timestamp_millis_opt(1337).unwrap();
+    let record_1 = record_with_timestamp_milliseconds(b"x", ts);
+    let record_2 = record_with_timestamp_milliseconds(b"y", ts + Duration::from_millis(100));
+    partition_client
+        .produce(
+            vec![record_1.clone(), record_2.clone()],
+            Compression::NoCompression,
+        )
+        .await
+        .unwrap();
+
+    let mut stream = StreamConsumerBuilder::new(
+        Arc::clone(&partition_client),
+        StartOffset::Timestamp(ts + Duration::from_millis(50)),
+    )
+    .with_max_wait_ms(50)
+    .build();
+    let (record_and_offset, _) = assert_ok(timeout(TEST_TIMEOUT, stream.next()).await);
+    assert_eq!(record_and_offset.record, record_2);
+    assert_stream_pending(&mut stream).await;
+
+    // timestamp beyond the end of the log starts at the end
+    let mut stream = StreamConsumerBuilder::new(
+        Arc::clone(&partition_client),
+        StartOffset::Timestamp(ts + Duration::from_secs(3600)),
+    )
+    .with_max_wait_ms(50)
+    .build();
+    assert_stream_pending(&mut stream).await;
+
+    let record_3 = record_with_timestamp_milliseconds(b"z", ts);
+    partition_client
+        .produce(vec![record_3.clone()], Compression::NoCompression)
+        .await
+        .unwrap();
+    let (record_and_offset, _) = assert_ok(timeout(TEST_TIMEOUT, stream.next()).await);
+    assert_eq!(record_and_offset.record, record_3);
+    assert_eq!(record_and_offset.offset, 2);
+}
+
+#[tokio::test]
+async fn test_stream_consumer_start_at_committed() {
+    maybe_start_logging();
+
+    let test_cfg = maybe_skip_kafka_integration!();
+    let client = ClientBuilder::new(test_cfg.bootstrap_brokers)
+        .build()
+        .await
+        .unwrap();
+    let controller_client = client.controller_client().unwrap();
+
+    let topic = random_topic_name();
+    controller_client
+        .create_topic(&topic, 1, 1, 5_000)
+        .await
+        .unwrap();
+
+    let record_1 = record(b"x");
+    let record_2 = record(b"y");
+
+    let partition_client = Arc::new(
+        client
+            .partition_client(&topic, 0, UnknownTopicHandling::Retry)
+            .await
+            .unwrap(),
+    );
+    partition_client
+        .produce(
+            vec![record_1.clone(), record_2.clone()],
+            Compression::NoCompression,
+        )
+        .await
+        .unwrap();
+
+    let group = format!("test_group_{}", uuid::Uuid::new_v4());
+    let start_offset = StartOffset::Committed {
+        group: group.as_str().into(),
+        fallback: CommittedFallback::Earliest,
+    };
+
+    // nothing committed yet, so we use the fallback
+    let mut stream =
+        StreamConsumerBuilder::new(Arc::clone(&partition_client), start_offset.clone())
+            .with_max_wait_ms(50)
+            .build();
+    let (record_and_offset, _watermark) = assert_ok(timeout(TEST_TIMEOUT, stream.next()).await);
+    assert_eq!(record_and_offset.record, record_1);
+
+    client
+        .group_offsets_client(&group)
+        .commit_offsets(&[CommittedOffset {
+            topic: topic.clone(),
+            partition: 0,
+            offset: 1,
+            metadata: None,
+        }])
+        .await
+        .unwrap();
+
+    // resume from committed offset
+    let mut stream = StreamConsumerBuilder::new(Arc::clone(&partition_client), start_offset)
+        .with_max_wait_ms(50)
+        .build();
+    let (record_and_offset, _watermark) = assert_ok(timeout(TEST_TIMEOUT, stream.next()).await);
+    assert_eq!(record_and_offset.record, record_2);
+
+    // No further records
+    assert_stream_pending(&mut stream).await;
+}
+
+#[tokio::test]
+async fn test_multi_partition_consumer() {
+    maybe_start_logging();
+
+    let test_cfg = maybe_skip_kafka_integration!();
+    let client = ClientBuilder::new(test_cfg.bootstrap_brokers)
+        .build()
+        .await
+        .unwrap();
+    let controller_client = client.controller_client().unwrap();
+
+    let topic_1 = random_topic_name();
+    controller_client
+        .create_topic(&topic_1, 3, 1, 5_000)
+        .await
+        .unwrap();
+    let topic_2 = random_topic_name();
+    controller_client
+        .create_topic(&topic_2, 1, 1, 5_000)
+        .await
+        .unwrap();
+
+    let mut expected = BTreeMap::new();
+    for (topic, partition) in [(&topic_1, 0), (&topic_1, 1), (&topic_1, 2), (&topic_2, 0)] {
+        let partition_client = client
+            .partition_client(topic, partition, UnknownTopicHandling::Retry)
+            .await
+            .unwrap();
+        let records = vec![
+            record(format!("{topic}-{partition}-a").as_bytes()),
+            record(format!("{topic}-{partition}-b").as_bytes()),
+        ];
+        partition_client
+            .produce(records.clone(), Compression::NoCompression)
+            .await
+            .unwrap();
+        expected.insert((topic.clone(), partition), records);
+    }
+
+    // the first record of topic 1 partition 1 is skipped
+    let mut stream = client
+        .multi_partition_consumer()
+        .with_partition(&topic_1, 0, StartOffset::Earliest)
+        .with_partition(&topic_1, 1, StartOffset::At(1))
+        .with_partition(&topic_1, 2, StartOffset::Earliest)
+        .with_partition(&topic_2, 0, StartOffset::Earliest)
+        .with_max_wait_ms(50)
+        .build();
+    expected.get_mut(&(topic_1.clone(), 1)).unwrap().remove(0);
+
+    let n = expected
+        .values()
+        .map(|records| records.len())
+        .sum::<usize>();
+    let mut actual: BTreeMap<_, Vec<_>> = BTreeMap::new();
+    for _ in 0..n {
+        let partition_record = timeout(TEST_TIMEOUT, stream.next())
+            .await
+            .expect("no timeout")
+            .expect("some records")
+            .expect("no error");
+        assert_eq!(partition_record.high_watermark, 2);
+        let records = actual
+            .entry((partition_record.topic, partition_record.partition))
+            .or_default();
+        // records of a partition are emitted in order
+        assert_eq!(
+            partition_record.record.offset,
+            records.len() as i64 + i64::from(partition_record.partition == 1)
+        );
+        records.push(partition_record.record.record);
+    }
+    assert_eq!(actual, expected);
+
+    // No further records
+    assert_stream_pending(&mut stream).await;
+}
+
+#[tokio::test]
+async fn test_multi_partition_consumer_incremental_fetch() {
+    maybe_start_logging();
+
+    let test_cfg = maybe_skip_kafka_integration!();
+    let client = ClientBuilder::new(test_cfg.bootstrap_brokers)
+        .build()
+        .await
+        .unwrap();
+    let controller_client = client.controller_client().unwrap();
+
+    let topic = random_topic_name();
+    controller_client
+        .create_topic(&topic, 3, 1, 5_000)
+        .await
+        .unwrap();
+
+    let mut partition_clients = vec![];
+    for partition in 0..3 {
+        partition_clients.push(
+            client
+                .partition_client(&topic, partition, UnknownTopicHandling::Retry)
+                .await
+                .unwrap(),
+        );
+    }
+
+    let mut stream = client
+        .multi_partition_consumer()
+        .with_partition(&topic, 0, StartOffset::Earliest)
+        .with_partition(&topic, 1, StartOffset::Earliest)
+        .with_partition(&topic, 2, StartOffset::Earliest)
+        .with_max_wait_ms(50)
+        .build();
+    assert_stream_pending(&mut stream).await;
+
+    // Data is written to one partition at a time, so once the fetch session is established, most partitions are
+    // neither part of the request nor of the response.
+    for i in 0..6 {
+        let partition = i % 3;
+        let record = record(format!("{i}").as_bytes());
+        partition_clients[partition as usize]
+            .produce(vec![record.clone()], Compression::NoCompression)
+            .await
+            .unwrap();
+
+        let partition_record = timeout(TEST_TIMEOUT, stream.next())
+            .await
+            .expect("no timeout")
+            .expect("some records")
+            .expect("no error");
+        assert_eq!(partition_record.partition, partition);
+        assert_eq!(partition_record.record.offset, i64::from(i / 3));
+        assert_eq!(partition_record.record.record, record);
+
+        assert_stream_pending(&mut stream).await;
+    }
+}
+
 fn assert_ok(
     r: Result<Option<<StreamConsumer as Stream>::Item>, tokio::time::error::Elapsed>,
 ) -> (RecordAndOffset, i64) {
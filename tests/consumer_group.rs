@@ -0,0 +1,236 @@
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use futures::StreamExt;
+use tokio::time::timeout;
+
+use rskafka::client::{
+    ClientBuilder,
+    consumer::CommittedFallback,
+    consumer_group::AssignmentStrategy,
+    group_offsets::CommittedOffset,
+    partition::{Compression, UnknownTopicHandling},
+};
+use test_helpers::{TEST_TIMEOUT, maybe_start_logging, random_topic_name, record};
+
+mod test_helpers;
+
+/// Group ID for testing.
+fn random_group_id() -> String {
+    format!("test_group_{}", uuid::Uuid::new_v4())
+}
+
+#[tokio::test]
+async fn test_consumer_group_single_member() {
+    maybe_start_logging();
+
+    let test_cfg = maybe_skip_kafka_integration!();
+    let client = ClientBuilder::new(test_cfg.bootstrap_brokers)
+        .build()
+        .await
+        .unwrap();
+    let controller_client = client.controller_client().unwrap();
+
+    let topic = random_topic_name();
+    controller_client
+        .create_topic(&topic, 2, 1, 5_000)
+        .await
+        .unwrap();
+
+    let partition_client = client
+        .partition_client(&topic, 1, UnknownTopicHandling::Retry)
+        .await
+        .unwrap();
+    let record = record(b"x");
+    partition_client
+        .produce(vec![record.clone()], Compression::NoCompression)
+        .await
+        .unwrap();
+
+    let mut group = client
+        .consumer_group(random_group_id(), vec![topic.clone()])
+        .with_heartbeat_interval(Duration::from_millis(100))
+        .build();
+
+    let generation = timeout(TEST_TIMEOUT, group.next_generation())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert!(!generation.is_revoked());
+    assert_eq!(
+        generation.assignment().get(&topic),
+        Some(&BTreeSet::from([0, 1]))
+    );
+
+    let streams = generation
+        .stream_consumers(CommittedFallback::Earliest)
+        .await
+        .unwrap();
+    assert_eq!(streams.len(), 2);
+    let mut stream = futures::stream::select_all(streams);
+    let (record_and_offset, _high_watermark) = timeout(TEST_TIMEOUT, stream.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(record_and_offset.record, record);
+
+    group.leave().await.unwrap();
+    assert!(generation.is_revoked());
+
+    // streams end after revocation
+    assert!(
+        timeout(TEST_TIMEOUT, stream.next())
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn test_consumer_group_rebalance() {
+    maybe_start_logging();
+
+    let test_cfg = maybe_skip_kafka_integration!();
+    let client = ClientBuilder::new(test_cfg.bootstrap_brokers)
+        .build()
+        .await
+        .unwrap();
+    let controller_client = client.controller_client().unwrap();
+
+    let topic = random_topic_name();
+    controller_client
+        .create_topic(&topic, 4, 1, 5_000)
+        .await
+        .unwrap();
+
+    let group_id = random_group_id();
+    let mut group_1 = client
+        .consumer_group(&group_id, vec![topic.clone()])
+        .with_heartbeat_interval(Duration::from_millis(100))
+        .with_rebalance_timeout(Duration::from_secs(10))
+        .with_assignment_strategy(AssignmentStrategy::RoundRobin)
+        .build();
+    let generation_1 = timeout(TEST_TIMEOUT, group_1.next_generation())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        generation_1.assignment().get(&topic),
+        Some(&BTreeSet::from([0, 1, 2, 3]))
+    );
+
+    // second member triggers a rebalance
+    let mut group_2 = client
+        .consumer_group(&group_id, vec![topic.clone()])
+        .with_heartbeat_interval(Duration::from_millis(100))
+        .with_rebalance_timeout(Duration::from_secs(10))
+        .with_assignment_strategy(AssignmentStrategy::RoundRobin)
+        .build();
+    timeout(TEST_TIMEOUT, generation_1.revoked()).await.unwrap();
+
+    let (generation_1, generation_2) = timeout(TEST_TIMEOUT, async {
+        futures::join!(group_1.next_generation(), group_2.next_generation())
+    })
+    .await
+    .unwrap();
+    let generation_1 = generation_1.unwrap().unwrap();
+    let generation_2 = generation_2.unwrap().unwrap();
+    assert_eq!(generation_1.generation_id(), generation_2.generation_id());
+
+    let partitions_1 = generation_1.assignment().get(&topic).cloned().unwrap();
+    let partitions_2 = generation_2.assignment().get(&topic).cloned().unwrap();
+    assert_eq!(partitions_1.len(), 2);
+    assert_eq!(partitions_2.len(), 2);
+    assert!(partitions_1.is_disjoint(&partitions_2));
+
+    // first member leaves, second member takes over all partitions
+    group_1.leave().await.unwrap();
+    timeout(TEST_TIMEOUT, generation_2.revoked()).await.unwrap();
+    let generation_2 = timeout(TEST_TIMEOUT, group_2.next_generation())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        generation_2.assignment().get(&topic),
+        Some(&BTreeSet::from([0, 1, 2, 3]))
+    );
+
+    group_2.leave().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_consumer_group_offsets() {
+    maybe_start_logging();
+
+    let test_cfg = maybe_skip_kafka_integration!();
+    let client = ClientBuilder::new(test_cfg.bootstrap_brokers)
+        .build()
+        .await
+        .unwrap();
+    let controller_client = client.controller_client().unwrap();
+
+    let topic = random_topic_name();
+    controller_client
+        .create_topic(&topic, 2, 1, 5_000)
+        .await
+        .unwrap();
+
+    let group_id = random_group_id();
+    let offsets_client = client.group_offsets_client(&group_id);
+    assert_eq!(offsets_client.fetch_offset(&topic, 0).await.unwrap(), None);
+
+    // commit as non-member
+    let committed = CommittedOffset {
+        topic: topic.clone(),
+        partition: 0,
+        offset: 3,
+        metadata: Some("foo".to_owned()),
+    };
+    offsets_client
+        .commit_offsets(std::slice::from_ref(&committed))
+        .await
+        .unwrap();
+    assert_eq!(
+        offsets_client.fetch_offset(&topic, 0).await.unwrap(),
+        Some(3)
+    );
+
+    // commit as group member
+    let mut group = client
+        .consumer_group(&group_id, vec![topic.clone()])
+        .with_heartbeat_interval(Duration::from_millis(100))
+        .build();
+    let generation = timeout(TEST_TIMEOUT, group.next_generation())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        generation.committed_offsets().await.unwrap(),
+        vec![committed]
+    );
+
+    let committed = CommittedOffset {
+        topic: topic.clone(),
+        partition: 1,
+        offset: 5,
+        metadata: None,
+    };
+    generation
+        .commit_offsets(std::slice::from_ref(&committed))
+        .await
+        .unwrap();
+    assert_eq!(
+        offsets_client.fetch_offset(&topic, 1).await.unwrap(),
+        Some(5)
+    );
+
+    group.leave().await.unwrap();
+
+    // commits of a revoked generation are rejected
+    generation.commit_offsets(&[committed]).await.unwrap_err();
+}
@@ -1,8 +1,14 @@
+use assert_matches::assert_matches;
 use futures::{FutureExt, future::FusedFuture, pin_mut};
-use rskafka::client::{
-    ClientBuilder,
-    partition::UnknownTopicHandling,
-    producer::{BatchProducerBuilder, aggregator::RecordAggregator},
+use rskafka::{
+    client::{
+        ClientBuilder,
+        error::Error,
+        partition::{Compression, UnknownTopicHandling},
+        partitioner::Partitioner,
+        producer::{BatchProducerBuilder, aggregator::RecordAggregator},
+    },
+    record::Record,
 };
 use std::time::Duration;
 
@@ -74,3 +80,183 @@ async fn test_batch_producer() {
         .expect("no timeout")
         .unwrap();
 }
+
+#[tokio::test]
+async fn test_topic_producer() {
+    maybe_start_logging();
+
+    let test_cfg = maybe_skip_kafka_integration!();
+    let client = ClientBuilder::new(test_cfg.bootstrap_brokers)
+        .build()
+        .await
+        .unwrap();
+    let controller_client = client.controller_client().unwrap();
+
+    let topic = random_topic_name();
+    controller_client
+        .create_topic(&topic, 3, 1, 5_000)
+        .await
+        .unwrap();
+
+    let producer = client
+        .topic_producer(&topic, UnknownTopicHandling::Retry)
+        .build()
+        .await
+        .unwrap();
+    assert_eq!(producer.num_partitions(), 3);
+
+    let record_a = record(b"a");
+    let record_b = record(b"b");
+    let mut record_c = record(b"");
+    record_c.key = None;
+    let mut record_d = record(b"");
+    record_d.key = None;
+    record_d.value = Some(b"other value".to_vec());
+
+    let produced = producer
+        .produce(
+            vec![
+                record_a.clone(),
+                record_b.clone(),
+                record_c.clone(),
+                record_a.clone(),
+                record_d.clone(),
+            ],
+            Compression::NoCompression,
+        )
+        .await
+        .unwrap();
+    assert_eq!(produced.len(), 5);
+
+    // same key, same partition
+    assert_eq!(produced[0].partition, produced[3].partition);
+    assert!(produced[0].offset < produced[3].offset);
+
+    // keyless records of one batch stick to the same partition
+    assert_eq!(produced[2].partition, produced[4].partition);
+
+    // records are where the producer said they are
+    for (produced, record) in produced
+        .iter()
+        .zip([&record_a, &record_b, &record_c, &record_a, &record_d])
+    {
+        let partition_client = client
+            .partition_client(&topic, produced.partition, UnknownTopicHandling::Retry)
+            .await
+            .unwrap();
+        let (records, _watermark) = partition_client
+            .fetch_records(produced.offset, 1..100_000, 1_000)
+            .await
+            .unwrap();
+        assert_eq!(&records[0].record, record);
+        assert_eq!(records[0].offset, produced.offset);
+    }
+}
+
+#[tokio::test]
+async fn test_topic_producer_invalid_partition() {
+    maybe_start_logging();
+
+    #[derive(Debug)]
+    struct OutOfRangePartitioner;
+
+    impl Partitioner for OutOfRangePartitioner {
+        fn partition(&self, _topic: &str, _record: &Record, num_partitions: i32) -> i32 {
+            num_partitions
+        }
+    }
+
+    let test_cfg = maybe_skip_kafka_integration!();
+    let client = ClientBuilder::new(test_cfg.bootstrap_brokers)
+        .build()
+        .await
+        .unwrap();
+    let controller_client = client.controller_client().unwrap();
+
+    let topic = random_topic_name();
+    controller_client
+        .create_topic(&topic, 2, 1, 5_000)
+        .await
+        .unwrap();
+
+    let producer = client
+        .topic_producer(&topic, UnknownTopicHandling::Retry)
+        .with_partitioner(Arc::new(OutOfRangePartitioner))
+        .build()
+        .await
+        .unwrap();
+
+    let err = producer
+        .produce(vec![record(b"a")], Compression::NoCompression)
+        .await
+        .unwrap_err();
+    assert_matches!(
+        err,
+        Error::InvalidPartition {
+            topic: t,
+            partition: 2,
+            num_partitions: 2,
+        } if t == topic
+    );
+}
+
+#[tokio::test]
+async fn test_broker_producer() {
+    maybe_start_logging();
+
+    let test_cfg = maybe_skip_kafka_integration!();
+    let client = ClientBuilder::new(test_cfg.bootstrap_brokers)
+        .build()
+        .await
+        .unwrap();
+    let controller_client = client.controller_client().unwrap();
+
+    let topic = random_topic_name();
+    controller_client
+        .create_topic(&topic, 4, 1, 5_000)
+        .await
+        .unwrap();
+
+    let producer = client.broker_producer().build();
+
+    // two rounds of concurrent writes to all partitions, incl. two batches for the same partition in one round
+    for round in 0..2 {
+        let results = futures::future::join_all((0..4).chain([0]).map(|partition| {
+            let record = record(format!("{round}-{partition}").as_bytes());
+            let producer = &producer;
+            let topic = &topic;
+            async move {
+                producer
+                    .produce(topic, partition, vec![record], Compression::NoCompression)
+                    .await
+            }
+        }))
+        .await;
+        let offsets: Vec<_> = results.into_iter().map(|r| r.unwrap()).collect();
+        for partition_offsets in &offsets[1..4] {
+            assert_eq!(partition_offsets, &vec![round]);
+        }
+        let mut offsets_0 = [offsets[0][0], offsets[4][0]];
+        offsets_0.sort_unstable();
+        assert_eq!(offsets_0, [round * 2, round * 2 + 1]);
+    }
+
+    for partition in 1..4 {
+        let partition_client = client
+            .partition_client(&topic, partition, UnknownTopicHandling::Retry)
+            .await
+            .unwrap();
+        let (records, _watermark) = partition_client
+            .fetch_records(0, 1..100_000, 1_000)
+            .await
+            .unwrap();
+        let records: Vec<_> = records.into_iter().map(|r| r.record).collect();
+        assert_eq!(
+            records,
+            vec![
+                record(format!("0-{partition}").as_bytes()),
+                record(format!("1-{partition}").as_bytes()),
+            ]
+        );
+    }
+}
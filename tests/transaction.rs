@@ -0,0 +1,274 @@
+use std::sync::Arc;
+
+use assert_matches::assert_matches;
+use futures::StreamExt;
+use rskafka::client::{
+    ClientBuilder,
+    consumer::{EndOffset, StartOffset, StreamConsumerBuilder},
+    error::Error,
+    group_offsets::CommittedOffset,
+    partition::{Compression, IsolationLevel, UnknownTopicHandling},
+};
+use test_helpers::{TEST_TIMEOUT, maybe_start_logging, random_topic_name, record};
+
+mod test_helpers;
+
+/// Transactional ID for testing.
+fn random_transactional_id() -> String {
+    format!("test_txn_{}", uuid::Uuid::new_v4())
+}
+
+#[tokio::test]
+async fn test_transaction_commit_and_abort() {
+    maybe_start_logging();
+
+    let test_cfg = maybe_skip_kafka_integration!();
+    let client = ClientBuilder::new(test_cfg.bootstrap_brokers)
+        .build()
+        .await
+        .unwrap();
+    let controller_client = client.controller_client().unwrap();
+
+    let topic = random_topic_name();
+    controller_client
+        .create_topic(&topic, 2, 1, 5_000)
+        .await
+        .unwrap();
+
+    let producer = client
+        .transactional_producer(random_transactional_id())
+        .build()
+        .await
+        .unwrap();
+
+    // producing requires a transaction
+    assert_matches!(
+        producer
+            .produce(&topic, 0, vec![record(b"x")], Compression::NoCompression)
+            .await,
+        Err(Error::InvalidTransactionState(_))
+    );
+
+    // committed transaction
+    let record_1 = record(b"a");
+    let record_2 = record(b"b");
+    producer.begin_transaction().await.unwrap();
+    producer
+        .produce(
+            &topic,
+            0,
+            vec![record_1.clone()],
+            Compression::NoCompression,
+        )
+        .await
+        .unwrap();
+    producer
+        .produce(
+            &topic,
+            1,
+            vec![record_2.clone()],
+            Compression::NoCompression,
+        )
+        .await
+        .unwrap();
+    producer.commit_transaction().await.unwrap();
+
+    // aborted transaction
+    producer.begin_transaction().await.unwrap();
+    producer
+        .produce(&topic, 0, vec![record(b"c")], Compression::NoCompression)
+        .await
+        .unwrap();
+    producer.abort_transaction().await.unwrap();
+
+    // and another committed one
+    let record_3 = record(b"d");
+    producer.begin_transaction().await.unwrap();
+    producer
+        .produce(
+            &topic,
+            0,
+            vec![record_3.clone()],
+            Compression::NoCompression,
+        )
+        .await
+        .unwrap();
+    producer.commit_transaction().await.unwrap();
+
+    // only committed records are visible
+    let partition_client = client
+        .partition_client(&topic, 0, UnknownTopicHandling::Retry)
+        .await
+        .unwrap();
+    let (records, _watermark) = partition_client
+        .fetch_records(0, 1..100_000, 1_000)
+        .await
+        .unwrap();
+    let records: Vec<_> = records.into_iter().map(|r| r.record).collect();
+    assert_eq!(records, vec![record_1.clone(), record_3.clone()]);
+
+    // read-uncommitted also returns aborted records
+    let (records, _watermark) = partition_client
+        .fetch_records_with_isolation_level(0, 1..100_000, 1_000, IsolationLevel::ReadUncommitted)
+        .await
+        .unwrap();
+    let records: Vec<_> = records.into_iter().map(|r| r.record).collect();
+    assert_eq!(
+        records,
+        vec![record_1.clone(), record(b"c"), record_3.clone()]
+    );
+
+    // same for the stream consumer
+    let partition_client = Arc::new(partition_client);
+    let mut stream =
+        StreamConsumerBuilder::new(Arc::clone(&partition_client), StartOffset::Earliest)
+            .with_max_wait_ms(50)
+            .with_isolation_level(IsolationLevel::ReadUncommitted)
+            .build();
+    let mut records = vec![];
+    for _ in 0..3 {
+        let (record_and_offset, _watermark) = stream.next().await.unwrap().unwrap();
+        records.push(record_and_offset.record);
+    }
+    assert_eq!(
+        records,
+        vec![record_1.clone(), record(b"c"), record_3.clone()]
+    );
+
+    // a bounded stream ends although the log ends with a transaction marker
+    let stream = StreamConsumerBuilder::new(Arc::clone(&partition_client), StartOffset::Earliest)
+        .with_max_wait_ms(50)
+        .with_end_offset(EndOffset::Latest)
+        .build();
+    let records = tokio::time::timeout(
+        TEST_TIMEOUT,
+        stream.map(|r| r.unwrap().0.record).collect::<Vec<_>>(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(records, vec![record_1.clone(), record_3.clone()]);
+
+    let mut stream = StreamConsumerBuilder::new(partition_client, StartOffset::Earliest)
+        .with_max_wait_ms(50)
+        .build();
+    let mut records = vec![];
+    for _ in 0..2 {
+        let (record_and_offset, _watermark) = stream.next().await.unwrap().unwrap();
+        records.push(record_and_offset.record);
+    }
+    assert_eq!(records, vec![record_1, record_3]);
+
+    let partition_client = client
+        .partition_client(&topic, 1, UnknownTopicHandling::Retry)
+        .await
+        .unwrap();
+    let (records, _watermark) = partition_client
+        .fetch_records(0, 1..100_000, 1_000)
+        .await
+        .unwrap();
+    let records: Vec<_> = records.into_iter().map(|r| r.record).collect();
+    assert_eq!(records, vec![record_2]);
+}
+
+#[tokio::test]
+async fn test_transaction_send_offsets() {
+    maybe_start_logging();
+
+    let test_cfg = maybe_skip_kafka_integration!();
+    let client = ClientBuilder::new(test_cfg.bootstrap_brokers)
+        .build()
+        .await
+        .unwrap();
+    let controller_client = client.controller_client().unwrap();
+
+    let topic = random_topic_name();
+    controller_client
+        .create_topic(&topic, 1, 1, 5_000)
+        .await
+        .unwrap();
+
+    let group_id = format!("test_group_{}", uuid::Uuid::new_v4());
+    let offsets_client = client.group_offsets_client(&group_id);
+    let producer = client
+        .transactional_producer(random_transactional_id())
+        .build()
+        .await
+        .unwrap();
+    let offsets = [CommittedOffset {
+        topic: topic.clone(),
+        partition: 0,
+        offset: 42,
+        metadata: None,
+    }];
+
+    // offsets of aborted transactions are discarded
+    producer.begin_transaction().await.unwrap();
+    producer
+        .send_offsets_to_transaction(&group_id, &offsets)
+        .await
+        .unwrap();
+    producer.abort_transaction().await.unwrap();
+    assert_eq!(offsets_client.fetch_offset(&topic, 0).await.unwrap(), None);
+
+    producer.begin_transaction().await.unwrap();
+    producer
+        .send_offsets_to_transaction(&group_id, &offsets)
+        .await
+        .unwrap();
+    producer.commit_transaction().await.unwrap();
+    assert_eq!(
+        offsets_client.fetch_offset(&topic, 0).await.unwrap(),
+        Some(42)
+    );
+}
+
+#[tokio::test]
+async fn test_transaction_fencing() {
+    maybe_start_logging();
+
+    let test_cfg = maybe_skip_kafka_integration!();
+    let client = ClientBuilder::new(test_cfg.bootstrap_brokers)
+        .build()
+        .await
+        .unwrap();
+    let controller_client = client.controller_client().unwrap();
+
+    let topic = random_topic_name();
+    controller_client
+        .create_topic(&topic, 1, 1, 5_000)
+        .await
+        .unwrap();
+
+    let transactional_id = random_transactional_id();
+    let producer_1 = client
+        .transactional_producer(&transactional_id)
+        .build()
+        .await
+        .unwrap();
+    producer_1.begin_transaction().await.unwrap();
+    producer_1
+        .produce(&topic, 0, vec![record(b"x")], Compression::NoCompression)
+        .await
+        .unwrap();
+
+    // new producer with the same ID fences the first one and aborts its transaction
+    let producer_2 = client
+        .transactional_producer(&transactional_id)
+        .build()
+        .await
+        .unwrap();
+    producer_1.commit_transaction().await.unwrap_err();
+    producer_1.abort_transaction().await.unwrap_err();
+    assert_matches!(
+        producer_1.begin_transaction().await,
+        Err(Error::InvalidTransactionState(_))
+    );
+
+    // second producer works
+    producer_2.begin_transaction().await.unwrap();
+    producer_2
+        .produce(&topic, 0, vec![record(b"y")], Compression::NoCompression)
+        .await
+        .unwrap();
+    producer_2.commit_transaction().await.unwrap();
+}